@@ -0,0 +1,59 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! The report of `--audit-font`, listing the characters of the input a font family can't render.
+//!
+
+use serde::Serialize;
+use unic::ucd::Name;
+
+use crate::preview::{missing_glyphs, MissingGlyphReason};
+use crate::ucd::code_point_description;
+
+use super::Result;
+
+#[derive(Serialize, Debug)]
+pub struct MissingGlyph {
+    pub character: char,
+    pub code_point: u32,
+    pub name: String,
+    pub reason: MissingGlyphReason,
+}
+
+impl MissingGlyph {
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}  {}  {} ({})",
+            code_point_description(self.character),
+            self.character,
+            self.name,
+            self.reason.description()
+        )
+    }
+}
+
+pub fn audit_font(input: &str, font_family: &str) -> Result<Vec<MissingGlyph>> {
+    Ok(missing_glyphs(input, font_family)?
+        .into_iter()
+        .map(|(chr, reason)| MissingGlyph {
+            character: chr,
+            code_point: chr as u32,
+            name: Name::of(chr)
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            reason,
+        })
+        .collect())
+}
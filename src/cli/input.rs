@@ -12,35 +12,68 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
-use std::char;
+use std::collections::HashSet;
+use std::io::{stdin, Read};
 
 use clap::ArgMatches;
 
+use crate::ucd::char_from_code_point;
+
+use super::entity::decode_entities;
 use super::{Error, Result, FLAG_NAME_CODE_POINT_INPUT_MODE};
 
 pub const OPTION_NAME_INPUT_TYPE: &str = "input_type";
 pub const OPTION_VALUE_INPUT_TYPE_STRING: &str = "string";
 pub const OPTION_VALUE_INPUT_TYPE_CODE_POINTS: &str = "code-points";
+pub const OPTION_VALUE_INPUT_TYPE_ENTITIES: &str = "entities";
 
 pub const ARGUMENT_VALUE_NAME_INPUT: &str = "INPUT";
+pub const ARGUMENT_VALUE_STDIN: &str = "-";
 
-fn characters_from_input_string(input_string: &str) -> Vec<char> {
-    input_string
-        .split(',')
-        .map(|component| {
-            if !component.to_lowercase().starts_with("u+") || component[2..].is_empty() {
-                return None;
-            }
-            match u32::from_str_radix(&component[2..], 16) {
-                Ok(code_point) => char::from_u32(code_point),
-                Err(_) => None,
+// Piping a large document should not end up with an unusable list of characters
+const MAX_UNIQUE_CHARACTERS_FROM_STDIN: usize = 4096;
+
+// Malformed components are ignored, but code points which aren't characters (surrogates and code
+// points past U+10FFFF) are reported
+fn characters_from_input_string(input_string: &str) -> Result<Vec<char>> {
+    let mut characters = Vec::new();
+    for component in input_string.split(',') {
+        if !component.to_lowercase().starts_with("u+") || component[2..].is_empty() {
+            continue;
+        }
+        let code_point = match u32::from_str_radix(&component[2..], 16) {
+            Ok(code_point) => code_point,
+            Err(_) => continue,
+        };
+        match char_from_code_point(code_point) {
+            Ok(chr) => characters.push(chr),
+            Err(invalid_code_point) => {
+                return Err(Box::new(Error::InvalidCodePoint(
+                    component.to_owned(),
+                    invalid_code_point,
+                )))
             }
-        })
-        .filter(Option::is_some)
-        .map(Option::unwrap)
+        }
+    }
+    Ok(characters)
+}
+
+// Returns each character once in order of appearance (line breaks excluded), up to `limit`
+fn unique_characters(text: &str, limit: usize) -> Vec<char> {
+    let mut seen_characters = HashSet::new();
+    text.chars()
+        .filter(|chr| *chr != '\n' && *chr != '\r')
+        .filter(|chr| seen_characters.insert(*chr))
+        .take(limit)
         .collect()
 }
 
+fn read_stdin() -> Result<String> {
+    let mut input_string = String::new();
+    stdin().read_to_string(&mut input_string)?;
+    Ok(input_string)
+}
+
 #[derive(Debug)]
 pub enum Input {
     String(String),
@@ -64,30 +97,77 @@ impl ToString for Input {
 }
 
 pub fn parse_input(args: &ArgMatches) -> Result<Input> {
-    let input_string = args
-        .value_of(ARGUMENT_VALUE_NAME_INPUT)
-        .unwrap_or_else(|| ""); // No input is provided, fallback to an empty string
+    let is_reading_stdin = args.value_of(ARGUMENT_VALUE_NAME_INPUT) == Some(ARGUMENT_VALUE_STDIN);
+    let input_string = if is_reading_stdin {
+        read_stdin()?
+    } else {
+        args.value_of(ARGUMENT_VALUE_NAME_INPUT)
+            .unwrap_or_else(|| "") // No input is provided, fallback to an empty string
+            .to_owned()
+    };
+    let input_string = input_string.as_str();
 
-    match args.value_of(OPTION_NAME_INPUT_TYPE) {
+    let input = match args.value_of(OPTION_NAME_INPUT_TYPE) {
         Some(input_type) => match input_type {
-            OPTION_VALUE_INPUT_TYPE_STRING => Ok(Input::String(input_string.to_owned())),
-            OPTION_VALUE_INPUT_TYPE_CODE_POINTS => Ok(Input::Characters(
-                characters_from_input_string(input_string),
-            )),
-            _ => Err(Box::new(Error::UnrecognizedInputType(
-                input_type.to_owned(),
-            ))),
+            OPTION_VALUE_INPUT_TYPE_STRING => Input::String(input_string.to_owned()),
+            OPTION_VALUE_INPUT_TYPE_CODE_POINTS => {
+                Input::Characters(characters_from_input_string(input_string)?)
+            }
+            OPTION_VALUE_INPUT_TYPE_ENTITIES => Input::String(decode_entities(input_string)?),
+            _ => {
+                return Err(Box::new(Error::UnrecognizedInputType(
+                    input_type.to_owned(),
+                )))
+            }
         },
         None => {
             if args.is_present(FLAG_NAME_CODE_POINT_INPUT_MODE) {
-                Ok(Input::Characters(characters_from_input_string(
-                    input_string,
-                )))
+                Input::Characters(characters_from_input_string(input_string)?)
             } else {
-                Ok(Input::String(input_string.to_owned()))
+                Input::String(input_string.to_owned())
             }
         }
+    };
+
+    if is_reading_stdin {
+        Ok(Input::Characters(unique_characters(
+            &input.to_string(),
+            MAX_UNIQUE_CHARACTERS_FROM_STDIN,
+        )))
+    } else {
+        Ok(input)
     }
 }
 
-// TODO: Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_characters_from_input_string() {
+        assert_eq!(
+            characters_from_input_string("U+0041,u+1F600").unwrap(),
+            vec!['A', '\u{1F600}']
+        );
+        assert_eq!(characters_from_input_string("U+,41,U+ZZ").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_surrogates_in_input_string() {
+        for input_string in &["U+D800", "U+0041,U+DFFF", "U+DBFF,U+0041"] {
+            let error = characters_from_input_string(input_string).err().unwrap();
+            assert!(error.to_string().contains("is a surrogate"), "{}", error);
+        }
+        assert!(characters_from_input_string("U+110000").is_err());
+    }
+
+    #[test]
+    fn test_unique_characters() {
+        assert_eq!(
+            unique_characters("abcabd\r\nda", 100),
+            vec!['a', 'b', 'c', 'd']
+        );
+        assert_eq!(unique_characters("abcabd", 2), vec!['a', 'b']);
+        assert_eq!(unique_characters("", 2), vec![]);
+    }
+}
@@ -0,0 +1,103 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Metadata of a single character printed by `--json`, meant for scripts. The fields are part of
+//! the interface of Cicero: new ones may be added, but existing ones are not renamed or removed.
+//!
+
+use serde::Serialize;
+
+use crate::preview::{CharacterPreview, GlyphMetrics, PreviewContext};
+use crate::settings::Settings;
+use crate::ucd::{CharacterProperties, StringValuedProperty};
+
+#[derive(Serialize, Debug)]
+pub struct CharacterMetadata {
+    pub character: char,
+    pub code_point: u32,
+    pub name: String,
+    pub block: Option<&'static str>,
+    pub general_category: StringValuedProperty,
+
+    // Paths of the fonts having a glyph for the character, in the order of the preview pane
+    pub fonts: Vec<String>,
+
+    // The glyph previewed by default, None if no font has a glyph for the character
+    pub glyph: Option<GlyphMetadata>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GlyphMetadata {
+    pub font_path: String,
+    pub index: u32,
+    pub name: Option<String>,
+
+    // None for bitmap fonts
+    pub metrics: Option<GlyphMetrics>,
+}
+
+pub fn character_metadata(chr: char, settings: &Settings) -> CharacterMetadata {
+    let character_properties = CharacterProperties::new(chr);
+    let character_preview = PreviewContext::new()
+        .and_then(|context| {
+            CharacterPreview::new(
+                &context,
+                chr,
+                settings.private_use_font_of(chr),
+                &settings.font_match_options(),
+            )
+        })
+        .ok();
+
+    CharacterMetadata {
+        character: chr,
+        code_point: character_properties.code_point,
+        name: character_properties.name,
+        block: character_properties.block_name,
+        general_category: character_properties.general_category,
+        fonts: character_preview
+            .as_ref()
+            .map(|character_preview| character_preview.matching_font_paths().to_vec())
+            .unwrap_or_default(),
+        glyph: character_preview.map(|character_preview| {
+            let glyph_info = character_preview.glyph_info();
+            GlyphMetadata {
+                font_path: character_preview
+                    .get_current_font_path()
+                    .unwrap_or_default(),
+                index: glyph_info.index,
+                name: glyph_info.name,
+                metrics: character_preview.glyph_metrics().ok(),
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_character_metadata() {
+        let character_metadata = character_metadata('A', &Settings::default());
+        assert_eq!(character_metadata.code_point, 0x41);
+        assert_eq!(character_metadata.name, "LATIN CAPITAL LETTER A");
+        assert_eq!(character_metadata.block, Some("Basic Latin"));
+        assert_eq!(character_metadata.general_category.abbr, "Lu");
+        if let Some(glyph) = &character_metadata.glyph {
+            assert!(character_metadata.fonts.contains(&glyph.font_path));
+        }
+    }
+}
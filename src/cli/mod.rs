@@ -19,26 +19,39 @@
 use std::error;
 use std::fmt;
 
+use crate::ucd::InvalidCodePoint;
+
+mod audit;
+mod entity;
 mod input;
+mod metadata;
 mod output;
 
+pub use audit::{audit_font, MissingGlyph};
 pub use input::{
-    parse_input, ARGUMENT_VALUE_NAME_INPUT, OPTION_NAME_INPUT_TYPE,
-    OPTION_VALUE_INPUT_TYPE_CODE_POINTS, OPTION_VALUE_INPUT_TYPE_STRING,
+    parse_input, ARGUMENT_VALUE_NAME_INPUT, ARGUMENT_VALUE_STDIN, OPTION_NAME_INPUT_TYPE,
+    OPTION_VALUE_INPUT_TYPE_CODE_POINTS, OPTION_VALUE_INPUT_TYPE_ENTITIES,
+    OPTION_VALUE_INPUT_TYPE_STRING,
 };
+pub use metadata::{character_metadata, CharacterMetadata, GlyphMetadata};
 pub use output::{
-    generate_output, OPTION_NAME_OUTPUT_FORMAT, OPTION_VALUE_OUTPUT_FORMAT_JSON,
-    OPTION_VALUE_OUTPUT_FORMAT_TEXT,
+    generate_output, FLAG_NAME_JSON_MODE, FLAG_NAME_REBUILD_FONT_INDEX, OPTION_NAME_AUDIT_FONT,
+    OPTION_NAME_OUTPUT_FORMAT, OPTION_NAME_OUTPUT_PNG, OPTION_NAME_OUTPUT_SVG,
+    OPTION_VALUE_OUTPUT_FORMAT_JSON, OPTION_VALUE_OUTPUT_FORMAT_TEXT,
 };
 
 pub const FLAG_NAME_TUI_MODE: &str = "tui_mode";
 pub const FLAG_NAME_CODE_POINT_INPUT_MODE: &str = "code_point_input_mode";
+pub const FLAG_NAME_VERBOSE: &str = "verbose";
 
 pub type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 pub enum Error {
     UnrecognizedInputType(String),
     UnrecognizedOutputFormat(String),
+    ExpectedSingleCharacter(String),
+    UnknownEntity(String),
+    InvalidCodePoint(String, InvalidCodePoint),
 }
 
 impl fmt::Debug for Error {
@@ -56,6 +69,13 @@ impl fmt::Display for Error {
             Error::UnrecognizedOutputFormat(output_format) => {
                 write!(f, "Unrecognized output format '{}'", output_format)
             }
+            Error::ExpectedSingleCharacter(input) => {
+                write!(f, "Expected a single character, got '{}'", input)
+            }
+            Error::UnknownEntity(entity) => write!(f, "Unknown entity '{}'", entity),
+            Error::InvalidCodePoint(input, invalid_code_point) => {
+                write!(f, "Invalid code point '{}': {}", input, invalid_code_point)
+            }
         }
     }
 }
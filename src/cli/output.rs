@@ -12,10 +12,23 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
+use std::path::Path;
+
 use clap::ArgMatches;
+use log::warn;
+
+use super::{audit_font, character_metadata, parse_input, Error, Result};
+#[cfg(feature = "image")]
+use crate::preview::export_png;
+use crate::preview::{add_font_directories, export_svg, CharacterPreview, PreviewContext};
+use crate::settings::Settings;
+use crate::ucd::{code_point_description, GraphemeProperties};
 
-use super::{parse_input, Error, Result};
-use crate::ucd::GraphemeProperties;
+pub const FLAG_NAME_JSON_MODE: &str = "json_mode";
+pub const FLAG_NAME_REBUILD_FONT_INDEX: &str = "rebuild_font_index";
+pub const OPTION_NAME_AUDIT_FONT: &str = "audit_font";
+pub const OPTION_NAME_OUTPUT_PNG: &str = "output_png";
+pub const OPTION_NAME_OUTPUT_SVG: &str = "output_svg";
 
 pub const OPTION_NAME_OUTPUT_FORMAT: &str = "output_format";
 pub const OPTION_VALUE_OUTPUT_FORMAT_TEXT: &str = "text";
@@ -29,8 +42,149 @@ fn graphems_to_string(graphemes: &[GraphemeProperties]) -> String {
         .join("\n")
 }
 
+fn single_character(input: &str) -> Result<char> {
+    let mut characters = input.chars();
+    match (characters.next(), characters.next()) {
+        (Some(chr), None) => Ok(chr),
+        _ => Err(Box::new(Error::ExpectedSingleCharacter(input.to_owned()))),
+    }
+}
+
+// The settings of the TUI, with the fonts of their font directories matched as well
+fn load_settings() -> Result<Settings> {
+    let settings = Settings::load()?;
+    add_font_directories(&settings.font_directories)?;
+    Ok(settings)
+}
+
+// A preview context keeping its font index in the cache directory of the settings, or in memory if
+// the directory can't be used
+fn preview_context(settings: &Settings) -> Result<PreviewContext> {
+    let mut preview_context = PreviewContext::new()?;
+    if let Some(cache_directory) = settings.cache_directory() {
+        if let Err(error) = preview_context.cache_in(&cache_directory) {
+            warn!("{}, the font index is only kept in memory", error);
+        }
+    }
+    Ok(preview_context)
+}
+
+// Clears the font index in the cache directory and indexes every installed font again, unlike
+// previews this fails if the directory can't be used
+fn rebuild_font_index() -> Result<String> {
+    let settings = load_settings()?;
+    let cache_directory = match settings.cache_directory() {
+        Some(cache_directory) => cache_directory,
+        None => return Err("No cache directory to save the font index to".into()),
+    };
+    let mut preview_context = PreviewContext::new()?;
+    preview_context.cache_in(&cache_directory)?;
+    let hashed_font_count = preview_context.rebuild_font_index()?;
+    Ok(format!(
+        "Rebuilt the font index in {} ({} fonts hashed)",
+        cache_directory.display(),
+        hashed_font_count
+    ))
+}
+
+// The metadata of a single character, fonts are matched with the settings of the TUI
+fn generate_character_json(input: &str) -> Result<String> {
+    let chr = single_character(input)?;
+    let settings = load_settings()?;
+    let metadata = character_metadata(chr, &settings);
+    Ok(serde_json::to_string_pretty(&metadata)?)
+}
+
+// The characters of the input which the font family can't render, one per line, the family can be
+// one of the font directories of the settings
+fn generate_font_audit(
+    input: &str,
+    font_family: &str,
+    output_format: Option<&str>,
+) -> Result<String> {
+    load_settings()?;
+    let missing_glyphs = audit_font(input, font_family)?;
+    match output_format.unwrap_or(OPTION_VALUE_OUTPUT_FORMAT_TEXT) {
+        OPTION_VALUE_OUTPUT_FORMAT_TEXT => Ok(missing_glyphs
+            .iter()
+            .map(|missing_glyph| missing_glyph.to_text())
+            .collect::<Vec<String>>()
+            .join("\n")),
+        OPTION_VALUE_OUTPUT_FORMAT_JSON => Ok(serde_json::to_string_pretty(&missing_glyphs)?),
+        output_format => Err(Box::new(Error::UnrecognizedOutputFormat(
+            output_format.to_owned(),
+        ))),
+    }
+}
+
+// Saves the glyph of a single character in its first matching font (with the settings of the TUI)
+// as a PNG image
+#[cfg(feature = "image")]
+fn generate_png(input: &str, path: &Path) -> Result<String> {
+    let chr = single_character(input)?;
+    let settings = load_settings()?;
+    let character_preview = CharacterPreview::new(
+        &preview_context(&settings)?,
+        chr,
+        settings.private_use_font_of(chr),
+        &settings.font_match_options(),
+    )?;
+    export_png(&character_preview, settings.export_pixel_size, path)?;
+    Ok(format!(
+        "Saved {} in {} to {}",
+        code_point_description(chr),
+        character_preview.get_current_font_display_name(),
+        path.display()
+    ))
+}
+
+#[cfg(not(feature = "image"))]
+fn generate_png(_input: &str, _path: &Path) -> Result<String> {
+    Err("Cicero was built without the image feature".into())
+}
+
+// Saves the outline of the glyph of a single character in its first matching font as an SVG
+// document, the font has to be an outline font
+fn generate_svg(input: &str, path: &Path) -> Result<String> {
+    let chr = single_character(input)?;
+    let settings = load_settings()?;
+    let character_preview = CharacterPreview::new(
+        &preview_context(&settings)?,
+        chr,
+        settings.private_use_font_of(chr),
+        &settings.font_match_options(),
+    )?;
+    export_svg(&character_preview, path)?;
+    Ok(format!(
+        "Saved the outline of {} in {} to {}",
+        code_point_description(chr),
+        character_preview.get_current_font_display_name(),
+        path.display()
+    ))
+}
+
 pub fn generate_output(args: ArgMatches) -> Result<String> {
+    if args.is_present(FLAG_NAME_REBUILD_FONT_INDEX) {
+        return rebuild_font_index();
+    }
     let input = parse_input(&args)?;
+    if args.is_present(FLAG_NAME_JSON_MODE) {
+        return generate_character_json(&input.to_string());
+    }
+    if let Some(path) = args.value_of(OPTION_NAME_OUTPUT_PNG) {
+        return generate_png(&input.to_string(), Path::new(path));
+    }
+    if let Some(path) = args.value_of(OPTION_NAME_OUTPUT_SVG) {
+        return generate_svg(&input.to_string(), Path::new(path));
+    }
+    if let Some(font_family) = args.value_of(OPTION_NAME_AUDIT_FONT) {
+        return generate_font_audit(
+            &input.to_string(),
+            font_family,
+            args.value_of(OPTION_NAME_OUTPUT_FORMAT),
+        );
+    }
+
     let graphemes = GraphemeProperties::from_string(&input.to_string());
     match args.value_of(OPTION_NAME_OUTPUT_FORMAT) {
         Some(output_format) => match output_format {
@@ -0,0 +1,37 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Cicero is a Unicode tool with a terminal user interface. Besides the `cicero` executable, the
+//! building blocks are exposed as a library:
+//! - `ucd` supplements `unic::ucd` for querying the Unicode Character Database.
+//! - `preview` matches system fonts with characters and renders glyphs.
+//!
+
+#![warn(clippy::all)]
+
+#[macro_use(defer)]
+extern crate scopeguard;
+
+pub mod preview;
+pub mod ucd;
+
+// The command line interface, the settings and the TUI only make up the `cicero` executable, they
+// are not part of the library API
+#[doc(hidden)]
+pub mod cli;
+#[doc(hidden)]
+pub mod settings;
+#[doc(hidden)]
+pub mod tui;
@@ -14,22 +14,87 @@
 
 #![warn(clippy::all)]
 
-#[macro_use(defer)]
-extern crate scopeguard;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 use clap::{crate_version, App, Arg, ArgMatches};
+use log::LevelFilter;
 use unic::ucd::version::UNICODE_VERSION;
 
-mod cli;
-mod preview;
-mod tui;
-mod ucd;
+use cicero::cli::Result;
+use cicero::{cli, preview, settings, tui};
 
-use cli::Result;
+// Logs are off unless asked for with `-v` (or RUST_LOG), and go to the standard error. The TUI
+// draws on the terminal, so it only logs when the standard error is redirected, e.g. to a file.
+fn init_logging(verbosity: u64, tui_mode: bool) {
+    if tui_mode && atty::is(atty::Stream::Stderr) {
+        return;
+    }
+
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("off"));
+    let level = match verbosity {
+        0 => None,
+        1 => Some(LevelFilter::Info),
+        2 => Some(LevelFilter::Debug),
+        _ => Some(LevelFilter::Trace),
+    };
+    if let Some(level) = level {
+        builder.filter_level(level);
+    }
+    builder.target(env_logger::Target::Stderr).init();
+}
+
+fn run_tui(
+    user_input: String,
+    missing_glyph_reasons: HashMap<char, preview::MissingGlyphReason>,
+) -> Result<()> {
+    let mut settings = settings::Settings::load()?;
+    settings.preview_renderer = tui::resolve_preview_renderer(settings.preview_renderer);
+    settings.preview_protocol = tui::resolve_preview_protocol(settings.preview_protocol);
+    let (image_cell_width, image_cell_height) =
+        tui::resolve_image_cell_size(settings.image_cell_width, settings.image_cell_height);
+    settings.image_cell_width = image_cell_width;
+    settings.image_cell_height = image_cell_height;
+
+    // The startup character only applies when no input is given, an invalid one is reported once
+    // the TUI is shown along with the other startup warnings
+    let mut startup_warnings = vec![];
+    let user_input = if user_input.is_empty() {
+        match settings.startup_character() {
+            Ok(startup_character) => startup_character
+                .map(|chr| chr.to_string())
+                .unwrap_or_default(),
+            Err(error) => {
+                startup_warnings.push(error.to_string());
+                String::new()
+            }
+        }
+    } else {
+        user_input
+    };
+    // Fonts are still matched without a font directory which can't be added, which is reported
+    if let Err(error) = preview::add_font_directories(&settings.font_directories) {
+        startup_warnings.push(error.to_string());
+    }
+    let settings = Rc::new(settings);
 
-fn run_tui(user_input: String) -> Result<()> {
     let mut state = tui::ApplicationState::default();
-    let mut main_view = tui::MainView::new(user_input);
+    let mut preview_context = preview::PreviewContext::new()?;
+    // Without a usable cache directory the font index is kept in memory, which is reported
+    if let Some(cache_directory) = settings.cache_directory() {
+        if let Err(error) = preview_context.cache_in(&cache_directory) {
+            startup_warnings.push(format!("{}, the font index is only kept in memory", error));
+        }
+    }
+    if settings.background_rendering {
+        preview_context = preview_context.with_render_worker();
+    }
+    let mut main_view = tui::MainView::new(user_input, preview_context, settings);
+    main_view.set_missing_glyph_reasons(missing_glyph_reasons);
+    if !startup_warnings.is_empty() {
+        main_view.set_status_message(startup_warnings.join("; "));
+    }
 
     let renderer = tui::Renderer::new();
     match renderer.run(|terminal| {
@@ -70,6 +135,81 @@ fn main() -> Result<()> {
                     cli::OPTION_VALUE_INPUT_TYPE_CODE_POINTS,
                 )),
         )
+        .arg(
+            Arg::with_name(cli::FLAG_NAME_VERBOSE)
+                .short("v")
+                .long("verbose")
+                .multiple(true)
+                .help(
+                    "Logs font matching and rendering to standard error,\n\
+                     repeat for more detail (-vv, -vvv), with '-t'\n\
+                     only if standard error is redirected",
+                ),
+        )
+        .arg(
+            Arg::with_name(cli::FLAG_NAME_JSON_MODE)
+                .long("json")
+                .conflicts_with(cli::FLAG_NAME_TUI_MODE)
+                .help(&format!(
+                    "Prints the metadata of the single character in {}\n\
+                     as a JSON object, including its fonts and glyph",
+                    cli::ARGUMENT_VALUE_NAME_INPUT,
+                )),
+        )
+        .arg(
+            Arg::with_name(cli::FLAG_NAME_REBUILD_FONT_INDEX)
+                .long("rebuild-font-index")
+                .conflicts_with(cli::FLAG_NAME_TUI_MODE)
+                .help(
+                    "Clears the font index in the cache directory\n\
+                     and indexes every installed font again",
+                ),
+        )
+        .arg(
+            Arg::with_name(cli::OPTION_NAME_AUDIT_FONT)
+                .long("audit-font")
+                .takes_value(true)
+                .value_name("FAMILY")
+                .conflicts_with(cli::FLAG_NAME_JSON_MODE)
+                .help(&format!(
+                    "Lists the characters in {} which the font\n\
+                     family can't render, in the TUI with '-t'",
+                    cli::ARGUMENT_VALUE_NAME_INPUT
+                )),
+        )
+        .arg(
+            Arg::with_name(cli::OPTION_NAME_OUTPUT_PNG)
+                .long("output-png")
+                .takes_value(true)
+                .value_name("FILE")
+                .conflicts_with_all(&[
+                    cli::FLAG_NAME_TUI_MODE,
+                    cli::FLAG_NAME_JSON_MODE,
+                    cli::OPTION_NAME_AUDIT_FONT,
+                ])
+                .help(&format!(
+                    "Saves the glyph of the single character in {}\n\
+                     as a PNG image",
+                    cli::ARGUMENT_VALUE_NAME_INPUT
+                )),
+        )
+        .arg(
+            Arg::with_name(cli::OPTION_NAME_OUTPUT_SVG)
+                .long("output-svg")
+                .takes_value(true)
+                .value_name("FILE")
+                .conflicts_with_all(&[
+                    cli::FLAG_NAME_TUI_MODE,
+                    cli::FLAG_NAME_JSON_MODE,
+                    cli::OPTION_NAME_AUDIT_FONT,
+                    cli::OPTION_NAME_OUTPUT_PNG,
+                ])
+                .help(&format!(
+                    "Saves the outline of the single character in {}\n\
+                     as an SVG image",
+                    cli::ARGUMENT_VALUE_NAME_INPUT
+                )),
+        )
         .arg(
             Arg::with_name(cli::OPTION_NAME_OUTPUT_FORMAT)
                 .short("o")
@@ -92,21 +232,49 @@ fn main() -> Result<()> {
                 .value_name("TYPE")
                 .help(&format!(
                     "Specifies input type, '{}' by default,\n\
-                     valid values: {}, {}",
+                     valid values: {}, {}, {}",
                     cli::OPTION_VALUE_INPUT_TYPE_STRING,
                     cli::OPTION_VALUE_INPUT_TYPE_STRING,
                     cli::OPTION_VALUE_INPUT_TYPE_CODE_POINTS,
+                    cli::OPTION_VALUE_INPUT_TYPE_ENTITIES,
                 )),
         )
         .arg(
-            Arg::with_name(cli::ARGUMENT_VALUE_NAME_INPUT)
-                .help("a string or comma separated code points"),
+            Arg::with_name(cli::ARGUMENT_VALUE_NAME_INPUT).help(&format!(
+                "a string or comma separated code points,\n\
+                     '{}' reads unique characters from standard input",
+                cli::ARGUMENT_VALUE_STDIN
+            )),
         )
         .get_matches();
 
+    init_logging(
+        args.occurrences_of(cli::FLAG_NAME_VERBOSE),
+        args.is_present(cli::FLAG_NAME_TUI_MODE),
+    );
+
     if args.is_present(cli::FLAG_NAME_TUI_MODE) {
-        let user_input = cli::parse_input(&args)?;
-        run_tui(user_input.to_string())
+        let user_input = cli::parse_input(&args)?.to_string();
+
+        // The TUI lists the characters failing the audit along with the reason, to look for fonts
+        // rendering them
+        let (user_input, missing_glyph_reasons) = match args.value_of(cli::OPTION_NAME_AUDIT_FONT) {
+            Some(font_family) => {
+                let missing_glyphs = cli::audit_font(&user_input, font_family)?;
+                (
+                    missing_glyphs
+                        .iter()
+                        .map(|missing_glyph| missing_glyph.character)
+                        .collect(),
+                    missing_glyphs
+                        .iter()
+                        .map(|missing_glyph| (missing_glyph.character, missing_glyph.reason))
+                        .collect(),
+                )
+            }
+            None => (user_input, HashMap::new()),
+        };
+        run_tui(user_input, missing_glyph_reasons)
     } else {
         run_cli(args)
     }
@@ -12,15 +12,37 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
-use std::cmp::min;
+use std::cell::{Cell, RefCell};
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-use freetype::{Face, Library};
+use freetype::bitmap::PixelMode;
+use freetype::face::LoadFlag;
+use freetype::{Bitmap, Face, Library, RenderMode};
+use log::{debug, info, warn};
+use serde::Serialize;
 
-use super::font_match::fonts_for;
+use super::font_match::{
+    deduplicate_fonts, exclude_fonts, filter_fonts, font_of_family, limit_fonts, prioritize_fonts,
+    FontMatchOptions, MatchedFont,
+};
+use super::font_probe::{load_face, FaceLoad};
+use super::freetype_ext::{
+    best_strike, charmap_characters, decompose_outline, font_format, glyph_name, has_color_glyphs,
+    has_variation_sequence, is_variable, named_instance_count, named_instance_face_index,
+    nearest_strike, select_strike, set_variation_axis_values, strike_sizes, synthesize_style,
+    variation_axes, OutlineSegment, VariationAxis,
+};
+use super::opentype::{font_revision, opentype_features, x_height, OpenTypeFeature};
+use super::preview_context::PreviewContext;
+use super::render_cache::RenderKey;
+#[cfg(feature = "shaping")]
+use super::shaping::{compose_glyphs, PositionedGlyph};
 use super::stateful_vec::StatefulVec;
 use super::{Error, Result};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct RenderSize {
     pub width: usize,
     pub height: usize,
@@ -30,45 +52,963 @@ impl RenderSize {
     pub fn new(width: usize, height: usize) -> Self {
         RenderSize { width, height }
     }
+
+    pub fn largest_square(&self) -> Self {
+        let length = min(self.width, self.height);
+        RenderSize::new(length, length)
+    }
+}
+
+// Hinting may grow the ink of a glyph by a pixel on each side
+const GLYPH_FIT_MARGIN_IN_PIXELS: usize = 2;
+
+// How long a render done in the background is waited for before drawing a placeholder instead,
+// most glyphs render well within it so browsing doesn't flash the placeholder
+const RENDER_TIME_BUDGET_MS: u64 = 30;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Sizing {
+    // The em square of the font is scaled to the requested render size
+    Pixels,
+
+    // The font is scaled to a point size on a display of the given resolution, regardless of the
+    // requested render size (the glyph is clipped to it)
+    Points { size: f64, dpi: u32 },
+}
+
+impl Default for Sizing {
+    fn default() -> Self {
+        Sizing::Pixels
+    }
+}
+
+// Strength of the synthetic bold in thousandths of the em size, the `FT_GlyphSlot_Embolden` helper
+// of FreeType uses 1/24 of it. Thinner than the minimum barely shows, thicker than the maximum
+// closes up the counters of most glyphs.
+pub const DEFAULT_BOLD_STRENGTH: u32 = 42;
+pub const MIN_BOLD_STRENGTH: u32 = 5;
+pub const MAX_BOLD_STRENGTH: u32 = 120;
+
+// How FreeType fits outlines to the pixel grid, which changes small sizes drastically. Hinting
+// doesn't apply to bitmap fonts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Hinting {
+    // The hinter of the font (its instructions), the auto-hinter for fonts without any
+    Default,
+    NoHinting,
+
+    // Hinted vertically only, keeping the shapes of the glyphs closer to their design
+    Light,
+
+    // Hinted for monochrome rendering, pixels are either covered or not
+    Mono,
+    ForceAutohint,
+}
+
+impl Hinting {
+    pub fn next(self) -> Self {
+        match self {
+            Hinting::Default => Hinting::NoHinting,
+            Hinting::NoHinting => Hinting::Light,
+            Hinting::Light => Hinting::Mono,
+            Hinting::Mono => Hinting::ForceAutohint,
+            Hinting::ForceAutohint => Hinting::Default,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Hinting::Default => "Default hinting",
+            Hinting::NoHinting => "No hinting",
+            Hinting::Light => "Light hinting",
+            Hinting::Mono => "Monochrome hinting",
+            Hinting::ForceAutohint => "Forced auto-hinting",
+        }
+    }
+
+    fn load_flags(self) -> LoadFlag {
+        match self {
+            Hinting::Default => LoadFlag::DEFAULT,
+            Hinting::NoHinting => LoadFlag::NO_HINTING,
+            Hinting::Light => LoadFlag::TARGET_LIGHT,
+            Hinting::Mono => LoadFlag::TARGET_MONO,
+            Hinting::ForceAutohint => LoadFlag::FORCE_AUTOHINT,
+        }
+    }
+
+    fn render_mode(self) -> RenderMode {
+        match self {
+            Hinting::Light => RenderMode::Light,
+            Hinting::Mono => RenderMode::Mono,
+            _ => RenderMode::Normal,
+        }
+    }
+}
+
+// Approximations of styles missing from a font, made by FreeType from the outline of the glyph,
+// and the hinting of the outline
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RenderOptions {
+    pub synthetic_bold: bool,
+    pub synthetic_oblique: bool,
+
+    // See `DEFAULT_BOLD_STRENGTH`, set with `with_bold_strength` to keep it within bounds
+    pub bold_strength: u32,
+
+    pub hinting: Hinting,
 }
 
-#[derive(Debug)]
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            synthetic_bold: false,
+            synthetic_oblique: false,
+            bold_strength: DEFAULT_BOLD_STRENGTH,
+            hinting: Hinting::Default,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn with_bold_strength(self, bold_strength: u32) -> Self {
+        RenderOptions {
+            bold_strength: min(max(bold_strength, MIN_BOLD_STRENGTH), MAX_BOLD_STRENGTH),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct RenderedCharacter {
     pub bitmap: Vec<Vec<u8>>, // TODO: This naive 2D vector is not really optimized
-    pub glyph_size: RenderSize, // TODO: Expose all glyph metrics
+
+    // Colors of the pixels of a color glyph (e.g. an emoji), whose bitmap holds their opacity
+    pub colors: Option<Vec<Vec<[u8; 3]>>>,
+
+    pub glyph_size: RenderSize,
+
+    // Metrics in pixels: the horizontal bearings are the position of the bitmap relative to the
+    // origin, y going up, and the vertical ones are relative to the origin of vertical layouts
+    // (e.g. vertical CJK text), which FreeType synthesizes for fonts without vertical metrics
+    pub bitmap_left: i32,
+    pub bitmap_top: i32,
+    pub advance_width: usize,
+    pub advance_height: usize,
+    pub vertical_bearing_x: i32,
+    pub vertical_bearing_y: i32,
+}
+
+// How long a render took, a render served from the cache keeps the duration it was rendered in
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RenderTiming {
+    pub duration: Duration,
+    pub cached: bool,
+}
+
+// Vertical metrics of the current font in pixels at a render size, y going up from the baseline
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FaceMetrics {
+    pub em_size: usize,
+    pub ascender: i32,
+    pub descender: i32,
+
+    // None for fonts which don't tell their x-height
+    pub x_height: Option<i32>,
+}
+
+// Bounds of the ink of a rendered glyph in pixels, relative to its origin with y going up
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BoundingBox {
+    pub x_min: i32,
+    pub y_min: i32,
+    pub x_max: i32,
+    pub y_max: i32,
+}
+
+impl RenderedCharacter {
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            x_min: self.bitmap_left,
+            y_min: self.bitmap_top - self.glyph_size.height as i32,
+            x_max: self.bitmap_left + self.glyph_size.width as i32,
+            y_max: self.bitmap_top,
+        }
+    }
+
+    // A successful render might have no ink at all, e.g. for whitespace or control characters
+    pub fn is_blank(&self) -> bool {
+        self.bitmap
+            .iter()
+            .all(|row| row.iter().all(|pixel| *pixel == 0))
+    }
+
+    // Fraction of the rendered area that has any ink, a rough measure of how dark a glyph is
+    pub fn ink_coverage(&self) -> f64 {
+        let pixel_count: usize = self.bitmap.iter().map(|row| row.len()).sum();
+        if pixel_count == 0 {
+            return 0.0;
+        }
+        let inked_pixel_count = self
+            .bitmap
+            .iter()
+            .flatten()
+            .filter(|pixel| **pixel != 0)
+            .count();
+        inked_pixel_count as f64 / pixel_count as f64
+    }
+
+    // The shape of the glyph without its coverage, row by row over the glyph size: a pixel is set
+    // if its coverage is at least `threshold`
+    pub fn mask(&self, threshold: u8) -> Vec<bool> {
+        let glyph_size = self.glyph_size;
+        self.bitmap
+            .iter()
+            .take(glyph_size.height)
+            .flat_map(|row| row.iter().take(glyph_size.width))
+            .map(|pixel| *pixel >= threshold)
+            .collect()
+    }
+
+    // The contour of the glyph: pixels with a coverage of at least `threshold` are kept only if a
+    // side of them touches a pixel below it (or the edge of the bitmap), the inside is cleared
+    pub fn edges(&self, threshold: u8) -> RenderedCharacter {
+        let is_ink = |x: isize, y: isize| {
+            y >= 0
+                && x >= 0
+                && self
+                    .bitmap
+                    .get(y as usize)
+                    .and_then(|row| row.get(x as usize))
+                    .map_or(false, |pixel| *pixel >= threshold)
+        };
+        let bitmap = self
+            .bitmap
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, pixel)| {
+                        let (x, y) = (x as isize, y as isize);
+                        let is_edge = !is_ink(x - 1, y)
+                            || !is_ink(x + 1, y)
+                            || !is_ink(x, y - 1)
+                            || !is_ink(x, y + 1);
+                        if is_ink(x, y) && is_edge {
+                            *pixel
+                        } else {
+                            0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        RenderedCharacter {
+            bitmap,
+            colors: self.colors.clone(),
+            glyph_size: self.glyph_size,
+            bitmap_left: self.bitmap_left,
+            bitmap_top: self.bitmap_top,
+            advance_width: self.advance_width,
+            advance_height: self.advance_height,
+            vertical_bearing_x: self.vertical_bearing_x,
+            vertical_bearing_y: self.vertical_bearing_y,
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl RenderedCharacter {
+    fn bitmap_dimensions(&self) -> (u32, u32) {
+        let width = self.bitmap.first().map_or(0, |row| row.len());
+        (width as u32, self.bitmap.len() as u32)
+    }
+
+    /// Converts the bitmap to a grayscale image, where each pixel is the ink coverage (i.e. a
+    /// white glyph on a black background).
+    ///
+    /// ```
+    /// use cicero::preview::{RenderSize, RenderedCharacter};
+    ///
+    /// let rendered_character = RenderedCharacter {
+    ///     bitmap: vec![vec![0, 255], vec![128, 0]],
+    ///     colors: None,
+    ///     glyph_size: RenderSize::new(2, 2),
+    ///     bitmap_left: 0,
+    ///     bitmap_top: 2,
+    ///     advance_width: 2,
+    ///     advance_height: 2,
+    ///     vertical_bearing_x: 0,
+    ///     vertical_bearing_y: 0,
+    /// };
+    ///
+    /// let image = rendered_character.to_gray_image();
+    /// assert_eq!(image.dimensions(), (2, 2));
+    /// assert_eq!(image.get_pixel(1, 0).0, [255]);
+    /// assert_eq!(image.get_pixel(0, 1).0, [128]);
+    /// ```
+    pub fn to_gray_image(&self) -> image::GrayImage {
+        let (width, height) = self.bitmap_dimensions();
+        image::GrayImage::from_fn(width, height, |x, y| {
+            image::Luma([self.bitmap[y as usize][x as usize]])
+        })
+    }
+
+    /// Converts the bitmap to an image with a transparent background and the ink coverage as
+    /// alpha, where the glyph is drawn in its own colors (e.g. emoji) or else with the given color.
+    ///
+    /// ```
+    /// use cicero::preview::{RenderSize, RenderedCharacter};
+    ///
+    /// let rendered_character = RenderedCharacter {
+    ///     bitmap: vec![vec![0, 255], vec![128, 0]],
+    ///     colors: None,
+    ///     glyph_size: RenderSize::new(2, 2),
+    ///     bitmap_left: 0,
+    ///     bitmap_top: 2,
+    ///     advance_width: 2,
+    ///     advance_height: 2,
+    ///     vertical_bearing_x: 0,
+    ///     vertical_bearing_y: 0,
+    /// };
+    /// let image = rendered_character.to_rgba_image([0, 0, 255]);
+    /// assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255, 255]);
+    /// assert_eq!(image.get_pixel(0, 1).0, [0, 0, 255, 128]);
+    ///
+    /// let color_character = RenderedCharacter {
+    ///     colors: Some(vec![vec![[255, 200, 0]; 2]; 2]),
+    ///     ..rendered_character
+    /// };
+    /// let image = color_character.to_rgba_image([0, 0, 255]);
+    /// assert_eq!(image.get_pixel(1, 0).0, [255, 200, 0, 255]);
+    /// ```
+    pub fn to_rgba_image(&self, color: [u8; 3]) -> image::RgbaImage {
+        let (width, height) = self.bitmap_dimensions();
+        image::RgbaImage::from_fn(width, height, |x, y| {
+            let (x, y) = (x as usize, y as usize);
+            let [red, green, blue] = match &self.colors {
+                Some(colors) => colors[y][x],
+                None => color,
+            };
+            image::Rgba([red, green, blue, self.bitmap[y][x]])
+        })
+    }
+}
+
+// Details of the font file being previewed
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FontInfo {
+    pub display_name: String,
+    pub path: String,
+
+    // As named by FreeType, e.g. "TrueType", "CFF" or "BDF"
+    pub format: Option<String>,
+
+    // The revision of the font from its head table, e.g. "2.001"
+    pub version: Option<String>,
+
+    pub glyph_count: usize,
+    pub is_variable: bool,
+    pub has_color_glyphs: bool,
+}
+
+// The glyph of the character within the current font
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GlyphInfo {
+    pub index: u32,
+
+    // The PostScript glyph name, None if the font has no glyph names
+    pub name: Option<String>,
+
+    // OpenType layout features of the font, sorted by tag
+    pub features: Vec<OpenTypeFeature>,
+}
+
+// Measurements which previews show on every draw, each kept for the render key it was measured for
+// until another key is asked for
+#[derive(Default)]
+struct Measurements {
+    glyph_metrics: Option<(RenderKey, Option<GlyphMetrics>)>,
+    face_metrics: Option<(RenderKey, FaceMetrics)>,
+    ink_coverage: Option<(RenderKey, f64)>,
+}
+
+// Metrics of the glyph of the character in font units, independent of any render size
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct GlyphMetrics {
+    pub units_per_em: u32,
+    pub advance_width: i64,
+    pub width: i64,
+    pub height: i64,
+    pub bearing_x: i64,
+    pub bearing_y: i64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OutlinePointKind {
+    OnCurve,
+    Conic,
+    Cubic,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct OutlinePoint {
+    pub x: i64,
+    pub y: i64,
+    pub kind: OutlinePointKind,
+}
+
+// A closed contour of a glyph outline, in font units
+pub type OutlineContour = Vec<OutlinePoint>;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FontAttemptOutcome {
+    Selected,
+    GlyphNotFound,
+    LoadFailed(String),
+    TimedOut,
+}
+
+// A font that was tried while looking for a font to preview the character with
+#[derive(Debug, Clone)]
+pub struct FontAttempt {
+    pub font_path: String,
+    pub outcome: FontAttemptOutcome,
 }
 
 pub struct CharacterPreview {
     pub chr: char,
 
     paths_for_matching_fonts: StatefulVec<String>,
+    font_display_names: Vec<String>, // Same order as `paths_for_matching_fonts`
+
+    // The faces of each font which have a glyph for the character and passed the exclusions and
+    // the style filter, in the same order as `paths_for_matching_fonts`
+    matched_faces: Vec<Vec<isize>>,
+    font_attempts: Vec<FontAttempt>,
 
-    library: Library, // TODO: Make this a long-living object to avoid re-init it for each character
+    context: PreviewContext,
     current_font: Face,
+    sizing: Sizing,
+    render_options: RenderOptions,
+
+    // Face of a font collection to preview for the preferred font path, other fonts use their first
+    // face until another face of the current font is selected
+    preferred_face: Option<(String, isize)>,
+    current_face_index: isize,
+    load_timeout: Option<Duration>,
+
+    // Names of the named instances of the current font if it is a variable font
+    named_instances: Vec<String>,
+    current_named_instance: Option<usize>,
+
+    // Read once per font, since the layout tables of a font can be large
+    opentype_features: Vec<OpenTypeFeature>,
+
+    // Timings to tell slow fonts (or systems) apart, the render duration is the one of the last
+    // render of the character
+    font_matching_duration: Duration,
+    last_render_timing: Cell<Option<RenderTiming>>,
+
+    // The design axes of the current font at its current variation, read again once the font, its
+    // named instance or its variation changes
+    variation_axes: RefCell<Option<Vec<VariationAxis>>>,
+
+    measurements: RefCell<Measurements>,
+
+    // Color bitmap strikes are scaled down to the requested size by this factor, 1 otherwise
+    strike_scale: Cell<f64>,
+
+    // Strike of a bitmap font chosen by hand, the strike closest to the render size otherwise
+    selected_strike: Option<usize>,
+
+    // Matching fonts left out of the list by the maximum number of fonts of the options
+    omitted_font_count: usize,
+
+    // The whole sequence (e.g. a ZWJ emoji sequence) rendered instead of the character alone, the
+    // character being its first one
+    sequence: Option<String>,
+}
+
+fn font_error(font_path: &str, operation: &'static str, error: freetype::Error) -> Error {
+    Error::FontOperationFailed {
+        font_path: font_path.to_owned(),
+        operation,
+        reason: error.to_string(),
+    }
+}
+
+fn display_name(family_name: Option<String>, style_name: Option<String>) -> String {
+    format!(
+        "{} - {}",
+        family_name.unwrap_or_else(|| "Unknown Family".to_owned()),
+        style_name.unwrap_or_else(|| "Unknown Style".to_owned())
+    )
+}
+
+fn font_display_name(face: &Face) -> String {
+    display_name(face.family_name(), face.style_name())
+}
+
+fn matched_font_display_name(font: &MatchedFont) -> String {
+    let non_empty = |name: &String| Some(name.clone()).filter(|name| !name.is_empty());
+    display_name(non_empty(&font.family), non_empty(&font.style))
+}
+
+// The preferred face for its font path, otherwise the first face of the font which matched
+fn face_index_of(
+    preferred_face: &Option<(String, isize)>,
+    font_path: &str,
+    matched_faces: &[isize],
+) -> isize {
+    match preferred_face {
+        Some((preferred_font_path, face_index)) if preferred_font_path == font_path => *face_index,
+        _ => matched_faces.first().copied().unwrap_or(0),
+    }
+}
+
+// Scales the em square so that the ink of the glyph fits `available`, small glyphs (e.g. a period)
+// are only scaled up until the em square fits the longer side of `available`
+fn fit_glyph(glyph_metrics: &GlyphMetrics, available: RenderSize) -> Option<RenderSize> {
+    if glyph_metrics.width <= 0 || glyph_metrics.height <= 0 || glyph_metrics.units_per_em == 0 {
+        return None;
+    }
+
+    let units_per_em = glyph_metrics.units_per_em as f64;
+    let width = available.width.saturating_sub(GLYPH_FIT_MARGIN_IN_PIXELS) as f64;
+    let height = available.height.saturating_sub(GLYPH_FIT_MARGIN_IN_PIXELS) as f64;
+    let pixels_per_em = f64::min(
+        f64::min(
+            width * units_per_em / glyph_metrics.width as f64,
+            height * units_per_em / glyph_metrics.height as f64,
+        ),
+        max(available.width, available.height) as f64,
+    ) as usize;
+    if pixels_per_em == 0 {
+        return None;
+    }
+    Some(RenderSize::new(pixels_per_em, pixels_per_em))
+}
+
+// Only the character map of the face is looked up, no glyph is loaded
+pub fn face_has_glyph(face: &Face, chr: char) -> bool {
+    face.get_char_index(chr as usize) != 0
+}
+
+fn is_color_bitmap(bitmap: &Bitmap) -> bool {
+    matches!(bitmap.pixel_mode(), Ok(PixelMode::Bgra))
+}
+
+// Reads the coverage of each pixel of a rendered glyph, along with its color for color glyphs.
+// Color glyphs are rendered into premultiplied BGRA pixels, their colors are unpremultiplied.
+fn read_glyph_bitmap(bitmap: &Bitmap) -> (Vec<Vec<u8>>, Option<Vec<Vec<[u8; 3]>>>) {
+    let (width, height) = (bitmap.width() as usize, bitmap.rows() as usize);
+
+    // Rows of the glyph bitmap might be padded
+    let pitch = bitmap.pitch().abs() as usize;
+    let buffer = bitmap.buffer();
+
+    match bitmap.pixel_mode() {
+        Ok(PixelMode::Bgra) => {}
+        // Bitmap strikes of monochrome fonts pack 8 pixels per byte
+        Ok(PixelMode::Mono) => {
+            let coverage = (0..height)
+                .map(|y| {
+                    (0..width)
+                        .map(|x| match (buffer[y * pitch + x / 8] >> (7 - x % 8)) & 1 {
+                            0 => 0,
+                            _ => 255,
+                        })
+                        .collect()
+                })
+                .collect();
+            return (coverage, None);
+        }
+        _ => {
+            let coverage = (0..height)
+                .map(|y| buffer[y * pitch..y * pitch + width].to_vec())
+                .collect();
+            return (coverage, None);
+        }
+    }
+
+    let mut coverage = vec![vec![0; width]; height];
+    let mut colors = vec![vec![[0; 3]; width]; height];
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = &buffer[y * pitch + x * 4..y * pitch + x * 4 + 4];
+            let (blue, green, red, alpha) = (pixel[0], pixel[1], pixel[2], pixel[3]);
+            coverage[y][x] = alpha;
+            if alpha != 0 {
+                let unpremultiply = |channel: u8| (channel as u32 * 255 / alpha as u32) as u8;
+                colors[y][x] = [
+                    unpremultiply(red),
+                    unpremultiply(green),
+                    unpremultiply(blue),
+                ];
+            }
+        }
+    }
+    (coverage, Some(colors))
+}
+
+fn scaled_length(length: usize, scale: f64) -> usize {
+    (length as f64 * scale).ceil() as usize
+}
+
+// Scales a color glyph down by averaging the pixels covered by each scaled pixel, colors are
+// weighted by their coverage so that transparent pixels don't darken the edges
+fn scale_down_color_glyph(
+    coverage: &[Vec<u8>],
+    colors: &[Vec<[u8; 3]>],
+    scale: f64,
+) -> (Vec<Vec<u8>>, Vec<Vec<[u8; 3]>>) {
+    let (width, height) = (coverage.first().map_or(0, |row| row.len()), coverage.len());
+    let (scaled_width, scaled_height) = (scaled_length(width, scale), scaled_length(height, scale));
+    let source_range = |scaled_index: usize, length: usize| {
+        let start = (scaled_index as f64 / scale).floor() as usize;
+        let end = ((scaled_index + 1) as f64 / scale).ceil() as usize;
+        min(start, length)..min(max(end, start + 1), length)
+    };
+
+    let mut scaled_coverage = vec![vec![0; scaled_width]; scaled_height];
+    let mut scaled_colors = vec![vec![[0; 3]; scaled_width]; scaled_height];
+    for scaled_y in 0..scaled_height {
+        for scaled_x in 0..scaled_width {
+            let (mut coverage_sum, mut color_sums, mut pixel_count) = (0, [0; 3], 0);
+            for y in source_range(scaled_y, height) {
+                for x in source_range(scaled_x, width) {
+                    let alpha = coverage[y][x] as u32;
+                    coverage_sum += alpha;
+                    for (color_sum, channel) in color_sums.iter_mut().zip(&colors[y][x]) {
+                        *color_sum += *channel as u32 * alpha;
+                    }
+                    pixel_count += 1;
+                }
+            }
+            if pixel_count == 0 || coverage_sum == 0 {
+                continue;
+            }
+            scaled_coverage[scaled_y][scaled_x] = (coverage_sum / pixel_count) as u8;
+            for (channel, color_sum) in scaled_colors[scaled_y][scaled_x]
+                .iter_mut()
+                .zip(&color_sums)
+            {
+                *channel = (color_sum / coverage_sum) as u8;
+            }
+        }
+    }
+    (scaled_coverage, scaled_colors)
+}
+
+// Copies the top left corner of the pixels into rows of the size, padded with `blank` pixels
+fn crop_pixels<T: Copy>(rows: &[Vec<T>], size: RenderSize, blank: T) -> Vec<Vec<T>> {
+    let mut pixels = vec![vec![blank; size.width]; size.height];
+    for (row, cropped_row) in rows.iter().zip(pixels.iter_mut()) {
+        let width = min(row.len(), size.width);
+        cropped_row[..width].copy_from_slice(&row[..width]);
+    }
+    pixels
+}
+
+// Loads a font to preview the character with, on a separate thread when a load timeout is given.
+// Fontconfig may list a font for a character its face doesn't map (e.g. another face of a font
+// collection), such fonts are rejected as well.
+fn load_font(
+    library: &Library,
+    font_path: &str,
+    face_index: isize,
+    chr: char,
+    load_timeout: Option<Duration>,
+    font_attempts: &mut Vec<FontAttempt>,
+) -> Result<Face> {
+    let face = match load_face(library, font_path, face_index, chr, load_timeout) {
+        FaceLoad::Loaded(face) => face,
+        FaceLoad::TimedOut => {
+            font_attempts.push(FontAttempt {
+                font_path: font_path.to_owned(),
+                outcome: FontAttemptOutcome::TimedOut,
+            });
+            return Err(Box::new(Error::FontLoadTimedOut {
+                font_path: font_path.to_owned(),
+            }));
+        }
+    };
+
+    match face {
+        Ok(face) if !face_has_glyph(&face, chr) => {
+            debug!("{} has no glyph for U+{:04X}", font_path, chr as u32);
+            font_attempts.push(FontAttempt {
+                font_path: font_path.to_owned(),
+                outcome: FontAttemptOutcome::GlyphNotFound,
+            });
+            Err(Box::new(Error::GlyphNotInFont {
+                chr,
+                font_path: font_path.to_owned(),
+            }))
+        }
+        Ok(face) => {
+            info!("Previewing U+{:04X} with {}", chr as u32, font_path);
+            font_attempts.push(FontAttempt {
+                font_path: font_path.to_owned(),
+                outcome: FontAttemptOutcome::Selected,
+            });
+            Ok(face)
+        }
+        Err(error) => {
+            warn!("Failed to load {}: {}", font_path, error);
+            font_attempts.push(FontAttempt {
+                font_path: font_path.to_owned(),
+                outcome: FontAttemptOutcome::LoadFailed(error.to_string()),
+            });
+            Err(Box::new(font_error(font_path, "load", error)))
+        }
+    }
 }
 
 impl CharacterPreview {
-    pub fn new(chr: char, preferred_font_path: Option<&String>) -> Result<CharacterPreview> {
-        let font_paths = fonts_for(chr)?;
-        if font_paths.is_empty() {
+    // The font is selected from the fonts matching the character, preferring the given font path,
+    // then the most regular style of the font family the options prefer for the block of the
+    // character, then of their preferred font family
+    pub fn new(
+        context: &PreviewContext,
+        chr: char,
+        preferred_font_path: Option<&String>,
+        font_match_options: &FontMatchOptions,
+    ) -> Result<CharacterPreview> {
+        CharacterPreview::with_face_index(context, chr, preferred_font_path, 0, font_match_options)
+    }
+
+    // Same as `new`, but previews the face at `face_index` of the preferred font path, e.g. a face
+    // of a TrueType collection other than the first one
+    pub fn with_face_index(
+        context: &PreviewContext,
+        chr: char,
+        preferred_font_path: Option<&String>,
+        face_index: isize,
+        font_match_options: &FontMatchOptions,
+    ) -> Result<CharacterPreview> {
+        let font_matching_started_at = Instant::now();
+        let fonts = exclude_fonts(context.fonts_for(chr)?, &font_match_options.excluded_fonts);
+        let mut fonts = filter_fonts(fonts, &font_match_options.style_filter);
+        if font_match_options.deduplicate {
+            fonts = deduplicate_fonts(fonts, &mut context.content_hashes());
+        }
+        prioritize_fonts(&mut fonts, &font_match_options.directory_priority);
+        let omitted_font_count = match font_match_options.max_fonts {
+            Some(max_fonts) => limit_fonts(&mut fonts, max_fonts),
+            None => 0,
+        };
+        let font_matching_duration = font_matching_started_at.elapsed();
+        if fonts.is_empty() {
             return Err(Box::new(Error::GlyphNotFound { chr }));
         }
 
+        let mut font_attempts = vec![];
+
+        // A preferred family without a font matching the character gives way to the next one
+        let family_font_path = font_match_options
+            .preferred_font_families_of(chr)
+            .into_iter()
+            .find_map(|family| font_of_family(&fonts, family))
+            .map(|font| font.path.clone());
+
+        // Faces of a font collection share the same path, the first face names the font, even when
+        // they aren't listed next to each other. Named instances of a variable font are listed as
+        // faces too, but previewed from their face.
+        let mut font_paths: Vec<String> = vec![];
+        let mut font_display_names = vec![];
+        let mut matched_faces: Vec<Vec<isize>> = vec![];
+        let mut font_indices: HashMap<String, usize> = HashMap::new();
+        for font in fonts {
+            let font_index = *font_indices.entry(font.path.clone()).or_insert_with(|| {
+                font_display_names.push(matched_font_display_name(&font));
+                font_paths.push(font.path.clone());
+                matched_faces.push(vec![]);
+                font_paths.len() - 1
+            });
+            let faces = &mut matched_faces[font_index];
+            let face_index = font.face_index & 0xFFFF;
+            if !faces.contains(&face_index) {
+                faces.push(face_index);
+            }
+        }
+        for faces in &mut matched_faces {
+            faces.sort_unstable();
+        }
+
         let mut paths_for_matching_fonts = StatefulVec::new(font_paths, Some(0));
+        if let Some(font_path) = &family_font_path {
+            paths_for_matching_fonts.select_if_found(font_path);
+        }
         if let Some(font_path) = preferred_font_path {
             paths_for_matching_fonts.select_if_found(font_path);
+            if paths_for_matching_fonts.current_item() != Some(font_path) {
+                font_attempts.push(FontAttempt {
+                    font_path: font_path.to_owned(),
+                    outcome: FontAttemptOutcome::GlyphNotFound,
+                });
+            }
         }
 
-        let library = Library::init()?;
-        let current_font =
-            library.new_face(&paths_for_matching_fonts.current_item().unwrap(), 0)?;
+        let preferred_face = preferred_font_path.map(|font_path| (font_path.clone(), face_index));
+
+        let (current_font, current_face_index) = loop {
+            let font_path = paths_for_matching_fonts.current_item().unwrap().to_owned();
+            let face_index = face_index_of(
+                &preferred_face,
+                &font_path,
+                &matched_faces[paths_for_matching_fonts.current_index().unwrap()],
+            );
+            match load_font(
+                context.library(),
+                &font_path,
+                face_index,
+                chr,
+                font_match_options.load_timeout,
+                &mut font_attempts,
+            ) {
+                Ok(face) => break (face, face_index),
+                Err(error) if !paths_for_matching_fonts.has_next() => return Err(error),
+                Err(_) => paths_for_matching_fonts.select_next(),
+            }
+        };
 
-        Ok(CharacterPreview {
+        let mut character_preview = CharacterPreview {
             chr,
             paths_for_matching_fonts,
-            library,
+            font_display_names,
+            matched_faces,
+            font_attempts,
+            context: context.clone(),
             current_font,
-        })
+            sizing: Sizing::default(),
+            render_options: RenderOptions::default(),
+            preferred_face,
+            current_face_index,
+            load_timeout: font_match_options.load_timeout,
+            named_instances: vec![],
+            current_named_instance: None,
+            opentype_features: vec![],
+            font_matching_duration,
+            last_render_timing: Cell::new(None),
+            variation_axes: RefCell::new(None),
+            measurements: RefCell::new(Measurements::default()),
+            strike_scale: Cell::new(1.0),
+            selected_strike: None,
+            omitted_font_count,
+            sequence: None,
+        };
+        character_preview.current_font_changed();
+        Ok(character_preview)
+    }
+
+    // Previews a sequence of characters composed into a single glyph by shaping (e.g. a family or
+    // profession emoji, joined by ZWJ), with the fonts matching its first character. Without the
+    // shaping feature, only the first character is rendered. The face index applies to the
+    // preferred font path, as with `with_face_index`.
+    pub fn for_sequence(
+        context: &PreviewContext,
+        sequence: &str,
+        preferred_font_path: Option<&String>,
+        face_index: isize,
+        font_match_options: &FontMatchOptions,
+    ) -> Result<CharacterPreview> {
+        let mut characters = sequence.chars();
+        let chr = characters.next().ok_or(Error::EmptySequence)?;
+        let mut character_preview = CharacterPreview::with_face_index(
+            context,
+            chr,
+            preferred_font_path,
+            face_index,
+            font_match_options,
+        )?;
+        if characters.next().is_some() {
+            character_preview.sequence = Some(sequence.to_owned());
+        }
+        Ok(character_preview)
+    }
+
+    // Previews the character exactly as rendered for the key, with its font alone and without
+    // matching fonts again, e.g. to render it on another thread
+    pub(super) fn for_render_key(
+        context: &PreviewContext,
+        render_key: &RenderKey,
+    ) -> Result<CharacterPreview> {
+        let current_font = load_font(
+            context.library(),
+            &render_key.font_path,
+            render_key.face_index,
+            render_key.chr,
+            None,
+            &mut vec![],
+        )?;
+        let mut character_preview = CharacterPreview {
+            chr: render_key.chr,
+            paths_for_matching_fonts: StatefulVec::new(vec![render_key.font_path.clone()], Some(0)),
+            font_display_names: vec![font_display_name(&current_font)],
+            matched_faces: vec![vec![render_key.face_index & 0xFFFF]],
+            font_attempts: vec![],
+            context: context.clone(),
+            current_font,
+            sizing: Sizing::default(),
+            render_options: RenderOptions::default(),
+            preferred_face: None,
+            current_face_index: render_key.face_index,
+            load_timeout: None,
+            named_instances: vec![],
+            current_named_instance: None,
+            opentype_features: vec![],
+            font_matching_duration: Duration::default(),
+            last_render_timing: Cell::new(None),
+            variation_axes: RefCell::new(None),
+            measurements: RefCell::new(Measurements::default()),
+            strike_scale: Cell::new(1.0),
+            selected_strike: None,
+            omitted_font_count: 0,
+            sequence: None,
+        };
+        character_preview.set_render_key(render_key)?;
+        Ok(character_preview)
+    }
+
+    // Whether the key is rendered with the font of the preview, which can then be reused for it
+    // with `set_render_key` instead of opening the font again
+    pub(super) fn has_font_of(&self, render_key: &RenderKey) -> bool {
+        self.get_current_font_path().as_ref() == Some(&render_key.font_path)
+            && self.current_face_index == render_key.face_index
+    }
+
+    // Previews the character of the key with its options, the font has to be that of the key
+    pub(super) fn set_render_key(&mut self, render_key: &RenderKey) -> Result<()> {
+        self.chr = render_key.chr;
+        self.sequence = render_key.text.clone();
+        self.sizing = render_key.sizing();
+        self.render_options = render_key.render_options;
+        self.selected_strike = render_key.selected_strike;
+        self.variation_axes.replace(None);
+        if !render_key.variation.is_empty() {
+            let values: Vec<f64> = render_key
+                .variation
+                .iter()
+                .map(|value| f64::from_bits(*value))
+                .collect();
+            set_variation_axis_values(&self.current_font, &values).map_err(|error| {
+                font_error(&render_key.font_path, "set the variation of", error)
+            })?;
+        }
+        Ok(())
+    }
+
+    // The sequence previewed instead of the character alone, if any
+    pub fn sequence(&self) -> Option<&str> {
+        self.sequence.as_deref()
+    }
+
+    // The context shared with other previews, e.g. to preview another character along this one
+    pub fn context(&self) -> &PreviewContext {
+        &self.context
     }
 
     pub fn get_current_font_path(&self) -> Option<String> {
@@ -78,68 +1018,2049 @@ impl CharacterPreview {
         }
     }
 
+    // The index of the current font in `matching_font_paths`
+    pub fn current_font_index(&self) -> Option<usize> {
+        self.paths_for_matching_fonts.current_index()
+    }
+
+    pub fn matching_font_paths(&self) -> &[String] {
+        self.paths_for_matching_fonts.items()
+    }
+
+    // Display names of every matching font, in the order they are cycled through. The names come
+    // from fontconfig while matching, so no font has to be opened for them.
+    pub fn matching_font_display_names(&self) -> &[String] {
+        &self.font_display_names
+    }
+
+    // Fonts tried by the last font selection, in order, the last successful one is `Selected`
+    pub fn font_attempts(&self) -> &[FontAttempt] {
+        &self.font_attempts
+    }
+
+    // Display names of the matching fonts with a glyph for the character followed by the variation
+    // selector, e.g. those providing its emoji presentation. Every matching font is opened for it,
+    // those which fail to open are left out.
+    pub fn fonts_with_variation_sequence(&self, variation_selector: char) -> Vec<String> {
+        self.paths_for_matching_fonts
+            .items()
+            .iter()
+            .zip(&self.font_display_names)
+            .filter(|(font_path, _)| {
+                self.context
+                    .library()
+                    .new_face(font_path.as_str(), self.face_index_of(font_path))
+                    .map_or(false, |face| {
+                        has_variation_sequence(&face, self.chr, variation_selector)
+                    })
+            })
+            .map(|(_, font_display_name)| font_display_name.clone())
+            .collect()
+    }
+
+    // Cycling past the last matching font selects the first one, and vice versa
+    pub fn set_font_cycling_wraps(&mut self, wraps: bool) {
+        self.paths_for_matching_fonts.set_wraps(wraps);
+    }
+
+    // The number of faces in the file of the current font, more than one for font collections
+    // (e.g. the JP, KR, SC and TC faces of Noto Sans CJK)
+    pub fn face_count(&self) -> usize {
+        max(self.current_font.raw().num_faces, 1) as usize
+    }
+
+    pub fn current_face_index(&self) -> usize {
+        self.current_face_index as usize
+    }
+
+    pub fn is_at_last_font(&self) -> bool {
+        self.paths_for_matching_fonts.is_last() && self.next_faces().is_empty()
+    }
+
+    // The matched faces of the current font before and after the current face
+    fn previous_faces(&self) -> Vec<isize> {
+        self.current_matched_faces()
+            .iter()
+            .copied()
+            .filter(|face_index| *face_index < self.current_face_index & 0xFFFF)
+            .rev()
+            .collect()
+    }
+
+    fn next_faces(&self) -> Vec<isize> {
+        self.current_matched_faces()
+            .iter()
+            .copied()
+            .filter(|face_index| *face_index > self.current_face_index & 0xFFFF)
+            .collect()
+    }
+
+    fn current_matched_faces(&self) -> &[isize] {
+        self.paths_for_matching_fonts
+            .current_index()
+            .and_then(|index| self.matched_faces.get(index))
+            .map_or(&[], |faces| faces.as_slice())
+    }
+
+    // The matched faces of a font collection are stepped through before moving to another font
     pub fn has_previous_font(&self) -> bool {
-        self.paths_for_matching_fonts.has_previous()
+        !self.previous_faces().is_empty() || self.paths_for_matching_fonts.has_previous()
     }
 
     pub fn select_previous_font(&mut self) -> Result<()> {
-        self.paths_for_matching_fonts.select_previous();
-        self.current_font = match self.paths_for_matching_fonts.current_item() {
-            Some(current_font_path) => self.library.new_face(current_font_path, 0)?,
-            None => return Err(Box::new(Error::GlyphNotFound { chr: self.chr })),
-        };
+        if self.select_face_from(self.previous_faces()) {
+            return Ok(());
+        }
+        if !self.paths_for_matching_fonts.has_previous() {
+            return Ok(());
+        }
+        self.select_font_with(StatefulVec::has_previous, StatefulVec::select_previous)?;
+
+        // Moving back into a font collection selects its last matched face
+        let mut faces = self.next_faces();
+        faces.reverse();
+        self.select_face_from(faces);
         Ok(())
     }
 
     pub fn has_next_font(&self) -> bool {
-        self.paths_for_matching_fonts.has_next()
+        !self.next_faces().is_empty() || self.paths_for_matching_fonts.has_next()
     }
 
     pub fn select_next_font(&mut self) -> Result<()> {
-        self.paths_for_matching_fonts.select_next();
-        self.current_font = match self.paths_for_matching_fonts.current_item() {
-            Some(current_font_path) => self.library.new_face(current_font_path, 0)?,
-            None => return Err(Box::new(Error::GlyphNotFound { chr: self.chr })),
+        if self.select_face_from(self.next_faces()) {
+            return Ok(());
+        }
+        self.select_font_with(StatefulVec::has_next, StatefulVec::select_next)
+    }
+
+    // Selects the first face of the current font file which has a glyph for the character, in the
+    // order of `face_indices`. Returns false if none of them could be loaded.
+    fn select_face_from(&mut self, face_indices: Vec<isize>) -> bool {
+        let font_path = match self.get_current_font_path() {
+            Some(font_path) => font_path,
+            None => return false,
         };
-        Ok(())
+        for face_index in face_indices {
+            if let Ok(face) = load_font(
+                self.context.library(),
+                &font_path,
+                face_index,
+                self.chr,
+                self.load_timeout,
+                &mut vec![],
+            ) {
+                self.current_font = face;
+                self.current_face_index = face_index;
+                self.current_font_changed();
+                return true;
+            }
+        }
+        false
     }
 
-    pub fn get_current_font_display_name(&self) -> String {
-        let family_name = self
-            .current_font
-            .family_name()
-            .unwrap_or_else(|| "Unknown Family".to_owned());
-        let style_name = self
-            .current_font
-            .style_name()
-            .unwrap_or_else(|| "Unknown Style".to_owned());
-        format!("{} - {}", family_name, style_name)
+    // Selects the matching font at `index` of `matching_font_paths`, e.g. picked from a list of them,
+    // at the face it is matched with. The current font is kept if it fails to load.
+    pub fn select_font(&mut self, index: usize) -> Result<()> {
+        let font_path = match self.paths_for_matching_fonts.items().get(index) {
+            Some(font_path) => font_path.clone(),
+            None => return Ok(()),
+        };
+        let face_index = self.face_index_of(&font_path);
+        self.font_attempts.clear();
+        self.current_font = load_font(
+            self.context.library(),
+            &font_path,
+            face_index,
+            self.chr,
+            self.load_timeout,
+            &mut self.font_attempts,
+        )?;
+        self.paths_for_matching_fonts.select_if_found(&font_path);
+        self.current_face_index = face_index;
+        self.current_font_changed();
+        Ok(())
     }
 
-    pub fn render(&self, size: RenderSize) -> Result<RenderedCharacter> {
-        self.current_font
-            .set_pixel_sizes(size.width as u32, size.height as u32)?;
-        self.current_font
-            .load_char(self.chr as usize, freetype::face::LoadFlag::RENDER)?;
+    pub fn select_first_font(&mut self) -> Result<()> {
+        let previous_font_path = self.get_current_font_path();
+        self.paths_for_matching_fonts.unselect();
 
-        let (bitmap, glyph_size) = {
-            let mut pixels = vec![vec![0; size.width as usize]; size.height as usize];
+        let result = self.select_font_with(StatefulVec::has_next, StatefulVec::select_next);
+        if result.is_err() {
+            if let Some(previous_font_path) = previous_font_path {
+                self.paths_for_matching_fonts
+                    .select_if_found(&previous_font_path);
+            }
+        }
+        result
+    }
 
-            let glyph_bitmap = self.current_font.glyph().bitmap();
-            let x_max = min(size.width, glyph_bitmap.width() as usize);
-            let y_max = min(size.height, glyph_bitmap.rows() as usize);
+    // Moves through the matching fonts until one of them can be loaded, fonts which fail to load
+    // are skipped (and recorded in `font_attempts`)
+    fn select_font_with(
+        &mut self,
+        has_more: fn(&StatefulVec<String>) -> bool,
+        select: fn(&mut StatefulVec<String>),
+    ) -> Result<()> {
+        if !has_more(&self.paths_for_matching_fonts) {
+            return Ok(());
+        }
 
-            let glyph_bitmap_buffer = glyph_bitmap.buffer();
+        let previous_font_path = self.get_current_font_path();
+        self.font_attempts.clear();
 
-            for x in 0..x_max {
-                for y in 0..y_max {
-                    pixels[y][x] = glyph_bitmap_buffer[y * x_max + x];
+        // Each font is tried at most once, even when cycling wraps around
+        let mut last_error = None;
+        for _ in 0..self.paths_for_matching_fonts.items().len() {
+            if !has_more(&self.paths_for_matching_fonts) {
+                break;
+            }
+            select(&mut self.paths_for_matching_fonts);
+            let font_path = self
+                .paths_for_matching_fonts
+                .current_item()
+                .unwrap()
+                .to_owned();
+            let face_index = self.face_index_of(&font_path);
+            match load_font(
+                self.context.library(),
+                &font_path,
+                face_index,
+                self.chr,
+                self.load_timeout,
+                &mut self.font_attempts,
+            ) {
+                Ok(face) => {
+                    self.current_font = face;
+                    self.current_face_index = face_index;
+                    self.current_font_changed();
+                    return Ok(());
                 }
+                Err(error) => last_error = Some(error),
             }
+        }
+
+        if let Some(previous_font_path) = previous_font_path {
+            self.paths_for_matching_fonts
+                .select_if_found(&previous_font_path);
+        }
+        Err(last_error.unwrap_or_else(|| Box::new(Error::GlyphNotFound { chr: self.chr })))
+    }
+
+    fn face_index_of(&self, font_path: &str) -> isize {
+        let matched_faces = self
+            .paths_for_matching_fonts
+            .items()
+            .iter()
+            .position(|path| path == font_path)
+            .map_or(&[][..], |index| self.matched_faces[index].as_slice());
+        face_index_of(&self.preferred_face, font_path, matched_faces)
+    }
+
+    fn current_font_changed(&mut self) {
+        self.selected_strike = None;
+        self.variation_axes.replace(None);
+        self.update_named_instances();
+        self.opentype_features = opentype_features(
+            &self.current_font,
+            self.current_font.get_char_index(self.chr as usize),
+        );
+    }
+
+    fn update_named_instances(&mut self) {
+        self.current_named_instance = None;
+        self.named_instances = match self.get_current_font_path() {
+            Some(font_path) => (0..named_instance_count(&self.current_font))
+                .map(|instance| {
+                    self.context
+                        .library()
+                        .new_face(
+                            &font_path,
+                            named_instance_face_index(self.current_face_index, instance),
+                        )
+                        .ok()
+                        .and_then(|face| face.style_name())
+                        .unwrap_or_else(|| format!("Instance {}", instance + 1))
+                })
+                .collect(),
+            None => vec![],
+        };
+    }
+
+    // Loads the current font again from its file, e.g. after it has been changed on disk. Named
+    // instances and variations of the previous face are reset.
+    pub fn reload_current_font(&mut self) -> Result<()> {
+        let font_path = match self.get_current_font_path() {
+            Some(font_path) => font_path,
+            None => return Ok(()),
+        };
+        self.current_font = load_font(
+            self.context.library(),
+            &font_path,
+            self.current_face_index,
+            self.chr,
+            self.load_timeout,
+            &mut vec![],
+        )?;
+        self.context.forget_font_renders(&font_path);
+        self.current_font_changed();
+        Ok(())
+    }
+
+    // Named instances (e.g. "Thin", "Black") of the current font, empty if it isn't a variable font
+    pub fn named_instances(&self) -> &[String] {
+        &self.named_instances
+    }
+
+    pub fn current_named_instance(&self) -> Option<usize> {
+        self.current_named_instance
+    }
+
+    // Selects a named instance of the current font, or the default instance with `None`
+    pub fn select_named_instance(&mut self, instance: Option<usize>) -> Result<()> {
+        let font_path = match self.get_current_font_path() {
+            Some(font_path) => font_path,
+            None => return Ok(()),
+        };
+        let face_index = match instance {
+            Some(instance) if instance < self.named_instances.len() => {
+                named_instance_face_index(self.current_face_index, instance)
+            }
+            Some(_) => return Ok(()),
+            None => self.current_face_index,
+        };
+
+        self.current_font = load_font(
+            self.context.library(),
+            &font_path,
+            face_index,
+            self.chr,
+            self.load_timeout,
+            &mut vec![],
+        )?;
+        self.current_named_instance = instance;
+        self.variation_axes.replace(None);
+        Ok(())
+    }
+
+    // Design axes of the current font with their current values, empty if it isn't a variable font
+    pub fn variation_axes(&self) -> Vec<VariationAxis> {
+        self.variation_axes
+            .borrow_mut()
+            .get_or_insert_with(|| variation_axes(self.context.library(), &self.current_font))
+            .clone()
+    }
+
+    // Sets the value of a design axis of the current font, clamped to the range of the axis. The
+    // font is no longer at a named instance afterwards.
+    pub fn set_variation_axis_value(&mut self, axis: usize, value: f64) -> Result<()> {
+        let variation_axes = self.variation_axes();
+        let variation_axis = match variation_axes.get(axis) {
+            Some(variation_axis) => variation_axis,
+            None => return Ok(()),
+        };
+
+        let mut values: Vec<f64> = variation_axes
+            .iter()
+            .map(|variation_axis| variation_axis.value)
+            .collect();
+        values[axis] = value
+            .max(variation_axis.minimum)
+            .min(variation_axis.maximum);
+        self.variation_axes.replace(None);
+        set_variation_axis_values(&self.current_font, &values)
+            .map_err(|error| self.current_font_error("set the variation of", error))?;
+        self.current_named_instance = None;
+        Ok(())
+    }
+
+    pub fn get_current_font_display_name(&self) -> String {
+        font_display_name(&self.current_font)
+    }
+
+    // Every character the current font has a glyph for, in code point order
+    pub fn font_repertoire(&self) -> Vec<char> {
+        charmap_characters(&self.current_font)
+    }
+
+    pub fn font_info(&self) -> FontInfo {
+        FontInfo {
+            display_name: self.get_current_font_display_name(),
+            path: self.get_current_font_path().unwrap_or_default(),
+            format: font_format(&self.current_font),
+            version: font_revision(&self.current_font),
+            glyph_count: max(self.current_font.raw().num_glyphs, 0) as usize,
+            is_variable: is_variable(&self.current_font),
+            has_color_glyphs: has_color_glyphs(&self.current_font),
+        }
+    }
+
+    // Whether the current font has a glyph for any character, a cheap check which doesn't load
+    // (let alone render) the glyph
+    pub fn has_glyph(&self, chr: char) -> bool {
+        face_has_glyph(&self.current_font, chr)
+    }
+
+    pub fn glyph_info(&self) -> GlyphInfo {
+        let index = self.current_font.get_char_index(self.chr as usize);
+        GlyphInfo {
+            index,
+            name: glyph_name(&self.current_font, index),
+            features: self.opentype_features.clone(),
+        }
+    }
+
+    // Only available for outline fonts, bitmap fonts have no font units to measure glyphs in
+    pub fn glyph_metrics(&self) -> Result<GlyphMetrics> {
+        self.current_font
+            .load_char(self.chr as usize, LoadFlag::NO_SCALE)
+            .map_err(|error| self.current_font_error("load a glyph from", error))?;
+
+        let metrics = self.current_font.glyph().metrics();
+        Ok(GlyphMetrics {
+            units_per_em: self.current_font.em_size() as u32,
+            advance_width: metrics.horiAdvance as i64,
+            width: metrics.width as i64,
+            height: metrics.height as i64,
+            bearing_x: metrics.horiBearingX as i64,
+            bearing_y: metrics.horiBearingY as i64,
+        })
+    }
+
+    // Same as `glyph_metrics`, measured once per font, character and variation
+    pub fn cached_glyph_metrics(&self) -> Option<GlyphMetrics> {
+        // Glyph metrics don't depend on the size, the key of any size stands for all of them
+        let render_key =
+            self.render_key_of(self.chr, None, RenderSize::new(0, 0), false, Sizing::Pixels);
+        if let Some((measured_key, glyph_metrics)) = &self.measurements.borrow().glyph_metrics {
+            if *measured_key == render_key {
+                return *glyph_metrics;
+            }
+        }
+        let glyph_metrics = self.glyph_metrics().ok();
+        self.measurements.borrow_mut().glyph_metrics = Some((render_key, glyph_metrics));
+        glyph_metrics
+    }
+
+    // Fraction of the glyph rendered at `size` covered by ink, measured once per render. None while
+    // the render is pending in the background or if it failed.
+    pub fn ink_coverage_in_background(&self, size: RenderSize) -> Option<f64> {
+        let render_key = self.render_key_of(self.chr, None, size, true, Sizing::Pixels);
+        if let Some((measured_key, ink_coverage)) = &self.measurements.borrow().ink_coverage {
+            if *measured_key == render_key {
+                return Some(*ink_coverage);
+            }
+        }
+        let ink_coverage = self
+            .render_key_in_background(render_key.clone(), || self.render_character(self.chr, size))?
+            .ok()?
+            .ink_coverage();
+        self.measurements.borrow_mut().ink_coverage = Some((render_key, ink_coverage));
+        Some(ink_coverage)
+    }
+
+    // Attaches the path of the current font to an error of a FreeType operation on it
+    fn current_font_error(&self, operation: &'static str, error: freetype::Error) -> Error {
+        font_error(
+            &self.get_current_font_path().unwrap_or_default(),
+            operation,
+            error,
+        )
+    }
+
+    pub fn set_sizing(&mut self, sizing: Sizing) {
+        self.sizing = sizing;
+    }
+
+    pub fn render_options(&self) -> RenderOptions {
+        self.render_options
+    }
+
+    // Synthetic styles only apply to outline fonts, they are ignored for bitmap fonts
+    pub fn set_render_options(&mut self, render_options: RenderOptions) {
+        self.render_options = render_options;
+    }
+
+    pub fn is_outline_font(&self) -> bool {
+        self.current_font.is_scalable()
+    }
+
+    // Sizes in pixels per em of the bitmap strikes of the current font, empty for outline fonts
+    pub fn strike_sizes(&self) -> Vec<usize> {
+        if self.current_font.is_scalable() {
+            return vec![];
+        }
+        strike_sizes(&self.current_font)
+    }
+
+    // The strike chosen by hand with `select_next_strike`, None when the closest one is used
+    pub fn selected_strike(&self) -> Option<usize> {
+        self.selected_strike
+    }
+
+    // Cycles through the strikes of a bitmap font by hand, then back to the closest strike. Returns
+    // false if the current font has no strikes.
+    pub fn select_next_strike(&mut self) -> bool {
+        let strike_count = self.strike_sizes().len();
+        if strike_count == 0 {
+            return false;
+        }
+        self.selected_strike = match self.selected_strike {
+            None => Some(0),
+            Some(strike_index) if strike_index + 1 < strike_count => Some(strike_index + 1),
+            Some(_) => None,
+        };
+        true
+    }
+
+    // Whether the current font has color glyphs (CBDT, COLR or sbix), e.g. an emoji font
+    pub fn has_color_glyphs(&self) -> bool {
+        has_color_glyphs(&self.current_font)
+    }
+
+    pub fn render(&self, size: RenderSize) -> Result<RenderedCharacter> {
+        self.render_cached(size, true)
+    }
+
+    pub fn omitted_font_count(&self) -> usize {
+        self.omitted_font_count
+    }
+
+    // How long fontconfig took to list the fonts matching the character
+    pub fn font_matching_duration(&self) -> Duration {
+        self.font_matching_duration
+    }
+
+    // How long FreeType took to size, load and render the glyph of the character the last time it
+    // was rendered (with `render` or `render_uncropped`), even if it was then served from the cache
+    pub fn last_render_timing(&self) -> Option<RenderTiming> {
+        self.last_render_timing.get()
+    }
+
+    // Forgets the last render timing, e.g. before drawing a frame to time only its own render
+    pub fn clear_render_timing(&self) {
+        self.last_render_timing.set(None);
+    }
+
+    // The square render size at which the ink of the glyph best fills `available` while keeping its
+    // proportions, so that wide (e.g. an em dash) or tall glyphs aren't sized by their shorter side.
+    // Falls back to the largest square of `available` when the glyph has no metrics (e.g. in a
+    // bitmap font) or no ink.
+    pub fn fitting_render_size(&self, available: RenderSize) -> RenderSize {
+        self.cached_glyph_metrics()
+            .and_then(|glyph_metrics| fit_glyph(&glyph_metrics, available))
+            .unwrap_or_else(|| available.largest_square())
+    }
+
+    // Same as `render`, but the glyph isn't cropped to the size, e.g. when it is rendered at a point
+    // size too large for the size
+    pub fn render_uncropped(&self, size: RenderSize) -> Result<RenderedCharacter> {
+        self.render_cached(size, false)
+    }
+
+    // Same as `render_uncropped`, except that renders missing from the cache are done in the
+    // background when the context has a render worker, see `render_in_background`
+    pub fn render_uncropped_in_background(
+        &self,
+        size: RenderSize,
+    ) -> Option<Result<RenderedCharacter>> {
+        if !self.render_in_background(&self.render_key(size, false)) {
+            return None;
+        }
+        Some(self.render_uncropped(size))
+    }
+
+    // Same as `render_alongside`, with renders missing from the cache done in the background
+    pub fn render_alongside_in_background(
+        &self,
+        chr: char,
+        size: RenderSize,
+    ) -> Option<Result<RenderedCharacter>> {
+        let render_key = self.render_key_of(chr, None, size, true, self.sizing);
+        self.render_key_in_background(render_key, || self.render_alongside(chr, size))
+    }
+
+    // Same as `render_character`, with renders missing from the cache done in the background
+    pub fn render_character_in_background(
+        &self,
+        chr: char,
+        size: RenderSize,
+    ) -> Option<Result<RenderedCharacter>> {
+        let render_key = self.render_key_of(chr, None, size, true, Sizing::Pixels);
+        self.render_key_in_background(render_key, || self.render_character(chr, size))
+    }
+
+    // Same as `render_native`, with renders missing from the cache done in the background since
+    // glyphs at their design size (often 1000 to 2048 pixels) are slow to render
+    pub fn render_native_in_background(&self) -> Option<Result<RenderedCharacter>> {
+        let render_key = self.native_render_key();
+        self.render_key_in_background(render_key, || self.render_native())
+    }
+
+    // Same as `render_shaped`, with renders missing from the cache done in the background
+    #[cfg(feature = "shaping")]
+    pub fn render_shaped_in_background(
+        &self,
+        text: &str,
+        size: RenderSize,
+    ) -> Option<Result<RenderedCharacter>> {
+        let render_key = self.render_key_of(self.chr, Some(text), size, false, self.sizing);
+        self.render_key_in_background(render_key, || self.render_shaped(text, size))
+    }
+
+    // Renders missing from the cache are requested from the render worker of the context, and
+    // waited for a little so that quick renders are drawn right away instead of a placeholder.
+    // Returns false while the render is still pending, true once it can be done in the foreground:
+    // it is cached, failed in the worker (to report its error) or there is no worker.
+    fn render_in_background(&self, render_key: &RenderKey) -> bool {
+        self.context.cached_render(render_key).is_some()
+            || !self.context.request_render(render_key.clone())
+            || self
+                .context
+                .wait_for_render(render_key, Duration::from_millis(RENDER_TIME_BUDGET_MS))
+    }
+
+    // Renders that aren't of the previewed character itself (or at its native size) are cached
+    // here, its own renders are cached by `render_cached` to also time them
+    fn render_key_in_background(
+        &self,
+        render_key: RenderKey,
+        render: impl FnOnce() -> Result<RenderedCharacter>,
+    ) -> Option<Result<RenderedCharacter>> {
+        if !self.render_in_background(&render_key) {
+            return None;
+        }
+        if let Some(rendered_character) = self.context.cached_render(&render_key) {
+            return Some(Ok(rendered_character));
+        }
+        let render_started_at = Instant::now();
+        let rendered_character = render();
+        if let Ok(rendered_character) = &rendered_character {
+            self.context.cache_render(
+                render_key,
+                rendered_character.clone(),
+                render_started_at.elapsed(),
+            );
+        }
+        Some(rendered_character)
+    }
+
+    // Renders of the current font are shared through the context, the size is still set on a
+    // cached render since other metrics (e.g. the scale of strikes) depend on it
+    fn render_cached(&self, size: RenderSize, cropped: bool) -> Result<RenderedCharacter> {
+        let render_key = self.render_key(size, cropped);
+        let (rendered_character, render_timing) =
+            match self.context.cached_render_with_duration(&render_key) {
+                Some(cached_render) => {
+                    self.set_size(size, self.sizing)?;
+                    let render_timing = RenderTiming {
+                        duration: cached_render.duration,
+                        cached: true,
+                    };
+                    (cached_render.rendered_character, render_timing)
+                }
+                None => {
+                    let render_started_at = Instant::now();
+                    let rendered_character = self.render_uncached(&render_key)?;
+                    let duration = render_started_at.elapsed();
+                    self.context
+                        .cache_render(render_key, rendered_character.clone(), duration);
+                    let render_timing = RenderTiming {
+                        duration,
+                        cached: false,
+                    };
+                    (rendered_character, render_timing)
+                }
+            };
+        self.last_render_timing.set(Some(render_timing));
+        Ok(rendered_character)
+    }
+
+    // Renders exactly what the key is a render of with the current font, whose options (e.g. its
+    // variation) must already be those of the key
+    pub(super) fn render_uncached(&self, render_key: &RenderKey) -> Result<RenderedCharacter> {
+        if render_key.native_size {
+            return self.render_native();
+        }
+        #[cfg(feature = "shaping")]
+        {
+            if let Some(text) = &render_key.text {
+                return self.render_shaped_with(text, render_key.size, render_key.sizing());
+            }
+        }
+        self.render_glyph(
+            render_key.chr,
+            render_key.size,
+            render_key.cropped,
+            render_key.sizing(),
+        )
+    }
+
+    fn render_glyph(
+        &self,
+        chr: char,
+        size: RenderSize,
+        cropped: bool,
+        sizing: Sizing,
+    ) -> Result<RenderedCharacter> {
+        self.set_size(size, sizing)?;
+        self.load_rendered_glyph(chr)?;
+        let size = if cropped {
+            size
+        } else {
+            self.rendered_glyph_size()
+        };
+        Ok(self.copy_rendered_glyph(size))
+    }
+
+    // Renders of the previewed character, or of its sequence which is shaped as a whole and never
+    // cropped
+    pub(super) fn render_key(&self, size: RenderSize, cropped: bool) -> RenderKey {
+        let text = self.sequence.as_deref();
+        let cropped = cropped && text.is_none();
+        self.render_key_of(self.chr, text, size, cropped, self.sizing)
+    }
+
+    fn render_key_of(
+        &self,
+        chr: char,
+        text: Option<&str>,
+        size: RenderSize,
+        cropped: bool,
+        sizing: Sizing,
+    ) -> RenderKey {
+        let point_size = match sizing {
+            Sizing::Pixels => None,
+            Sizing::Points {
+                size: point_size,
+                dpi,
+            } => Some((point_size.to_bits(), dpi)),
+        };
+        RenderKey {
+            chr,
+            text: if cfg!(feature = "shaping") {
+                text.map(str::to_owned)
+            } else {
+                None
+            },
+            font_path: self.get_current_font_path().unwrap_or_default(),
+            face_index: self.current_face_index,
+            size,
+            cropped,
+            point_size,
+            render_options: self.render_options,
+            selected_strike: self.selected_strike,
+            variation: self
+                .variation_axes()
+                .iter()
+                .map(|variation_axis| variation_axis.value.to_bits())
+                .collect(),
+            native_size: false,
+        }
+    }
+
+    // Native size renders of the previewed character, whatever the size of the preview
+    fn native_render_key(&self) -> RenderKey {
+        RenderKey {
+            native_size: true,
+            ..self.render_key_of(self.chr, None, RenderSize::new(0, 0), false, Sizing::Pixels)
+        }
+    }
+
+    // Renders another character exactly like the previewed one, e.g. as a reference to compare
+    // proportions with
+    pub fn render_alongside(&self, chr: char, size: RenderSize) -> Result<RenderedCharacter> {
+        self.set_size(size, self.sizing)?;
+        self.load_rendered_glyph(chr)?;
+
+        Ok(self.copy_rendered_glyph(size))
+    }
+
+    // Renders any character with the current font, always using `Sizing::Pixels`
+    pub fn render_character(&self, chr: char, size: RenderSize) -> Result<RenderedCharacter> {
+        self.set_size(size, Sizing::Pixels)?;
+        self.load_rendered_glyph(chr)?;
+
+        Ok(self.copy_rendered_glyph(size))
+    }
+
+    // Renders text (e.g. a grapheme cluster of the character) shaped by HarfBuzz with the current
+    // font at its variation, with the sizing and render options of the character. Each shaped
+    // glyph is rendered on its own and composed at the position HarfBuzz gives it, the render isn't
+    // cropped to the size.
+    #[cfg(feature = "shaping")]
+    pub fn render_shaped(&self, text: &str, size: RenderSize) -> Result<RenderedCharacter> {
+        self.render_shaped_with(text, size, self.sizing)
+    }
+
+    #[cfg(feature = "shaping")]
+    fn render_shaped_with(
+        &self,
+        text: &str,
+        size: RenderSize,
+        sizing: Sizing,
+    ) -> Result<RenderedCharacter> {
+        let font_path = self.get_current_font_path().unwrap_or_default();
+        let face_index = (self.current_face_index & 0xFFFF) as u32;
+        let shaped_glyphs = self.context.shaping_faces().shape(
+            &font_path,
+            face_index,
+            &self.variation_axes(),
+            text,
+        )?;
+
+        self.set_size(size, sizing)?;
+        let (x_scale, y_scale) = self.pixels_per_font_unit();
+        let (mut pen_x, mut pen_y) = (0.0, 0.0);
+        let mut positioned_glyphs = vec![];
+        for shaped_glyph in shaped_glyphs {
+            self.load_rendered_glyph_index(shaped_glyph.glyph_index)?;
+            positioned_glyphs.push(PositionedGlyph {
+                rendered_character: self.copy_rendered_glyph(self.rendered_glyph_size()),
+                x: (pen_x + shaped_glyph.x_offset as f64 * x_scale).round() as i32,
+                y: (pen_y + shaped_glyph.y_offset as f64 * y_scale).round() as i32,
+            });
+            pen_x += shaped_glyph.x_advance as f64 * x_scale;
+            pen_y += shaped_glyph.y_advance as f64 * y_scale;
+        }
+
+        let advance_width = max(pen_x.round() as i64, 0) as usize;
+        Ok(compose_glyphs(&positioned_glyphs, advance_width))
+    }
+
+    // The vertical metrics of the current font at the size the glyph is rendered at, to draw guides
+    // around the rendered glyph. Measured once per font and size.
+    pub fn face_metrics(&self, size: RenderSize) -> Result<FaceMetrics> {
+        let render_key = self.render_key(size, false);
+        if let Some((measured_key, face_metrics)) = &self.measurements.borrow().face_metrics {
+            if *measured_key == render_key {
+                return Ok(*face_metrics);
+            }
+        }
+        let face_metrics = self.measure_face_metrics(size)?;
+        self.measurements.borrow_mut().face_metrics = Some((render_key, face_metrics));
+        Ok(face_metrics)
+    }
+
+    fn measure_face_metrics(&self, size: RenderSize) -> Result<FaceMetrics> {
+        self.set_size(size, self.sizing)?;
+        let size_metrics = self
+            .current_font
+            .size_metrics()
+            .ok_or(Error::RenderSizeTooSmall)?;
+
+        // Size metrics are in 26.6 pixels, those of strikes are scaled down like their glyphs
+        let strike_scale = self.strike_scale.get();
+        let pixels = |length: i64| (length as f64 / 64.0 * strike_scale).round() as i32;
+        let (_, y_pixels_per_font_unit) = self.pixels_per_font_unit();
+        Ok(FaceMetrics {
+            em_size: (size_metrics.y_ppem as f64 * strike_scale).round() as usize,
+            ascender: pixels(size_metrics.ascender as i64),
+            descender: pixels(size_metrics.descender as i64),
+            x_height: x_height(&self.current_font)
+                .map(|x_height| (x_height as f64 * y_pixels_per_font_unit).round() as i32),
+        })
+    }
+
+    // The horizontal and vertical size of a font unit in pixels at the current size, strikes of
+    // color bitmap fonts being scaled down
+    fn pixels_per_font_unit(&self) -> (f64, f64) {
+        let size_metrics = match self.current_font.size_metrics() {
+            Some(size_metrics) => size_metrics,
+            None => return (0.0, 0.0),
+        };
+        if self.current_font.is_scalable() {
+            // 16.16 scales from font units to 26.6 pixels
+            let scale = |scale: i64| scale as f64 / 65536.0 / 64.0;
+            return (
+                scale(size_metrics.x_scale as i64),
+                scale(size_metrics.y_scale as i64),
+            );
+        }
+        let em_size = max(self.current_font.em_size(), 1) as f64;
+        let strike_scale = self.strike_scale.get();
+        (
+            size_metrics.x_ppem as f64 * strike_scale / em_size,
+            size_metrics.y_ppem as f64 * strike_scale / em_size,
+        )
+    }
+
+    // Renders the character with every matching font (with the current sizing and render options),
+    // along with the display name of each font. The current font is swapped out for each render and
+    // restored afterwards, fonts which fail to load (or lack the glyph) are named after their path.
+    pub fn render_matching_fonts(
+        &mut self,
+        size: RenderSize,
+    ) -> Vec<(String, Result<RenderedCharacter>)> {
+        let font_paths = self.paths_for_matching_fonts.items().to_vec();
+        let mut renders: Vec<(String, Result<RenderedCharacter>)> = vec![];
+        for font_path in font_paths {
+            let face = match load_font(
+                self.context.library(),
+                &font_path,
+                self.face_index_of(&font_path),
+                self.chr,
+                self.load_timeout,
+                &mut vec![],
+            ) {
+                Ok(face) => face,
+                Err(error) => {
+                    renders.push((font_path.clone(), Err(error)));
+                    continue;
+                }
+            };
+
+            let current_font = self.swap_current_font(face);
+            let rendered_character = self.render_glyph(self.chr, size, true, self.sizing);
+            let face = self.swap_current_font(current_font);
+            renders.push((font_display_name(&face), rendered_character));
+        }
+        renders
+    }
+
+    // Renders the character with another matching font (with the current sizing and render
+    // options), the current font is swapped out for the render and restored afterwards
+    pub fn render_with_font(
+        &mut self,
+        font_path: &str,
+        size: RenderSize,
+    ) -> Result<RenderedCharacter> {
+        let face = load_font(
+            self.context.library(),
+            font_path,
+            self.face_index_of(font_path),
+            self.chr,
+            self.load_timeout,
+            &mut vec![],
+        )?;
+        let current_font = self.swap_current_font(face);
+        let rendered_character = self.render_glyph(self.chr, size, true, self.sizing);
+        self.swap_current_font(current_font);
+        rendered_character
+    }
+
+    // Swaps the current font for another one, e.g. to render with it for a moment
+    fn swap_current_font(&mut self, face: Face) -> Face {
+        self.variation_axes.replace(None);
+        std::mem::replace(&mut self.current_font, face)
+    }
+
+    // Renders the character at the design size of the font, i.e. one pixel per font unit. Bitmap
+    // fonts only come in the sizes of their strikes, they have no design size to render at.
+    pub fn render_native(&self) -> Result<RenderedCharacter> {
+        if !self.current_font.is_scalable() {
+            return Err(Box::new(Error::OutlineNotAvailable { chr: self.chr }));
+        }
+        let units_per_em = self.current_font.em_size() as isize;
+        self.current_font
+            .set_char_size(units_per_em << 6, units_per_em << 6, 72, 72)
+            .map_err(|error| self.current_font_error("set the size of", error))?;
+        self.load_rendered_glyph(self.chr)?;
+
+        let glyph_bitmap = self.current_font.glyph().bitmap();
+        let size = RenderSize::new(glyph_bitmap.width() as usize, glyph_bitmap.rows() as usize);
+        Ok(self.copy_rendered_glyph(size))
+    }
+
+    // Loads and renders the glyph of a character into the glyph slot of the current font, applying
+    // the synthetic styles of the render options to outlines
+    fn load_rendered_glyph(&self, chr: char) -> Result<()> {
+        self.load_rendered_glyph_index(self.current_font.get_char_index(chr as usize))
+    }
+
+    // Same as `load_rendered_glyph`, with the index of the glyph in the current font
+    fn load_rendered_glyph_index(&self, glyph_index: u32) -> Result<()> {
+        let render_options = self.render_options;
+        let hinting_flags = render_options.hinting.load_flags();
+        if !render_options.synthetic_bold && !render_options.synthetic_oblique {
+            self.current_font
+                .load_glyph(
+                    glyph_index,
+                    LoadFlag::RENDER | LoadFlag::COLOR | hinting_flags,
+                )
+                .map_err(|error| self.current_font_error("render a glyph from", error))?;
+            return Ok(());
+        }
+
+        self.current_font
+            .load_glyph(
+                glyph_index,
+                LoadFlag::DEFAULT | LoadFlag::COLOR | hinting_flags,
+            )
+            .map_err(|error| self.current_font_error("load a glyph from", error))?;
+        let is_outline = synthesize_style(
+            &self.current_font,
+            render_options.synthetic_bold,
+            render_options.bold_strength,
+            render_options.synthetic_oblique,
+        )
+        .map_err(|error| self.current_font_error("synthesize a style with", error))?;
+        if is_outline {
+            self.current_font
+                .glyph()
+                .render_glyph(render_options.hinting.render_mode())
+                .map_err(|error| self.current_font_error("render a glyph from", error))?;
+        }
+        Ok(())
+    }
+
+    // Sizes below one pixel are rejected before reaching FreeType, which fails (or worse) on them
+    fn set_size(&self, size: RenderSize, sizing: Sizing) -> Result<()> {
+        let is_too_small = match sizing {
+            Sizing::Pixels => size.width == 0 || size.height == 0,
+            Sizing::Points {
+                size: point_size, ..
+            } => size.width == 0 || size.height == 0 || point_size <= 0.0,
+        };
+        if is_too_small {
+            return Err(Box::new(Error::RenderSizeTooSmall));
+        }
+
+        // Bitmap fonts only come in the sizes of their strikes, unless a strike is selected the
+        // closest strike is. Strikes of color bitmap fonts (e.g. CBDT or sbix emoji fonts) are
+        // scaled down when copying the glyph, other glyphs are shown at the size of their strike.
+        self.strike_scale.set(1.0);
+        if !self.current_font.is_scalable() {
+            let requested_size = match sizing {
+                Sizing::Pixels => min(size.width, size.height),
+                Sizing::Points {
+                    size: point_size,
+                    dpi,
+                } => max((point_size * dpi as f64 / 72.0).round() as usize, 1),
+            };
+            let strike_sizes = strike_sizes(&self.current_font);
+            let is_color = has_color_glyphs(&self.current_font);
+            let strike_index = match self.selected_strike {
+                Some(strike_index) if strike_index < strike_sizes.len() => Some(strike_index),
+                _ if is_color => best_strike(&strike_sizes, requested_size),
+                _ => nearest_strike(&strike_sizes, requested_size),
+            };
+            if let Some(strike_index) = strike_index {
+                select_strike(&self.current_font, strike_index)
+                    .map_err(|error| self.current_font_error("select a strike of", error))?;
+                if is_color {
+                    let strike_scale = requested_size as f64 / strike_sizes[strike_index] as f64;
+                    self.strike_scale.set(strike_scale.min(1.0));
+                }
+                return Ok(());
+            }
+        }
+
+        let result = match sizing {
+            Sizing::Pixels => self
+                .current_font
+                .set_pixel_sizes(size.width as u32, size.height as u32),
+            Sizing::Points { size, dpi } => {
+                let char_size = (size * 64.0) as isize; // 26.6 fixed-point
+                self.current_font
+                    .set_char_size(char_size, char_size, dpi, dpi)
+            }
+        };
+        result.map_err(|error| self.current_font_error("set the size of", error))?;
+        Ok(())
+    }
+
+    fn copy_rendered_glyph(&self, size: RenderSize) -> RenderedCharacter {
+        let glyph = self.current_font.glyph();
+        let metrics = glyph.metrics();
+        let (coverage, colors) = read_glyph_bitmap(&glyph.bitmap());
+
+        // Color strikes are scaled down to the requested size, along with the metrics of the glyph
+        let scale = self.strike_scale.get();
+        let (coverage, colors) = match colors {
+            Some(colors) if scale < 1.0 => {
+                let (coverage, colors) = scale_down_color_glyph(&coverage, &colors, scale);
+                (coverage, Some(colors))
+            }
+            colors => (coverage, colors),
+        };
+        let scale = if colors.is_some() { scale } else { 1.0 };
+
+        // The bitmap might be wider or taller than the requested size, it is cropped then
+        let x_max = min(size.width, coverage.first().map_or(0, |row| row.len()));
+        let y_max = min(size.height, coverage.len());
+
+        RenderedCharacter {
+            bitmap: crop_pixels(&coverage, size, 0),
+            colors: colors.map(|colors| crop_pixels(&colors, size, [0, 0, 0])),
+            glyph_size: RenderSize::new(x_max, y_max),
+            bitmap_left: (glyph.bitmap_left() as f64 * scale).round() as i32,
+            bitmap_top: (glyph.bitmap_top() as f64 * scale).round() as i32,
+            advance_width: (max(glyph.advance().x >> 6, 0) as f64 * scale).round() as usize,
+            advance_height: (max(metrics.vertAdvance >> 6, 0) as f64 * scale).round() as usize,
+            vertical_bearing_x: ((metrics.vertBearingX >> 6) as f64 * scale).round() as i32,
+            vertical_bearing_y: ((metrics.vertBearingY >> 6) as f64 * scale).round() as i32,
+        }
+    }
+
+    // The size of the glyph rendered last, once scaled like `copy_rendered_glyph` does
+    fn rendered_glyph_size(&self) -> RenderSize {
+        let glyph_bitmap = self.current_font.glyph().bitmap();
+        let (width, height) = (glyph_bitmap.width() as usize, glyph_bitmap.rows() as usize);
+        let scale = self.strike_scale.get();
+        if scale < 1.0 && is_color_bitmap(&glyph_bitmap) {
+            RenderSize::new(scaled_length(width, scale), scaled_length(height, scale))
+        } else {
+            RenderSize::new(width, height)
+        }
+    }
+
+    // Bitmap-only fonts fail to load glyphs without their bitmaps, they have no outline to load
+    fn load_outline(&self) -> Result<()> {
+        if !self.current_font.is_scalable() {
+            return Err(Box::new(Error::OutlineNotAvailable { chr: self.chr }));
+        }
+        self.current_font
+            .load_char(self.chr as usize, LoadFlag::NO_SCALE | LoadFlag::NO_BITMAP)
+            .map_err(|error| self.current_font_error("load an outline from", error))?;
+        Ok(())
+    }
+
+    pub fn outline(&self) -> Result<Vec<OutlineContour>> {
+        self.load_outline()?;
+
+        let outline = match self.current_font.glyph().outline() {
+            Some(outline) => outline,
+            None => return Err(Box::new(Error::OutlineNotAvailable { chr: self.chr })),
+        };
+
+        let points = outline.points();
+        let tags = outline.tags();
+
+        let mut contours = vec![];
+        let mut contour_start = 0;
+        for contour_end in outline.contours() {
+            let contour_end = *contour_end as usize;
+            let contour = (contour_start..=contour_end)
+                .map(|index| {
+                    let tag = tags[index] as u8;
+                    OutlinePoint {
+                        x: points[index].x as i64,
+                        y: points[index].y as i64,
+                        kind: if tag & 0x01 != 0 {
+                            OutlinePointKind::OnCurve
+                        } else if tag & 0x02 != 0 {
+                            OutlinePointKind::Cubic
+                        } else {
+                            OutlinePointKind::Conic
+                        },
+                    }
+                })
+                .collect();
+            contours.push(contour);
+            contour_start = contour_end + 1;
+        }
+
+        Ok(contours)
+    }
+
+    // The outline of the glyph as drawing commands in font units, for vector exports
+    pub fn outline_segments(&self) -> Result<Vec<OutlineSegment>> {
+        self.load_outline()?;
+
+        match decompose_outline(&self.current_font)
+            .map_err(|error| self.current_font_error("decompose an outline from", error))?
+        {
+            Some(segments) => Ok(segments),
+            None => Err(Box::new(Error::OutlineNotAvailable { chr: self.chr })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Rendering relies on the fonts installed on the system, tests are skipped if there is none
+    fn test_context() -> PreviewContext {
+        PreviewContext::new().unwrap()
+    }
+
+    fn preview_for(chr: char) -> Option<CharacterPreview> {
+        CharacterPreview::new(&test_context(), chr, None, &FontMatchOptions::default()).ok()
+    }
+
+    #[test]
+    fn test_shared_context() {
+        let context = test_context();
+        let font_match_options = FontMatchOptions::default();
+        let (first_preview, second_preview) = match (
+            CharacterPreview::new(&context, 'A', None, &font_match_options),
+            CharacterPreview::new(&context, 'B', None, &font_match_options),
+        ) {
+            (Ok(first_preview), Ok(second_preview)) => (first_preview, second_preview),
+            _ => return,
+        };
+        assert!(std::ptr::eq(
+            first_preview.context().library(),
+            second_preview.context().library()
+        ));
+    }
+
+    #[test]
+    fn test_load_font_error_has_path() {
+        let font_path = std::env::temp_dir().join("cicero-test-not-a-font.ttf");
+        std::fs::write(&font_path, b"not a font").unwrap();
+        let font_path = font_path.to_string_lossy().into_owned();
+
+        let library = Library::init().unwrap();
+        let mut font_attempts = vec![];
+        let error = load_font(&library, &font_path, 0, 'A', None, &mut font_attempts)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains(&font_path));
+        match &font_attempts[0].outcome {
+            FontAttemptOutcome::LoadFailed(_) => {}
+            outcome => panic!("Unexpected outcome {:?}", outcome),
+        }
+
+        let _ = std::fs::remove_file(&font_path);
+    }
+
+    #[test]
+    fn test_matched_font_display_name() {
+        let font = MatchedFont {
+            path: "/fonts/a.ttf".to_owned(),
+            family: "Noto Sans".to_owned(),
+            style: "Bold".to_owned(),
+            face_index: 0,
+            monospace: false,
+        };
+        assert_eq!(matched_font_display_name(&font), "Noto Sans - Bold");
+
+        let font = MatchedFont {
+            path: "/fonts/b.ttf".to_owned(),
+            family: String::new(),
+            style: "Regular".to_owned(),
+            face_index: 0,
+            monospace: false,
+        };
+        assert_eq!(matched_font_display_name(&font), "Unknown Family - Regular");
+    }
+
+    #[test]
+    fn test_face_index_of() {
+        let preferred_face = Some(("/fonts/a.ttc".to_owned(), 2));
+        assert_eq!(face_index_of(&preferred_face, "/fonts/a.ttc", &[1, 3]), 2);
+        // Other fonts start at their first matched face, e.g. the only face passing the filter
+        assert_eq!(face_index_of(&preferred_face, "/fonts/b.ttc", &[1, 3]), 1);
+        assert_eq!(face_index_of(&None, "/fonts/b.ttf", &[]), 0);
+    }
+
+    #[test]
+    fn test_matching_font_display_names() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let display_names = character_preview.matching_font_display_names();
+        assert!(!display_names.is_empty());
+        assert!(display_names.iter().all(|name| name.contains(" - ")));
+    }
+
+    #[test]
+    fn test_max_fonts() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+        let display_names = character_preview.matching_font_display_names();
+        assert_eq!(character_preview.omitted_font_count(), 0);
+
+        let font_match_options = FontMatchOptions {
+            max_fonts: Some(1),
+            ..FontMatchOptions::default()
+        };
+        let limited_preview =
+            CharacterPreview::new(&test_context(), 'A', None, &font_match_options).unwrap();
+        assert_eq!(
+            limited_preview.matching_font_display_names(),
+            &display_names[..1]
+        );
+        assert_eq!(
+            limited_preview.omitted_font_count(),
+            display_names.len() - 1
+        );
+    }
+
+    #[test]
+    fn test_with_face_index() {
+        let font_path = match preview_for('A') {
+            Some(character_preview) => character_preview.get_current_font_path().unwrap(),
+            None => return,
+        };
+        let font_match_options = FontMatchOptions::default();
+
+        let character_preview = CharacterPreview::with_face_index(
+            &test_context(),
+            'A',
+            Some(&font_path),
+            0,
+            &font_match_options,
+        )
+        .unwrap();
+        assert_eq!(
+            character_preview.get_current_font_path(),
+            Some(font_path.clone())
+        );
+
+        // No font has that many faces, the next matching font is previewed instead (if any)
+        if let Ok(character_preview) = CharacterPreview::with_face_index(
+            &test_context(),
+            'A',
+            Some(&font_path),
+            0xFFF,
+            &font_match_options,
+        ) {
+            let preferred_font_attempt = &character_preview.font_attempts()[0];
+            assert_eq!(preferred_font_attempt.font_path, font_path);
+            match &preferred_font_attempt.outcome {
+                FontAttemptOutcome::LoadFailed(_) => {}
+                outcome => panic!("Unexpected outcome {:?}", outcome),
+            }
+        }
+    }
+
+    #[test]
+    fn test_select_next_strike() {
+        let mut character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let strike_count = character_preview.strike_sizes().len();
+        if strike_count == 0 {
+            assert!(!character_preview.select_next_strike());
+            assert_eq!(character_preview.selected_strike(), None);
+            return;
+        }
+        for strike_index in 0..strike_count {
+            assert!(character_preview.select_next_strike());
+            assert_eq!(character_preview.selected_strike(), Some(strike_index));
+        }
+        assert!(character_preview.select_next_strike());
+        assert_eq!(character_preview.selected_strike(), None);
+    }
+
+    #[test]
+    fn test_select_face() {
+        let mut character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+        assert!(character_preview.current_face_index() < character_preview.face_count());
+        if character_preview.face_count() < 2 || character_preview.current_face_index() > 0 {
+            return;
+        }
+
+        // The next face of a font collection comes before the next font, and back
+        let font_path = character_preview.get_current_font_path();
+        assert!(character_preview.has_next_font());
+        character_preview.select_next_font().unwrap();
+        if character_preview.get_current_font_path() == font_path {
+            assert!(character_preview.current_face_index() > 0);
+            assert!(character_preview.has_previous_font());
+            character_preview.select_previous_font().unwrap();
+            assert_eq!(character_preview.current_face_index(), 0);
+        }
+    }
+
+    #[test]
+    fn test_largest_square() {
+        let size = RenderSize::new(120, 80).largest_square();
+        assert_eq!((size.width, size.height), (80, 80));
+    }
+
+    #[test]
+    fn test_render_square() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let rendered_character = character_preview.render(RenderSize::new(64, 64)).unwrap();
+        assert_eq!(rendered_character.bitmap.len(), 64);
+        assert!(rendered_character.bitmap.iter().all(|row| row.len() == 64));
+        assert!(rendered_character.glyph_size.width > 0);
+        assert!(rendered_character.glyph_size.width <= 64);
+        assert!(rendered_character.glyph_size.height > 0);
+        assert!(rendered_character.glyph_size.height <= 64);
+    }
+
+    #[test]
+    fn test_render_with_sizing() {
+        let mut character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let size = RenderSize::new(128, 128);
+
+        character_preview.set_sizing(Sizing::Pixels);
+        assert!(!character_preview.render(size).unwrap().is_blank());
+
+        character_preview.set_sizing(Sizing::Points {
+            size: 48.0,
+            dpi: 96,
+        });
+        assert!(!character_preview.render(size).unwrap().is_blank());
+    }
+
+    #[test]
+    fn test_render_with_dpi() {
+        let mut character_preview = match preview_for('H') {
+            Some(character_preview) if character_preview.is_outline_font() => character_preview,
+            _ => return,
+        };
+
+        // The same point size covers twice as many pixels on a display of twice the DPI
+        let glyph_height = |character_preview: &mut CharacterPreview, dpi: u32| {
+            character_preview.set_sizing(Sizing::Points { size: 24.0, dpi });
+            let rendered_character = character_preview
+                .render_uncropped(RenderSize::new(1, 1))
+                .unwrap();
+            rendered_character.glyph_size.height as f64
+        };
+        let height_at_72_dpi = glyph_height(&mut character_preview, 72);
+        let height_at_144_dpi = glyph_height(&mut character_preview, 144);
+        assert!(height_at_72_dpi > 0.0);
+        assert!((height_at_144_dpi / height_at_72_dpi - 2.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_render_uncropped() {
+        let mut character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        character_preview.set_sizing(Sizing::Points {
+            size: 96.0,
+            dpi: 96,
+        });
+        let size = RenderSize::new(16, 16);
+        let cropped = character_preview.render(size).unwrap();
+        let uncropped = character_preview.render_uncropped(size).unwrap();
+        assert!(cropped.glyph_size.height <= 16);
+        assert!(uncropped.glyph_size.height > 16);
+    }
+
+    #[test]
+    fn test_fit_glyph() {
+        let metrics = |width, height| GlyphMetrics {
+            units_per_em: 1000,
+            advance_width: width,
+            width,
+            height,
+            bearing_x: 0,
+            bearing_y: height,
+        };
+        let available = RenderSize::new(202, 102);
+
+        // An em dash fills the width, a tall glyph the height
+        assert_eq!(
+            fit_glyph(&metrics(1000, 50), available),
+            Some(RenderSize::new(200, 200))
+        );
+        assert_eq!(
+            fit_glyph(&metrics(400, 1000), available),
+            Some(RenderSize::new(100, 100))
+        );
+
+        // A period is not scaled beyond the em square fitting the longer side
+        assert_eq!(
+            fit_glyph(&metrics(100, 100), available),
+            Some(RenderSize::new(202, 202))
+        );
+
+        assert_eq!(fit_glyph(&metrics(0, 0), available), None);
+    }
+
+    #[test]
+    fn test_fitting_render_size() {
+        let available = RenderSize::new(200, 100);
+        if let Some(character_preview) = preview_for('\u{2014}') {
+            if character_preview.is_outline_font() {
+                let render_size = character_preview.fitting_render_size(available);
+                assert!(render_size.width > available.height);
+                let rendered_character = character_preview.render_uncropped(render_size).unwrap();
+                assert!(rendered_character.glyph_size.width <= available.width);
+            }
+        }
+        if let Some(character_preview) = preview_for(' ') {
+            assert_eq!(
+                character_preview.fitting_render_size(available),
+                available.largest_square()
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_durations() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+        assert_eq!(character_preview.last_render_timing(), None);
+
+        // Rendering other characters leaves the duration of the previewed one alone
+        let size = RenderSize::new(32, 32);
+        character_preview.render_character('B', size).unwrap();
+        assert_eq!(character_preview.last_render_timing(), None);
+
+        character_preview.render(size).unwrap();
+        let render_timing = character_preview.last_render_timing().unwrap();
+        assert!(!render_timing.cached);
+
+        // The render served from the cache keeps its duration
+        character_preview.render(size).unwrap();
+        assert_eq!(
+            character_preview.last_render_timing(),
+            Some(RenderTiming {
+                cached: true,
+                ..render_timing
+            })
+        );
+
+        character_preview.clear_render_timing();
+        assert_eq!(character_preview.last_render_timing(), None);
+    }
+
+    #[test]
+    fn test_render_native_cached() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+        if !character_preview.is_outline_font() {
+            return;
+        }
+        let rendered_character = character_preview
+            .render_native_in_background()
+            .unwrap()
+            .unwrap();
+        let cached_render = character_preview
+            .context()
+            .cached_render(&character_preview.native_render_key())
+            .unwrap();
+        assert_eq!(cached_render.bitmap, rendered_character.bitmap);
+        assert_eq!(
+            rendered_character.bitmap,
+            character_preview.render_native().unwrap().bitmap
+        );
+    }
+
+    #[test]
+    fn test_for_sequence() {
+        let font_match_options = FontMatchOptions::default();
+        let error =
+            CharacterPreview::for_sequence(&test_context(), "", None, 0, &font_match_options)
+                .err()
+                .unwrap();
+        match error.downcast_ref::<Error>() {
+            Some(Error::EmptySequence) => {}
+            _ => panic!("Unexpected error {}", error),
+        }
+
+        // A single character isn't a sequence
+        if let Ok(character_preview) =
+            CharacterPreview::for_sequence(&test_context(), "A", None, 0, &font_match_options)
+        {
+            assert_eq!(character_preview.sequence(), None);
+        }
+
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        if let Ok(character_preview) =
+            CharacterPreview::for_sequence(&test_context(), family, None, 0, &font_match_options)
+        {
+            assert_eq!(character_preview.sequence(), Some(family));
+            let size = RenderSize::new(32, 32);
+            character_preview.render(size).unwrap();
+            assert!(character_preview.last_render_timing().is_some());
+        }
+    }
+
+    #[test]
+    fn test_render_cache() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+        let size = RenderSize::new(32, 32);
+        let render_key = character_preview.render_key(size, true);
+        assert!(character_preview
+            .context()
+            .cached_render(&render_key)
+            .is_none());
+
+        let rendered_character = character_preview.render(size).unwrap();
+        let cached_render = character_preview
+            .context()
+            .cached_render(&render_key)
+            .unwrap();
+        assert_eq!(cached_render.bitmap, rendered_character.bitmap);
+        assert_eq!(
+            character_preview.render(size).unwrap().bitmap,
+            rendered_character.bitmap
+        );
+
+        // Renders with other options are cached apart
+        let mut character_preview = character_preview;
+        character_preview.set_render_options(RenderOptions {
+            synthetic_bold: true,
+            ..RenderOptions::default()
+        });
+        assert_ne!(character_preview.render_key(size, true), render_key);
+
+        character_preview.context().clear_render_cache();
+        let render_key = character_preview.render_key(size, true);
+        assert!(character_preview
+            .context()
+            .cached_render(&render_key)
+            .is_none());
+    }
+
+    #[test]
+    fn test_render_blank() {
+        let size = RenderSize::new(64, 64);
+
+        if let Some(character_preview) = preview_for(' ') {
+            assert!(character_preview.render(size).unwrap().is_blank());
+        }
+        if let Some(character_preview) = preview_for('A') {
+            assert!(!character_preview.render(size).unwrap().is_blank());
+        }
+    }
+
+    #[test]
+    fn test_face_metrics() {
+        let character_preview = match preview_for('x') {
+            Some(character_preview) if character_preview.is_outline_font() => character_preview,
+            _ => return,
+        };
+
+        let face_metrics = character_preview
+            .face_metrics(RenderSize::new(100, 100))
+            .unwrap();
+        assert_eq!(face_metrics.em_size, 100);
+        assert!(face_metrics.ascender > 0);
+        assert!(face_metrics.descender <= 0);
+        if let Some(x_height) = face_metrics.x_height {
+            assert!(x_height > 0 && x_height < face_metrics.ascender);
+        }
+    }
+
+    #[test]
+    fn test_measurements_cached() {
+        let character_preview = match preview_for('x') {
+            Some(character_preview) if character_preview.is_outline_font() => character_preview,
+            _ => return,
+        };
+
+        let small_size = RenderSize::new(50, 50);
+        let large_size = RenderSize::new(100, 100);
+        let small_face_metrics = character_preview.face_metrics(small_size).unwrap();
+        assert_eq!(
+            character_preview.face_metrics(small_size).unwrap(),
+            small_face_metrics
+        );
+        assert_eq!(
+            character_preview.face_metrics(large_size).unwrap().em_size,
+            100
+        );
+        assert_eq!(
+            character_preview.cached_glyph_metrics(),
+            character_preview.glyph_metrics().ok()
+        );
+
+        let ink_coverage = character_preview
+            .render(large_size)
+            .map(|rendered_character| rendered_character.ink_coverage())
+            .ok();
+        assert_eq!(
+            character_preview.ink_coverage_in_background(large_size),
+            ink_coverage
+        );
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![255; 3]; 4],
+            colors: None,
+            glyph_size: RenderSize::new(3, 4),
+            bitmap_left: -1,
+            bitmap_top: 3,
+            advance_width: 2,
+            advance_height: 5,
+            vertical_bearing_x: -2,
+            vertical_bearing_y: 1,
+        };
+        assert_eq!(
+            rendered_character.bounding_box(),
+            BoundingBox {
+                x_min: -1,
+                y_min: -1,
+                x_max: 2,
+                y_max: 3,
+            }
+        );
+
+        // The vertical metrics are synthesized for fonts without them
+        if let Some(character_preview) = preview_for('A') {
+            let rendered_character = character_preview.render(RenderSize::new(64, 64)).unwrap();
+            assert!(rendered_character.advance_height > 0);
+        }
+    }
+
+    #[test]
+    fn test_ink_coverage() {
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![0, 255], vec![12, 0]],
+            colors: None,
+            glyph_size: RenderSize::new(2, 2),
+            bitmap_left: 0,
+            bitmap_top: 2,
+            advance_width: 2,
+            advance_height: 2,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        assert_eq!(rendered_character.ink_coverage(), 0.5);
+
+        let size = RenderSize::new(64, 64);
+        if let Some(character_preview) = preview_for(' ') {
+            assert_eq!(character_preview.render(size).unwrap().ink_coverage(), 0.0);
+        }
+        if let Some(character_preview) = preview_for('\u{2588}') {
+            // FULL BLOCK fills most of the em square in most fonts
+            assert!(character_preview.render(size).unwrap().ink_coverage() > 0.3);
+        }
+        if let Some(character_preview) = preview_for('.') {
+            assert!(character_preview.render(size).unwrap().ink_coverage() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_scale_down_color_glyph() {
+        // A red opaque pixel and a transparent one in each pair of columns, then a blue row
+        let coverage = vec![vec![255, 0, 255, 255], vec![0, 0, 0, 0]];
+        let colors = vec![
+            vec![[255, 0, 0], [0, 0, 0], [0, 0, 255], [0, 0, 255]],
+            vec![[0; 3]; 4],
+        ];
+        let (scaled_coverage, scaled_colors) = scale_down_color_glyph(&coverage, &colors, 0.5);
+        assert_eq!(scaled_coverage, vec![vec![63, 127]]);
+
+        // Transparent pixels don't darken the color
+        assert_eq!(scaled_colors, vec![vec![[255, 0, 0], [0, 0, 255]]]);
+
+        assert_eq!(scaled_length(136, 64.0 / 109.0), 80);
+    }
+
+    #[test]
+    fn test_crop_pixels() {
+        let rows = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        assert_eq!(
+            crop_pixels(&rows, RenderSize::new(2, 3), 0),
+            vec![vec![1, 2], vec![4, 5], vec![0, 0]]
+        );
+    }
+
+    #[test]
+    fn test_edges() {
+        // A filled 4x4 square with a faint pixel, only its border is left
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![
+                vec![255, 255, 255, 255, 0],
+                vec![255, 255, 255, 255, 0],
+                vec![255, 255, 255, 255, 0],
+                vec![255, 255, 255, 200, 40],
+            ],
+            colors: None,
+            glyph_size: RenderSize::new(5, 4),
+            bitmap_left: 0,
+            bitmap_top: 4,
+            advance_width: 5,
+            advance_height: 4,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        let edges = rendered_character.edges(128);
+        assert_eq!(
+            edges.bitmap,
+            vec![
+                vec![255, 255, 255, 255, 0],
+                vec![255, 0, 0, 255, 0],
+                vec![255, 0, 0, 255, 0],
+                vec![255, 255, 255, 200, 0],
+            ]
+        );
+        assert_eq!(edges.glyph_size, rendered_character.glyph_size);
+        assert_eq!(edges.advance_width, rendered_character.advance_width);
+    }
+
+    #[test]
+    fn test_mask() {
+        // An "L" with an antialiased corner, in a bitmap wider and taller than the glyph
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![
+                vec![255, 0, 0, 0],
+                vec![255, 0, 0, 0],
+                vec![255, 128, 64, 0],
+                vec![0, 0, 0, 0],
+            ],
+            colors: None,
+            glyph_size: RenderSize::new(3, 3),
+            bitmap_left: 0,
+            bitmap_top: 3,
+            advance_width: 3,
+            advance_height: 3,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        #[rustfmt::skip]
+        let expected_mask = vec![
+            true, false, false,
+            true, false, false,
+            true, true, false,
+        ];
+        assert_eq!(rendered_character.mask(128), expected_mask);
+        assert_eq!(
+            rendered_character
+                .mask(1)
+                .iter()
+                .filter(|set| **set)
+                .count(),
+            5
+        );
+        assert_eq!(rendered_character.mask(0), vec![true; 9]);
+
+        let size = RenderSize::new(64, 64);
+        if let Some(character_preview) = preview_for(' ') {
+            assert!(!character_preview
+                .render(size)
+                .unwrap()
+                .mask(1)
+                .contains(&true));
+        }
+        if let Some(character_preview) = preview_for('\u{2588}') {
+            // FULL BLOCK is solid, its mask is set in the middle of the glyph at any threshold
+            let rendered_character = character_preview.render(size).unwrap();
+            let glyph_size = rendered_character.glyph_size;
+            let mask = rendered_character.mask(255);
+            assert_eq!(mask.len(), glyph_size.width * glyph_size.height);
+            assert!(mask[glyph_size.height / 2 * glyph_size.width + glyph_size.width / 2]);
+        }
+    }
+
+    #[test]
+    fn test_render_synthetic_bold() {
+        let mut character_preview = match preview_for('l') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+        if !character_preview.is_outline_font() {
+            return;
+        }
+
+        let ink = |rendered_character: RenderedCharacter| -> usize {
+            rendered_character
+                .bitmap
+                .iter()
+                .flatten()
+                .map(|pixel| *pixel as usize)
+                .sum()
+        };
+
+        let size = RenderSize::new(64, 64);
+        let regular_ink = ink(character_preview.render(size).unwrap());
+        character_preview.set_render_options(RenderOptions {
+            synthetic_bold: true,
+            ..RenderOptions::default()
+        });
+        let bold_ink = ink(character_preview.render(size).unwrap());
+        assert!(bold_ink > regular_ink);
+
+        character_preview.set_render_options(RenderOptions {
+            synthetic_bold: true,
+            ..RenderOptions::default().with_bold_strength(MAX_BOLD_STRENGTH)
+        });
+        assert!(ink(character_preview.render(size).unwrap()) > bold_ink);
+    }
+
+    #[test]
+    fn test_hinting() {
+        // Every mode is cycled through once
+        let mut hinting = Hinting::Default;
+        let mut modes = std::collections::HashSet::new();
+        for _ in 0..5 {
+            modes.insert(hinting);
+            hinting = hinting.next();
+        }
+        assert_eq!(modes.len(), 5);
+        assert_eq!(hinting, Hinting::Default);
+
+        let mut character_preview = match preview_for('A') {
+            Some(character_preview) if character_preview.is_outline_font() => character_preview,
+            _ => return,
+        };
+        character_preview.set_render_options(RenderOptions {
+            hinting: Hinting::Mono,
+            ..RenderOptions::default()
+        });
+        let rendered_character = character_preview.render(RenderSize::new(16, 16)).unwrap();
+        assert!(rendered_character
+            .bitmap
+            .iter()
+            .flatten()
+            .all(|pixel| *pixel == 0 || *pixel == 255));
+    }
+
+    #[test]
+    fn test_bold_strength_bounds() {
+        let render_options = RenderOptions::default();
+        assert_eq!(render_options.bold_strength, DEFAULT_BOLD_STRENGTH);
+        assert_eq!(
+            render_options.with_bold_strength(0).bold_strength,
+            MIN_BOLD_STRENGTH
+        );
+        assert_eq!(
+            render_options.with_bold_strength(1000).bold_strength,
+            MAX_BOLD_STRENGTH
+        );
+        assert_eq!(render_options.with_bold_strength(60).bold_strength, 60);
+    }
+
+    #[test]
+    fn test_render_zero_size() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        for size in &[RenderSize::new(0, 0), RenderSize::new(0, 64)] {
+            let error = character_preview.render(*size).err().unwrap();
+            match error.downcast_ref::<Error>() {
+                Some(Error::RenderSizeTooSmall) => {}
+                _ => panic!("Unexpected error {}", error),
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_non_square() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let square = character_preview.render(RenderSize::new(64, 64)).unwrap();
+        let wide = character_preview.render(RenderSize::new(128, 64)).unwrap();
+        assert_eq!(wide.bitmap.len(), 64);
+        assert!(wide.bitmap.iter().all(|row| row.len() == 128));
+        assert!(wide.glyph_size.width > square.glyph_size.width);
+        assert!(wide.glyph_size.height <= 64);
+    }
+
+    #[test]
+    fn test_glyph_info() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let glyph_info = character_preview.glyph_info();
+        assert_ne!(glyph_info.index, 0);
+        if let Some(name) = glyph_info.name {
+            assert!(!name.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_font_repertoire() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let font_repertoire = character_preview.font_repertoire();
+        assert!(font_repertoire.contains(&'A'));
+        assert!(font_repertoire.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_font_info() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        let font_info = character_preview.font_info();
+        assert_eq!(
+            Some(font_info.path.clone()),
+            character_preview.get_current_font_path()
+        );
+        assert_eq!(
+            font_info.display_name,
+            character_preview.get_current_font_display_name()
+        );
+        assert!(font_info.glyph_count > 0);
+        assert!(font_info.format.is_some());
+    }
+
+    #[test]
+    fn test_has_glyph() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) => character_preview,
+            None => return,
+        };
+
+        // A full render falls back to the .notdef glyph (index 0) for characters without a glyph
+        let size = RenderSize::new(32, 32);
+        for chr in &['A', 'a', '\u{10FFFD}', '\u{E0001}'] {
+            character_preview.render_character(*chr, size).unwrap();
+            let rendered_glyph_index = character_preview.current_font.glyph().raw().glyph_index;
+            assert_eq!(
+                character_preview.has_glyph(*chr),
+                rendered_glyph_index != 0,
+                "U+{:04X}",
+                *chr as u32
+            );
+        }
+        assert!(character_preview.has_glyph('A'));
+        assert!(!character_preview.has_glyph('\u{10FFFD}'));
+    }
+
+    #[test]
+    fn test_glyph_metrics() {
+        let character_preview = match preview_for('A') {
+            Some(character_preview) if character_preview.is_outline_font() => character_preview,
+            _ => return,
+        };
+
+        let glyph_metrics = character_preview.glyph_metrics().unwrap();
+        assert!(glyph_metrics.units_per_em > 0);
+        assert!(glyph_metrics.advance_width > 0);
+        assert!(glyph_metrics.height > 0);
+    }
 
-            (pixels, RenderSize::new(x_max, y_max))
+    #[test]
+    fn test_outline_segments() {
+        let character_preview = match preview_for('O') {
+            Some(character_preview) if character_preview.is_outline_font() => character_preview,
+            _ => return,
         };
 
-        Ok(RenderedCharacter { bitmap, glyph_size })
+        // The outer and inner contours of the letter, each starting with a move
+        let segments = character_preview.outline_segments().unwrap();
+        let contour_count = segments
+            .iter()
+            .filter(|segment| matches!(segment, OutlineSegment::MoveTo(_)))
+            .count();
+        assert!(matches!(segments.first(), Some(OutlineSegment::MoveTo(_))));
+        assert!(contour_count >= 2);
     }
 }
@@ -0,0 +1,253 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! A contact sheet is a single image of a character rendered by every matching font, laid out in a
+//! grid with the name of each font under its cell.
+//!
+
+use image::{GrayImage, Luma};
+
+use super::character_preview::{CharacterPreview, RenderSize, RenderedCharacter};
+use super::font_match::FontMatchOptions;
+use super::{Error, Result};
+
+// Labels are rendered with whichever font matches this character first
+const LABEL_FONT_CHARACTER: char = 'A';
+const LABEL_HEIGHT: u32 = 16;
+const LABEL_BASELINE: i64 = 12;
+const CELL_PADDING: u32 = 8;
+
+const BACKGROUND: u8 = 255;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ContactSheetOptions {
+    pub columns: u32,
+
+    // Width and height of the glyph area of each cell, in pixels
+    pub cell_size: u32,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        ContactSheetOptions {
+            columns: 4,
+            cell_size: 128,
+        }
+    }
+}
+
+// Renders the character of the preview with each of its matching fonts into a contact sheet, dark
+// glyphs on a light background. Fonts which fail to render leave their cell blank but labeled.
+pub fn render_contact_sheet(
+    character_preview: &mut CharacterPreview,
+    options: &ContactSheetOptions,
+) -> Result<GrayImage> {
+    if options.columns == 0 || options.cell_size == 0 {
+        return Err(Box::new(Error::RenderSizeTooSmall));
+    }
+
+    let glyph_size = options.cell_size as usize;
+    let renders = character_preview.render_matching_fonts(RenderSize::new(glyph_size, glyph_size));
+
+    let cell_width = options.cell_size + CELL_PADDING * 2;
+    let cell_height = options.cell_size + LABEL_HEIGHT + CELL_PADDING * 2;
+    let columns = options.columns.min(renders.len() as u32).max(1);
+    let rows = (renders.len() as u32 + columns - 1) / columns;
+
+    let mut contact_sheet =
+        GrayImage::from_pixel(columns * cell_width, rows * cell_height, Luma([BACKGROUND]));
+
+    // Labels are optional, the contact sheet is still useful without them
+    let label_font = CharacterPreview::new(
+        character_preview.context(),
+        LABEL_FONT_CHARACTER,
+        None,
+        &FontMatchOptions::default(),
+    )
+    .ok();
+
+    for (index, (font_name, rendered_character)) in renders.iter().enumerate() {
+        let cell_x = (index as u32 % columns) * cell_width;
+        let cell_y = (index as u32 / columns) * cell_height;
+
+        if let Ok(rendered_character) = rendered_character {
+            let glyph_x = cell_x
+                + CELL_PADDING
+                + centering_offset(
+                    options.cell_size,
+                    rendered_character.glyph_size.width as u32,
+                );
+            let glyph_y = cell_y
+                + CELL_PADDING
+                + centering_offset(
+                    options.cell_size,
+                    rendered_character.glyph_size.height as u32,
+                );
+            draw_coverage(
+                &mut contact_sheet,
+                rendered_character,
+                glyph_x as i64,
+                glyph_y as i64,
+                (cell_x + cell_width) as i64,
+            );
+        }
+
+        if let Some(label_font) = &label_font {
+            draw_label(
+                &mut contact_sheet,
+                label_font,
+                font_name,
+                (cell_x + CELL_PADDING) as i64,
+                (cell_y + CELL_PADDING + options.cell_size) as i64 + LABEL_BASELINE,
+                (cell_x + cell_width - CELL_PADDING) as i64,
+            );
+        }
+    }
+
+    Ok(contact_sheet)
+}
+
+fn centering_offset(available: u32, used: u32) -> u32 {
+    available.saturating_sub(used) / 2
+}
+
+// Darkens the image with the coverage of a rendered character whose top left corner is at (x, y),
+// pixels at or right of `x_limit` are clipped
+fn draw_coverage(
+    image: &mut GrayImage,
+    rendered_character: &RenderedCharacter,
+    x: i64,
+    y: i64,
+    x_limit: i64,
+) {
+    let glyph_size = rendered_character.glyph_size;
+    for (row_index, row) in rendered_character
+        .bitmap
+        .iter()
+        .take(glyph_size.height)
+        .enumerate()
+    {
+        for (column_index, coverage) in row.iter().take(glyph_size.width).enumerate() {
+            let pixel_x = x + column_index as i64;
+            let pixel_y = y + row_index as i64;
+            if pixel_x < 0
+                || pixel_y < 0
+                || pixel_x >= x_limit.min(image.width() as i64)
+                || pixel_y >= image.height() as i64
+            {
+                continue;
+            }
+
+            let pixel = image.get_pixel_mut(pixel_x as u32, pixel_y as u32);
+            pixel.0[0] = pixel.0[0].min(BACKGROUND - *coverage);
+        }
+    }
+}
+
+// Draws a single line of text with its baseline at `baseline`, truncated at `x_limit`
+fn draw_label(
+    image: &mut GrayImage,
+    label_font: &CharacterPreview,
+    text: &str,
+    x: i64,
+    baseline: i64,
+    x_limit: i64,
+) {
+    let size = RenderSize::new(LABEL_HEIGHT as usize, LABEL_HEIGHT as usize);
+    let mut pen_x = x;
+    for chr in text.chars() {
+        if pen_x >= x_limit {
+            break;
+        }
+        let rendered_character = match label_font.render_character(chr, size) {
+            Ok(rendered_character) => rendered_character,
+            Err(_) => continue,
+        };
+        draw_coverage(
+            image,
+            &rendered_character,
+            pen_x + rendered_character.bitmap_left as i64,
+            baseline - rendered_character.bitmap_top as i64,
+            x_limit,
+        );
+        pen_x += rendered_character.advance_width as i64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preview::PreviewContext;
+
+    #[test]
+    fn test_centering_offset() {
+        assert_eq!(centering_offset(128, 64), 32);
+        assert_eq!(centering_offset(128, 200), 0);
+    }
+
+    #[test]
+    fn test_draw_coverage() {
+        let mut image = GrayImage::from_pixel(4, 4, Luma([BACKGROUND]));
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![255, 128], vec![0, 64]],
+            colors: None,
+            glyph_size: RenderSize::new(2, 2),
+            bitmap_left: 0,
+            bitmap_top: 2,
+            advance_width: 2,
+            advance_height: 2,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+
+        draw_coverage(&mut image, &rendered_character, 1, 1, 3);
+        assert_eq!(image.get_pixel(1, 1).0, [0]);
+        assert_eq!(image.get_pixel(2, 1).0, [127]);
+        assert_eq!(image.get_pixel(1, 2).0, [255]);
+        assert_eq!(image.get_pixel(2, 2).0, [191]);
+
+        // Clipped by the limit and by the image
+        draw_coverage(&mut image, &rendered_character, 2, 3, 3);
+        assert_eq!(image.get_pixel(2, 3).0, [0]);
+        assert_eq!(image.get_pixel(3, 3).0, [255]);
+    }
+
+    #[test]
+    fn test_render_contact_sheet() {
+        let mut character_preview = match CharacterPreview::new(
+            &PreviewContext::new().unwrap(),
+            'A',
+            None,
+            &FontMatchOptions::default(),
+        ) {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+
+        let options = ContactSheetOptions {
+            columns: 2,
+            cell_size: 32,
+        };
+        let contact_sheet = render_contact_sheet(&mut character_preview, &options).unwrap();
+        assert_eq!(contact_sheet.width() % (32 + CELL_PADDING * 2), 0);
+        assert!(contact_sheet.pixels().any(|pixel| pixel.0[0] < BACKGROUND));
+
+        let options = ContactSheetOptions {
+            columns: 0,
+            cell_size: 32,
+        };
+        assert!(render_contact_sheet(&mut character_preview, &options).is_err());
+    }
+}
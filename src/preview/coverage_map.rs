@@ -0,0 +1,213 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! A coverage map shows which code points of a range (usually a Unicode block) a font has a glyph
+//! for, laid out 16 code points per row like the code charts of the Unicode standard.
+//!
+
+use std::char;
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "image")]
+use image::{Rgb, RgbImage};
+use unic::ucd::GeneralCategory;
+
+use super::character_preview::CharacterPreview;
+
+#[cfg(feature = "image")]
+const COLUMNS: u32 = 16;
+#[cfg(feature = "image")]
+const CELL_GAP: u32 = 1;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CodePointCoverage {
+    Covered,
+    Missing,
+
+    // No character is assigned to the code point, so no font is expected to cover it
+    Unassigned,
+}
+
+// Returns the coverage of each code point of the range by the current font of the preview,
+// surrogates are skipped since they aren't characters
+pub fn range_coverage(
+    character_preview: &CharacterPreview,
+    range: RangeInclusive<char>,
+) -> Vec<(char, CodePointCoverage)> {
+    (*range.start() as u32..=*range.end() as u32)
+        .filter_map(char::from_u32)
+        .map(|chr| {
+            let coverage = if GeneralCategory::of(chr) == GeneralCategory::Unassigned {
+                CodePointCoverage::Unassigned
+            } else if character_preview.has_glyph(chr) {
+                CodePointCoverage::Covered
+            } else {
+                CodePointCoverage::Missing
+            };
+            (chr, coverage)
+        })
+        .collect()
+}
+
+// The number of code points covered by the font, and the number of those assigned a character
+pub fn coverage_counts(coverage: &[(char, CodePointCoverage)]) -> (usize, usize) {
+    let covered_count = coverage
+        .iter()
+        .filter(|(_, coverage)| *coverage == CodePointCoverage::Covered)
+        .count();
+    let assigned_count = coverage
+        .iter()
+        .filter(|(_, coverage)| *coverage != CodePointCoverage::Unassigned)
+        .count();
+    (covered_count, assigned_count)
+}
+
+#[cfg(feature = "image")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CoverageMapOptions {
+    // Width and height of the cell of each code point, in pixels
+    pub cell_size: u32,
+
+    pub covered_color: [u8; 3],
+    pub missing_color: [u8; 3],
+    pub unassigned_color: [u8; 3],
+    pub background_color: [u8; 3],
+}
+
+#[cfg(feature = "image")]
+impl Default for CoverageMapOptions {
+    fn default() -> Self {
+        CoverageMapOptions {
+            cell_size: 12,
+            covered_color: [67, 160, 71],
+            missing_color: [229, 57, 53],
+            unassigned_color: [224, 224, 224],
+            background_color: [255, 255, 255],
+        }
+    }
+}
+
+// Renders the coverage of a range as a grid of colored cells, 16 per row. Rows start at multiples
+// of 16, so code points keep their column of the code charts even when the range doesn't.
+#[cfg(feature = "image")]
+pub fn render_coverage_map(
+    coverage: &[(char, CodePointCoverage)],
+    options: &CoverageMapOptions,
+) -> RgbImage {
+    let first_row_start = coverage
+        .first()
+        .map(|(chr, _)| *chr as u32 & !(COLUMNS - 1))
+        .unwrap_or(0);
+    let last_code_point = coverage.last().map(|(chr, _)| *chr as u32).unwrap_or(0);
+    let rows = if coverage.is_empty() {
+        0
+    } else {
+        (last_code_point - first_row_start) / COLUMNS + 1
+    };
+
+    let cell_pitch = options.cell_size + CELL_GAP;
+    let mut coverage_map = RgbImage::from_pixel(
+        COLUMNS * cell_pitch + CELL_GAP,
+        rows * cell_pitch + CELL_GAP,
+        Rgb(options.background_color),
+    );
+
+    for (chr, code_point_coverage) in coverage {
+        let offset = *chr as u32 - first_row_start;
+        let cell_x = CELL_GAP + (offset % COLUMNS) * cell_pitch;
+        let cell_y = CELL_GAP + (offset / COLUMNS) * cell_pitch;
+        let color = Rgb(match code_point_coverage {
+            CodePointCoverage::Covered => options.covered_color,
+            CodePointCoverage::Missing => options.missing_color,
+            CodePointCoverage::Unassigned => options.unassigned_color,
+        });
+        for y in cell_y..cell_y + options.cell_size {
+            for x in cell_x..cell_x + options.cell_size {
+                coverage_map.put_pixel(x, y, color);
+            }
+        }
+    }
+
+    coverage_map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preview::{FontMatchOptions, PreviewContext};
+
+    #[test]
+    fn test_coverage_counts() {
+        let coverage = vec![
+            ('A', CodePointCoverage::Covered),
+            ('B', CodePointCoverage::Missing),
+            ('\u{0378}', CodePointCoverage::Unassigned),
+            ('C', CodePointCoverage::Covered),
+        ];
+        assert_eq!(coverage_counts(&coverage), (2, 3));
+        assert_eq!(coverage_counts(&[]), (0, 0));
+    }
+
+    #[test]
+    fn test_range_coverage() {
+        let character_preview = match CharacterPreview::new(
+            &PreviewContext::new().unwrap(),
+            'A',
+            None,
+            &FontMatchOptions::default(),
+        ) {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+
+        let coverage = range_coverage(&character_preview, 'A'..='Z');
+        assert_eq!(coverage.len(), 26);
+        assert!(coverage
+            .iter()
+            .all(|(_, coverage)| *coverage == CodePointCoverage::Covered));
+
+        // U+0378 is unassigned, surrogates are skipped
+        let coverage = range_coverage(&character_preview, '\u{0378}'..='\u{0378}');
+        assert_eq!(coverage, vec![('\u{0378}', CodePointCoverage::Unassigned)]);
+        let coverage = range_coverage(&character_preview, '\u{D7FF}'..='\u{E000}');
+        assert_eq!(coverage.len(), 2);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_render_coverage_map() {
+        let coverage = vec![
+            ('\u{0012}', CodePointCoverage::Covered),
+            ('\u{0013}', CodePointCoverage::Missing),
+            ('\u{0020}', CodePointCoverage::Unassigned),
+        ];
+        let options = CoverageMapOptions {
+            cell_size: 2,
+            ..CoverageMapOptions::default()
+        };
+        let coverage_map = render_coverage_map(&coverage, &options);
+
+        // Rows of U+0010 and U+0020, cells of 2 pixels apart by 1
+        assert_eq!(coverage_map.width(), 16 * 3 + 1);
+        assert_eq!(coverage_map.height(), 2 * 3 + 1);
+        assert_eq!(coverage_map.get_pixel(7, 1).0, options.covered_color);
+        assert_eq!(coverage_map.get_pixel(10, 2).0, options.missing_color);
+        assert_eq!(coverage_map.get_pixel(1, 4).0, options.unassigned_color);
+        assert_eq!(coverage_map.get_pixel(0, 0).0, options.background_color);
+        assert_eq!(coverage_map.get_pixel(4, 1).0, options.background_color);
+
+        assert_eq!(render_coverage_map(&[], &options).height(), CELL_GAP);
+    }
+}
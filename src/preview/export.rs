@@ -0,0 +1,192 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Exports the glyph of a character out of Cicero, e.g. for bug reports and design documents, as
+//! a PNG image (with the `image` feature) or, for outline fonts, as the vector outline of an SVG
+//! document.
+//!
+
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "image")]
+use image::RgbaImage;
+
+use super::character_preview::{CharacterPreview, GlyphMetrics};
+#[cfg(feature = "image")]
+use super::character_preview::{RenderSize, RenderedCharacter};
+use super::freetype_ext::OutlineSegment;
+use super::Result;
+
+// Glyphs without a color of their own are exported in black
+#[cfg(feature = "image")]
+const INK_COLOR: [u8; 3] = [0, 0, 0];
+
+// Images can't be empty, blank glyphs are saved as a single transparent pixel
+#[cfg(feature = "image")]
+fn png_image(rendered_character: &RenderedCharacter) -> RgbaImage {
+    let image = rendered_character.to_rgba_image(INK_COLOR);
+    if image.width() == 0 || image.height() == 0 {
+        return RgbaImage::new(1, 1);
+    }
+    image
+}
+
+// Renders the character with the current font and render options of the preview at a pixel size,
+// and saves its glyph as a PNG image
+#[cfg(feature = "image")]
+pub fn export_png(
+    character_preview: &CharacterPreview,
+    pixel_size: u32,
+    path: &Path,
+) -> Result<()> {
+    let size = RenderSize::new(pixel_size as usize, pixel_size as usize);
+    let rendered_character = character_preview.render_character(character_preview.chr, size)?;
+    png_image(&rendered_character).save(path)?;
+    Ok(())
+}
+
+// SVG path data of the segments of an outline, with y flipped since it goes down in SVG
+pub fn svg_path_data(segments: &[OutlineSegment]) -> String {
+    let mut commands = vec![];
+    for segment in segments {
+        let command = match segment {
+            OutlineSegment::MoveTo((x, y)) => {
+                if !commands.is_empty() {
+                    commands.push("Z".to_owned());
+                }
+                format!("M{} {}", x, -y)
+            }
+            OutlineSegment::LineTo((x, y)) => format!("L{} {}", x, -y),
+            OutlineSegment::ConicTo((control_x, control_y), (x, y)) => {
+                format!("Q{} {} {} {}", control_x, -control_y, x, -y)
+            }
+            OutlineSegment::CubicTo((control1_x, control1_y), (control2_x, control2_y), (x, y)) => {
+                format!(
+                    "C{} {} {} {} {} {}",
+                    control1_x, -control1_y, control2_x, -control2_y, x, -y
+                )
+            }
+        };
+        commands.push(command);
+    }
+    if !commands.is_empty() {
+        commands.push("Z".to_owned());
+    }
+    commands.join(" ")
+}
+
+// An SVG document of the outline of a glyph in font units, framed by the bounding box of the glyph
+pub fn glyph_svg(segments: &[OutlineSegment], glyph_metrics: &GlyphMetrics) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n  \
+         <path d=\"{}\"/>\n</svg>\n",
+        glyph_metrics.bearing_x,
+        -glyph_metrics.bearing_y,
+        glyph_metrics.width.max(1),
+        glyph_metrics.height.max(1),
+        svg_path_data(segments)
+    )
+}
+
+// Saves the outline of the glyph of the character in the current font as an SVG document, only
+// available for outline fonts
+pub fn export_svg(character_preview: &CharacterPreview, path: &Path) -> Result<()> {
+    let segments = character_preview.outline_segments()?;
+    let glyph_metrics = character_preview.glyph_metrics()?;
+    fs::write(path, glyph_svg(&segments, &glyph_metrics))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg_path_data() {
+        assert_eq!(svg_path_data(&[]), "");
+
+        // A triangle and a contour of a quadratic and a cubic curve
+        let segments = vec![
+            OutlineSegment::MoveTo((0, 0)),
+            OutlineSegment::LineTo((100, 0)),
+            OutlineSegment::LineTo((50, 80)),
+            OutlineSegment::MoveTo((10, 10)),
+            OutlineSegment::ConicTo((20, 30), (40, 10)),
+            OutlineSegment::CubicTo((40, -10), (10, -10), (10, 10)),
+        ];
+        assert_eq!(
+            svg_path_data(&segments),
+            "M0 0 L100 0 L50 -80 Z M10 -10 Q20 -30 40 -10 C40 10 10 10 10 -10 Z"
+        );
+    }
+
+    #[test]
+    fn test_glyph_svg() {
+        let glyph_metrics = GlyphMetrics {
+            units_per_em: 1000,
+            advance_width: 600,
+            width: 500,
+            height: 700,
+            bearing_x: 50,
+            bearing_y: 700,
+        };
+        let segments = vec![
+            OutlineSegment::MoveTo((50, 0)),
+            OutlineSegment::LineTo((550, 0)),
+            OutlineSegment::LineTo((300, 700)),
+        ];
+        assert_eq!(
+            glyph_svg(&segments, &glyph_metrics),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"50 -700 500 700\">\n  \
+             <path d=\"M50 0 L550 0 L300 -700 Z\"/>\n</svg>\n"
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_png_image() {
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![0, 255], vec![128, 0]],
+            colors: None,
+            glyph_size: RenderSize::new(2, 2),
+            bitmap_left: 0,
+            bitmap_top: 2,
+            advance_width: 2,
+            advance_height: 2,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        let image = png_image(&rendered_character);
+        assert_eq!(image.dimensions(), (2, 2));
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![],
+            colors: None,
+            glyph_size: RenderSize::new(0, 0),
+            bitmap_left: 0,
+            bitmap_top: 0,
+            advance_width: 4,
+            advance_height: 4,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        let image = png_image(&rendered_character);
+        assert_eq!(image.dimensions(), (1, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+    }
+}
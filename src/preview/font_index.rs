@@ -0,0 +1,289 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//! Font index
+//!
+//! Content hashes of font files, which tell identical fonts installed under several paths apart
+//! from distinct fonts. The index can be saved in a cache directory, so that fonts hashed once
+//! aren't read again on the next start.
+//!
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use super::font_match::{deduplicate_fonts, MatchedFont};
+use super::{Error, Result};
+
+const INDEX_FILE_NAME: &str = "content-hashes.json";
+
+// Bumped whenever fonts are hashed differently, so that hashes saved before aren't compared with
+// new ones
+const INDEX_VERSION: u32 = 1;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+// The index as saved, along with the version it was hashed with
+#[derive(Serialize, Deserialize)]
+struct Index<H> {
+    version: u32,
+    hashes: H,
+}
+
+// The 64-bit FNV-1a hash of the contents of a font. Unlike `DefaultHasher`, its algorithm is fixed,
+// so hashes stay comparable across Rust releases.
+fn content_hash(contents: &[u8]) -> u64 {
+    contents.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct ContentHash {
+    size: u64,
+    modified: Option<SystemTime>,
+    hash: u64,
+}
+
+// Hashes of the contents of font files by path, kept as long as a file keeps its size and
+// modification time so that each font is read at most once
+#[derive(Debug, Default)]
+pub struct ContentHashes {
+    hashes: HashMap<String, ContentHash>,
+
+    // The file the hashes are saved to once they change, none to keep them in memory only
+    index_file: Option<PathBuf>,
+    changed: bool,
+}
+
+impl ContentHashes {
+    // Loads the index saved in the cache directory, which is created if missing. Fails if the
+    // directory isn't writable, an unreadable index is started over.
+    pub fn in_cache_directory(directory: &Path) -> Result<ContentHashes> {
+        let cache_directory_error = |error: std::io::Error| {
+            Box::new(Error::CacheDirectoryNotWritable {
+                directory: directory.display().to_string(),
+                reason: error.to_string(),
+            })
+        };
+        fs::create_dir_all(directory).map_err(cache_directory_error)?;
+        let write_test_file = directory.join(".write-test");
+        fs::write(&write_test_file, b"").map_err(cache_directory_error)?;
+        let _ = fs::remove_file(&write_test_file);
+
+        let index_file = directory.join(INDEX_FILE_NAME);
+        let hashes = match fs::read(&index_file) {
+            Ok(contents) => match serde_json::from_slice::<Index<_>>(&contents) {
+                Ok(index) if index.version == INDEX_VERSION => index.hashes,
+                Ok(index) => {
+                    debug!(
+                        "Ignoring the font index {} of version {}",
+                        index_file.display(),
+                        index.version
+                    );
+                    HashMap::new()
+                }
+                Err(error) => {
+                    warn!(
+                        "Ignoring the font index {}: {}",
+                        index_file.display(),
+                        error
+                    );
+                    HashMap::new()
+                }
+            },
+            Err(_) => HashMap::new(),
+        };
+        debug!(
+            "Loaded {} font hashes from {}",
+            hashes.len(),
+            index_file.display()
+        );
+        Ok(ContentHashes {
+            hashes,
+            index_file: Some(index_file),
+            changed: false,
+        })
+    }
+
+    pub(super) fn hash_of(&mut self, path: &str, metadata: &fs::Metadata) -> Option<u64> {
+        let size = metadata.len();
+        let modified = metadata.modified().ok();
+        if let Some(content_hash) = self.hashes.get(path) {
+            if content_hash.size == size && content_hash.modified == modified {
+                return Some(content_hash.hash);
+            }
+        }
+
+        let hash = content_hash(&fs::read(path).ok()?);
+        self.hashes.insert(
+            path.to_owned(),
+            ContentHash {
+                size,
+                modified,
+                hash,
+            },
+        );
+        self.changed = true;
+        Some(hash)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    // Forgets every hash, then hashes the fonts which can only be told apart by their contents,
+    // i.e. those sharing their family, style and size with another one
+    pub fn rebuild(&mut self, fonts: Vec<MatchedFont>) -> Result<()> {
+        self.hashes.clear();
+        self.changed = true;
+        deduplicate_fonts(fonts, self);
+        self.save()
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        let index_file = match &self.index_file {
+            Some(index_file) if self.changed => index_file,
+            _ => return Ok(()),
+        };
+        // Written next to the index first, so that an interrupted save leaves the previous one
+        let partial_index_file = index_file.with_extension("json.partial");
+        let index = Index {
+            version: INDEX_VERSION,
+            hashes: &self.hashes,
+        };
+        fs::write(&partial_index_file, serde_json::to_vec(&index)?)?;
+        fs::rename(&partial_index_file, index_file)?;
+        self.changed = false;
+        debug!(
+            "Saved {} font hashes to {}",
+            self.hashes.len(),
+            index_file.display()
+        );
+        Ok(())
+    }
+}
+
+impl Drop for ContentHashes {
+    fn drop(&mut self) {
+        if let Err(error) = self.save() {
+            warn!("Failed to save the font index: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebuild() {
+        let directory =
+            std::env::temp_dir().join(format!("cicero-test-font-index-{}", std::process::id()));
+        let fonts_directory = directory.join("fonts");
+        fs::create_dir_all(&fonts_directory).unwrap();
+        let font = |file_name: &str, contents: &[u8]| {
+            let path = fonts_directory.join(file_name);
+            fs::write(&path, contents).unwrap();
+            MatchedFont {
+                path: path.to_string_lossy().into_owned(),
+                family: "Serif".to_owned(),
+                style: "Regular".to_owned(),
+                face_index: 0,
+                monospace: false,
+            }
+        };
+        let fonts = vec![
+            font("a.ttf", b"font data"),
+            font("b.ttf", b"font data"),
+            font("c.ttf", b"other font data"),
+        ];
+
+        let cache_directory = directory.join("cache");
+        let mut content_hashes = ContentHashes::in_cache_directory(&cache_directory).unwrap();
+        content_hashes.hashes.insert(
+            "/removed/font.ttf".to_owned(),
+            ContentHash {
+                size: 1,
+                modified: None,
+                hash: 1,
+            },
+        );
+        content_hashes.rebuild(fonts.clone()).unwrap();
+        assert_eq!(content_hashes.len(), 2);
+        assert!(!content_hashes.hashes.contains_key("/removed/font.ttf"));
+        assert!(!content_hashes.hashes.contains_key(&fonts[2].path));
+        drop(content_hashes);
+
+        let content_hashes = ContentHashes::in_cache_directory(&cache_directory).unwrap();
+        assert_eq!(content_hashes.len(), 2);
+        assert!(content_hashes.hashes.contains_key(&fonts[0].path));
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(content_hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_outdated_index() {
+        let directory =
+            std::env::temp_dir().join(format!("cicero-test-outdated-index-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let index = Index {
+            version: INDEX_VERSION + 1,
+            hashes: vec![(
+                "/some/font.ttf".to_owned(),
+                ContentHash {
+                    size: 1,
+                    modified: None,
+                    hash: 1,
+                },
+            )]
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+        };
+        fs::write(
+            directory.join(INDEX_FILE_NAME),
+            serde_json::to_vec(&index).unwrap(),
+        )
+        .unwrap();
+
+        // Hashes of another version (or those saved before versions) are started over
+        let content_hashes = ContentHashes::in_cache_directory(&directory).unwrap();
+        assert_eq!(content_hashes.len(), 0);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_unwritable_cache_directory() {
+        let file = std::env::temp_dir().join(format!(
+            "cicero-test-unwritable-cache-{}",
+            std::process::id()
+        ));
+        fs::write(&file, b"").unwrap();
+        assert!(ContentHashes::in_cache_directory(&file.join("cache")).is_err());
+        let _ = fs::remove_file(&file);
+    }
+}
@@ -12,17 +12,199 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
+use std::cmp::min;
+use std::collections::{HashMap, HashSet};
 use std::ffi;
 use std::ffi::CStr;
-use std::os::raw::c_char;
+use std::fs;
+use std::os::raw::{c_char, c_int};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 use std::slice;
+use std::time::Duration;
 
 use fontconfig::fontconfig as fc;
+use log::{debug, warn};
+use unic::ucd::Block;
 
+use super::font_index::ContentHashes;
 use super::{Error, Result};
 
-pub fn fonts_for(chr: char) -> Result<Vec<String>> {
-    unsafe {
+// Styles considered to be the regular one of a family, in order of preference
+const REGULAR_STYLE_NAMES: &[&str] = &["Regular", "Book", "Normal", "Roman", "Medium"];
+
+// The `spacing` of fontconfig for dual width (e.g. CJK) fonts, monospaced and character cell fonts
+// are above it
+const FC_DUAL: c_int = 90;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MatchedFont {
+    pub path: String,
+    pub family: String,
+    pub style: String,
+
+    // The face in the font file, faces of a font collection (and the named instances of a variable
+    // font, in the upper 16 bits) are listed as fonts of the same path
+    pub face_index: isize,
+
+    // Every glyph has the same advance, or twice that advance for dual width fonts
+    pub monospace: bool,
+}
+
+// Which of the fonts matching a character are listed, by their style. Fonts are kept when they pass
+// every filter.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FontStyleFilter {
+    // Only the most regular style of each family, e.g. no Bold or Condensed Italic
+    pub regular_only: bool,
+
+    pub monospace_only: bool,
+
+    // Fonts whose style has one of these words (case insensitive), e.g. "Italic", if any are given
+    pub include_styles: Vec<String>,
+
+    // Fonts whose style has one of these words are left out, e.g. "Condensed"
+    pub exclude_styles: Vec<String>,
+}
+
+impl FontStyleFilter {
+    pub fn is_active(&self) -> bool {
+        self.regular_only
+            || self.monospace_only
+            || !self.include_styles.is_empty()
+            || !self.exclude_styles.is_empty()
+    }
+
+    fn keeps(&self, font: &MatchedFont) -> bool {
+        let has_style_word = |words: &[String]| {
+            font.style.split_whitespace().any(|style_word| {
+                words
+                    .iter()
+                    .any(|word| style_word.eq_ignore_ascii_case(word))
+            })
+        };
+        (!self.monospace_only || font.monospace)
+            && (self.include_styles.is_empty() || has_style_word(&self.include_styles))
+            && !has_style_word(&self.exclude_styles)
+    }
+}
+
+// How the fonts matching a character are filtered, ordered and loaded
+#[derive(Debug, Clone)]
+pub struct FontMatchOptions {
+    pub preferred_font_family: Option<String>,
+
+    // Font families preferred for the characters of a Unicode block (by name, e.g. "Arabic"), over
+    // the preferred font family
+    pub block_font_families: Vec<(String, String)>,
+    pub deduplicate: bool,
+    pub style_filter: FontStyleFilter,
+
+    // Families (case insensitive), font paths or directories never matched, see `exclude_fonts`
+    pub excluded_fonts: Vec<String>,
+
+    // Fonts in these directories (or their subdirectories) come first, in the order of the list
+    pub directory_priority: Vec<PathBuf>,
+
+    // Fonts taking longer than this to load are skipped, see `font_probe`
+    pub load_timeout: Option<Duration>,
+
+    // Only the first fonts (after prioritization) are kept, a character can match hundreds of
+    // fonts on systems with large font collections
+    pub max_fonts: Option<usize>,
+}
+
+impl Default for FontMatchOptions {
+    fn default() -> Self {
+        FontMatchOptions {
+            preferred_font_family: None,
+            block_font_families: vec![],
+            deduplicate: true,
+            style_filter: FontStyleFilter::default(),
+            excluded_fonts: vec![],
+            directory_priority: vec![],
+            load_timeout: None,
+            max_fonts: None,
+        }
+    }
+}
+
+impl FontMatchOptions {
+    // The font families to preview the character with by default, in order of preference: the
+    // family of its block first, then the preferred font family
+    pub fn preferred_font_families_of(&self, chr: char) -> Vec<&String> {
+        let block_name = Block::of(chr).map(|block| block.name);
+        self.block_font_families
+            .iter()
+            .filter(|(block, _)| Some(block.as_str()) == block_name)
+            .map(|(_, family)| family)
+            .chain(self.preferred_font_family.iter())
+            .collect()
+    }
+}
+
+unsafe fn pattern_string(pattern: *mut fc::FcPattern, object: &str) -> Result<Option<String>> {
+    let mut value: *mut u8 = std::ptr::null_mut();
+    let result = fc::FcPatternGetString(
+        pattern,
+        ffi::CString::new(object)?.as_ptr(),
+        0,
+        &mut value as *mut *mut u8,
+    );
+
+    if result != fc::FcResultMatch {
+        return Ok(None);
+    }
+    Ok(Some(
+        CStr::from_ptr(value as *mut c_char).to_str()?.to_owned(),
+    ))
+}
+
+unsafe fn pattern_integer(pattern: *mut fc::FcPattern, object: &str) -> Result<Option<c_int>> {
+    let mut value: c_int = 0;
+    let result = fc::FcPatternGetInteger(
+        pattern,
+        ffi::CString::new(object)?.as_ptr(),
+        0,
+        &mut value as *mut c_int,
+    );
+
+    if result != fc::FcResultMatch {
+        return Ok(None);
+    }
+    Ok(Some(value))
+}
+
+// Adds the fonts of the directories (and their subdirectories) to those fontconfig lists, e.g. the
+// build of a font under development which isn't installed. Every directory is tried, the error names
+// the first one which couldn't be added (e.g. a missing directory).
+pub fn add_font_directories(directories: &[PathBuf]) -> Result<()> {
+    let mut first_error = None;
+    for directory in directories {
+        let directory_path = ffi::CString::new(directory.as_os_str().as_bytes())?;
+        let added = unsafe {
+            fc::FcConfigAppFontAddDir(std::ptr::null_mut(), directory_path.as_ptr() as *const u8)
+        };
+        if added == 0 {
+            warn!("Failed to add the fonts of {}", directory.display());
+            first_error.get_or_insert_with(|| Error::FontDirectoryNotAdded {
+                directory: directory.display().to_string(),
+            });
+        } else {
+            debug!("Added the fonts of {}", directory.display());
+        }
+    }
+    match first_error {
+        Some(error) => Err(Box::new(error)),
+        None => Ok(()),
+    }
+}
+
+// Font coverage is looked up in the charset index fontconfig keeps in its cache, no font file is
+// opened here, so matching isn't worth spreading over threads. The faces of a font collection are
+// each listed, and fonts are sorted so that the order fonts are cycled through is stable.
+pub fn fonts_for(chr: char) -> Result<Vec<MatchedFont>> {
+    let fonts = unsafe {
         let char_set = fc::FcCharSetCreate();
         defer! {
             fc::FcCharSetDestroy(char_set);
@@ -35,49 +217,550 @@ pub fn fonts_for(chr: char) -> Result<Vec<String>> {
         }
         fc::FcPatternAddCharSet(pattern, ffi::CString::new("charset")?.as_ptr(), char_set);
 
-        let object_set = fc::FcObjectSetCreate();
-        defer! {
-            fc::FcObjectSetDestroy(object_set);
-        }
-        fc::FcObjectSetAdd(object_set, ffi::CString::new("file")?.as_ptr());
+        list_fonts(pattern)?
+    };
+
+    if fonts.is_empty() {
+        return Err(Box::new(Error::GlyphNotFound { chr }));
+    }
+    debug!("{} fonts match U+{:04X}", fonts.len(), chr as u32);
+    Ok(fonts)
+}
 
-        let font_set = fc::FcFontList(std::ptr::null_mut(), pattern, object_set);
+// Every font fontconfig knows of, e.g. to index them all at once
+pub fn all_fonts() -> Result<Vec<MatchedFont>> {
+    unsafe {
+        let pattern = fc::FcPatternCreate();
         defer! {
-            fc::FcFontSetDestroy(font_set);
+            fc::FcPatternDestroy(pattern);
         }
+        list_fonts(pattern)
+    }
+}
+
+// The fonts matching the pattern sorted by path, then face index for the faces of a font collection.
+// Fonts without a file are left out.
+unsafe fn list_fonts(pattern: *mut fc::FcPattern) -> Result<Vec<MatchedFont>> {
+    let object_set = fc::FcObjectSetCreate();
+    defer! {
+        fc::FcObjectSetDestroy(object_set);
+    }
+    for object in &["file", "index", "family", "style", "spacing"] {
+        fc::FcObjectSetAdd(object_set, ffi::CString::new(*object)?.as_ptr());
+    }
 
-        if (*font_set).nfont <= 0 {
-            return Err(Box::new(Error::GlyphNotFound { chr }));
+    let font_set = fc::FcFontList(std::ptr::null_mut(), pattern, object_set);
+    defer! {
+        fc::FcFontSetDestroy(font_set);
+    }
+
+    if (*font_set).nfont <= 0 {
+        return Ok(vec![]);
+    }
+
+    let patterns_slice =
+        slice::from_raw_parts::<*mut fc::FcPattern>((*font_set).fonts, (*font_set).nfont as usize);
+    let mut fonts = Vec::with_capacity(patterns_slice.len());
+    for pattern in patterns_slice {
+        let path = match pattern_string(*pattern, "file")? {
+            Some(path) => path,
+            None => continue,
+        };
+        fonts.push(MatchedFont {
+            path,
+            family: pattern_string(*pattern, "family")?.unwrap_or_default(),
+            style: pattern_string(*pattern, "style")?.unwrap_or_default(),
+            face_index: pattern_integer(*pattern, "index")?.unwrap_or(0) as isize,
+            monospace: pattern_integer(*pattern, "spacing")?
+                .map_or(false, |spacing| spacing >= FC_DUAL),
+        });
+    }
+
+    fonts.sort_by(|lhs, rhs| {
+        (&lhs.path, lhs.face_index, &lhs.style).cmp(&(&rhs.path, rhs.face_index, &rhs.style))
+    });
+    Ok(fonts)
+}
+
+// The same font is often installed under several paths (symlinks, multiple packages), fonts of the
+// same family and style with identical contents are reduced to the first one of them. Only files
+// which share their family, style and size with another font are read.
+pub fn deduplicate_fonts(
+    fonts: Vec<MatchedFont>,
+    content_hashes: &mut ContentHashes,
+) -> Vec<MatchedFont> {
+    let mut style_counts: HashMap<(String, String), usize> = HashMap::new();
+    for font in &fonts {
+        *style_counts
+            .entry((font.family.clone(), font.style.clone()))
+            .or_insert(0) += 1;
+    }
+
+    let fonts_with_metadata: Vec<(MatchedFont, Option<fs::Metadata>)> = fonts
+        .into_iter()
+        .map(|font| {
+            let style = (font.family.clone(), font.style.clone());
+            let metadata = if style_counts[&style] < 2 {
+                None
+            } else {
+                fs::metadata(&font.path).ok()
+            };
+            (font, metadata)
+        })
+        .collect();
+    let mut size_counts: HashMap<(&str, &str, u64), usize> = HashMap::new();
+    for (font, metadata) in &fonts_with_metadata {
+        if let Some(metadata) = metadata {
+            *size_counts
+                .entry((&font.family, &font.style, metadata.len()))
+                .or_insert(0) += 1;
         }
+    }
+    let size_collisions: HashSet<(String, String, u64)> = size_counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|((family, style, size), _)| (family.to_owned(), style.to_owned(), size))
+        .collect();
 
-        let mut font_paths = vec![];
-        {
-            font_paths.reserve((*font_set).nfont as usize);
-
-            let patterns_slice = slice::from_raw_parts::<*mut fc::FcPattern>(
-                (*font_set).fonts,
-                (*font_set).nfont as usize,
-            );
-
-            for pattern in patterns_slice {
-                let mut value: *mut u8 = std::ptr::null_mut();
-                let result = fc::FcPatternGetString(
-                    *pattern,
-                    ffi::CString::new("file")?.as_ptr(),
-                    0,
-                    &mut value as *mut *mut u8,
-                );
-
-                if result != fc::FcResultMatch {
-                    return Err(Box::new(Error::GlyphNotFound { chr }));
-                }
-
-                let font_path = CStr::from_ptr(value as *mut c_char).to_str()?.to_owned();
-                font_paths.push(font_path);
+    let font_count = fonts_with_metadata.len();
+    let mut seen_fonts = HashSet::new();
+    let deduplicated_fonts: Vec<MatchedFont> = fonts_with_metadata
+        .into_iter()
+        .filter(|(font, metadata)| {
+            let metadata = match metadata {
+                Some(metadata) => metadata,
+                None => return true,
+            };
+            let size_key = (font.family.clone(), font.style.clone(), metadata.len());
+            if !size_collisions.contains(&size_key) {
+                return true;
             }
+            match content_hashes.hash_of(&font.path, metadata) {
+                Some(hash) => seen_fonts.insert((size_key, hash)),
+                None => true,
+            }
+        })
+        .map(|(font, _)| font)
+        .collect();
+    debug!(
+        "{} duplicate fonts left out",
+        font_count - deduplicated_fonts.len()
+    );
+    deduplicated_fonts
+}
+
+// Leaves out the fonts of an excluded family, at an excluded path or in an excluded directory, e.g.
+// fallback fonts such as "Symbola" which are never worth previewing. Unlike the style filter, this
+// can leave no font.
+pub fn exclude_fonts(fonts: Vec<MatchedFont>, excluded_fonts: &[String]) -> Vec<MatchedFont> {
+    if excluded_fonts.is_empty() {
+        return fonts;
+    }
+    let font_count = fonts.len();
+    let kept_fonts: Vec<MatchedFont> = fonts
+        .into_iter()
+        .filter(|font| {
+            // An empty path would be the prefix of every path
+            !excluded_fonts.iter().any(|excluded_font| {
+                !excluded_font.is_empty()
+                    && (font.family.eq_ignore_ascii_case(excluded_font)
+                        || Path::new(&font.path).starts_with(excluded_font))
+            })
+        })
+        .collect();
+    debug!("{} excluded fonts left out", font_count - kept_fonts.len());
+    kept_fonts
+}
+
+// Keeps the fonts passing the filter, unless none of them does: the character is then still previewed
+// with every font rather than none. With `regular_only`, the most regular style of each family is
+// kept even if the family has no face named as a regular one (e.g. a Bold-only family).
+pub fn filter_fonts(fonts: Vec<MatchedFont>, style_filter: &FontStyleFilter) -> Vec<MatchedFont> {
+    if !style_filter.is_active() {
+        return fonts;
+    }
+    let font_count = fonts.len();
+    let mut filtered_fonts: Vec<MatchedFont> = fonts
+        .iter()
+        .filter(|font| style_filter.keeps(font))
+        .cloned()
+        .collect();
+    if style_filter.regular_only {
+        let mut regular_style_ranks: HashMap<String, usize> = HashMap::new();
+        for font in &filtered_fonts {
+            let rank = regular_style_ranks
+                .entry(font.family.to_lowercase())
+                .or_insert_with(|| REGULAR_STYLE_NAMES.len());
+            *rank = min(*rank, regular_style_rank(&font.style));
+        }
+        let mut kept_families = HashSet::new();
+        filtered_fonts.retain(|font| {
+            let family = font.family.to_lowercase();
+            regular_style_rank(&font.style) == regular_style_ranks[&family]
+                && (regular_style_ranks[&family] < REGULAR_STYLE_NAMES.len()
+                    || kept_families.insert(family))
+        });
+    }
+    if filtered_fonts.is_empty() {
+        debug!("No font passes the style filter, it is ignored");
+        return fonts;
+    }
+    debug!(
+        "{} fonts left out by the style filter",
+        font_count - filtered_fonts.len()
+    );
+    filtered_fonts
+}
+
+// The position of the style in `REGULAR_STYLE_NAMES`, styles which aren't regular rank last
+fn regular_style_rank(style: &str) -> usize {
+    REGULAR_STYLE_NAMES
+        .iter()
+        .position(|regular_style| style.eq_ignore_ascii_case(regular_style))
+        .unwrap_or_else(|| REGULAR_STYLE_NAMES.len())
+}
+
+// Orders fonts by the first directory of `directory_priority` containing them, fonts outside of all
+// of these directories come last. The order of fonts within the same directory is kept.
+pub fn prioritize_fonts(fonts: &mut Vec<MatchedFont>, directory_priority: &[PathBuf]) {
+    let priority = |font: &MatchedFont| {
+        directory_priority
+            .iter()
+            .position(|directory| Path::new(&font.path).starts_with(directory))
+            .unwrap_or_else(|| directory_priority.len())
+    };
+    fonts.sort_by_key(|font| priority(font));
+}
 
-            font_paths.sort();
+// Keeps the first `max_fonts` fonts in their order, faces of a font collection (which share their
+// path, wherever they are in the list) count as a single font. Returns the number of fonts left
+// out.
+pub fn limit_fonts(fonts: &mut Vec<MatchedFont>, max_fonts: usize) -> usize {
+    let mut font_paths = HashSet::new();
+    let mut kept_font_paths = HashSet::new();
+    fonts.retain(|font| {
+        if font_paths.insert(font.path.clone()) && kept_font_paths.len() < max_fonts {
+            kept_font_paths.insert(font.path.clone());
         }
-        Ok(font_paths)
+        kept_font_paths.contains(&font.path)
+    });
+    let font_count = font_paths.len();
+    let omitted_font_count = font_count.saturating_sub(max_fonts);
+    if omitted_font_count > 0 {
+        debug!(
+            "{} fonts past the maximum of {} left out",
+            omitted_font_count, max_fonts
+        );
+    }
+    omitted_font_count
+}
+
+// Finds the font of the given family (case insensitive) with the most regular style
+pub fn font_of_family<'a>(fonts: &'a [MatchedFont], family: &str) -> Option<&'a MatchedFont> {
+    fonts
+        .iter()
+        .filter(|font| font.family.eq_ignore_ascii_case(family))
+        .min_by_key(|font| regular_style_rank(&font.style))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fonts_for_is_sorted() {
+        // Matching relies on the fonts installed on the system, skip if there is none
+        let fonts = match fonts_for('A') {
+            Ok(fonts) => fonts,
+            Err(_) => return,
+        };
+        // Faces of a font collection share their path, they aren't merged here
+        let font_keys: Vec<(&String, &String, &String)> = fonts
+            .iter()
+            .map(|font| (&font.path, &font.family, &font.style))
+            .collect();
+
+        let mut sorted_font_keys = font_keys.clone();
+        sorted_font_keys.sort();
+        assert_eq!(font_keys, sorted_font_keys);
+        assert_eq!(fonts_for('A').unwrap(), fonts);
+    }
+
+    #[test]
+    fn test_deduplicate_fonts() {
+        let directory = std::env::temp_dir().join(format!(
+            "cicero-test-deduplicate-fonts-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).unwrap();
+        let font = |file_name: &str, contents: &[u8]| {
+            let path = directory.join(file_name);
+            fs::write(&path, contents).unwrap();
+            MatchedFont {
+                path: path.to_string_lossy().into_owned(),
+                family: "Serif".to_owned(),
+                style: "Regular".to_owned(),
+                face_index: 0,
+                monospace: false,
+            }
+        };
+
+        let fonts = vec![
+            font("a.ttf", b"font data"),
+            font("b.ttf", b"font data"),
+            font("c.ttf", b"font date"),
+            font("d.ttf", b"other font data"),
+        ];
+        let mut content_hashes = ContentHashes::default();
+        let deduplicated_fonts = deduplicate_fonts(fonts.clone(), &mut content_hashes);
+        assert_eq!(
+            deduplicated_fonts,
+            vec![fonts[0].clone(), fonts[2].clone(), fonts[3].clone()]
+        );
+        // Only the fonts of the same size are hashed
+        assert_eq!(content_hashes.len(), 3);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_prioritize_fonts() {
+        let font = |path: &str| MatchedFont {
+            path: path.to_owned(),
+            family: String::new(),
+            style: String::new(),
+            face_index: 0,
+            monospace: false,
+        };
+        let mut fonts = vec![
+            font("/a/1.ttf"),
+            font("/b/1.ttf"),
+            font("/b/2.ttf"),
+            font("/c/d/1.ttf"),
+            font("/e/1.ttf"),
+        ];
+
+        prioritize_fonts(&mut fonts, &[PathBuf::from("/c"), PathBuf::from("/b")]);
+        let font_paths: Vec<&str> = fonts.iter().map(|font| font.path.as_str()).collect();
+        assert_eq!(
+            font_paths,
+            vec!["/c/d/1.ttf", "/b/1.ttf", "/b/2.ttf", "/a/1.ttf", "/e/1.ttf"]
+        );
+    }
+
+    #[test]
+    fn test_limit_fonts() {
+        let font = |path: &str, style: &str| MatchedFont {
+            path: path.to_owned(),
+            family: String::new(),
+            style: style.to_owned(),
+            face_index: 0,
+            monospace: false,
+        };
+        let fonts = vec![
+            font("/c/1.ttf", "Regular"),
+            font("/a/1.ttc", "Regular"),
+            font("/a/1.ttc", "Bold"),
+            font("/b/1.ttf", "Regular"),
+            font("/a/2.ttf", "Regular"),
+        ];
+
+        let mut limited_fonts = fonts.clone();
+        assert_eq!(limit_fonts(&mut limited_fonts, 2), 2);
+        assert_eq!(limited_fonts, fonts[..3].to_vec());
+
+        let mut limited_fonts = fonts.clone();
+        assert_eq!(limit_fonts(&mut limited_fonts, 4), 0);
+        assert_eq!(limited_fonts, fonts);
+
+        let mut limited_fonts = fonts.clone();
+        assert_eq!(limit_fonts(&mut limited_fonts, 0), 4);
+        assert!(limited_fonts.is_empty());
+
+        // Faces of a collection apart from each other count once, and are kept together
+        let fonts = vec![
+            font("/a/1.ttc", "Regular"),
+            font("/b/1.ttf", "Regular"),
+            font("/a/1.ttc", "Bold"),
+            font("/c/1.ttf", "Regular"),
+        ];
+        let mut limited_fonts = fonts.clone();
+        assert_eq!(limit_fonts(&mut limited_fonts, 2), 1);
+        assert_eq!(limited_fonts, fonts[..3].to_vec());
+    }
+
+    #[test]
+    fn test_preferred_font_families_of() {
+        let font_match_options = FontMatchOptions {
+            preferred_font_family: Some("Noto Sans".to_owned()),
+            block_font_families: vec![
+                ("Arabic".to_owned(), "Amiri".to_owned()),
+                (
+                    "CJK Unified Ideographs".to_owned(),
+                    "Source Han Sans".to_owned(),
+                ),
+            ],
+            ..FontMatchOptions::default()
+        };
+        assert_eq!(
+            font_match_options.preferred_font_families_of('\u{0628}'),
+            vec!["Amiri", "Noto Sans"]
+        );
+        assert_eq!(
+            font_match_options.preferred_font_families_of('\u{6F22}'),
+            vec!["Source Han Sans", "Noto Sans"]
+        );
+        assert_eq!(
+            font_match_options.preferred_font_families_of('A'),
+            vec!["Noto Sans"]
+        );
+        assert!(FontMatchOptions::default()
+            .preferred_font_families_of('A')
+            .is_empty());
+    }
+
+    #[test]
+    fn test_add_font_directories() {
+        assert!(add_font_directories(&[]).is_ok());
+
+        let missing_directory = std::env::temp_dir().join("cicero-test-missing-font-directory");
+        let error = add_font_directories(&[missing_directory.clone()]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Failed to add the fonts of {}", missing_directory.display())
+        );
+    }
+
+    #[test]
+    fn test_exclude_fonts() {
+        let font = |path: &str, family: &str| MatchedFont {
+            path: path.to_owned(),
+            family: family.to_owned(),
+            style: "Regular".to_owned(),
+            face_index: 0,
+            monospace: false,
+        };
+        let fonts = vec![
+            font("/usr/share/fonts/Symbola.ttf", "Symbola"),
+            font(
+                "/usr/share/fonts/noto/NotoColorEmoji.ttf",
+                "Noto Color Emoji",
+            ),
+            font("/usr/share/fonts/noto/NotoSans-Regular.ttf", "Noto Sans"),
+            font("/opt/fonts/DejaVuSans.ttf", "DejaVu Sans"),
+        ];
+        let kept_families = |excluded_fonts: &[&str]| {
+            let excluded_fonts: Vec<String> = excluded_fonts
+                .iter()
+                .map(|excluded_font| excluded_font.to_string())
+                .collect();
+            exclude_fonts(fonts.clone(), &excluded_fonts)
+                .into_iter()
+                .map(|font| font.family)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(exclude_fonts(fonts.clone(), &[]), fonts);
+        assert_eq!(
+            kept_families(&["symbola", "Noto Color Emoji"]),
+            vec!["Noto Sans", "DejaVu Sans"]
+        );
+        assert_eq!(
+            kept_families(&["/usr/share/fonts/noto", "/opt/fonts/DejaVuSans.ttf"]),
+            vec!["Symbola"]
+        );
+        // Only whole path components match
+        assert_eq!(kept_families(&["/opt/fonts/DejaVu"]).len(), 4);
+        assert_eq!(kept_families(&[""]).len(), 4);
+    }
+
+    #[test]
+    fn test_filter_fonts() {
+        let font = |path: &str, style: &str, monospace: bool| MatchedFont {
+            path: path.to_owned(),
+            family: path[3..path.find('-').unwrap()].to_owned(),
+            style: style.to_owned(),
+            face_index: 0,
+            monospace,
+        };
+        let fonts = vec![
+            font("/a/Sans-Regular.ttf", "Regular", false),
+            font("/a/Sans-BoldItalic.ttf", "Bold Italic", false),
+            font("/a/Sans-CondensedItalic.ttf", "Condensed Italic", false),
+            font("/a/Mono-Book.ttf", "Book", true),
+            font("/a/Display-Bold.ttf", "Bold", false),
+            font("/a/Display-Black.ttf", "Black", false),
+        ];
+        let filtered_paths = |style_filter: &FontStyleFilter| {
+            filter_fonts(fonts.clone(), style_filter)
+                .into_iter()
+                .map(|font| font.path)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            filter_fonts(fonts.clone(), &FontStyleFilter::default()),
+            fonts
+        );
+        let style_filter = FontStyleFilter {
+            regular_only: true,
+            ..FontStyleFilter::default()
+        };
+        // A family without a regular face keeps its first one
+        assert_eq!(
+            filtered_paths(&style_filter),
+            vec![
+                "/a/Sans-Regular.ttf",
+                "/a/Mono-Book.ttf",
+                "/a/Display-Bold.ttf"
+            ]
+        );
+        let style_filter = FontStyleFilter {
+            monospace_only: true,
+            ..FontStyleFilter::default()
+        };
+        assert_eq!(filtered_paths(&style_filter), vec!["/a/Mono-Book.ttf"]);
+        let style_filter = FontStyleFilter {
+            include_styles: vec!["italic".to_owned()],
+            exclude_styles: vec!["Condensed".to_owned()],
+            ..FontStyleFilter::default()
+        };
+        assert_eq!(
+            filtered_paths(&style_filter),
+            vec!["/a/Sans-BoldItalic.ttf"]
+        );
+
+        // Fonts are all kept rather than none
+        let style_filter = FontStyleFilter {
+            include_styles: vec!["Oblique".to_owned()],
+            ..FontStyleFilter::default()
+        };
+        assert_eq!(filter_fonts(fonts.clone(), &style_filter), fonts);
+    }
+
+    #[test]
+    fn test_font_of_family() {
+        let font = |path: &str, family: &str, style: &str| MatchedFont {
+            path: path.to_owned(),
+            family: family.to_owned(),
+            style: style.to_owned(),
+            face_index: 0,
+            monospace: false,
+        };
+        let fonts = vec![
+            font("/a/Serif-Bold.ttf", "Serif", "Bold"),
+            font("/a/Serif-Regular.ttf", "Serif", "Regular"),
+            font("/a/Sans-Bold.ttf", "Sans", "Bold"),
+        ];
+
+        assert_eq!(
+            font_of_family(&fonts, "serif").unwrap().path,
+            "/a/Serif-Regular.ttf"
+        );
+        assert_eq!(
+            font_of_family(&fonts, "Sans").unwrap().path,
+            "/a/Sans-Bold.ttf"
+        );
+        assert!(font_of_family(&fonts, "Mono").is_none());
     }
 }
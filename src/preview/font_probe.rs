@@ -0,0 +1,164 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! A malformed font can make FreeType spin for a long time. With a load timeout, fonts are loaded
+//! on a loader thread, so that such a font is reported instead of hanging the application.
+//!
+
+use std::cell::RefCell;
+use std::io;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use freetype::face::LoadFlag;
+use freetype::{Face, FtResult, Library};
+use log::warn;
+
+pub enum FaceLoad {
+    Loaded(FtResult<Face>),
+    TimedOut,
+}
+
+// A face loaded on the loader thread. FreeType objects aren't tied to the thread which created
+// them, they only can't be used from several threads at once. Each face is loaded with a library
+// of its own, which the loader lets go of (the face keeps it alive) once the face is sent.
+struct LoadedFace(Face);
+
+unsafe impl Send for LoadedFace {}
+
+struct LoadRequest {
+    font_path: String,
+    face_index: isize,
+    chr: char,
+}
+
+// Loads the faces and the glyphs of the requested characters one after another, on a thread which
+// lives as long as none of the loads times out
+struct FontLoader {
+    requests: Sender<LoadRequest>,
+    faces: Receiver<FtResult<LoadedFace>>,
+}
+
+impl FontLoader {
+    fn spawn() -> io::Result<Self> {
+        let (requests, request_receiver) = channel::<LoadRequest>();
+        let (face_sender, faces) = channel();
+        thread::Builder::new()
+            .name("font-loader".to_owned())
+            .spawn(move || {
+                for request in request_receiver {
+                    let face = Library::init().and_then(|library| {
+                        let face = library.new_face(&request.font_path, request.face_index)?;
+                        // A glyph failing to load is reported once it is rendered
+                        let _ = face.load_char(request.chr as usize, LoadFlag::DEFAULT);
+                        Ok(LoadedFace(face))
+                    });
+                    if face_sender.send(face).is_err() {
+                        return;
+                    }
+                }
+            })?;
+        Ok(FontLoader { requests, faces })
+    }
+
+    fn load(
+        &self,
+        font_path: &str,
+        face_index: isize,
+        chr: char,
+        timeout: Duration,
+    ) -> Result<FtResult<LoadedFace>, RecvTimeoutError> {
+        let request = LoadRequest {
+            font_path: font_path.to_owned(),
+            face_index,
+            chr,
+        };
+        if self.requests.send(request).is_err() {
+            return Err(RecvTimeoutError::Disconnected);
+        }
+        self.faces.recv_timeout(timeout)
+    }
+}
+
+thread_local! {
+    // The loader of the fonts loaded from this thread, started with the first load. A loader
+    // which times out is left running, as FreeType can't be interrupted, and the next load
+    // starts another one.
+    static FONT_LOADER: RefCell<Option<FontLoader>> = RefCell::new(None);
+}
+
+// Without a timeout, the face is loaded right away with the library. Otherwise the face and the
+// glyph of the character are loaded once on the loader thread, and the face is used as is if this
+// takes less than the timeout.
+pub fn load_face(
+    library: &Library,
+    font_path: &str,
+    face_index: isize,
+    chr: char,
+    timeout: Option<Duration>,
+) -> FaceLoad {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return FaceLoad::Loaded(library.new_face(font_path, face_index)),
+    };
+
+    FONT_LOADER.with(|font_loader| {
+        let mut font_loader = font_loader.borrow_mut();
+        if font_loader.is_none() {
+            match FontLoader::spawn() {
+                Ok(spawned_font_loader) => *font_loader = Some(spawned_font_loader),
+                Err(error) => warn!("Failed to start the font loader: {}", error),
+            }
+        }
+        let loaded_face = match font_loader.as_ref() {
+            Some(font_loader) => font_loader.load(font_path, face_index, chr, timeout),
+            None => return FaceLoad::Loaded(library.new_face(font_path, face_index)),
+        };
+
+        match loaded_face {
+            Ok(face) => FaceLoad::Loaded(face.map(|LoadedFace(face)| face)),
+            Err(RecvTimeoutError::Disconnected) => {
+                *font_loader = None;
+                FaceLoad::Loaded(library.new_face(font_path, face_index))
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                warn!("{} took longer than {:?} to load", font_path, timeout);
+                *font_loader = None;
+                FaceLoad::TimedOut
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_face() {
+        // Missing fonts fail right away, whether loaded on a separate thread or not
+        let library = Library::init().unwrap();
+        for timeout in &[None, Some(Duration::from_secs(5))] {
+            match load_face(&library, "/nonexistent/font.ttf", 0, 'A', *timeout) {
+                FaceLoad::Loaded(face) => assert!(face.is_err()),
+                FaceLoad::TimedOut => panic!("Timed out loading a missing font"),
+            }
+        }
+
+        // The loader is kept for the loads that follow
+        assert!(FONT_LOADER.with(|font_loader| font_loader.borrow().is_some()));
+    }
+}
@@ -0,0 +1,570 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! FreeType APIs which are not covered by `freetype-rs`, along with safe wrappers of them.
+//!
+
+use std::cmp::max;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+use std::slice;
+
+use freetype::ffi::{
+    FT_Byte, FT_Error, FT_Face, FT_FaceRec, FT_Fixed, FT_Int, FT_Library, FT_Long, FT_Matrix,
+    FT_Outline, FT_Pos, FT_UInt, FT_ULong, FT_Vector, FT_FACE_FLAG_MULTIPLE_MASTERS,
+    FT_GLYPH_FORMAT_OUTLINE,
+};
+use freetype::{Face, FtResult, Library};
+
+// Same value as the `FT_GlyphSlot_Oblique` helper of FreeType
+const SYNTHETIC_OBLIQUE_SHEAR: FT_Fixed = 0x0366A; // About 12 degrees
+
+// Set for faces with color glyph tables (CBDT, COLR or sbix), missing from `freetype-sys`
+const FT_FACE_FLAG_COLOR: FT_Long = 1 << 14;
+
+// PostScript glyph names are limited to 63 characters
+const GLYPH_NAME_BUFFER_SIZE: usize = 64;
+
+#[allow(non_camel_case_types, dead_code)]
+#[repr(C)]
+struct FT_Var_Axis {
+    name: *mut c_char,
+    minimum: FT_Fixed,
+    def: FT_Fixed,
+    maximum: FT_Fixed,
+    tag: FT_ULong,
+    strid: FT_UInt,
+}
+
+#[allow(non_camel_case_types, dead_code)]
+#[repr(C)]
+struct FT_Var_Named_Style {
+    coords: *mut FT_Fixed,
+    strid: FT_UInt,
+    psid: FT_UInt,
+}
+
+#[allow(non_camel_case_types, dead_code)]
+#[repr(C)]
+struct FT_MM_Var {
+    num_axis: FT_UInt,
+    num_designs: FT_UInt,
+    num_namedstyles: FT_UInt,
+    axis: *mut FT_Var_Axis,
+    namedstyle: *mut FT_Var_Named_Style,
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct FT_Outline_Funcs {
+    move_to: extern "C" fn(to: *const FT_Vector, user: *mut c_void) -> c_int,
+    line_to: extern "C" fn(to: *const FT_Vector, user: *mut c_void) -> c_int,
+    conic_to:
+        extern "C" fn(control: *const FT_Vector, to: *const FT_Vector, user: *mut c_void) -> c_int,
+    cubic_to: extern "C" fn(
+        control1: *const FT_Vector,
+        control2: *const FT_Vector,
+        to: *const FT_Vector,
+        user: *mut c_void,
+    ) -> c_int,
+    shift: c_int,
+    delta: FT_Pos,
+}
+
+#[allow(non_snake_case)]
+extern "C" {
+    fn FT_Face_GetCharVariantIndex(
+        face: FT_Face,
+        charcode: FT_ULong,
+        variant_selector: FT_ULong,
+    ) -> FT_UInt;
+    fn FT_Get_First_Char(face: FT_Face, agindex: *mut FT_UInt) -> FT_ULong;
+    fn FT_Get_Font_Format(face: FT_Face) -> *const c_char;
+    fn FT_Get_Glyph_Name(
+        face: FT_Face,
+        glyph_index: FT_UInt,
+        buffer: *mut c_char,
+        buffer_max: FT_UInt,
+    ) -> FT_Error;
+    fn FT_Get_Next_Char(face: FT_Face, char_code: FT_ULong, agindex: *mut FT_UInt) -> FT_ULong;
+    fn FT_Get_MM_Var(face: FT_Face, amaster: *mut *mut FT_MM_Var) -> FT_Error;
+    fn FT_Load_Sfnt_Table(
+        face: FT_Face,
+        tag: FT_ULong,
+        offset: FT_Long,
+        buffer: *mut FT_Byte,
+        length: *mut FT_ULong,
+    ) -> FT_Error;
+    fn FT_Done_MM_Var(library: FT_Library, amaster: *mut FT_MM_Var) -> FT_Error;
+    fn FT_Get_Var_Design_Coordinates(
+        face: FT_Face,
+        num_coords: FT_UInt,
+        coords: *mut FT_Fixed,
+    ) -> FT_Error;
+    fn FT_Outline_Decompose(
+        outline: *mut FT_Outline,
+        func_interface: *const FT_Outline_Funcs,
+        user: *mut c_void,
+    ) -> FT_Error;
+    fn FT_Outline_Embolden(outline: *mut FT_Outline, strength: FT_Pos) -> FT_Error;
+    fn FT_Outline_Transform(outline: *const FT_Outline, matrix: *const FT_Matrix);
+    fn FT_Select_Size(face: FT_Face, strike_index: FT_Int) -> FT_Error;
+    fn FT_Set_Var_Design_Coordinates(
+        face: FT_Face,
+        num_coords: FT_UInt,
+        coords: *mut FT_Fixed,
+    ) -> FT_Error;
+}
+
+// A design axis of a variable font, values are in the units of the axis (e.g. 100 to 900 for weight)
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariationAxis {
+    pub name: String,
+
+    // Four letter tag of the axis, e.g. "wght" or "wdth"
+    pub tag: String,
+
+    pub minimum: f64,
+    pub default: f64,
+    pub maximum: f64,
+    pub value: f64,
+}
+
+fn tag_to_string(tag: FT_ULong) -> String {
+    (tag as u32)
+        .to_be_bytes()
+        .iter()
+        .map(|byte| *byte as char)
+        .collect()
+}
+
+fn fixed_to_f64(value: FT_Fixed) -> f64 {
+    value as f64 / 65536.0
+}
+
+fn f64_to_fixed(value: f64) -> FT_Fixed {
+    (value * 65536.0).round() as FT_Fixed
+}
+
+// FreeType mutates faces through `FT_Face` even for read-only queries, as does `freetype-rs`
+fn raw_face(face: &Face) -> FT_Face {
+    face.raw() as *const FT_FaceRec as FT_Face
+}
+
+pub fn is_variable(face: &Face) -> bool {
+    face.raw().face_flags & FT_FACE_FLAG_MULTIPLE_MASTERS != 0
+}
+
+// Color glyphs are rendered into BGRA bitmaps when loaded with `LoadFlag::COLOR`
+pub fn has_color_glyphs(face: &Face) -> bool {
+    face.raw().face_flags & FT_FACE_FLAG_COLOR != 0
+}
+
+// Whether the face maps the character followed by the variation selector (e.g. VS16 for the emoji
+// presentation) to a glyph, through the variation sequences of its cmap (format 14 subtable)
+pub fn has_variation_sequence(face: &Face, chr: char, variation_selector: char) -> bool {
+    unsafe {
+        FT_Face_GetCharVariantIndex(
+            raw_face(face),
+            chr as FT_ULong,
+            variation_selector as FT_ULong,
+        ) != 0
+    }
+}
+
+// The size of each bitmap strike of a face in pixels per em, e.g. the single 109 pixels strike of
+// Noto Color Emoji. Faces with outlines usually have none.
+pub fn strike_sizes(face: &Face) -> Vec<usize> {
+    let face_record = face.raw();
+    if face_record.available_sizes.is_null() || face_record.num_fixed_sizes <= 0 {
+        return vec![];
+    }
+    let strikes = unsafe {
+        slice::from_raw_parts(
+            face_record.available_sizes,
+            face_record.num_fixed_sizes as usize,
+        )
+    };
+    strikes
+        .iter()
+        .map(|strike| max(strike.y_ppem >> 6, 1) as usize)
+        .collect()
+}
+
+// The index of the strike to scale down to the size: the smallest strike at least as large, or the
+// largest strike if they are all smaller
+pub fn best_strike(strike_sizes: &[usize], size: usize) -> Option<usize> {
+    let large_enough = strike_sizes
+        .iter()
+        .enumerate()
+        .filter(|(_, strike_size)| **strike_size >= size)
+        .min_by_key(|(_, strike_size)| **strike_size);
+    large_enough
+        .or_else(|| {
+            strike_sizes
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, strike_size)| **strike_size)
+        })
+        .map(|(index, _)| index)
+}
+
+// The index of the strike closest to the size, for bitmap faces whose glyphs can't be scaled (e.g.
+// Terminus), the smaller strike wins a tie
+pub fn nearest_strike(strike_sizes: &[usize], size: usize) -> Option<usize> {
+    strike_sizes
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, strike_size)| {
+            let distance = (**strike_size as isize - size as isize).abs();
+            (distance, **strike_size)
+        })
+        .map(|(index, _)| index)
+}
+
+// Bitmap-only faces can't be scaled by FreeType, a strike is selected instead of a size
+pub fn select_strike(face: &Face, strike_index: usize) -> FtResult<()> {
+    let error = unsafe { FT_Select_Size(raw_face(face), strike_index as FT_Int) };
+    if error == 0 {
+        Ok(())
+    } else {
+        Err(error.into())
+    }
+}
+
+// Named instances are selected by opening a face with the 1-based instance index in the high 16
+// bits of the face index (the low 16 bits being the index of the face in a collection), see
+// `FT_Open_Face`
+pub fn named_instance_count(face: &Face) -> usize {
+    if !is_variable(face) {
+        return 0;
+    }
+    ((face.raw().style_flags >> 16) & 0x7FFF) as usize
+}
+
+pub fn named_instance_face_index(face_index: isize, instance: usize) -> isize {
+    ((instance + 1) << 16) as isize | (face_index & 0xFFFF)
+}
+
+// Returns the design axes of a variable font with their current values, or an empty list for other
+// fonts
+pub fn variation_axes(library: &Library, face: &Face) -> Vec<VariationAxis> {
+    if !is_variable(face) {
+        return vec![];
+    }
+
+    unsafe {
+        let mut mm_var: *mut FT_MM_Var = std::ptr::null_mut();
+        if FT_Get_MM_Var(raw_face(face), &mut mm_var) != 0 || mm_var.is_null() {
+            return vec![];
+        }
+        defer! {
+            FT_Done_MM_Var(library.raw(), mm_var);
+        }
+
+        let axes = slice::from_raw_parts((*mm_var).axis, (*mm_var).num_axis as usize);
+        let mut coordinates = vec![0 as FT_Fixed; axes.len()];
+        if FT_Get_Var_Design_Coordinates(
+            raw_face(face),
+            coordinates.len() as FT_UInt,
+            coordinates.as_mut_ptr(),
+        ) != 0
+        {
+            return vec![];
+        }
+
+        axes.iter()
+            .zip(coordinates)
+            .map(|(axis, coordinate)| VariationAxis {
+                name: if axis.name.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(axis.name).to_string_lossy().into_owned()
+                },
+                tag: tag_to_string(axis.tag),
+                minimum: fixed_to_f64(axis.minimum),
+                default: fixed_to_f64(axis.def),
+                maximum: fixed_to_f64(axis.maximum),
+                value: fixed_to_f64(coordinate),
+            })
+            .collect()
+    }
+}
+
+// Returns the PostScript name of a glyph, or None if the font has no glyph names
+pub fn glyph_name(face: &Face, glyph_index: u32) -> Option<String> {
+    if !face.has_glyph_names() {
+        return None;
+    }
+
+    let mut buffer = [0 as c_char; GLYPH_NAME_BUFFER_SIZE];
+    let error = unsafe {
+        FT_Get_Glyph_Name(
+            raw_face(face),
+            glyph_index as FT_UInt,
+            buffer.as_mut_ptr(),
+            buffer.len() as FT_UInt,
+        )
+    };
+    if error != 0 || buffer[0] == 0 {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+// The name FreeType gives to the format of a face, e.g. "TrueType", "CFF", "PCF" or "BDF"
+pub fn font_format(face: &Face) -> Option<String> {
+    let format = unsafe { FT_Get_Font_Format(raw_face(face)) };
+    if format.is_null() {
+        return None;
+    }
+    let format = unsafe { CStr::from_ptr(format) };
+    Some(format.to_string_lossy().into_owned())
+}
+
+// Every character mapped to a glyph by the selected charmap of a face (the Unicode one, if any), in
+// code point order
+pub fn charmap_characters(face: &Face) -> Vec<char> {
+    let mut characters = vec![];
+    let mut glyph_index: FT_UInt = 0;
+    let mut char_code = unsafe { FT_Get_First_Char(raw_face(face), &mut glyph_index) };
+    // A glyph index of zero marks the end of the charmap
+    while glyph_index != 0 {
+        if let Some(chr) = std::char::from_u32(char_code as u32) {
+            characters.push(chr);
+        }
+        char_code = unsafe { FT_Get_Next_Char(raw_face(face), char_code, &mut glyph_index) };
+    }
+    characters
+}
+
+// Returns the raw contents of an SFNT table (e.g. b"GSUB"), or None if the font doesn't have it
+pub fn sfnt_table(face: &Face, tag: &[u8; 4]) -> Option<Vec<u8>> {
+    let tag = u32::from_be_bytes(*tag) as FT_ULong;
+
+    // The length of the table is queried first, by passing no buffer
+    let mut length: FT_ULong = 0;
+    let error =
+        unsafe { FT_Load_Sfnt_Table(raw_face(face), tag, 0, std::ptr::null_mut(), &mut length) };
+    if error != 0 || length == 0 {
+        return None;
+    }
+
+    let mut table = vec![0 as FT_Byte; length as usize];
+    let error =
+        unsafe { FT_Load_Sfnt_Table(raw_face(face), tag, 0, table.as_mut_ptr(), &mut length) };
+    if error != 0 {
+        return None;
+    }
+    Some(table)
+}
+
+// Sets the value of every design axis of a variable font, in the order of `variation_axes`
+pub fn set_variation_axis_values(face: &Face, values: &[f64]) -> FtResult<()> {
+    let mut coordinates: Vec<FT_Fixed> = values.iter().map(|value| f64_to_fixed(*value)).collect();
+    let error = unsafe {
+        FT_Set_Var_Design_Coordinates(
+            raw_face(face),
+            coordinates.len() as FT_UInt,
+            coordinates.as_mut_ptr(),
+        )
+    };
+    if error == 0 {
+        Ok(())
+    } else {
+        Err(error.into())
+    }
+}
+
+// Emboldens (by `bold_strength` thousandths of the em size) and/or shears the outline of the glyph
+// loaded (but not yet rendered) in the face. Returns false if the glyph has no outline, e.g. if it
+// comes from a bitmap strike.
+pub fn synthesize_style(
+    face: &Face,
+    bold: bool,
+    bold_strength: u32,
+    oblique: bool,
+) -> FtResult<bool> {
+    if face.glyph().raw().format != FT_GLYPH_FORMAT_OUTLINE {
+        return Ok(false);
+    }
+    // The glyph slot is owned by FreeType, which hands it out as a mutable pointer through the face
+    // record. The outline is reached through that pointer instead of casting away the constness of
+    // the reference `freetype-rs` gives to the slot.
+    let glyph_slot = face.raw().glyph;
+    let outline = unsafe { &mut (*glyph_slot).outline as *mut FT_Outline };
+
+    if bold {
+        let y_scale = face.size_metrics().map_or(0, |metrics| metrics.y_scale) as i64;
+        let strength = ((face.em_size() as i64 * y_scale) >> 16) * bold_strength as i64 / 1000;
+        let error = unsafe { FT_Outline_Embolden(outline, strength as FT_Pos) };
+        if error != 0 {
+            return Err(error.into());
+        }
+    }
+
+    if oblique {
+        let matrix = FT_Matrix {
+            xx: 0x10000,
+            xy: SYNTHETIC_OBLIQUE_SHEAR,
+            yx: 0,
+            yy: 0x10000,
+        };
+        unsafe { FT_Outline_Transform(outline, &matrix) };
+    }
+
+    Ok(true)
+}
+
+// A drawing command of a glyph outline as decomposed by FreeType, in font units for glyphs loaded
+// with `LoadFlag::NO_SCALE`. Every contour starts with a move and is implicitly closed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OutlineSegment {
+    MoveTo((i64, i64)),
+    LineTo((i64, i64)),
+
+    // Control point and end point of a quadratic Bézier curve
+    ConicTo((i64, i64), (i64, i64)),
+
+    // Both control points and end point of a cubic Bézier curve
+    CubicTo((i64, i64), (i64, i64), (i64, i64)),
+}
+
+fn vector_position(vector: *const FT_Vector) -> (i64, i64) {
+    let vector = unsafe { &*vector };
+    (vector.x as i64, vector.y as i64)
+}
+
+// The segments are collected into the vector passed as the user data of `FT_Outline_Decompose`
+fn push_segment(user: *mut c_void, segment: OutlineSegment) -> c_int {
+    let segments = unsafe { &mut *(user as *mut Vec<OutlineSegment>) };
+    segments.push(segment);
+    0
+}
+
+extern "C" fn move_to(to: *const FT_Vector, user: *mut c_void) -> c_int {
+    push_segment(user, OutlineSegment::MoveTo(vector_position(to)))
+}
+
+extern "C" fn line_to(to: *const FT_Vector, user: *mut c_void) -> c_int {
+    push_segment(user, OutlineSegment::LineTo(vector_position(to)))
+}
+
+extern "C" fn conic_to(
+    control: *const FT_Vector,
+    to: *const FT_Vector,
+    user: *mut c_void,
+) -> c_int {
+    push_segment(
+        user,
+        OutlineSegment::ConicTo(vector_position(control), vector_position(to)),
+    )
+}
+
+extern "C" fn cubic_to(
+    control1: *const FT_Vector,
+    control2: *const FT_Vector,
+    to: *const FT_Vector,
+    user: *mut c_void,
+) -> c_int {
+    push_segment(
+        user,
+        OutlineSegment::CubicTo(
+            vector_position(control1),
+            vector_position(control2),
+            vector_position(to),
+        ),
+    )
+}
+
+// Decomposes the outline of the glyph loaded in the face into drawing commands, resolving the
+// implicit on-curve points of TrueType contours. Returns None if the glyph has no outline, e.g. if
+// it comes from a bitmap strike.
+pub fn decompose_outline(face: &Face) -> FtResult<Option<Vec<OutlineSegment>>> {
+    if face.glyph().raw().format != FT_GLYPH_FORMAT_OUTLINE {
+        return Ok(None);
+    }
+    // Through the glyph slot pointer of the face record, like in `synthesize_style`
+    let glyph_slot = face.raw().glyph;
+    let outline = unsafe { &mut (*glyph_slot).outline as *mut FT_Outline };
+
+    let outline_funcs = FT_Outline_Funcs {
+        move_to,
+        line_to,
+        conic_to,
+        cubic_to,
+        shift: 0,
+        delta: 0,
+    };
+    let mut segments: Vec<OutlineSegment> = vec![];
+    let error = unsafe {
+        FT_Outline_Decompose(
+            outline,
+            &outline_funcs,
+            &mut segments as *mut Vec<OutlineSegment> as *mut c_void,
+        )
+    };
+    if error == 0 {
+        Ok(Some(segments))
+    } else {
+        Err(error.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_to_f64() {
+        assert_eq!(fixed_to_f64(0x10000), 1.0);
+        assert_eq!(fixed_to_f64(400 << 16), 400.0);
+        assert_eq!(fixed_to_f64(0x8000), 0.5);
+        assert_eq!(fixed_to_f64(f64_to_fixed(412.5)), 412.5);
+    }
+
+    #[test]
+    fn test_tag_to_string() {
+        assert_eq!(tag_to_string(0x7767_6874), "wght");
+        assert_eq!(tag_to_string(0x6F70_737A), "opsz");
+    }
+
+    #[test]
+    fn test_best_strike() {
+        assert_eq!(best_strike(&[], 64), None);
+        assert_eq!(best_strike(&[109], 64), Some(0));
+        assert_eq!(best_strike(&[109], 200), Some(0));
+        assert_eq!(best_strike(&[20, 160, 40, 96], 64), Some(3));
+        assert_eq!(best_strike(&[20, 160, 40, 96], 400), Some(1));
+        assert_eq!(best_strike(&[20, 160, 40, 96], 40), Some(2));
+    }
+
+    #[test]
+    fn test_nearest_strike() {
+        assert_eq!(nearest_strike(&[], 16), None);
+        assert_eq!(nearest_strike(&[12, 14, 16, 20, 32], 16), Some(2));
+        assert_eq!(nearest_strike(&[12, 14, 16, 20, 32], 100), Some(4));
+        assert_eq!(nearest_strike(&[12, 14, 16, 20, 32], 1), Some(0));
+        assert_eq!(nearest_strike(&[20, 12, 16], 18), Some(2));
+    }
+
+    #[test]
+    fn test_named_instance_face_index() {
+        assert_eq!(named_instance_face_index(0, 0), 0x10000);
+        assert_eq!(named_instance_face_index(0, 2), 0x30000);
+        assert_eq!(named_instance_face_index(3, 1), 0x20003);
+    }
+}
@@ -0,0 +1,116 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Audits a font family against a text, listing the characters the family can't render, e.g. to
+//! check that a font covers every string of a localization.
+//!
+
+use std::collections::{HashMap, HashSet};
+
+use freetype::{Face, Library};
+use serde::Serialize;
+use unic::ucd::GeneralCategory;
+
+use super::character_preview::face_has_glyph;
+use super::font_match::{font_of_family, fonts_for};
+use super::Result;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissingGlyphReason {
+    // No font of the family is listed for the character
+    NoMatch,
+
+    // A font of the family is listed for the character, but its character map has no glyph for it
+    NoGlyph,
+}
+
+impl MissingGlyphReason {
+    pub fn description(self) -> &'static str {
+        match self {
+            MissingGlyphReason::NoMatch => "no matching font",
+            MissingGlyphReason::NoGlyph => "no glyph",
+        }
+    }
+}
+
+// Returns each character of the text (once, in order of appearance) which the most regular font of
+// the family can't render. Control characters (e.g. the line breaks of a file) are never drawn, so
+// they are left out.
+pub fn missing_glyphs(text: &str, font_family: &str) -> Result<Vec<(char, MissingGlyphReason)>> {
+    let library = Library::init()?;
+    let mut faces: HashMap<(String, isize), Option<Face>> = HashMap::new();
+    let mut seen_characters = HashSet::new();
+
+    let mut missing_glyphs = vec![];
+    for chr in text.chars() {
+        if GeneralCategory::of(chr) == GeneralCategory::Control || !seen_characters.insert(chr) {
+            continue;
+        }
+
+        let fonts = fonts_for(chr)?;
+        let font = match font_of_family(&fonts, font_family) {
+            Some(font) => font,
+            None => {
+                missing_glyphs.push((chr, MissingGlyphReason::NoMatch));
+                continue;
+            }
+        };
+
+        // Faces are opened once per font, fonts which fail to load can't render anything. The upper
+        // bits of the index of a face name its named instance, which shares the character map.
+        let face_index = font.face_index & 0xFFFF;
+        let face = faces
+            .entry((font.path.clone(), face_index))
+            .or_insert_with(|| library.new_face(&font.path, face_index).ok());
+        let has_glyph = match face {
+            Some(face) => face_has_glyph(face, chr),
+            None => false,
+        };
+        if !has_glyph {
+            missing_glyphs.push((chr, MissingGlyphReason::NoGlyph));
+        }
+    }
+    Ok(missing_glyphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_glyphs() {
+        // Matching relies on the fonts installed on the system, skip if there is none
+        let font_family = match fonts_for('A') {
+            Ok(fonts) if !fonts.is_empty() => fonts[0].family.clone(),
+            _ => return,
+        };
+
+        let missing_glyphs = missing_glyphs("AA\n\u{10FFFD}", &font_family).unwrap();
+        assert_eq!(
+            missing_glyphs,
+            vec![('\u{10FFFD}', MissingGlyphReason::NoMatch)]
+        );
+
+        let missing_glyphs = missing_glyphs("AB", "No Such Font Family").unwrap();
+        assert_eq!(
+            missing_glyphs,
+            vec![
+                ('A', MissingGlyphReason::NoMatch),
+                ('B', MissingGlyphReason::NoMatch)
+            ]
+        );
+    }
+}
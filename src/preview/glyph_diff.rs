@@ -0,0 +1,173 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Compares the glyphs of two fonts pixel by pixel. Both glyphs are placed at the same pen origin,
+//! so that differences in metrics show up as well as differences in shape.
+//!
+
+use std::cmp::{max, min};
+
+use super::character_preview::{RenderSize, RenderedCharacter};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DiffPixel {
+    Neither,
+    OnlyFirst,
+    OnlySecond,
+    Both,
+}
+
+#[derive(Debug)]
+pub struct GlyphDiff {
+    // Pixels of the area covering both glyphs, row by row
+    pub pixels: Vec<DiffPixel>,
+    pub size: RenderSize,
+
+    // Position of the top left corner of the area relative to the pen origin, as in
+    // `RenderedCharacter`
+    pub left: i32,
+    pub top: i32,
+}
+
+impl GlyphDiff {
+    // Fraction of the pixels inked by either glyph which only one of them inks, 0 if neither has
+    // any ink
+    pub fn difference_ratio(&self) -> f64 {
+        let count_of = |kind: DiffPixel| self.pixels.iter().filter(|pixel| **pixel == kind).count();
+        let different_count = count_of(DiffPixel::OnlyFirst) + count_of(DiffPixel::OnlySecond);
+        let inked_count = different_count + count_of(DiffPixel::Both);
+        if inked_count == 0 {
+            return 0.0;
+        }
+        different_count as f64 / inked_count as f64
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> DiffPixel {
+        self.pixels[y * self.size.width + x]
+    }
+}
+
+// Compares two glyphs rendered at the same size, a pixel is inked if its coverage is at least
+// `threshold`
+pub fn diff_glyphs(
+    first: &RenderedCharacter,
+    second: &RenderedCharacter,
+    threshold: u8,
+) -> GlyphDiff {
+    let right_of = |rendered_character: &RenderedCharacter| {
+        rendered_character.bitmap_left + rendered_character.glyph_size.width as i32
+    };
+    let bottom_of = |rendered_character: &RenderedCharacter| {
+        rendered_character.bitmap_top - rendered_character.glyph_size.height as i32
+    };
+    let left = min(first.bitmap_left, second.bitmap_left);
+    let top = max(first.bitmap_top, second.bitmap_top);
+    let right = max(right_of(first), right_of(second));
+    let bottom = min(bottom_of(first), bottom_of(second));
+    let size = RenderSize::new(max(right - left, 0) as usize, max(top - bottom, 0) as usize);
+
+    let first_mask = first.mask(threshold);
+    let second_mask = second.mask(threshold);
+    // Whether the glyph inks the pixel at (x, y) of the area
+    let is_inked = |rendered_character: &RenderedCharacter, mask: &[bool], x: usize, y: usize| {
+        let glyph_x = left + x as i32 - rendered_character.bitmap_left;
+        let glyph_y = y as i32 - (top - rendered_character.bitmap_top);
+        let glyph_size = rendered_character.glyph_size;
+        glyph_x >= 0
+            && glyph_y >= 0
+            && (glyph_x as usize) < glyph_size.width
+            && (glyph_y as usize) < glyph_size.height
+            && mask[glyph_y as usize * glyph_size.width + glyph_x as usize]
+    };
+
+    let mut pixels = Vec::with_capacity(size.width * size.height);
+    for y in 0..size.height {
+        for x in 0..size.width {
+            pixels.push(
+                match (
+                    is_inked(first, &first_mask, x, y),
+                    is_inked(second, &second_mask, x, y),
+                ) {
+                    (false, false) => DiffPixel::Neither,
+                    (true, false) => DiffPixel::OnlyFirst,
+                    (false, true) => DiffPixel::OnlySecond,
+                    (true, true) => DiffPixel::Both,
+                },
+            );
+        }
+    }
+
+    GlyphDiff {
+        pixels,
+        size,
+        left,
+        top,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered_character(
+        bitmap: Vec<Vec<u8>>,
+        bitmap_left: i32,
+        bitmap_top: i32,
+    ) -> RenderedCharacter {
+        let glyph_size = RenderSize::new(bitmap[0].len(), bitmap.len());
+        RenderedCharacter {
+            bitmap,
+            colors: None,
+            glyph_size,
+            bitmap_left,
+            bitmap_top,
+            advance_width: glyph_size.width,
+            advance_height: glyph_size.height,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        }
+    }
+
+    #[test]
+    fn test_diff_glyphs() {
+        // A 2x2 square, and the same square shifted one pixel to the right and up
+        let first = rendered_character(vec![vec![255, 255], vec![255, 255]], 0, 2);
+        let second = rendered_character(vec![vec![255, 255], vec![255, 255]], 1, 3);
+
+        let glyph_diff = diff_glyphs(&first, &second, 128);
+        assert_eq!(glyph_diff.size, RenderSize::new(3, 3));
+        assert_eq!((glyph_diff.left, glyph_diff.top), (0, 3));
+        assert_eq!(glyph_diff.pixel(0, 0), DiffPixel::Neither);
+        assert_eq!(glyph_diff.pixel(1, 0), DiffPixel::OnlySecond);
+        assert_eq!(glyph_diff.pixel(1, 1), DiffPixel::Both);
+        assert_eq!(glyph_diff.pixel(0, 2), DiffPixel::OnlyFirst);
+        assert_eq!(glyph_diff.pixel(2, 2), DiffPixel::Neither);
+
+        // 6 of the 7 inked pixels differ
+        assert!((glyph_diff.difference_ratio() - 6.0 / 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_diff_identical_glyphs() {
+        let glyph = rendered_character(vec![vec![0, 200], vec![100, 0]], -1, 1);
+        let glyph_diff = diff_glyphs(&glyph, &glyph, 128);
+        assert_eq!(glyph_diff.difference_ratio(), 0.0);
+        assert_eq!(glyph_diff.pixel(1, 0), DiffPixel::Both);
+        assert_eq!(glyph_diff.pixel(0, 1), DiffPixel::Neither);
+
+        let blank = rendered_character(vec![vec![0]], 0, 0);
+        assert_eq!(diff_glyphs(&blank, &blank, 1).difference_ratio(), 0.0);
+    }
+}
@@ -18,12 +18,67 @@
 //!   character.
 //! - Glyph shaping, which turns a given character into a rendered bitmap using a font.
 //!
+//! A `PreviewContext` holds the FreeType state shared by every preview, it is created once and
+//! passed to each `CharacterPreview`.
+//!
+//! Library users can also walk a whole range of characters with their matching fonts lazily, with
+//! `FontsInRange`.
+//!
+//! The glyphs of two fonts can be compared pixel by pixel with `diff_glyphs`, and the coverage of
+//! a range of characters by a font is given by `range_coverage`. Glyph outlines can be exported
+//! as SVG documents with `export_svg`.
+//!
+//! With the `shaping` feature, text such as a grapheme cluster can be shaped with HarfBuzz and
+//! rendered as a whole, for characters whose glyphs depend on their context.
+//!
+//! With the `image` feature, rendered characters can also be converted to images and glyphs
+//! exported as PNG images, every matching font can be rendered into a single contact sheet, and
+//! coverage can be rendered into a map.
+//!
 
 mod character_preview;
+#[cfg(feature = "image")]
+mod contact_sheet;
+mod coverage_map;
+mod export;
+mod font_index;
 mod font_match;
+mod font_probe;
+mod freetype_ext;
+mod glyph_audit;
+mod glyph_diff;
+mod opentype;
+mod preview_context;
+mod range_fonts;
+mod render_cache;
+mod render_worker;
+#[cfg(feature = "shaping")]
+mod shaping;
 mod stateful_vec;
 
-pub use character_preview::{CharacterPreview, RenderSize, RenderedCharacter};
+pub use character_preview::{
+    BoundingBox, CharacterPreview, FaceMetrics, FontAttempt, FontAttemptOutcome, FontInfo,
+    GlyphInfo, GlyphMetrics, Hinting, OutlineContour, OutlinePoint, OutlinePointKind,
+    RenderOptions, RenderSize, RenderTiming, RenderedCharacter, Sizing, DEFAULT_BOLD_STRENGTH,
+    MAX_BOLD_STRENGTH, MIN_BOLD_STRENGTH,
+};
+#[cfg(feature = "image")]
+pub use contact_sheet::{render_contact_sheet, ContactSheetOptions};
+pub use coverage_map::{coverage_counts, range_coverage, CodePointCoverage};
+#[cfg(feature = "image")]
+pub use coverage_map::{render_coverage_map, CoverageMapOptions};
+#[cfg(feature = "image")]
+pub use export::export_png;
+pub use export::{export_svg, glyph_svg, svg_path_data};
+pub use font_match::{add_font_directories, FontMatchOptions, FontStyleFilter, MatchedFont};
+pub use freetype_ext::{OutlineSegment, VariationAxis};
+pub use glyph_audit::{missing_glyphs, MissingGlyphReason};
+pub use glyph_diff::{diff_glyphs, DiffPixel, GlyphDiff};
+pub use opentype::OpenTypeFeature;
+pub use preview_context::PreviewContext;
+pub use range_fonts::FontsInRange;
+#[cfg(feature = "shaping")]
+pub use shaping::{shape_text, ShapedGlyph};
 
 use std::error;
 use std::fmt;
@@ -32,7 +87,33 @@ pub type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
 #[derive(Debug)]
 pub enum Error {
-    GlyphNotFound { chr: char },
+    GlyphNotFound {
+        chr: char,
+    },
+    OutlineNotAvailable {
+        chr: char,
+    },
+    GlyphNotInFont {
+        chr: char,
+        font_path: String,
+    },
+    FontOperationFailed {
+        font_path: String,
+        operation: &'static str,
+        reason: String,
+    },
+    RenderSizeTooSmall,
+    FontLoadTimedOut {
+        font_path: String,
+    },
+    EmptySequence,
+    FontDirectoryNotAdded {
+        directory: String,
+    },
+    CacheDirectoryNotWritable {
+        directory: String,
+        reason: String,
+    },
 }
 
 impl fmt::Display for Error {
@@ -43,6 +124,32 @@ impl fmt::Display for Error {
                 "Glyph for U+{:04X} not found in all system fonts",
                 *chr as u32
             ),
+            Error::OutlineNotAvailable { chr } => write!(
+                f,
+                "Outline for U+{:04X} not available in the current font",
+                *chr as u32
+            ),
+            Error::GlyphNotInFont { chr, font_path } => write!(
+                f,
+                "Glyph for U+{:04X} not found in {}",
+                *chr as u32, font_path
+            ),
+            Error::FontOperationFailed {
+                font_path,
+                operation,
+                reason,
+            } => write!(f, "Failed to {} {}: {}", operation, font_path, reason),
+            Error::RenderSizeTooSmall => write!(f, "Too small to render"),
+            Error::FontLoadTimedOut { font_path } => write!(f, "Timed out loading {}", font_path),
+            Error::EmptySequence => write!(f, "Nothing to preview in an empty sequence"),
+            Error::FontDirectoryNotAdded { directory } => {
+                write!(f, "Failed to add the fonts of {}", directory)
+            }
+            Error::CacheDirectoryNotWritable { directory, reason } => write!(
+                f,
+                "Cache directory {} is not writable: {}",
+                directory, reason
+            ),
         }
     }
 }
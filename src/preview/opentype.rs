@@ -0,0 +1,319 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! OpenType layout features (ligatures, small caps, stylistic sets, kerning...) read from the raw
+//! GSUB and GPOS tables of a font, see <https://docs.microsoft.com/typography/opentype/spec/>.
+//! Only the coverage of lookups is read, no text is shaped. The x-height of a font is read from its
+//! OS/2 table as well, and its revision from its head table.
+//!
+
+use freetype::Face;
+
+use super::freetype_ext::sfnt_table;
+
+const COVERAGE_FORMAT_GLYPHS: u16 = 1;
+const COVERAGE_FORMAT_RANGES: u16 = 2;
+
+// Contextual lookups list the coverage of each glyph of their context in their third format
+const CONTEXT_FORMAT_COVERAGES: u16 = 3;
+
+// `fontRevision` follows the version of the head table, as a 16.16 fixed-point number
+const HEAD_FONT_REVISION_OFFSET: usize = 4;
+
+// `sxHeight` was added to the OS/2 table in its second version
+const OS2_X_HEIGHT_MIN_VERSION: u16 = 2;
+const OS2_X_HEIGHT_OFFSET: usize = 86;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OpenTypeFeature {
+    pub tag: String,
+
+    // Whether a lookup of the feature applies to the glyph of the character, as the first glyph of
+    // the input for contextual lookups
+    pub involves_glyph: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum LayoutTable {
+    Gsub,
+    Gpos,
+}
+
+impl LayoutTable {
+    // Lookup types of contextual, chained contextual and extension lookups
+    fn special_lookup_types(self) -> (u16, u16, u16) {
+        match self {
+            LayoutTable::Gsub => (5, 6, 7),
+            LayoutTable::Gpos => (7, 8, 9),
+        }
+    }
+}
+
+// Features of both the GSUB and GPOS tables of the font, sorted by tag. Fonts without these tables
+// (e.g. bitmap fonts) have no features.
+pub fn opentype_features(face: &Face, glyph_index: u32) -> Vec<OpenTypeFeature> {
+    let mut features: Vec<OpenTypeFeature> = vec![];
+    for (tag, layout_table) in &[(b"GSUB", LayoutTable::Gsub), (b"GPOS", LayoutTable::Gpos)] {
+        if let Some(table) = sfnt_table(face, tag) {
+            features.extend(layout_features(&table, *layout_table, glyph_index));
+        }
+    }
+
+    // A feature is listed once for each script and language system it applies to
+    features.sort_by(|a, b| {
+        a.tag
+            .cmp(&b.tag)
+            .then(b.involves_glyph.cmp(&a.involves_glyph))
+    });
+    features.dedup_by(|a, b| a.tag == b.tag);
+    features
+}
+
+// The x-height of the font in font units, None if its OS/2 table is missing, too old or leaves it
+// unset
+pub fn x_height(face: &Face) -> Option<i64> {
+    os2_x_height(&sfnt_table(face, b"OS/2")?)
+}
+
+fn os2_x_height(table: &[u8]) -> Option<i64> {
+    if read_u16(table, 0)? < OS2_X_HEIGHT_MIN_VERSION {
+        return None;
+    }
+    match read_u16(table, OS2_X_HEIGHT_OFFSET)? as i16 {
+        x_height if x_height > 0 => Some(x_height as i64),
+        _ => None,
+    }
+}
+
+// The revision of the font set by its vendor, e.g. "2.001", None if it has no head table (e.g. a
+// bitmap font in the PCF format)
+pub fn font_revision(face: &Face) -> Option<String> {
+    head_font_revision(&sfnt_table(face, b"head")?)
+}
+
+fn head_font_revision(table: &[u8]) -> Option<String> {
+    let revision = read_u32(table, HEAD_FONT_REVISION_OFFSET)? as i32;
+    Some(format!("{:.3}", revision as f64 / 65536.0))
+}
+
+fn read_u16(table: &[u8], offset: usize) -> Option<u16> {
+    let bytes = table.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(table: &[u8], offset: usize) -> Option<u32> {
+    let bytes = table.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// Reads the feature list of a GSUB or GPOS table, a malformed table has no features
+fn layout_features(
+    table: &[u8],
+    layout_table: LayoutTable,
+    glyph_index: u32,
+) -> Vec<OpenTypeFeature> {
+    let (feature_list, lookup_list) = match (read_u16(table, 6), read_u16(table, 8)) {
+        (Some(feature_list), Some(lookup_list)) => (feature_list as usize, lookup_list as usize),
+        _ => return vec![],
+    };
+    let feature_count = read_u16(table, feature_list).unwrap_or(0) as usize;
+
+    (0..feature_count)
+        .filter_map(|feature_index| {
+            let feature_record = feature_list + 2 + feature_index * 6;
+            let tag = table.get(feature_record..feature_record + 4)?;
+            let feature = feature_list + read_u16(table, feature_record + 4)? as usize;
+            let lookup_count = read_u16(table, feature + 2)? as usize;
+
+            let involves_glyph = (0..lookup_count).any(|index| {
+                read_u16(table, feature + 4 + index * 2).map_or(false, |lookup_index| {
+                    lookup_covers(
+                        table,
+                        layout_table,
+                        lookup_list,
+                        lookup_index as usize,
+                        glyph_index,
+                    )
+                })
+            });
+            Some(OpenTypeFeature {
+                tag: String::from_utf8_lossy(tag).trim_end().to_owned(),
+                involves_glyph,
+            })
+        })
+        .collect()
+}
+
+fn lookup_covers(
+    table: &[u8],
+    layout_table: LayoutTable,
+    lookup_list: usize,
+    lookup_index: usize,
+    glyph_index: u32,
+) -> bool {
+    let covers = || -> Option<bool> {
+        let lookup = lookup_list + read_u16(table, lookup_list + 2 + lookup_index * 2)? as usize;
+        let lookup_type = read_u16(table, lookup)?;
+        let subtable_count = read_u16(table, lookup + 4)? as usize;
+        Some((0..subtable_count).any(|index| {
+            read_u16(table, lookup + 6 + index * 2)
+                .and_then(|subtable| {
+                    subtable_coverage(table, layout_table, lookup_type, lookup + subtable as usize)
+                })
+                .map_or(false, |coverage| {
+                    coverage_contains(table, coverage, glyph_index)
+                })
+        }))
+    };
+    covers().unwrap_or(false)
+}
+
+// Returns the offset of the coverage table of a lookup subtable (of its first input glyph for
+// contextual lookups)
+fn subtable_coverage(
+    table: &[u8],
+    layout_table: LayoutTable,
+    lookup_type: u16,
+    subtable: usize,
+) -> Option<usize> {
+    let (context, chained_context, extension) = layout_table.special_lookup_types();
+    let format = read_u16(table, subtable)?;
+
+    let coverage = if lookup_type == extension {
+        // Extensions wrap a single subtable of another type, with a 32-bit offset
+        let extension_type = read_u16(table, subtable + 2)?;
+        if extension_type == extension {
+            return None;
+        }
+        let extension_subtable = subtable + read_u32(table, subtable + 4)? as usize;
+        return subtable_coverage(table, layout_table, extension_type, extension_subtable);
+    } else if lookup_type == context && format == CONTEXT_FORMAT_COVERAGES {
+        read_u16(table, subtable + 6)?
+    } else if lookup_type == chained_context && format == CONTEXT_FORMAT_COVERAGES {
+        let backtrack_count = read_u16(table, subtable + 2)? as usize;
+        read_u16(table, subtable + 4 + backtrack_count * 2 + 2)?
+    } else {
+        read_u16(table, subtable + 2)?
+    };
+    Some(subtable + coverage as usize)
+}
+
+fn coverage_contains(table: &[u8], coverage: usize, glyph_index: u32) -> bool {
+    let contains = || -> Option<bool> {
+        let count = read_u16(table, coverage + 2)? as usize;
+        match read_u16(table, coverage)? {
+            COVERAGE_FORMAT_GLYPHS => Some((0..count).any(|index| {
+                read_u16(table, coverage + 4 + index * 2) == Some(glyph_index as u16)
+            })),
+            COVERAGE_FORMAT_RANGES => Some((0..count).any(|index| {
+                let range = coverage + 4 + index * 6;
+                match (read_u16(table, range), read_u16(table, range + 2)) {
+                    (Some(start), Some(end)) => {
+                        (start as u32) <= glyph_index && glyph_index <= end as u32
+                    }
+                    _ => false,
+                }
+            })),
+            _ => None,
+        }
+    };
+    glyph_index <= u16::MAX as u32 && contains().unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_of(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    // Two features with a single substitution each, "liga" for glyph 5 and "smcp" for glyphs 10
+    // to 20
+    fn gsub_table() -> Vec<u8> {
+        table_of(&[
+            1, 0, 0, 10, 36, // Header, with the feature list at 10 and the lookup list at 36
+            2, 0x6C69, 0x6761, 14, 0x736D, 0x6370, 20, // Feature list
+            0, 1, 0, // "liga" feature, lookup 0
+            0, 1, 1, // "smcp" feature, lookup 1
+            2, 6, 26, // Lookup list
+            1, 0, 1, 8, // Lookup 0
+            1, 6, 1, // Single substitution
+            1, 1, 5, // Coverage of glyph 5
+            1, 0, 1, 8, // Lookup 1
+            2, 6, 0, // Single substitution
+            2, 1, 10, 20, 0, // Coverage of glyphs 10 to 20
+        ])
+    }
+
+    #[test]
+    fn test_head_font_revision() {
+        // Version 1.0, then a revision of 2.001
+        let table = table_of(&[1, 0, 2, 0x0041]);
+        assert_eq!(head_font_revision(&table), Some("2.001".to_owned()));
+        assert_eq!(head_font_revision(&table[..6]), None);
+    }
+
+    #[test]
+    fn test_os2_x_height() {
+        // Version, then the fields before `sxHeight`
+        let os2_table = |version: u16, x_height: u16| {
+            let mut values = vec![version];
+            values.extend(vec![0; OS2_X_HEIGHT_OFFSET / 2 - 1]);
+            values.push(x_height);
+            table_of(&values)
+        };
+        assert_eq!(os2_x_height(&os2_table(4, 536)), Some(536));
+        assert_eq!(os2_x_height(&os2_table(1, 536)), None);
+        assert_eq!(os2_x_height(&os2_table(4, 0)), None);
+        assert_eq!(os2_x_height(&table_of(&[4, 0])), None);
+    }
+
+    #[test]
+    fn test_layout_features() {
+        let table = gsub_table();
+        let feature = |tag: &str, involves_glyph| OpenTypeFeature {
+            tag: tag.to_owned(),
+            involves_glyph,
+        };
+
+        assert_eq!(
+            layout_features(&table, LayoutTable::Gsub, 5),
+            vec![feature("liga", true), feature("smcp", false)]
+        );
+        assert_eq!(
+            layout_features(&table, LayoutTable::Gsub, 15),
+            vec![feature("liga", false), feature("smcp", true)]
+        );
+        assert_eq!(
+            layout_features(&table, LayoutTable::Gsub, 70000),
+            vec![feature("liga", false), feature("smcp", false)]
+        );
+    }
+
+    #[test]
+    fn test_malformed_table() {
+        let table = gsub_table();
+        assert_eq!(layout_features(&table[..4], LayoutTable::Gsub, 5), vec![]);
+
+        // The feature records are read, but the lookups are cut off
+        let features = layout_features(&table[..40], LayoutTable::Gsub, 5);
+        assert_eq!(features.len(), 2);
+        assert!(features.iter().all(|feature| !feature.involves_glyph));
+    }
+}
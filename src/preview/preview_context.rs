@@ -0,0 +1,226 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::{RefCell, RefMut};
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use freetype::Library;
+use log::warn;
+
+use super::character_preview::RenderedCharacter;
+use super::font_index::ContentHashes;
+use super::font_match::{all_fonts, fonts_for, MatchedFont};
+use super::render_cache::{CachedRender, RenderCache, RenderKey};
+use super::render_worker::RenderWorker;
+#[cfg(feature = "shaping")]
+use super::shaping::ShapingFaces;
+use super::Result;
+
+// Characters whose matching fonts are kept around, all of them are forgotten past it
+const MATCHED_FONTS_CAPACITY: usize = 256;
+
+// FreeType state shared by the previews of every character, initialized once at startup instead
+// of for each character. Clones share the same library, faces keep it alive on their own.
+//
+// Renders are cached as well, so that browsing back and forth through characters doesn't render
+// the same glyphs over and over. With a render worker, renders missing from the cache can be done
+// in the background. The contents of font files hashed to leave out duplicate fonts are kept too,
+// in a font index which can be saved to a cache directory. The fonts matching characters are
+// kept as well, so that browsing back doesn't query fontconfig again, and so are the HarfBuzz faces
+// text is shaped with.
+#[derive(Clone)]
+pub struct PreviewContext {
+    library: Rc<Library>,
+    render_cache: Rc<RefCell<RenderCache>>,
+    render_worker: Option<Rc<RenderWorker>>,
+    content_hashes: Rc<RefCell<ContentHashes>>,
+    matched_fonts: Rc<RefCell<HashMap<char, Vec<MatchedFont>>>>,
+    #[cfg(feature = "shaping")]
+    shaping_faces: Rc<RefCell<ShapingFaces>>,
+}
+
+impl PreviewContext {
+    pub fn new() -> Result<Self> {
+        Ok(PreviewContext {
+            library: Rc::new(Library::init()?),
+            render_cache: Rc::new(RefCell::new(RenderCache::default())),
+            render_worker: None,
+            content_hashes: Rc::new(RefCell::new(ContentHashes::default())),
+            matched_fonts: Rc::new(RefCell::new(HashMap::new())),
+            #[cfg(feature = "shaping")]
+            shaping_faces: Rc::new(RefCell::new(ShapingFaces::default())),
+        })
+    }
+
+    // Renders glyphs on a separate thread from then on, in the foreground if it can't be started
+    pub fn with_render_worker(mut self) -> Self {
+        match RenderWorker::spawn() {
+            Ok(render_worker) => self.render_worker = Some(Rc::new(render_worker)),
+            Err(error) => warn!("Failed to start the render worker: {}", error),
+        }
+        self
+    }
+
+    // Keeps the font index in the cache directory from then on. It stays in memory only if the
+    // directory can't be used.
+    pub fn cache_in(&mut self, directory: &Path) -> Result<()> {
+        *self.content_hashes.borrow_mut() = ContentHashes::in_cache_directory(directory)?;
+        Ok(())
+    }
+
+    // Indexes every font from scratch, returns the number of fonts hashed
+    pub fn rebuild_font_index(&self) -> Result<usize> {
+        let mut content_hashes = self.content_hashes.borrow_mut();
+        content_hashes.rebuild(all_fonts()?)?;
+        Ok(content_hashes.len())
+    }
+
+    pub(super) fn library(&self) -> &Library {
+        &self.library
+    }
+
+    pub(super) fn content_hashes(&self) -> RefMut<ContentHashes> {
+        self.content_hashes.borrow_mut()
+    }
+
+    #[cfg(feature = "shaping")]
+    pub(super) fn shaping_faces(&self) -> RefMut<ShapingFaces> {
+        self.shaping_faces.borrow_mut()
+    }
+
+    // Same as `font_match::fonts_for`, matched once per character
+    pub(super) fn fonts_for(&self, chr: char) -> Result<Vec<MatchedFont>> {
+        if let Some(fonts) = self.matched_fonts.borrow().get(&chr) {
+            return Ok(fonts.clone());
+        }
+        let fonts = fonts_for(chr)?;
+        let mut matched_fonts = self.matched_fonts.borrow_mut();
+        if matched_fonts.len() >= MATCHED_FONTS_CAPACITY {
+            matched_fonts.clear();
+        }
+        matched_fonts.insert(chr, fonts.clone());
+        Ok(fonts)
+    }
+
+    pub(super) fn cached_render(&self, key: &RenderKey) -> Option<RenderedCharacter> {
+        self.cached_render_with_duration(key)
+            .map(|cached_render| cached_render.rendered_character)
+    }
+
+    // The cached render along with how long it took when it was rendered
+    pub(super) fn cached_render_with_duration(&self, key: &RenderKey) -> Option<CachedRender> {
+        self.render_cache.borrow_mut().get(key).cloned()
+    }
+
+    pub(super) fn cache_render(
+        &self,
+        key: RenderKey,
+        rendered_character: RenderedCharacter,
+        duration: Duration,
+    ) {
+        self.render_cache
+            .borrow_mut()
+            .insert(key, rendered_character, duration);
+    }
+
+    // Requests a render from the render worker, the render is cached once it is received. Returns
+    // false if the render has to be done in the foreground instead.
+    pub(super) fn request_render(&self, key: RenderKey) -> bool {
+        match &self.render_worker {
+            Some(render_worker) => render_worker.request(key),
+            None => false,
+        }
+    }
+
+    pub fn has_pending_renders(&self) -> bool {
+        self.render_worker
+            .as_ref()
+            .map_or(false, |render_worker| render_worker.has_pending_renders())
+    }
+
+    // Caches the renders done in the background since the last call. Returns true if any render
+    // was done or failed, i.e. the preview should be drawn again.
+    pub fn receive_renders(&self) -> bool {
+        self.receive_renders_until(None)
+    }
+
+    // Waits up to the timeout for a requested render, caching those received meanwhile. Returns
+    // true once it is cached or failed, false if it is still pending or was superseded by a later
+    // request (it is requested again the next time it is drawn).
+    pub(super) fn wait_for_render(&self, key: &RenderKey, timeout: Duration) -> bool {
+        let render_worker = match &self.render_worker {
+            Some(render_worker) => render_worker,
+            None => return false,
+        };
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.receive_renders_until(Some(deadline));
+            if self.render_cache.borrow_mut().get(key).is_some() || render_worker.has_failed(key) {
+                return true;
+            }
+            if !render_worker.is_pending(key) || Instant::now() >= deadline {
+                return false;
+            }
+        }
+    }
+
+    fn receive_renders_until(&self, deadline: Option<Instant>) -> bool {
+        let renders = match &self.render_worker {
+            Some(render_worker) => render_worker.receive(deadline),
+            None => return false,
+        };
+        let received_renders = !renders.is_empty();
+        for (key, cached_render) in renders {
+            if let Some(cached_render) = cached_render {
+                self.cache_render(
+                    key,
+                    cached_render.rendered_character,
+                    cached_render.duration,
+                );
+            }
+        }
+        received_renders
+    }
+
+    // Forgets the renders of a font whose file changed
+    pub(super) fn forget_font_renders(&self, font_path: &str) {
+        self.render_cache.borrow_mut().remove_font(font_path);
+        #[cfg(feature = "shaping")]
+        self.shaping_faces.borrow_mut().remove_font(font_path);
+    }
+
+    // Forgets every render, e.g. once the terminal is resized and renders of the previous sizes
+    // are unlikely to be needed again
+    pub fn clear_render_cache(&self) {
+        self.render_cache.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_library() {
+        let preview_context = PreviewContext::new().unwrap();
+        let shared_context = preview_context.clone();
+        assert!(std::ptr::eq(
+            preview_context.library(),
+            shared_context.library()
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Lists the fonts matching each character of a range as it goes, so that tools built on Cicero
+//! (e.g. coverage reports or missing glyph audits) can walk whole blocks without holding every
+//! match in memory.
+//!
+
+use std::cell::RefCell;
+use std::char;
+use std::ops::RangeInclusive;
+
+use unic::ucd::GeneralCategory;
+
+use super::font_index::ContentHashes;
+use super::font_match::{
+    deduplicate_fonts, exclude_fonts, filter_fonts, fonts_for, limit_fonts, prioritize_fonts,
+    FontMatchOptions, MatchedFont,
+};
+use super::Result;
+
+// Yields each assigned character of the range along with its matching fonts, in the order the
+// preview cycles through them. Unassigned code points (and surrogates) are skipped.
+pub struct FontsInRange {
+    code_points: RangeInclusive<u32>,
+    font_match_options: FontMatchOptions,
+    content_hashes: RefCell<ContentHashes>,
+}
+
+impl FontsInRange {
+    pub fn new(range: RangeInclusive<char>, font_match_options: &FontMatchOptions) -> Self {
+        FontsInRange {
+            code_points: *range.start() as u32..=*range.end() as u32,
+            font_match_options: font_match_options.clone(),
+            content_hashes: RefCell::new(ContentHashes::default()),
+        }
+    }
+
+    fn matching_fonts(&self, chr: char) -> Result<Vec<MatchedFont>> {
+        let fonts = exclude_fonts(fonts_for(chr)?, &self.font_match_options.excluded_fonts);
+        let mut fonts = filter_fonts(fonts, &self.font_match_options.style_filter);
+        if self.font_match_options.deduplicate {
+            fonts = deduplicate_fonts(fonts, &mut self.content_hashes.borrow_mut());
+        }
+        prioritize_fonts(&mut fonts, &self.font_match_options.directory_priority);
+        if let Some(max_fonts) = self.font_match_options.max_fonts {
+            limit_fonts(&mut fonts, max_fonts);
+        }
+        Ok(fonts)
+    }
+}
+
+impl Iterator for FontsInRange {
+    type Item = (char, Result<Vec<MatchedFont>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chr = self
+            .code_points
+            .by_ref()
+            .filter_map(char::from_u32)
+            .find(|chr| GeneralCategory::of(*chr) != GeneralCategory::Unassigned)?;
+        Some((chr, self.matching_fonts(chr)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn characters_of(range: RangeInclusive<char>) -> Vec<char> {
+        FontsInRange::new(range, &FontMatchOptions::default())
+            .map(|(chr, _)| chr)
+            .collect()
+    }
+
+    #[test]
+    fn test_fonts_in_range() {
+        assert_eq!(characters_of('A'..='C'), vec!['A', 'B', 'C']);
+
+        // U+0378 and U+0379 are unassigned
+        assert_eq!(
+            characters_of('\u{377}'..='\u{37A}'),
+            vec!['\u{377}', '\u{37A}']
+        );
+
+        // Surrogates can't be characters, the range just steps over them
+        assert_eq!(
+            characters_of('\u{D7FB}'..='\u{E000}'),
+            vec!['\u{D7FB}', '\u{E000}']
+        );
+    }
+}
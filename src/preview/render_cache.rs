@@ -0,0 +1,240 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use super::character_preview::{RenderOptions, RenderSize, RenderedCharacter, Sizing};
+
+// Renders kept around for characters browsed again, the least recently used ones are evicted first
+const RENDER_CACHE_CAPACITY: usize = 128;
+
+// Everything a render of a character depends on. Points sizes and the values of design axes are
+// compared by their bits, renders are only reused for the exact same ones.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenderKey {
+    pub chr: char,
+
+    // Text shaped as a whole instead of the character alone (e.g. a sequence), never cropped
+    pub text: Option<String>,
+    pub font_path: String,
+    pub face_index: isize,
+    pub size: RenderSize,
+    pub cropped: bool,
+    pub point_size: Option<(u64, u32)>,
+    pub render_options: RenderOptions,
+    pub selected_strike: Option<usize>,
+    pub variation: Vec<u64>,
+
+    // Rendered at the design size of the font (one pixel per font unit) instead of the size
+    pub native_size: bool,
+}
+
+impl RenderKey {
+    pub fn sizing(&self) -> Sizing {
+        match self.point_size {
+            Some((point_size, dpi)) => Sizing::Points {
+                size: f64::from_bits(point_size),
+                dpi,
+            },
+            None => Sizing::Pixels,
+        }
+    }
+}
+
+// A render along with how long it took, so that a render served from the cache is still timed
+#[derive(Debug, Clone)]
+pub struct CachedRender {
+    pub rendered_character: RenderedCharacter,
+    pub duration: Duration,
+}
+
+pub struct RenderCache {
+    capacity: usize,
+
+    // Each render with the last time it was used, as a count of uses of the cache
+    renders: HashMap<RenderKey, (CachedRender, u64)>,
+
+    // Keys of the renders by the last time they were used, the least recently used first
+    recently_used: BTreeMap<u64, RenderKey>,
+    use_count: u64,
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        RenderCache::with_capacity(RENDER_CACHE_CAPACITY)
+    }
+}
+
+impl RenderCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        RenderCache {
+            capacity,
+            renders: HashMap::new(),
+            recently_used: BTreeMap::new(),
+            use_count: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &RenderKey) -> Option<&CachedRender> {
+        let use_count = self.next_use();
+        let (cached_render, last_used) = self.renders.get_mut(key)?;
+        if let Some(used_key) = self.recently_used.remove(&*last_used) {
+            self.recently_used.insert(use_count, used_key);
+        }
+        *last_used = use_count;
+        Some(cached_render)
+    }
+
+    pub fn insert(
+        &mut self,
+        key: RenderKey,
+        rendered_character: RenderedCharacter,
+        duration: Duration,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let use_count = self.next_use();
+        let cached_render = CachedRender {
+            rendered_character,
+            duration,
+        };
+        match self.renders.insert(key.clone(), (cached_render, use_count)) {
+            Some((_, last_used)) => {
+                self.recently_used.remove(&last_used);
+            }
+            None if self.renders.len() > self.capacity => {
+                let least_recently_used = self.recently_used.keys().next().copied();
+                if let Some(evicted_key) =
+                    least_recently_used.and_then(|last_used| self.recently_used.remove(&last_used))
+                {
+                    self.renders.remove(&evicted_key);
+                }
+            }
+            None => {}
+        }
+        self.recently_used.insert(use_count, key);
+    }
+
+    // Forgets the renders of a font, e.g. once its file changed
+    pub fn remove_font(&mut self, font_path: &str) {
+        self.renders.retain(|key, _| key.font_path != font_path);
+        self.recently_used = std::mem::take(&mut self.recently_used)
+            .into_iter()
+            .filter(|(_, key)| key.font_path != font_path)
+            .collect();
+    }
+
+    pub fn clear(&mut self) {
+        self.renders.clear();
+        self.recently_used.clear();
+    }
+
+    fn next_use(&mut self) -> u64 {
+        self.use_count += 1;
+        self.use_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_of(chr: char, font_path: &str) -> RenderKey {
+        RenderKey {
+            chr,
+            text: None,
+            font_path: font_path.to_owned(),
+            face_index: 0,
+            size: RenderSize::new(32, 32),
+            cropped: true,
+            point_size: None,
+            render_options: RenderOptions::default(),
+            selected_strike: None,
+            variation: vec![],
+            native_size: false,
+        }
+    }
+
+    fn render_of(width: usize) -> RenderedCharacter {
+        RenderedCharacter {
+            bitmap: vec![vec![255; width]],
+            colors: None,
+            glyph_size: RenderSize::new(width, 1),
+            bitmap_left: 0,
+            bitmap_top: 1,
+            advance_width: width,
+            advance_height: 1,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        }
+    }
+
+    #[test]
+    fn test_least_recently_used_eviction() {
+        let mut render_cache = RenderCache::with_capacity(2);
+        render_cache.insert(key_of('A', "a.ttf"), render_of(1), Duration::default());
+        render_cache.insert(key_of('B', "a.ttf"), render_of(2), Duration::default());
+
+        // A is used again, B is evicted in its place
+        assert!(render_cache.get(&key_of('A', "a.ttf")).is_some());
+        render_cache.insert(key_of('C', "a.ttf"), render_of(3), Duration::default());
+        assert_eq!(render_cache.renders.len(), 2);
+        assert!(render_cache.get(&key_of('B', "a.ttf")).is_none());
+        assert_eq!(
+            render_cache
+                .get(&key_of('A', "a.ttf"))
+                .map(|cached_render| cached_render.rendered_character.advance_width),
+            Some(1)
+        );
+        assert_eq!(render_cache.recently_used.len(), 2);
+
+        // Another size is another render
+        let mut larger_key = key_of('A', "a.ttf");
+        larger_key.size = RenderSize::new(64, 64);
+        assert!(render_cache.get(&larger_key).is_none());
+
+        let mut render_cache = RenderCache::with_capacity(0);
+        render_cache.insert(key_of('A', "a.ttf"), render_of(1), Duration::default());
+        assert_eq!(render_cache.renders.len(), 0);
+    }
+
+    #[test]
+    fn test_remove_font() {
+        let mut render_cache = RenderCache::default();
+        render_cache.insert(key_of('A', "a.ttf"), render_of(1), Duration::default());
+        render_cache.insert(key_of('A', "b.ttf"), render_of(1), Duration::default());
+        render_cache.remove_font("a.ttf");
+        assert!(render_cache.get(&key_of('A', "a.ttf")).is_none());
+        assert!(render_cache.get(&key_of('A', "b.ttf")).is_some());
+
+        render_cache.clear();
+        assert_eq!(render_cache.renders.len(), 0);
+    }
+
+    #[test]
+    fn test_cached_duration() {
+        let mut render_cache = RenderCache::default();
+        let duration = Duration::from_millis(12);
+        render_cache.insert(key_of('A', "a.ttf"), render_of(1), duration);
+        assert_eq!(
+            render_cache
+                .get(&key_of('A', "a.ttf"))
+                .map(|cached_render| cached_render.duration),
+            Some(duration)
+        );
+    }
+}
@@ -0,0 +1,235 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::character_preview::{CharacterPreview, RenderedCharacter};
+use super::preview_context::PreviewContext;
+use super::render_cache::{CachedRender, RenderKey};
+use super::Result;
+
+// The outcome of a render request (with how long it took), None when a later request superseded it
+// before it was rendered. Errors are sent as text since they can't be sent across threads.
+type RenderOutcome = Option<std::result::Result<(RenderedCharacter, Duration), String>>;
+
+// Renders glyphs on a thread of its own, so that slow fonts don't block the UI. FreeType objects
+// can't be shared between threads, the worker opens the fonts it renders with itself and keeps the
+// last one open for the renders that follow.
+pub struct RenderWorker {
+    requests: Sender<RenderKey>,
+    outcomes: Receiver<(RenderKey, RenderOutcome)>,
+    pending: RefCell<HashSet<RenderKey>>,
+
+    // Renders which failed in the worker, rendered again in the foreground to report their error.
+    // Only those of the font last requested are kept.
+    failed: RefCell<HashSet<RenderKey>>,
+}
+
+impl RenderWorker {
+    pub fn spawn() -> Result<Self> {
+        let (requests, request_receiver) = channel();
+        let (outcome_sender, outcomes) = channel();
+        thread::Builder::new()
+            .name("render-worker".to_owned())
+            .spawn(move || work(request_receiver, outcome_sender))?;
+        Ok(RenderWorker {
+            requests,
+            outcomes,
+            pending: RefCell::new(HashSet::new()),
+            failed: RefCell::new(HashSet::new()),
+        })
+    }
+
+    // Requests a render, unless it is already pending. Returns false if the render failed in the
+    // worker before, or the worker is gone, so that it is rendered in the foreground instead.
+    pub fn request(&self, render_key: RenderKey) -> bool {
+        if self.has_failed(&render_key) {
+            return false;
+        }
+        self.failed
+            .borrow_mut()
+            .retain(|failed_key| failed_key.font_path == render_key.font_path);
+        if self.pending.borrow().contains(&render_key) {
+            return true;
+        }
+        if self.requests.send(render_key.clone()).is_err() {
+            return false;
+        }
+        self.pending.borrow_mut().insert(render_key);
+        true
+    }
+
+    pub fn has_pending_renders(&self) -> bool {
+        !self.pending.borrow().is_empty()
+    }
+
+    pub fn is_pending(&self, render_key: &RenderKey) -> bool {
+        self.pending.borrow().contains(render_key)
+    }
+
+    pub fn has_failed(&self, render_key: &RenderKey) -> bool {
+        self.failed.borrow().contains(render_key)
+    }
+
+    // Returns the renders done since the last call, waiting until the deadline for the first one
+    // if there is none yet and a deadline is given. Failed renders come without their render.
+    pub fn receive(&self, deadline: Option<Instant>) -> Vec<(RenderKey, Option<CachedRender>)> {
+        let mut outcomes: Vec<_> = self.outcomes.try_iter().collect();
+        if let Some(deadline) = deadline {
+            let now = Instant::now();
+            if outcomes.is_empty() && now < deadline {
+                if let Ok(outcome) = self.outcomes.recv_timeout(deadline - now) {
+                    outcomes.push(outcome);
+                }
+                outcomes.extend(self.outcomes.try_iter());
+            }
+        }
+
+        let mut renders = vec![];
+        for (render_key, outcome) in outcomes {
+            self.pending.borrow_mut().remove(&render_key);
+            match outcome {
+                Some(Ok((rendered_character, duration))) => renders.push((
+                    render_key,
+                    Some(CachedRender {
+                        rendered_character,
+                        duration,
+                    }),
+                )),
+                Some(Err(_)) => {
+                    self.failed.borrow_mut().insert(render_key.clone());
+                    renders.push((render_key, None));
+                }
+                None => {}
+            }
+        }
+        renders
+    }
+}
+
+// Only the latest request is rendered, those queued before it are skipped since the user has
+// already moved past them (e.g. while scrolling quickly)
+fn work(requests: Receiver<RenderKey>, outcomes: Sender<(RenderKey, RenderOutcome)>) {
+    let context = PreviewContext::new().map_err(|error| error.to_string());
+    let mut character_preview = None;
+    while let Ok(mut render_key) = requests.recv() {
+        for later_render_key in requests.try_iter() {
+            let superseded_render_key = std::mem::replace(&mut render_key, later_render_key);
+            if outcomes.send((superseded_render_key, None)).is_err() {
+                return;
+            }
+        }
+
+        let render_started_at = Instant::now();
+        let rendered_character = match &context {
+            Ok(context) => render(context, &mut character_preview, &render_key)
+                .map(|rendered_character| (rendered_character, render_started_at.elapsed()))
+                .map_err(|error| error.to_string()),
+            Err(error) => Err(error.clone()),
+        };
+        if outcomes
+            .send((render_key, Some(rendered_character)))
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+// Renders with the preview of the previous render when it has the font of the key, which is then
+// kept for the next renders. The font is only opened again once another one is rendered with.
+fn render(
+    context: &PreviewContext,
+    last_character_preview: &mut Option<CharacterPreview>,
+    render_key: &RenderKey,
+) -> Result<RenderedCharacter> {
+    let mut character_preview = match last_character_preview.take() {
+        Some(character_preview) if character_preview.has_font_of(render_key) => character_preview,
+        _ => CharacterPreview::for_render_key(context, render_key)?,
+    };
+    character_preview.set_render_key(render_key)?;
+    let rendered_character = character_preview.render_uncached(render_key);
+    *last_character_preview = Some(character_preview);
+    rendered_character
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preview::{FontMatchOptions, RenderSize};
+
+    #[test]
+    fn test_render_worker() {
+        let context = PreviewContext::new().unwrap();
+        let character_preview =
+            match CharacterPreview::new(&context, 'A', None, &FontMatchOptions::default()) {
+                Ok(character_preview) => character_preview,
+                Err(_) => return,
+            };
+        let render_worker = RenderWorker::spawn().unwrap();
+        let render_key = character_preview.render_key(RenderSize::new(32, 32), false);
+        assert!(render_worker.request(render_key.clone()));
+        assert!(render_worker.has_pending_renders());
+
+        let waiting_since = Instant::now();
+        let mut renders = vec![];
+        while renders.is_empty() && waiting_since.elapsed() < Duration::from_secs(10) {
+            renders = render_worker.receive(Some(Instant::now() + Duration::from_millis(10)));
+        }
+        assert!(!render_worker.has_pending_renders());
+        assert_eq!(renders.len(), 1);
+        let (rendered_key, cached_render) = &renders[0];
+        assert_eq!(rendered_key, &render_key);
+        assert_eq!(
+            cached_render.as_ref().unwrap().rendered_character.bitmap,
+            character_preview
+                .render_uncropped(RenderSize::new(32, 32))
+                .unwrap()
+                .bitmap
+        );
+    }
+
+    #[test]
+    fn test_render_keeps_font() {
+        let context = PreviewContext::new().unwrap();
+        let character_preview =
+            match CharacterPreview::new(&context, 'A', None, &FontMatchOptions::default()) {
+                Ok(character_preview) => character_preview,
+                Err(_) => return,
+            };
+        let size = RenderSize::new(32, 32);
+        let mut render_key = character_preview.render_key(size, true);
+        let mut last_character_preview = None;
+        assert!(render(&context, &mut last_character_preview, &render_key).is_ok());
+        assert!(last_character_preview.is_some());
+
+        render_key.chr = 'B';
+        let rendered_character = render(&context, &mut last_character_preview, &render_key);
+        assert_eq!(
+            rendered_character.unwrap().bitmap,
+            character_preview
+                .render_alongside('B', size)
+                .unwrap()
+                .bitmap
+        );
+        assert!(last_character_preview
+            .as_ref()
+            .map_or(false, |last_character_preview| last_character_preview
+                .has_font_of(&render_key)));
+    }
+}
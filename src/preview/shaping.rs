@@ -0,0 +1,323 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Shapes text with HarfBuzz, for characters which only make sense in context: Arabic joining,
+//! Indic conjuncts or emoji ZWJ sequences are shaped into glyphs which `load_char` alone can't
+//! find. The shaped glyphs are then rendered with FreeType and composed into a single render.
+//!
+
+use std::cmp::{max, min};
+use std::collections::HashMap;
+
+use harfbuzz_rs::{
+    shape, Face as HarfBuzzFace, Font as HarfBuzzFont, Shared, Tag, UnicodeBuffer, Variation,
+};
+
+use super::character_preview::{RenderSize, RenderedCharacter};
+use super::freetype_ext::VariationAxis;
+use super::{Error, Result};
+
+// HarfBuzz faces hold the whole font file, only those of the last fonts shaped with are kept
+const SHAPING_FACES_CAPACITY: usize = 8;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ShapedGlyph {
+    pub glyph_index: u32,
+
+    // Byte offset in the text of the first character the glyph was shaped from
+    pub cluster: u32,
+
+    // Positions are in font units, offsets move the glyph without moving the pen
+    pub x_advance: i32,
+    pub y_advance: i32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+}
+
+// The HarfBuzz faces text is shaped with, each font file is read once per face index instead of for
+// every shaping
+#[derive(Default)]
+pub struct ShapingFaces {
+    faces: HashMap<(String, u32), Shared<HarfBuzzFace<'static>>>,
+}
+
+impl ShapingFaces {
+    // Shapes the text with a face of the font, variable fonts at the values of the variation axes
+    // (e.g. those of a named instance)
+    pub fn shape(
+        &mut self,
+        font_path: &str,
+        face_index: u32,
+        variation_axes: &[VariationAxis],
+        text: &str,
+    ) -> Result<Vec<ShapedGlyph>> {
+        let mut font = HarfBuzzFont::new(self.face(font_path, face_index)?);
+        let variations: Vec<Variation> = variation_axes
+            .iter()
+            .filter_map(|variation_axis| {
+                let tag: Vec<char> = variation_axis.tag.chars().collect();
+                match tag.as_slice() {
+                    [a, b, c, d] => Some(Variation::new(
+                        Tag::new(*a, *b, *c, *d),
+                        variation_axis.value as f32,
+                    )),
+                    _ => None,
+                }
+            })
+            .collect();
+        if !variations.is_empty() {
+            font.set_variations(&variations);
+        }
+
+        let glyph_buffer = shape(&font, UnicodeBuffer::new().add_str(text), &[]);
+        Ok(glyph_buffer
+            .get_glyph_infos()
+            .iter()
+            .zip(glyph_buffer.get_glyph_positions())
+            .map(|(info, position)| ShapedGlyph {
+                glyph_index: info.codepoint,
+                cluster: info.cluster,
+                x_advance: position.x_advance,
+                y_advance: position.y_advance,
+                x_offset: position.x_offset,
+                y_offset: position.y_offset,
+            })
+            .collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.faces.len()
+    }
+
+    // Forgets the faces of a font, e.g. once its file changed
+    pub fn remove_font(&mut self, font_path: &str) {
+        self.faces
+            .retain(|(face_font_path, _), _| face_font_path != font_path);
+    }
+
+    fn face(&mut self, font_path: &str, face_index: u32) -> Result<Shared<HarfBuzzFace<'static>>> {
+        let key = (font_path.to_owned(), face_index);
+        if let Some(face) = self.faces.get(&key) {
+            return Ok(face.clone());
+        }
+        let face: Shared<HarfBuzzFace<'static>> = HarfBuzzFace::from_file(font_path, face_index)
+            .map_err(|error| Error::FontOperationFailed {
+                font_path: font_path.to_owned(),
+                operation: "shape text with",
+                reason: error.to_string(),
+            })?
+            .into();
+        if self.faces.len() >= SHAPING_FACES_CAPACITY {
+            self.faces.clear();
+        }
+        self.faces.insert(key, face.clone());
+        Ok(face)
+    }
+}
+
+// Shapes the text with a face of the font at its default instance, reading the font file for it
+pub fn shape_text(font_path: &str, face_index: u32, text: &str) -> Result<Vec<ShapedGlyph>> {
+    ShapingFaces::default().shape(font_path, face_index, &[], text)
+}
+
+// A rendered glyph along with the position of its origin in pixels, relative to the origin of the
+// first glyph, y going up
+pub struct PositionedGlyph {
+    pub rendered_character: RenderedCharacter,
+    pub x: i32,
+    pub y: i32,
+}
+
+// Composes rendered glyphs into a single render as large as their ink, overlapping pixels keep the
+// fullest coverage. Colors are kept only if every glyph is a color glyph, e.g. an emoji sequence.
+pub fn compose_glyphs(glyphs: &[PositionedGlyph], advance_width: usize) -> RenderedCharacter {
+    // Ink bounds of every glyph, in pixels from the origin with y going up
+    let bounds = |glyph: &PositionedGlyph| {
+        let glyph_size = glyph.rendered_character.glyph_size;
+        let left = glyph.x + glyph.rendered_character.bitmap_left;
+        let top = glyph.y + glyph.rendered_character.bitmap_top;
+        (
+            left,
+            top,
+            left + glyph_size.width as i32,
+            top - glyph_size.height as i32,
+        )
+    };
+    let (left, top, right, bottom) = glyphs.iter().map(bounds).fold(
+        (i32::MAX, i32::MIN, i32::MIN, i32::MAX),
+        |(left, top, right, bottom), (glyph_left, glyph_top, glyph_right, glyph_bottom)| {
+            (
+                min(left, glyph_left),
+                max(top, glyph_top),
+                max(right, glyph_right),
+                min(bottom, glyph_bottom),
+            )
+        },
+    );
+    let (width, height) = if glyphs.is_empty() {
+        (0, 0)
+    } else {
+        ((right - left) as usize, (top - bottom) as usize)
+    };
+
+    // Text is shaped horizontally, the vertical metrics are the ones of the base glyph
+    let vertical_metrics = glyphs.first().map_or((0, 0, 0), |glyph| {
+        (
+            glyph.rendered_character.advance_height,
+            glyph.rendered_character.vertical_bearing_x,
+            glyph.rendered_character.vertical_bearing_y,
+        )
+    });
+
+    let is_color = !glyphs.is_empty()
+        && glyphs
+            .iter()
+            .all(|glyph| glyph.rendered_character.colors.is_some());
+    let mut bitmap = vec![vec![0; width]; height];
+    let mut colors = vec![vec![[0; 3]; width]; height];
+    for glyph in glyphs {
+        let (glyph_left, glyph_top, _, _) = bounds(glyph);
+        let (x_start, y_start) = ((glyph_left - left) as usize, (top - glyph_top) as usize);
+        let rendered_character = &glyph.rendered_character;
+        let glyph_size = rendered_character.glyph_size;
+        for y in 0..glyph_size.height {
+            for x in 0..glyph_size.width {
+                let (composed_x, composed_y) = (x_start + x, y_start + y);
+                let coverage = rendered_character.bitmap[y][x];
+                if coverage <= bitmap[composed_y][composed_x] {
+                    continue;
+                }
+                bitmap[composed_y][composed_x] = coverage;
+                if let Some(glyph_colors) = &rendered_character.colors {
+                    colors[composed_y][composed_x] = glyph_colors[y][x];
+                }
+            }
+        }
+    }
+
+    RenderedCharacter {
+        bitmap,
+        colors: if is_color { Some(colors) } else { None },
+        glyph_size: RenderSize::new(width, height),
+        bitmap_left: if glyphs.is_empty() { 0 } else { left },
+        bitmap_top: if glyphs.is_empty() { 0 } else { top },
+        advance_width,
+        advance_height: vertical_metrics.0,
+        vertical_bearing_x: vertical_metrics.1,
+        vertical_bearing_y: vertical_metrics.2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preview::{CharacterPreview, FontMatchOptions, PreviewContext};
+
+    fn positioned_glyph(bitmap: Vec<Vec<u8>>, x: i32, y: i32) -> PositionedGlyph {
+        let glyph_size = RenderSize::new(bitmap[0].len(), bitmap.len());
+        PositionedGlyph {
+            rendered_character: RenderedCharacter {
+                bitmap,
+                colors: None,
+                glyph_size,
+                bitmap_left: 0,
+                bitmap_top: glyph_size.height as i32,
+                advance_width: glyph_size.width,
+                advance_height: glyph_size.height,
+                vertical_bearing_x: 0,
+                vertical_bearing_y: 0,
+            },
+            x,
+            y,
+        }
+    }
+
+    #[test]
+    fn test_compose_glyphs() {
+        // A base glyph and a mark above its right half
+        let glyphs = vec![
+            positioned_glyph(vec![vec![255, 255], vec![255, 255]], 0, 0),
+            positioned_glyph(vec![vec![128, 128]], 1, 3),
+        ];
+        let composed = compose_glyphs(&glyphs, 2);
+        assert_eq!(composed.glyph_size, RenderSize::new(3, 4));
+        assert_eq!(composed.bitmap_left, 0);
+        assert_eq!(composed.bitmap_top, 4);
+        assert_eq!(
+            composed.bitmap,
+            vec![
+                vec![0, 128, 128],
+                vec![0, 0, 0],
+                vec![255, 255, 0],
+                vec![255, 255, 0],
+            ]
+        );
+        assert!(composed.colors.is_none());
+
+        let composed = compose_glyphs(&[], 0);
+        assert_eq!(composed.glyph_size, RenderSize::new(0, 0));
+        assert!(composed.is_blank());
+    }
+
+    #[test]
+    fn test_shape_text() {
+        let character_preview = match CharacterPreview::new(
+            &PreviewContext::new().unwrap(),
+            'A',
+            None,
+            &FontMatchOptions::default(),
+        ) {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+        let font_path = character_preview.get_current_font_path().unwrap();
+
+        let shaped_glyphs = shape_text(&font_path, 0, "AB").unwrap();
+        assert_eq!(shaped_glyphs.len(), 2);
+        assert_eq!(shaped_glyphs[0].cluster, 0);
+        assert_eq!(shaped_glyphs[1].cluster, 1);
+        assert!(shaped_glyphs[0].x_advance > 0);
+
+        assert!(shape_text("/nonexistent/font.ttf", 0, "A").is_err());
+    }
+
+    #[test]
+    fn test_shaping_faces() {
+        let character_preview = match CharacterPreview::new(
+            &PreviewContext::new().unwrap(),
+            'A',
+            None,
+            &FontMatchOptions::default(),
+        ) {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+        let font_path = character_preview.get_current_font_path().unwrap();
+
+        let mut shaping_faces = ShapingFaces::default();
+        let shaped_glyphs = shaping_faces.shape(&font_path, 0, &[], "AB").unwrap();
+        assert_eq!(shaped_glyphs, shape_text(&font_path, 0, "AB").unwrap());
+        assert!(shaping_faces.shape(&font_path, 0, &[], "BA").is_ok());
+        assert_eq!(shaping_faces.len(), 1);
+
+        assert!(shaping_faces
+            .shape("/nonexistent/font.ttf", 0, &[], "A")
+            .is_err());
+        assert_eq!(shaping_faces.len(), 1);
+
+        shaping_faces.remove_font(&font_path);
+        assert_eq!(shaping_faces.len(), 0);
+    }
+}
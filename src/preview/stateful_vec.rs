@@ -16,6 +16,9 @@
 pub struct StatefulVec<T> {
     storage: Vec<T>,
     current: Option<usize>,
+
+    // Whether moving past the last item selects the first one, and vice versa
+    wraps: bool,
 }
 
 impl<T: Eq> StatefulVec<T> {
@@ -31,12 +34,25 @@ impl<T: Eq> StatefulVec<T> {
             None => None,
         };
 
-        StatefulVec { storage, current }
+        StatefulVec {
+            storage,
+            current,
+            wraps: false,
+        }
+    }
+
+    pub fn set_wraps(&mut self, wraps: bool) {
+        self.wraps = wraps;
+    }
+
+    // A single item has nothing to wrap around to
+    fn can_wrap(&self) -> bool {
+        self.wraps && self.storage.len() > 1
     }
 
     pub fn has_previous(&self) -> bool {
         match self.current {
-            Some(current) => current > 0,
+            Some(current) => current > 0 || self.can_wrap(),
             None => !self.storage.is_empty(),
         }
     }
@@ -48,18 +64,22 @@ impl<T: Eq> StatefulVec<T> {
         assert!(!self.storage.is_empty());
 
         self.current = match self.current {
+            Some(0) | None => Some(self.storage.len() - 1),
             Some(current) => Some(current - 1),
-            None => Some(self.storage.len() - 1),
         }
     }
 
     pub fn has_next(&self) -> bool {
         match self.current {
-            Some(current) => current + 1 < self.storage.len(),
+            Some(current) => current + 1 < self.storage.len() || self.can_wrap(),
             None => !self.storage.is_empty(),
         }
     }
 
+    pub fn is_last(&self) -> bool {
+        self.current == Some(self.storage.len().wrapping_sub(1))
+    }
+
     pub fn select_next(&mut self) {
         if !self.has_next() {
             return;
@@ -67,11 +87,16 @@ impl<T: Eq> StatefulVec<T> {
         assert!(!self.storage.is_empty());
 
         self.current = match self.current {
-            Some(current) => Some(current + 1),
-            None => Some(0),
+            Some(current) if current + 1 < self.storage.len() => Some(current + 1),
+            _ => Some(0),
         }
     }
 
+    // Clears the selection, so that the next item is the first one
+    pub fn unselect(&mut self) {
+        self.current = None;
+    }
+
     pub fn select_if_found(&mut self, item: &T) {
         if let Some(index) = self
             .storage
@@ -82,6 +107,14 @@ impl<T: Eq> StatefulVec<T> {
         }
     }
 
+    pub fn items(&self) -> &[T] {
+        &self.storage
+    }
+
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
+    }
+
     pub fn current_item(&self) -> Option<&T> {
         match self.current {
             Some(current) => Some(&self.storage[current]),
@@ -201,6 +234,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_unselect() {
+        let mut stateful_vec = StatefulVec::new(vec![1, 2, 3, 4, 5], Some(2));
+        stateful_vec.unselect();
+        assert_eq!(stateful_vec.current, None);
+        stateful_vec.select_next();
+        assert_eq!(stateful_vec.current_item(), Some(&1));
+    }
+
     #[test]
     fn test_select_if_found() {
         {
@@ -274,4 +316,24 @@ mod tests {
             assert_eq!(stateful_vec.current_item(), Some(&5));
         }
     }
+
+    #[test]
+    fn test_wraps() {
+        let mut stateful_vec = StatefulVec::new(vec![1, 2, 3], Some(2));
+        stateful_vec.set_wraps(true);
+        assert!(stateful_vec.is_last());
+        assert!(stateful_vec.has_next());
+        stateful_vec.select_next();
+        assert_eq!(stateful_vec.current, Some(0));
+
+        assert!(stateful_vec.has_previous());
+        stateful_vec.select_previous();
+        assert_eq!(stateful_vec.current, Some(2));
+
+        // A single item doesn't wrap onto itself
+        let mut stateful_vec = StatefulVec::new(vec![1], Some(0));
+        stateful_vec.set_wraps(true);
+        assert!(!stateful_vec.has_next());
+        assert!(!stateful_vec.has_previous());
+    }
 }
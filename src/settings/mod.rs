@@ -0,0 +1,988 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! This module implements user settings of Cicero, which are loaded from a TOML file located at
+//! `$XDG_CONFIG_HOME/cicero/settings.toml` (or `$HOME/.config/cicero/settings.toml`).
+//!
+//! Every setting is optional, a missing settings file is equivalent to an empty one.
+//!
+
+use std::convert::TryFrom;
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use log::debug;
+use serde::Deserialize;
+use unic::ucd::{BlockIter, GeneralCategory};
+
+#[cfg(feature = "image")]
+use crate::preview::{ContactSheetOptions, CoverageMapOptions};
+use crate::preview::{FontMatchOptions, FontStyleFilter, Sizing, DEFAULT_BOLD_STRENGTH};
+use crate::ucd::{blocks_of_script, is_non_printing, parse_code_point, parse_code_point_range};
+
+const SETTINGS_DIRECTORY_NAME: &str = "cicero";
+const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+const DEFAULT_COMPARISON_CHARACTERS: &str = "0Oo1lI";
+const DEFAULT_REFERENCE_CHARACTER: char = 'H';
+const DEFAULT_SIDE_BY_SIDE_FONTS: usize = 2;
+const DEFAULT_COMBINING_BASE_CHARACTER: char = '\u{25CC}';
+const DEFAULT_COMPARISON_SIZES: [usize; 5] = [8, 12, 16, 24, 48];
+const DEFAULT_DPI: u32 = 72;
+const DEFAULT_SLIDESHOW_INTERVAL_MS: u64 = 1000;
+const DEFAULT_CONTACT_SHEET_COLUMNS: u32 = 4;
+const DEFAULT_CONTACT_SHEET_CELL_SIZE: u32 = 128;
+const DEFAULT_EXPORT_PIXEL_SIZE: u32 = 256;
+const DEFAULT_FONT_LOAD_TIMEOUT_MS: u64 = 2000;
+const DEFAULT_COVERAGE_THRESHOLD: u8 = 1;
+const DEFAULT_IMAGE_CELL_WIDTH: u16 = 10;
+const DEFAULT_IMAGE_CELL_HEIGHT: u16 = 20;
+
+pub type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidSettingsFile { path: PathBuf, reason: String },
+    InvalidStartupCharacter(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidSettingsFile { path, reason } => write!(
+                f,
+                "Invalid settings file '{}': {}",
+                path.to_string_lossy(),
+                reason
+            ),
+            Error::InvalidStartupCharacter(startup_character) => write!(
+                f,
+                "Invalid startup character '{}', expected a printable character or code point",
+                startup_character
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+// How glyphs are drawn in the preview pane, from the most to the least detailed
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreviewRenderer {
+    // Chosen at startup depending on what the terminal is likely to display
+    Auto,
+    Braille,
+    HalfBlock,
+    Ascii,
+}
+
+impl Default for PreviewRenderer {
+    fn default() -> Self {
+        PreviewRenderer::Auto
+    }
+}
+
+// How the preview pane is sent to the terminal, as characters or as an image
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreviewProtocol {
+    // Chosen at startup depending on the images the terminal is known to display
+    Auto,
+
+    // Drawn with the preview renderer
+    Text,
+
+    // Drawn as a sixel image at the resolution of the terminal, for terminals such as foot,
+    // mlterm or xterm (started as a VT340)
+    Sixel,
+
+    // Drawn as an image with the graphics protocol of kitty (also supported by WezTerm)
+    Kitty,
+
+    // Drawn as an inline image of iTerm2, which needs Cicero to be built with the image feature
+    Iterm2,
+}
+
+impl Default for PreviewProtocol {
+    fn default() -> Self {
+        PreviewProtocol::Auto
+    }
+}
+
+// Colors of the hints, highlights and guides of the TUI
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    Default,
+
+    // Tells hints and guides apart without relying on red and green
+    ColorBlind,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::Default
+    }
+}
+
+// A color written as "#rrggbb"
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct RgbColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl TryFrom<String> for RgbColor {
+    type Error = String;
+
+    fn try_from(string: String) -> std::result::Result<Self, Self::Error> {
+        let hex = if string.starts_with('#') {
+            &string[1..]
+        } else {
+            ""
+        };
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("invalid color '{}', expected \"#rrggbb\"", string));
+        }
+        let component =
+            |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).unwrap();
+        Ok(RgbColor {
+            red: component(0),
+            green: component(1),
+            blue: component(2),
+        })
+    }
+}
+
+// Colors that the coverage of glyph pixels is mapped onto, from the faintest to the fullest
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+pub struct PreviewGradient {
+    pub start: RgbColor,
+    pub end: RgbColor,
+}
+
+// A range of private use characters written as "U+E000..U+F8FF", both ends included
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct PrivateUseRange {
+    pub first: char,
+    pub last: char,
+}
+
+impl PrivateUseRange {
+    pub fn contains(&self, chr: char) -> bool {
+        self.first <= chr && chr <= self.last
+    }
+}
+
+impl TryFrom<String> for PrivateUseRange {
+    type Error = String;
+
+    fn try_from(string: String) -> std::result::Result<Self, Self::Error> {
+        let invalid_range = || {
+            format!(
+                "invalid range '{}', expected private use code points as \"U+E000..U+F8FF\"",
+                string
+            )
+        };
+        let (first, last) = match parse_code_point_range(&string) {
+            Some(range) => (*range.start(), *range.end()),
+            None => return Err(invalid_range()),
+        };
+
+        let is_private_use = |chr| GeneralCategory::of(chr) == GeneralCategory::PrivateUse;
+        if !is_private_use(first) || !is_private_use(last) {
+            return Err(invalid_range());
+        }
+        Ok(PrivateUseRange { first, last })
+    }
+}
+
+// The name of a Unicode block as in the code charts, e.g. "Arabic" or "CJK Unified Ideographs", case
+// insensitive in the settings file
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct BlockName(String);
+
+impl BlockName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for BlockName {
+    type Error = String;
+
+    fn try_from(string: String) -> std::result::Result<Self, Self::Error> {
+        match BlockIter::new().find(|block| block.name.eq_ignore_ascii_case(&string)) {
+            Some(block) => Ok(BlockName(block.name.to_owned())),
+            None => Err(format!(
+                "unknown Unicode block '{}', expected a block name such as \"Arabic\"",
+                string
+            )),
+        }
+    }
+}
+
+// Characters of the block are previewed with the font family by default, when it has a glyph for
+// them, e.g. a CJK font of the preferred regional variant for "CJK Unified Ideographs"
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct BlockFont {
+    pub block: BlockName,
+    pub font_family: String,
+}
+
+// The name of a script as in ISO 15924, e.g. "Han" or "Arabic", case insensitive in the settings
+// file, standing for the blocks made of its characters
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ScriptName(&'static str);
+
+impl ScriptName {
+    pub fn as_str(&self) -> &str {
+        self.0
+    }
+
+    pub fn block_names(&self) -> &'static [&'static str] {
+        blocks_of_script(self.0).map_or(&[], |(_, blocks)| blocks)
+    }
+}
+
+impl TryFrom<String> for ScriptName {
+    type Error = String;
+
+    fn try_from(string: String) -> std::result::Result<Self, Self::Error> {
+        match blocks_of_script(&string) {
+            Some((name, _)) => Ok(ScriptName(name)),
+            None => Err(format!(
+                "unknown script '{}', expected a script name such as \"Han\" (or use a block)",
+                string
+            )),
+        }
+    }
+}
+
+// Characters of the blocks of the script are previewed with the font family by default, after
+// the font families of their own block
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct ScriptFont {
+    pub script: ScriptName,
+    pub font_family: String,
+}
+
+// Icon fonts (and other fonts using the private use areas) are the only ones whose glyphs make
+// sense for their private use characters
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct PrivateUseFont {
+    pub range: PrivateUseRange,
+    pub font_path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Characters rendered next to each other by the comparison grid of the preview pane
+    pub comparison_characters: String,
+
+    // Pixel sizes the character is rendered at side by side, to judge how a font holds up small
+    pub comparison_sizes: Vec<usize>,
+
+    // Number of matching fonts (from 2 to 4) the character is drawn in side by side
+    pub side_by_side_fonts: usize,
+
+    // Character drawn faintly behind the previewed one to compare proportions with
+    pub reference_character: char,
+
+    // Stretches the glyph to fill the preview pane instead of preserving its aspect ratio
+    pub stretch_preview: bool,
+
+    // Previews glyphs at this point size (on a display of `dpi`) instead of fitting the pane
+    pub preview_point_size: Option<f64>,
+    pub dpi: u32,
+
+    // Terminals (or their fonts) without braille patterns need a less detailed renderer
+    pub preview_renderer: PreviewRenderer,
+
+    // Terminals displaying images can show the glyph at their own resolution, the size of a cell
+    // in pixels depends on the terminal font. Unless set, it is the size the terminal reports.
+    pub preview_protocol: PreviewProtocol,
+    pub image_cell_width: Option<u16>,
+    pub image_cell_height: Option<u16>,
+
+    // The character the TUI opens on when no input is given, either the character itself or its
+    // code point (e.g. "U+0041")
+    pub startup_character: Option<String>,
+
+    // Glyphs are drawn with the default foreground color, unless a gradient is set
+    pub preview_gradient: Option<PreviewGradient>,
+
+    // Without a gradient, partially covered pixels are shaded in grays so that curves are smooth
+    pub antialiasing: bool,
+
+    // Shades partially covered pixels the other way along the gradient or the grays, e.g. for light
+    // backgrounds
+    pub invert_shading: bool,
+
+    // Glyph pixels are drawn when their coverage (from 1 to 255) reaches the threshold, a higher
+    // threshold leaves faint antialiased pixels out
+    pub coverage_threshold: u8,
+
+    // Strength of the synthetic bold in thousandths of the em size, kept within 5 and 120
+    pub synthetic_bold_strength: u32,
+
+    // Colors of the hints and guides, an alternate palette suits color vision deficiencies
+    pub palette: Palette,
+
+    // Non-printing characters without ink are previewed as their control picture or abbreviation,
+    // except for whitespace which is outlined by a box of its advance
+    pub show_control_pictures: bool,
+
+    // The "[←]: Prev. Font" and "[→]: Next Font" hints around the font name of the preview pane
+    pub show_font_hints: bool,
+
+    // Shows how long the glyph took to render and the fonts took to match, to diagnose slow fonts
+    pub show_render_timings: bool,
+
+    // Combining marks (e.g. U+0301) are previewed shaped on the base character, as in text, rather
+    // than alone. Needs the shaping feature.
+    pub attach_combining_marks: bool,
+    pub combining_base_character: char,
+
+    // The metrics of the previewed glyph in pixels (advances, bearings and bounding box), below the
+    // glyph preview
+    pub show_glyph_metrics: bool,
+
+    // Font family previewed by default whenever it has a glyph for the character, the font chosen
+    // in the preview pane still takes precedence
+    pub preferred_font_family: Option<String>,
+
+    // Font families preferred over the one above for the characters of a block
+    pub block_fonts: Vec<BlockFont>,
+
+    // Font families preferred over the preferred font family for the characters of a script
+    pub script_fonts: Vec<ScriptFont>,
+
+    // Identical fonts installed under several paths are listed once
+    pub deduplicate_fonts: bool,
+
+    // Only the matching fonts of a regular style, or monospaced, are listed. Both can be toggled
+    // from the preview pane.
+    pub regular_fonts_only: bool,
+    pub monospace_fonts_only: bool,
+
+    // Words the style of the listed fonts must have one of (e.g. "Italic"), and must not have any of
+    // (e.g. "Condensed"), case insensitive
+    pub include_font_styles: Vec<String>,
+    pub exclude_font_styles: Vec<String>,
+
+    // Fonts never matched, by family (e.g. "Symbola", case insensitive), path or directory
+    pub excluded_fonts: Vec<String>,
+
+    // Fonts of these directories are matched as well as the installed ones, e.g. the build of a font
+    // under development
+    pub font_directories: Vec<PathBuf>,
+
+    // Directories whose fonts are listed (and thus previewed by default) first, in order
+    pub font_directory_priority: Vec<PathBuf>,
+
+    // Where the font index is saved, `$XDG_CACHE_HOME/cicero` (or `$HOME/.cache/cicero`) if unset
+    pub cache_directory: Option<PathBuf>,
+
+    // Fonts taking longer than this to load (e.g. malformed ones) are skipped, 0 disables the limit
+    pub font_load_timeout_ms: u64,
+
+    // Only this many matching fonts are listed (in the order of the directory priority), 0 lists
+    // them all
+    pub max_matching_fonts: usize,
+
+    // Glyphs are rendered on a separate thread, a placeholder being shown until they are, so that
+    // slow fonts don't block keyboard input
+    pub background_rendering: bool,
+
+    // The slideshow shows the character in the next matching font every interval, and either stops
+    // at the last font or starts over from the first one
+    pub slideshow_interval_ms: u64,
+    pub loop_slideshow: bool,
+
+    // Jumping past the last (or before the first) Unicode block continues from the other end
+    pub wrap_block_navigation: bool,
+
+    // Cycling past the last (or before the first) matching font continues from the other end
+    pub wrap_font_cycling: bool,
+
+    // Layout of exported contact sheets, the cell size is in pixels
+    pub contact_sheet_columns: u32,
+    pub contact_sheet_cell_size: u32,
+
+    // Size in pixels glyphs are exported as PNG images at
+    pub export_pixel_size: u32,
+
+    // Command the previewed font is opened with (its path is appended as the last argument), the
+    // default one of the OS (e.g. "xdg-open") if unset
+    pub font_editor_command: Option<String>,
+
+    // Private use characters are previewed with the font mapped to their range, when it has a
+    // glyph for them
+    pub private_use_fonts: Vec<PrivateUseFont>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            comparison_characters: DEFAULT_COMPARISON_CHARACTERS.to_owned(),
+            comparison_sizes: DEFAULT_COMPARISON_SIZES.to_vec(),
+            side_by_side_fonts: DEFAULT_SIDE_BY_SIDE_FONTS,
+            reference_character: DEFAULT_REFERENCE_CHARACTER,
+            stretch_preview: false,
+            preview_point_size: None,
+            dpi: DEFAULT_DPI,
+            preview_renderer: PreviewRenderer::default(),
+            preview_protocol: PreviewProtocol::default(),
+            image_cell_width: None,
+            image_cell_height: None,
+            startup_character: None,
+            preview_gradient: None,
+            antialiasing: true,
+            invert_shading: false,
+            coverage_threshold: DEFAULT_COVERAGE_THRESHOLD,
+            synthetic_bold_strength: DEFAULT_BOLD_STRENGTH,
+            palette: Palette::default(),
+            show_control_pictures: true,
+            show_font_hints: true,
+            show_render_timings: false,
+            attach_combining_marks: true,
+            combining_base_character: DEFAULT_COMBINING_BASE_CHARACTER,
+            show_glyph_metrics: true,
+            preferred_font_family: None,
+            block_fonts: vec![],
+            script_fonts: vec![],
+            deduplicate_fonts: true,
+            regular_fonts_only: false,
+            monospace_fonts_only: false,
+            include_font_styles: vec![],
+            exclude_font_styles: vec![],
+            excluded_fonts: vec![],
+            font_directories: vec![],
+            font_directory_priority: vec![],
+            cache_directory: None,
+            font_load_timeout_ms: DEFAULT_FONT_LOAD_TIMEOUT_MS,
+            max_matching_fonts: 0,
+            background_rendering: true,
+            slideshow_interval_ms: DEFAULT_SLIDESHOW_INTERVAL_MS,
+            loop_slideshow: false,
+            wrap_block_navigation: false,
+            wrap_font_cycling: false,
+            contact_sheet_columns: DEFAULT_CONTACT_SHEET_COLUMNS,
+            contact_sheet_cell_size: DEFAULT_CONTACT_SHEET_CELL_SIZE,
+            export_pixel_size: DEFAULT_EXPORT_PIXEL_SIZE,
+            font_editor_command: None,
+            private_use_fonts: vec![],
+        }
+    }
+}
+
+impl Settings {
+    pub fn sizing(&self) -> Sizing {
+        match self.preview_point_size {
+            Some(size) => Sizing::Points {
+                size,
+                dpi: self.dpi,
+            },
+            None => Sizing::Pixels,
+        }
+    }
+
+    // The size of a terminal cell in pixels for image previews, a common size stands in for what
+    // is neither set nor reported by the terminal
+    pub fn image_cell_size(&self) -> (u16, u16) {
+        (
+            self.image_cell_width.unwrap_or(DEFAULT_IMAGE_CELL_WIDTH),
+            self.image_cell_height.unwrap_or(DEFAULT_IMAGE_CELL_HEIGHT),
+        )
+    }
+
+    pub fn font_match_options(&self) -> FontMatchOptions {
+        FontMatchOptions {
+            preferred_font_family: self.preferred_font_family.clone(),
+            block_font_families: self
+                .block_fonts
+                .iter()
+                .map(|block_font| {
+                    (
+                        block_font.block.as_str().to_owned(),
+                        block_font.font_family.clone(),
+                    )
+                })
+                .chain(self.script_fonts.iter().flat_map(|script_font| {
+                    script_font
+                        .script
+                        .block_names()
+                        .iter()
+                        .map(move |block| ((*block).to_owned(), script_font.font_family.clone()))
+                }))
+                .collect(),
+            deduplicate: self.deduplicate_fonts,
+            style_filter: FontStyleFilter {
+                regular_only: self.regular_fonts_only,
+                monospace_only: self.monospace_fonts_only,
+                include_styles: self.include_font_styles.clone(),
+                exclude_styles: self.exclude_font_styles.clone(),
+            },
+            excluded_fonts: self.excluded_fonts.clone(),
+            directory_priority: self.font_directory_priority.clone(),
+            load_timeout: match self.font_load_timeout_ms {
+                0 => None,
+                timeout_ms => Some(Duration::from_millis(timeout_ms)),
+            },
+            max_fonts: match self.max_matching_fonts {
+                0 => None,
+                max_fonts => Some(max_fonts),
+            },
+        }
+    }
+
+    #[cfg(feature = "image")]
+    pub fn contact_sheet_options(&self) -> ContactSheetOptions {
+        ContactSheetOptions {
+            columns: self.contact_sheet_columns,
+            cell_size: self.contact_sheet_cell_size,
+        }
+    }
+
+    // Covered code points are green and missing ones red, unless the palette avoids red and green
+    #[cfg(feature = "image")]
+    pub fn coverage_map_options(&self) -> CoverageMapOptions {
+        match self.palette {
+            Palette::Default => CoverageMapOptions::default(),
+            Palette::ColorBlind => CoverageMapOptions {
+                covered_color: [30, 136, 229],
+                missing_color: [255, 160, 0],
+                ..CoverageMapOptions::default()
+            },
+        }
+    }
+
+    // The font mapped to the range of a private use character, the first mapping wins
+    pub fn private_use_font_of(&self, chr: char) -> Option<&String> {
+        self.private_use_fonts
+            .iter()
+            .find(|private_use_font| private_use_font.range.contains(chr))
+            .map(|private_use_font| &private_use_font.font_path)
+    }
+
+    pub fn startup_character(&self) -> std::result::Result<Option<char>, Error> {
+        match &self.startup_character {
+            Some(startup_character) => match parse_startup_character(startup_character) {
+                Some(chr) => Ok(Some(chr)),
+                None => Err(Error::InvalidStartupCharacter(startup_character.clone())),
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub fn cache_directory(&self) -> Option<PathBuf> {
+        match &self.cache_directory {
+            Some(cache_directory) => Some(cache_directory.clone()),
+            None => default_cache_directory(),
+        }
+    }
+
+    pub fn load() -> Result<Settings> {
+        match settings_file_path() {
+            Some(path) if path.is_file() => {
+                debug!("Loading settings from {}", path.display());
+                Settings::from_file(&path)
+            }
+            _ => Ok(Settings::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Settings> {
+        let contents = fs::read_to_string(path)?;
+        match Settings::from_toml(&contents) {
+            Ok(settings) => Ok(settings),
+            Err(error) => Err(Box::new(Error::InvalidSettingsFile {
+                path: path.to_owned(),
+                reason: error.to_string(),
+            })),
+        }
+    }
+
+    fn from_toml(contents: &str) -> std::result::Result<Settings, toml::de::Error> {
+        toml::from_str(contents)
+    }
+}
+
+// Only characters with a visible glyph can be opened on, non-printing or unassigned ones are rejected
+fn parse_startup_character(startup_character: &str) -> Option<char> {
+    let mut characters = startup_character.chars();
+    let chr = match (characters.next(), characters.next()) {
+        (Some(chr), None) => chr,
+        _ => parse_code_point(startup_character)?,
+    };
+
+    if is_non_printing(chr) || GeneralCategory::of(chr) == GeneralCategory::Unassigned {
+        return None;
+    }
+    Some(chr)
+}
+
+fn settings_file_path() -> Option<PathBuf> {
+    let config_directory = match env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => PathBuf::from(config_home),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(
+        config_directory
+            .join(SETTINGS_DIRECTORY_NAME)
+            .join(SETTINGS_FILE_NAME),
+    )
+}
+
+fn default_cache_directory() -> Option<PathBuf> {
+    let cache_home = match env::var_os("XDG_CACHE_HOME") {
+        Some(cache_home) if !cache_home.is_empty() => PathBuf::from(cache_home),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+    Some(cache_home.join(SETTINGS_DIRECTORY_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_settings() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(
+            settings.comparison_characters,
+            DEFAULT_COMPARISON_CHARACTERS
+        );
+    }
+
+    #[test]
+    fn test_comparison_characters() {
+        let settings = Settings::from_toml("comparison_characters = \"rnm\"").unwrap();
+        assert_eq!(settings.comparison_characters, "rnm");
+    }
+
+    #[test]
+    fn test_comparison_sizes() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.comparison_sizes, DEFAULT_COMPARISON_SIZES);
+
+        let settings = Settings::from_toml("comparison_sizes = [10, 20]").unwrap();
+        assert_eq!(settings.comparison_sizes, vec![10, 20]);
+    }
+
+    #[test]
+    fn test_sizing() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.sizing(), Sizing::Pixels);
+
+        let settings = Settings::from_toml("preview_point_size = 12.0\ndpi = 144").unwrap();
+        assert_eq!(
+            settings.sizing(),
+            Sizing::Points {
+                size: 12.0,
+                dpi: 144
+            }
+        );
+    }
+
+    #[test]
+    fn test_font_load_timeout() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(
+            settings.font_match_options().load_timeout,
+            Some(Duration::from_millis(DEFAULT_FONT_LOAD_TIMEOUT_MS))
+        );
+
+        let settings = Settings::from_toml("font_load_timeout_ms = 0").unwrap();
+        assert_eq!(settings.font_match_options().load_timeout, None);
+    }
+
+    #[test]
+    fn test_max_matching_fonts() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.font_match_options().max_fonts, None);
+
+        let settings = Settings::from_toml("max_matching_fonts = 20").unwrap();
+        assert_eq!(settings.font_match_options().max_fonts, Some(20));
+    }
+
+    #[test]
+    fn test_preview_renderer() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.preview_renderer, PreviewRenderer::Auto);
+
+        let settings = Settings::from_toml("preview_renderer = \"half-block\"").unwrap();
+        assert_eq!(settings.preview_renderer, PreviewRenderer::HalfBlock);
+
+        assert!(Settings::from_toml("preview_renderer = \"sixel\"").is_err());
+    }
+
+    #[test]
+    fn test_preview_protocol() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.preview_protocol, PreviewProtocol::Auto);
+        assert_eq!(settings.image_cell_size(), (10, 20));
+        let settings = Settings::from_toml("image_cell_height = 24").unwrap();
+        assert_eq!(settings.image_cell_size(), (10, 24));
+
+        let settings = Settings::from_toml("preview_protocol = \"sixel\"").unwrap();
+        assert_eq!(settings.preview_protocol, PreviewProtocol::Sixel);
+
+        let settings = Settings::from_toml("preview_protocol = \"kitty\"").unwrap();
+        assert_eq!(settings.preview_protocol, PreviewProtocol::Kitty);
+
+        let settings = Settings::from_toml("preview_protocol = \"iterm2\"").unwrap();
+        assert_eq!(settings.preview_protocol, PreviewProtocol::Iterm2);
+    }
+
+    #[test]
+    fn test_palette() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.palette, Palette::Default);
+
+        let settings = Settings::from_toml("palette = \"color-blind\"").unwrap();
+        assert_eq!(settings.palette, Palette::ColorBlind);
+
+        assert!(Settings::from_toml("palette = \"neon\"").is_err());
+    }
+
+    #[test]
+    fn test_preview_gradient() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.preview_gradient, None);
+
+        let settings =
+            Settings::from_toml("preview_gradient = { start = \"#203040\", end = \"#FFfF00\" }")
+                .unwrap();
+        assert_eq!(
+            settings.preview_gradient,
+            Some(PreviewGradient {
+                start: RgbColor {
+                    red: 0x20,
+                    green: 0x30,
+                    blue: 0x40
+                },
+                end: RgbColor {
+                    red: 0xff,
+                    green: 0xff,
+                    blue: 0x00
+                },
+            })
+        );
+
+        for color in &["\"203040\"", "\"#2030\"", "\"#20304g\"", "\"#2030é\""] {
+            let toml = format!(
+                "preview_gradient = {{ start = {}, end = \"#ffffff\" }}",
+                color
+            );
+            assert!(Settings::from_toml(&toml).is_err());
+        }
+    }
+
+    #[test]
+    fn test_excluded_fonts() {
+        assert!(Settings::from_toml("")
+            .unwrap()
+            .font_match_options()
+            .excluded_fonts
+            .is_empty());
+        assert_eq!(
+            Settings::from_toml("excluded_fonts = [\"Symbola\", \"/usr/share/fonts/fallback\"]")
+                .unwrap()
+                .font_match_options()
+                .excluded_fonts,
+            vec!["Symbola".to_owned(), "/usr/share/fonts/fallback".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_font_directories() {
+        assert!(Settings::from_toml("").unwrap().font_directories.is_empty());
+        assert_eq!(
+            Settings::from_toml("font_directories = [\"/home/me/fonts/build\"]")
+                .unwrap()
+                .font_directories,
+            vec![PathBuf::from("/home/me/fonts/build")]
+        );
+    }
+
+    #[test]
+    fn test_cache_directory() {
+        if let Some(cache_directory) = Settings::from_toml("").unwrap().cache_directory() {
+            assert!(cache_directory.ends_with("cicero"));
+        }
+        assert_eq!(
+            Settings::from_toml("cache_directory = \"/tmp/cicero\"")
+                .unwrap()
+                .cache_directory(),
+            Some(PathBuf::from("/tmp/cicero"))
+        );
+    }
+
+    #[test]
+    fn test_font_style_filter() {
+        let settings = Settings::from_toml("").unwrap();
+        assert!(!settings.font_match_options().style_filter.is_active());
+
+        let settings =
+            Settings::from_toml("regular_fonts_only = true\nexclude_font_styles = [\"Condensed\"]")
+                .unwrap();
+        let style_filter = settings.font_match_options().style_filter;
+        assert!(style_filter.regular_only);
+        assert!(!style_filter.monospace_only);
+        assert_eq!(style_filter.exclude_styles, vec!["Condensed".to_owned()]);
+    }
+
+    #[test]
+    fn test_background_rendering() {
+        assert!(Settings::from_toml("").unwrap().background_rendering);
+        assert!(
+            !Settings::from_toml("background_rendering = false")
+                .unwrap()
+                .background_rendering
+        );
+    }
+
+    #[test]
+    fn test_antialiasing() {
+        assert!(Settings::from_toml("").unwrap().antialiasing);
+        assert!(
+            !Settings::from_toml("antialiasing = false")
+                .unwrap()
+                .antialiasing
+        );
+    }
+
+    #[test]
+    fn test_invert_shading() {
+        assert!(!Settings::from_toml("").unwrap().invert_shading);
+        assert!(
+            Settings::from_toml("invert_shading = true")
+                .unwrap()
+                .invert_shading
+        );
+    }
+
+    #[test]
+    fn test_startup_character() {
+        let settings = Settings::from_toml("").unwrap();
+        assert_eq!(settings.startup_character().unwrap(), None);
+
+        let settings = Settings::from_toml("startup_character = \"Z\"").unwrap();
+        assert_eq!(settings.startup_character().unwrap(), Some('Z'));
+
+        let settings = Settings::from_toml("startup_character = \"u+1F600\"").unwrap();
+        assert_eq!(settings.startup_character().unwrap(), Some('\u{1F600}'));
+
+        for startup_character in &["AB", "", "U+", "U+110000", "U+0000", " ", "U+0378"] {
+            let toml = format!("startup_character = \"{}\"", startup_character);
+            let settings = Settings::from_toml(&toml).unwrap();
+            assert!(settings.startup_character().is_err());
+        }
+    }
+
+    #[test]
+    fn test_private_use_fonts() {
+        let settings = Settings::from_toml(
+            "[[private_use_fonts]]\n\
+             range = \"U+F000..U+F2E0\"\n\
+             font_path = \"/usr/share/fonts/fontawesome.otf\"",
+        )
+        .unwrap();
+        assert_eq!(
+            settings.private_use_font_of('\u{F015}'),
+            Some(&"/usr/share/fonts/fontawesome.otf".to_owned())
+        );
+        assert_eq!(settings.private_use_font_of('\u{F2E1}'), None);
+        assert_eq!(settings.private_use_font_of('A'), None);
+
+        for range in &["U+0041..U+005A", "U+F2E0..U+F000", "U+F000", "F000..F2E0"] {
+            let contents = format!(
+                "[[private_use_fonts]]\nrange = \"{}\"\nfont_path = \"icons.ttf\"",
+                range
+            );
+            assert!(Settings::from_toml(&contents).is_err());
+        }
+    }
+
+    #[test]
+    fn test_block_fonts() {
+        let settings = Settings::from_toml(
+            "[[block_fonts]]\n\
+             block = \"cjk unified ideographs\"\n\
+             font_family = \"Source Han Sans\"",
+        )
+        .unwrap();
+        assert_eq!(
+            settings.font_match_options().block_font_families,
+            vec![(
+                "CJK Unified Ideographs".to_owned(),
+                "Source Han Sans".to_owned()
+            )]
+        );
+
+        assert!(Settings::from_toml(
+            "[[block_fonts]]\nblock = \"Han\"\nfont_family = \"Source Han Sans\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_script_fonts() {
+        let settings = Settings::from_toml(
+            "[[block_fonts]]\n\
+             block = \"CJK Unified Ideographs\"\n\
+             font_family = \"Noto Sans CJK JP\"\n\
+             [[script_fonts]]\n\
+             script = \"han\"\n\
+             font_family = \"Source Han Sans\"",
+        )
+        .unwrap();
+        assert_eq!(settings.script_fonts[0].script.as_str(), "Han");
+
+        let options = settings.font_match_options();
+        assert_eq!(
+            options.preferred_font_families_of('\u{4E00}'),
+            vec!["Noto Sans CJK JP", "Source Han Sans"]
+        );
+        assert_eq!(
+            options.preferred_font_families_of('\u{20000}'),
+            vec!["Source Han Sans"]
+        );
+        assert!(options.preferred_font_families_of('a').is_empty());
+
+        assert!(Settings::from_toml(
+            "[[script_fonts]]\nscript = \"Klingon\"\nfont_family = \"pIqaD\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        assert!(Settings::from_toml("comparison_characters = 42").is_err());
+    }
+}
@@ -0,0 +1,428 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Action {
+    Back,
+    ShowDetail,
+    SelectPrevious,
+    SelectNext,
+    DeleteInput,
+    PreviousBlock,
+    NextBlock,
+    CopyCharacter,
+    CopyEscape,
+    ShowHelp,
+    OpenCommandPalette,
+
+    // Actions below only apply while the detail is shown
+    PreviousFont,
+    NextFont,
+    ListMatchingFonts,
+    ToggleFontRepertoire,
+    ToggleRegularFontsOnly,
+    ToggleMonospaceFontsOnly,
+    ScrollDetailUp,
+    ScrollDetailDown,
+    FollowLink,
+    ToggleComparisonGrid,
+    ToggleNativeSize,
+    ToggleSizeComparison,
+    ToggleCodePointStrip,
+    ToggleShapedCluster,
+    ToggleShapedString,
+    ToggleTerminalGlyph,
+    ToggleFontDiff,
+    ToggleSideBySide,
+    NextDiffFont,
+    ToggleGlyphBounds,
+    ToggleGuides,
+    ToggleOutline,
+    ToggleCombiningBase,
+    CyclePresentation,
+    NextBitmapStrike,
+    ToggleReferenceGlyph,
+    ToggleDotGrid,
+    ToggleSlideshow,
+    ToggleSyntheticBold,
+    DecreaseBoldStrength,
+    IncreaseBoldStrength,
+    ToggleSyntheticOblique,
+    CycleHinting,
+    NextNamedInstance,
+    PreviousVariationAxis,
+    NextVariationAxis,
+    DecreaseVariationAxis,
+    IncreaseVariationAxis,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+    ExportContactSheet,
+    ExportCoverageMap,
+    ExportGlyphImage,
+    ExportGlyphOutline,
+    OpenFontInEditor,
+}
+
+// Every action, in the order they are listed by the command palette
+pub const ACTIONS: &[Action] = &[
+    Action::ShowDetail,
+    Action::Back,
+    Action::SelectPrevious,
+    Action::SelectNext,
+    Action::DeleteInput,
+    Action::PreviousBlock,
+    Action::NextBlock,
+    Action::CopyCharacter,
+    Action::CopyEscape,
+    Action::ShowHelp,
+    Action::OpenCommandPalette,
+    Action::PreviousFont,
+    Action::NextFont,
+    Action::ListMatchingFonts,
+    Action::ToggleFontRepertoire,
+    Action::ToggleRegularFontsOnly,
+    Action::ToggleMonospaceFontsOnly,
+    Action::ScrollDetailUp,
+    Action::ScrollDetailDown,
+    Action::FollowLink,
+    Action::ToggleComparisonGrid,
+    Action::ToggleNativeSize,
+    Action::ToggleSizeComparison,
+    Action::ToggleCodePointStrip,
+    Action::ToggleShapedCluster,
+    Action::ToggleShapedString,
+    Action::ToggleTerminalGlyph,
+    Action::ToggleFontDiff,
+    Action::ToggleSideBySide,
+    Action::NextDiffFont,
+    Action::ToggleGlyphBounds,
+    Action::ToggleGuides,
+    Action::ToggleOutline,
+    Action::ToggleCombiningBase,
+    Action::CyclePresentation,
+    Action::NextBitmapStrike,
+    Action::ToggleReferenceGlyph,
+    Action::ToggleDotGrid,
+    Action::ToggleSlideshow,
+    Action::ToggleSyntheticBold,
+    Action::DecreaseBoldStrength,
+    Action::IncreaseBoldStrength,
+    Action::ToggleSyntheticOblique,
+    Action::CycleHinting,
+    Action::NextNamedInstance,
+    Action::PreviousVariationAxis,
+    Action::NextVariationAxis,
+    Action::DecreaseVariationAxis,
+    Action::IncreaseVariationAxis,
+    Action::PanLeft,
+    Action::PanRight,
+    Action::PanUp,
+    Action::PanDown,
+    Action::ZoomIn,
+    Action::ZoomOut,
+    Action::ExportContactSheet,
+    Action::ExportCoverageMap,
+    Action::ExportGlyphImage,
+    Action::ExportGlyphOutline,
+    Action::OpenFontInEditor,
+];
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Back => "Hide detail, or quit",
+            Action::ShowDetail => "Show detail of the selected character",
+            Action::SelectPrevious => "Select previous character",
+            Action::SelectNext => "Select next character",
+            Action::DeleteInput => "Delete the last input character",
+            Action::PreviousBlock => "Go to the previous Unicode block",
+            Action::NextBlock => "Go to the next Unicode block",
+            Action::CopyCharacter => "Copy the character",
+            Action::CopyEscape => "Copy the character as an escape",
+            Action::ShowHelp => "Show key bindings",
+            Action::OpenCommandPalette => "Open command palette",
+            Action::PreviousFont => "Preview with the previous font",
+            Action::NextFont => "Preview with the next font",
+            Action::ListMatchingFonts => "List the matching fonts to preview with one of them",
+            Action::ToggleFontRepertoire => "Toggle listing every character of the font",
+            Action::ToggleRegularFontsOnly => "Toggle matching regular fonts only",
+            Action::ToggleMonospaceFontsOnly => "Toggle matching monospaced fonts only",
+            Action::ScrollDetailUp => "Scroll detail up",
+            Action::ScrollDetailDown => "Scroll detail down",
+            Action::FollowLink => "Follow the selected link",
+            Action::ToggleComparisonGrid => "Toggle comparison grid",
+            Action::ToggleNativeSize => "Toggle native size",
+            Action::ToggleSizeComparison => "Toggle comparison across sizes",
+            Action::ToggleCodePointStrip => "Toggle strip of every listed code point",
+            Action::ToggleShapedCluster => "Toggle grapheme cluster shaped as a whole",
+            Action::ToggleShapedString => "Toggle the whole input shaped as a single run",
+            Action::ToggleTerminalGlyph => "Toggle glyph of the terminal font",
+            Action::ToggleFontDiff => "Toggle diff with another font",
+            Action::ToggleSideBySide => "Toggle matching fonts side by side",
+            Action::NextDiffFont => "Diff with the next font",
+            Action::ToggleGlyphBounds => "Toggle glyph bounds",
+            Action::ToggleOutline => "Toggle between the filled glyph and its outline",
+            Action::ToggleCombiningBase => "Toggle the base combining marks are shaped on",
+            Action::CyclePresentation => "Cycle through the text and emoji presentations",
+            Action::NextBitmapStrike => "Select the next strike of a bitmap font",
+            Action::ToggleGuides => {
+                "Toggle baseline, x-height, ascender, descender and em box guides"
+            }
+            Action::ToggleReferenceGlyph => "Toggle reference glyph",
+            Action::ToggleDotGrid => "Toggle braille dot grid",
+            Action::ToggleSlideshow => "Start or stop font slideshow",
+            Action::ToggleSyntheticBold => "Toggle synthetic bold",
+            Action::DecreaseBoldStrength => "Decrease synthetic bold strength",
+            Action::IncreaseBoldStrength => "Increase synthetic bold strength",
+            Action::ToggleSyntheticOblique => "Toggle synthetic italic",
+            Action::CycleHinting => "Cycle through the hinting modes",
+            Action::NextNamedInstance => "Next named instance of a variable font",
+            Action::PreviousVariationAxis => "Select previous axis of a variable font",
+            Action::NextVariationAxis => "Select next axis of a variable font",
+            Action::DecreaseVariationAxis => "Decrease the selected font axis",
+            Action::IncreaseVariationAxis => "Increase the selected font axis",
+            Action::PanLeft => "Pan preview left",
+            Action::PanRight => "Pan preview right",
+            Action::PanUp => "Pan preview up",
+            Action::PanDown => "Pan preview down",
+            Action::ZoomIn => "Zoom into the preview",
+            Action::ZoomOut => "Zoom out of the preview",
+            Action::ExportContactSheet => "Export the character in every font as an image",
+            Action::ExportCoverageMap => "Export the coverage of the block by the font as an image",
+            Action::ExportGlyphImage => "Export the glyph as a PNG image",
+            Action::ExportGlyphOutline => "Export the outline of the glyph as an SVG image",
+            Action::OpenFontInEditor => "Open the font in an external editor",
+        }
+    }
+
+    pub fn requires_detail(self) -> bool {
+        match self {
+            Action::Back
+            | Action::ShowDetail
+            | Action::SelectPrevious
+            | Action::SelectNext
+            | Action::DeleteInput
+            | Action::PreviousBlock
+            | Action::NextBlock
+            | Action::CopyCharacter
+            | Action::CopyEscape
+            | Action::ShowHelp
+            | Action::OpenCommandPalette => false,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub action: Action,
+}
+
+const fn key(code: KeyCode, action: Action) -> KeyBinding {
+    KeyBinding {
+        code,
+        modifiers: KeyModifiers::NONE,
+        action,
+    }
+}
+
+const fn ctrl(code: KeyCode, action: Action) -> KeyBinding {
+    KeyBinding {
+        code,
+        modifiers: KeyModifiers::CONTROL,
+        action,
+    }
+}
+
+const fn shift(code: KeyCode, action: Action) -> KeyBinding {
+    KeyBinding {
+        code,
+        modifiers: KeyModifiers::SHIFT,
+        action,
+    }
+}
+
+const fn alt(code: KeyCode, action: Action) -> KeyBinding {
+    KeyBinding {
+        code,
+        modifiers: KeyModifiers::ALT,
+        action,
+    }
+}
+
+// Every key binding of the application, used for both dispatching key events and listing them
+pub const KEY_BINDINGS: &[KeyBinding] = &[
+    key(KeyCode::Esc, Action::Back),
+    key(KeyCode::Enter, Action::ShowDetail),
+    key(KeyCode::Up, Action::SelectPrevious),
+    key(KeyCode::Down, Action::SelectNext),
+    key(KeyCode::Backspace, Action::DeleteInput),
+    key(KeyCode::PageUp, Action::PreviousBlock),
+    key(KeyCode::PageDown, Action::NextBlock),
+    ctrl(KeyCode::Char('y'), Action::CopyCharacter),
+    ctrl(KeyCode::Char('e'), Action::CopyEscape),
+    key(KeyCode::Char('?'), Action::ShowHelp),
+    ctrl(KeyCode::Char('p'), Action::OpenCommandPalette),
+    key(KeyCode::Left, Action::PreviousFont),
+    key(KeyCode::Right, Action::NextFont),
+    alt(KeyCode::Char('f'), Action::ListMatchingFonts),
+    alt(KeyCode::Char('r'), Action::ToggleFontRepertoire),
+    alt(KeyCode::Char('g'), Action::ToggleRegularFontsOnly),
+    alt(KeyCode::Char('m'), Action::ToggleMonospaceFontsOnly),
+    ctrl(KeyCode::Char('u'), Action::ScrollDetailUp),
+    ctrl(KeyCode::Char('d'), Action::ScrollDetailDown),
+    ctrl(KeyCode::Char('l'), Action::FollowLink),
+    ctrl(KeyCode::Char('k'), Action::ToggleComparisonGrid),
+    ctrl(KeyCode::Char('o'), Action::ToggleNativeSize),
+    ctrl(KeyCode::Char('w'), Action::ToggleSizeComparison),
+    ctrl(KeyCode::Char('v'), Action::ToggleCodePointStrip),
+    key(KeyCode::F(8), Action::ToggleShapedCluster),
+    alt(KeyCode::Char('t'), Action::ToggleShapedString),
+    key(KeyCode::F(2), Action::ToggleTerminalGlyph),
+    key(KeyCode::F(3), Action::ToggleFontDiff),
+    alt(KeyCode::Char('s'), Action::ToggleSideBySide),
+    key(KeyCode::F(4), Action::NextDiffFont),
+    ctrl(KeyCode::Char('b'), Action::ToggleGlyphBounds),
+    key(KeyCode::F(12), Action::ToggleGuides),
+    ctrl(KeyCode::Char('z'), Action::ToggleOutline),
+    alt(KeyCode::Char('b'), Action::ToggleCombiningBase),
+    alt(KeyCode::Char('v'), Action::CyclePresentation),
+    alt(KeyCode::Char('p'), Action::NextBitmapStrike),
+    ctrl(KeyCode::Char('r'), Action::ToggleReferenceGlyph),
+    ctrl(KeyCode::Char('a'), Action::ToggleDotGrid),
+    ctrl(KeyCode::Char('s'), Action::ToggleSlideshow),
+    ctrl(KeyCode::Char('f'), Action::ToggleSyntheticBold),
+    key(KeyCode::F(5), Action::DecreaseBoldStrength),
+    key(KeyCode::F(6), Action::IncreaseBoldStrength),
+    ctrl(KeyCode::Char('t'), Action::ToggleSyntheticOblique),
+    alt(KeyCode::Char('h'), Action::CycleHinting),
+    ctrl(KeyCode::Char('n'), Action::NextNamedInstance),
+    shift(KeyCode::Up, Action::PreviousVariationAxis),
+    shift(KeyCode::Down, Action::NextVariationAxis),
+    shift(KeyCode::Left, Action::DecreaseVariationAxis),
+    shift(KeyCode::Right, Action::IncreaseVariationAxis),
+    ctrl(KeyCode::Left, Action::PanLeft),
+    ctrl(KeyCode::Right, Action::PanRight),
+    ctrl(KeyCode::Up, Action::PanUp),
+    ctrl(KeyCode::Down, Action::PanDown),
+    alt(KeyCode::Char('+'), Action::ZoomIn),
+    alt(KeyCode::Char('-'), Action::ZoomOut),
+    ctrl(KeyCode::Char('x'), Action::ExportContactSheet),
+    key(KeyCode::F(7), Action::ExportCoverageMap),
+    key(KeyCode::F(9), Action::ExportGlyphImage),
+    key(KeyCode::F(10), Action::ExportGlyphOutline),
+    ctrl(KeyCode::Char('g'), Action::OpenFontInEditor),
+];
+
+pub fn key_binding_for(action: Action) -> Option<&'static KeyBinding> {
+    KEY_BINDINGS
+        .iter()
+        .find(|key_binding| key_binding.action == action)
+}
+
+pub fn action_for(event: KeyEvent) -> Option<Action> {
+    KEY_BINDINGS
+        .iter()
+        .find(|key_binding| {
+            key_binding.code == event.code && key_binding.modifiers == event.modifiers
+        })
+        .map(|key_binding| key_binding.action)
+}
+
+impl KeyBinding {
+    pub fn key_description(&self) -> String {
+        let key = match self.code {
+            KeyCode::Esc => "ESC".to_owned(),
+            KeyCode::Enter => "ENTER".to_owned(),
+            KeyCode::Backspace => "BACKSPACE".to_owned(),
+            KeyCode::PageUp => "PGUP".to_owned(),
+            KeyCode::PageDown => "PGDN".to_owned(),
+            KeyCode::Up => "\u{2191}".to_owned(),
+            KeyCode::Down => "\u{2193}".to_owned(),
+            KeyCode::Left => "\u{2190}".to_owned(),
+            KeyCode::Right => "\u{2192}".to_owned(),
+            KeyCode::F(number) => format!("F{}", number),
+            KeyCode::Char(chr) => chr.to_ascii_uppercase().to_string(),
+            code => format!("{:?}", code),
+        };
+
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            format!("[C-{}]", key)
+        } else if self.modifiers.contains(KeyModifiers::SHIFT) {
+            format!("[S-{}]", key)
+        } else if self.modifiers.contains(KeyModifiers::ALT) {
+            format!("[A-{}]", key)
+        } else {
+            format!("[{}]", key)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_for() {
+        let event = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(action_for(event), Some(Action::ToggleComparisonGrid));
+
+        let event = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE);
+        assert_eq!(action_for(event), None);
+    }
+
+    #[test]
+    fn test_key_bindings_are_unique() {
+        for (index, key_binding) in KEY_BINDINGS.iter().enumerate() {
+            assert!(KEY_BINDINGS[index + 1..].iter().all(|other| {
+                other.code != key_binding.code || other.modifiers != key_binding.modifiers
+            }));
+        }
+    }
+
+    #[test]
+    fn test_every_key_binding_is_listed() {
+        assert!(KEY_BINDINGS
+            .iter()
+            .all(|key_binding| ACTIONS.contains(&key_binding.action)));
+    }
+
+    #[test]
+    fn test_key_description() {
+        assert_eq!(
+            ctrl(KeyCode::Char('k'), Action::ToggleComparisonGrid).key_description(),
+            "[C-K]"
+        );
+        assert_eq!(
+            key(KeyCode::Char('?'), Action::ShowHelp).key_description(),
+            "[?]"
+        );
+        assert_eq!(
+            shift(KeyCode::Up, Action::PreviousVariationAxis).key_description(),
+            "[S-\u{2191}]"
+        );
+        assert_eq!(
+            alt(KeyCode::Char('+'), Action::ZoomIn).key_description(),
+            "[A-+]"
+        );
+    }
+}
@@ -12,11 +12,18 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
+use std::rc::Rc;
+
 use tui::layout::{Constraint, Direction, Layout, Rect};
 
 use super::character_preview_canvas::CharacterPreviewCanvas;
 use super::character_property_view::CharacterPropertyView;
+use super::glyph_renderer::GlyphRenderer;
 use super::main_view::TerminalFrame;
+use super::terminal_image::TerminalImage;
+use super::theme::Theme;
+use crate::preview::{FontInfo, PreviewContext, Result};
+use crate::settings::Settings;
 
 pub struct CharacterDetailView {
     character_preview_canvas: CharacterPreviewCanvas,
@@ -24,10 +31,52 @@ pub struct CharacterDetailView {
 }
 
 impl CharacterDetailView {
-    pub fn new(chr: char, preferred_preview_font_path: Option<&String>) -> Self {
+    pub fn new(
+        preview_context: &PreviewContext,
+        chr: char,
+        preferred_preview_font_path: Option<&String>,
+        preferred_preview_face_index: isize,
+        glyph_renderer: Rc<dyn GlyphRenderer>,
+        settings: Rc<Settings>,
+    ) -> Self {
+        let theme = Theme::of(settings.palette);
+        CharacterDetailView {
+            character_preview_canvas: CharacterPreviewCanvas::new(
+                preview_context,
+                chr,
+                preferred_preview_font_path,
+                preferred_preview_face_index,
+                glyph_renderer,
+                settings,
+            ),
+            character_property_view: CharacterPropertyView::new(chr, theme),
+        }
+    }
+
+    // Previews a sequence (e.g. a ZWJ emoji sequence) as a single glyph, along with the properties
+    // of its first code point and the list of its code points
+    pub fn for_sequence(
+        preview_context: &PreviewContext,
+        sequence: &str,
+        preferred_preview_font_path: Option<&String>,
+        preferred_preview_face_index: isize,
+        glyph_renderer: Rc<dyn GlyphRenderer>,
+        settings: Rc<Settings>,
+    ) -> Self {
+        let theme = Theme::of(settings.palette);
+        let chr = sequence.chars().next().unwrap_or_default();
+        let mut character_property_view = CharacterPropertyView::new(chr, theme);
+        character_property_view.set_sequence(sequence.chars().collect());
         CharacterDetailView {
-            character_preview_canvas: CharacterPreviewCanvas::new(chr, preferred_preview_font_path),
-            character_property_view: CharacterPropertyView::new(chr),
+            character_preview_canvas: CharacterPreviewCanvas::for_sequence(
+                preview_context,
+                sequence,
+                preferred_preview_font_path,
+                preferred_preview_face_index,
+                glyph_renderer,
+                settings,
+            ),
+            character_property_view,
         }
     }
 
@@ -38,6 +87,8 @@ impl CharacterDetailView {
             .split(rect);
 
         self.character_preview_canvas.draw(frame, chunks[0]);
+        self.character_property_view
+            .set_glyph_info(self.character_preview_canvas.glyph_info());
         self.character_property_view.draw(frame, chunks[1]);
     }
 
@@ -45,12 +96,192 @@ impl CharacterDetailView {
         self.character_preview_canvas.get_current_preview_font()
     }
 
-    pub fn previous_preview_font(&mut self) {
-        self.character_preview_canvas.previous_preview_font();
+    pub fn get_current_preview_face_index(&self) -> isize {
+        self.character_preview_canvas
+            .get_current_preview_face_index()
+    }
+
+    pub fn sequence(&self) -> Option<&str> {
+        self.character_preview_canvas.sequence()
+    }
+
+    pub fn font_info(&self) -> Option<FontInfo> {
+        self.character_preview_canvas.font_info()
+    }
+
+    pub fn font_repertoire(&self) -> Option<Vec<char>> {
+        self.character_preview_canvas.font_repertoire()
+    }
+
+    pub fn reload_preview_font(&mut self) -> Result<()> {
+        self.character_preview_canvas.reload_preview_font()
+    }
+
+    // Both return true if cycling wrapped around to the other end of the matching fonts
+    pub fn previous_preview_font(&mut self) -> bool {
+        self.character_preview_canvas.previous_preview_font()
+    }
+
+    pub fn next_preview_font(&mut self) -> bool {
+        self.character_preview_canvas.next_preview_font()
+    }
+
+    pub fn matching_fonts(&self) -> (Vec<String>, Option<usize>) {
+        self.character_preview_canvas.matching_fonts()
+    }
+
+    pub fn select_preview_font(&mut self, index: usize) -> Result<()> {
+        self.character_preview_canvas.select_preview_font(index)
+    }
+
+    pub fn toggle_synthetic_bold(&mut self) -> bool {
+        self.character_preview_canvas.toggle_synthetic_bold()
+    }
+
+    pub fn adjust_bold_strength(&mut self, direction: i32) -> bool {
+        self.character_preview_canvas
+            .adjust_bold_strength(direction)
+    }
+
+    pub fn toggle_synthetic_oblique(&mut self) -> bool {
+        self.character_preview_canvas.toggle_synthetic_oblique()
+    }
+
+    pub fn cycle_hinting(&mut self) -> bool {
+        self.character_preview_canvas.cycle_hinting()
+    }
+
+    pub fn next_named_instance(&mut self) {
+        self.character_preview_canvas.next_named_instance();
+    }
+
+    pub fn select_variation_axis(&mut self, steps: isize) {
+        self.character_preview_canvas.select_variation_axis(steps);
+    }
+
+    pub fn adjust_variation_axis(&mut self, steps: isize) {
+        self.character_preview_canvas.adjust_variation_axis(steps);
+    }
+
+    pub fn advance_slideshow(&mut self, loop_around: bool) -> bool {
+        self.character_preview_canvas.advance_slideshow(loop_around)
+    }
+
+    pub fn export_contact_sheet(&mut self) -> Result<String> {
+        self.character_preview_canvas.export_contact_sheet()
+    }
+
+    pub fn export_coverage_map(&self) -> Result<(String, usize, usize)> {
+        self.character_preview_canvas.export_coverage_map()
+    }
+
+    pub fn export_glyph_png(&self) -> Result<String> {
+        self.character_preview_canvas.export_glyph_png()
+    }
+
+    pub fn export_glyph_svg(&self) -> Result<String> {
+        self.character_preview_canvas.export_glyph_svg()
+    }
+
+    pub fn toggle_shaped_string(&mut self, string: String) {
+        self.character_preview_canvas.toggle_shaped_string(string);
+    }
+
+    pub fn toggle_shaped_cluster(&mut self, cluster: Option<String>) {
+        self.character_preview_canvas.toggle_shaped_cluster(cluster);
+    }
+
+    pub fn toggle_side_by_side(&mut self) {
+        self.character_preview_canvas.toggle_side_by_side();
+    }
+
+    pub fn toggle_comparison_grid(&mut self) {
+        self.character_preview_canvas.toggle_comparison_grid();
+    }
+
+    pub fn toggle_native_size(&mut self) {
+        self.character_preview_canvas.toggle_native_size();
+    }
+
+    pub fn toggle_terminal_glyph(&mut self) {
+        self.character_preview_canvas.toggle_terminal_glyph();
+    }
+
+    pub fn toggle_font_diff(&mut self) {
+        self.character_preview_canvas.toggle_font_diff();
+    }
+
+    pub fn select_next_diff_font(&mut self) {
+        self.character_preview_canvas.select_next_diff_font();
+    }
+
+    pub fn toggle_size_comparison(&mut self) {
+        self.character_preview_canvas.toggle_size_comparison();
+    }
+
+    pub fn toggle_code_point_strip(&mut self, characters: Vec<char>) {
+        self.character_preview_canvas
+            .toggle_code_point_strip(characters);
+    }
+
+    pub fn toggle_glyph_bounds(&mut self) {
+        self.character_preview_canvas.toggle_glyph_bounds();
+    }
+
+    pub fn select_next_strike(&mut self) -> bool {
+        self.character_preview_canvas.select_next_strike()
+    }
+
+    pub fn toggle_outline(&mut self) {
+        self.character_preview_canvas.toggle_outline();
+    }
+
+    pub fn toggle_combining_base(&mut self) -> bool {
+        self.character_preview_canvas.toggle_combining_base()
+    }
+
+    pub fn cycle_presentation(&mut self) -> bool {
+        self.character_preview_canvas.cycle_presentation()
+    }
+
+    pub fn presentation_sequence(&self) -> Option<String> {
+        self.character_preview_canvas.presentation_sequence()
+    }
+
+    pub fn toggle_guides(&mut self) {
+        self.character_preview_canvas.toggle_guides();
+    }
+
+    pub fn toggle_dot_grid(&mut self) -> bool {
+        self.character_preview_canvas.toggle_dot_grid()
+    }
+
+    pub fn toggle_reference_glyph(&mut self) {
+        self.character_preview_canvas.toggle_reference_glyph();
+    }
+
+    pub fn take_terminal_images(&mut self) -> Vec<TerminalImage> {
+        self.character_preview_canvas.take_terminal_images()
+    }
+
+    pub fn pan_preview(&mut self, x_steps: isize, y_steps: isize) {
+        self.character_preview_canvas.pan(x_steps, y_steps);
+    }
+
+    pub fn zoom_in_preview(&mut self) {
+        self.character_preview_canvas.zoom_in();
+    }
+
+    pub fn zoom_out_preview(&mut self) {
+        self.character_preview_canvas.zoom_out();
+    }
+
+    pub fn character(&self) -> char {
+        self.character_property_view.character()
     }
 
-    pub fn next_preview_font(&mut self) {
-        self.character_preview_canvas.next_preview_font();
+    pub fn selected_link(&self) -> Option<char> {
+        self.character_property_view.selected_link()
     }
 
     pub fn scroll_down(&mut self) {
@@ -12,51 +12,577 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
-use std::cmp::min;
+use std::cell::RefCell;
+use std::cmp::{max, min};
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
 
+use log::warn;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Style};
 use tui::widgets::canvas::{Canvas, Painter, Shape};
-use tui::widgets::{Block, Borders, Paragraph, Text};
+use tui::widgets::{Block, Borders, Gauge, Paragraph, Text};
+use unic::ucd::Block as UnicodeBlock;
+use unic::ucd::GeneralCategory;
 
+use super::glyph_renderer::GlyphRenderer;
 use super::main_view::TerminalFrame;
-use crate::preview::{CharacterPreview, RenderSize, RenderedCharacter, Result};
+use super::terminal_image::{preview_backend, ImageCanvas, TerminalImage};
+use super::text_canvas::{
+    TextCanvas, TextMarker, TEXT_PIXELS_PER_CELL_HORIZONTAL, TEXT_PIXELS_PER_CELL_VERTICAL,
+};
+use super::theme::Theme;
+use crate::preview::{
+    coverage_counts, diff_glyphs, export_svg, range_coverage, CharacterPreview, DiffPixel,
+    Error as PreviewError, FaceMetrics, FontAttemptOutcome, FontInfo, GlyphDiff, GlyphInfo,
+    Hinting, PreviewContext, RenderOptions, RenderSize, RenderedCharacter, Result, Sizing,
+    VariationAxis,
+};
+#[cfg(feature = "image")]
+use crate::preview::{export_png, render_contact_sheet, render_coverage_map};
+use crate::settings::{PreviewRenderer, Settings};
+use crate::ucd::{
+    code_point_description, has_presentation_variants, non_printing_symbol,
+    EMOJI_PRESENTATION_SELECTOR, TEXT_PRESENTATION_SELECTOR,
+};
 
 const BRAILLE_PATTERN_DOTS_PER_CELL_HORIZONTAL: u16 = 2;
 const BRAILLE_PATTERN_DOTS_PER_CELL_VERTICAL: u16 = 4;
 
 const RENDER_PADDING_IN_CELLS: u16 = 4;
 
+const PAN_STEP_IN_PIXELS: isize = 16;
+
+// Zooming doubles or halves the render size, up to this factor
+const MAX_ZOOM: usize = 8;
+
+// In thousandths of the em size, as the bold strength of the render options
+const BOLD_STRENGTH_STEP: i32 = 5;
+
+const FONT_HINT_WIDTH_IN_CELLS: u16 = 15;
+
+// Wide enough for "100% of Block"
+const BLOCK_COVERAGE_WIDTH_IN_CELLS: u16 = 15;
+
+const GLYPH_METRICS_LINE_COUNT: u16 = 2;
+
+// Cells of the code point strip are wide enough for labels such as "U+10FFFF", glyphs are drawn
+// above their label with spacing around them
+const STRIP_CELL_WIDTH_IN_CELLS: u16 = 10;
+const STRIP_GLYPH_HEIGHT_IN_CELLS: u16 = 4;
+const STRIP_SPACING_IN_CELLS: u16 = 2;
+
+const TOO_SMALL_TO_RENDER_MESSAGE: &str = "Too Small to Render";
+const RENDERING_MESSAGE: &str = "Rendering...";
+
+// Ink coverage is measured at a fixed size so that it can be compared between fonts and panes
+const INK_COVERAGE_RENDER_SIZE: usize = 128;
+
+const DOTTED_CIRCLE: char = '\u{25CC}';
+
+// Bases combining marks are shaped on when the font lacks the one of the settings
+const FALLBACK_COMBINING_BASES: &[char] = &[DOTTED_CIRCLE, '\u{00A0}'];
+
+// Bounds of the number of fonts compared side by side
+const MIN_SIDE_BY_SIDE_FONTS: usize = 2;
+const MAX_SIDE_BY_SIDE_FONTS: usize = 4;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum PreviewMode {
+    Glyph,
+    ComparisonGrid,
+    NativeSize,
+    SizeComparison,
+    CodePointStrip,
+    TerminalGlyph,
+    FontDiff,
+    ShapedCluster,
+    ShapedString,
+    SideBySide,
+}
+
+// The render of the character with the font it is compared with, kept between draws along with
+// what it was rendered for, since loading another font for every draw would be slow
+struct DiffFontRender {
+    font_index: usize,
+    size: RenderSize,
+    render_options: RenderOptions,
+    rendered_character: Option<RenderedCharacter>,
+}
+
+// Renders of the fonts compared side by side, kept between draws like the render of the diff font
+struct SideBySideRenders {
+    font_paths: Vec<String>,
+    size: RenderSize,
+    render_options: RenderOptions,
+    rendered_characters: Vec<Option<RenderedCharacter>>,
+}
+
+// Text which failed to shape with the current font, its error is shown without shaping it again
+// (and logging the error again) until the font, the size or the render options change. Renders of
+// text which did shape are kept in the render cache.
+#[cfg(feature = "shaping")]
+struct ShapingFailure {
+    text: String,
+    font_path: Option<String>,
+    face_index: usize,
+    size: RenderSize,
+    render_options: RenderOptions,
+    message: String,
+}
+
+// How much of the block of the character the current font covers, kept between draws since a block
+// can span thousands of code points
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct BlockCoverage {
+    font_path: Option<String>,
+    face_index: usize,
+    covered_count: usize,
+    assigned_count: usize,
+}
+
+impl BlockCoverage {
+    fn ratio(&self) -> f64 {
+        if self.assigned_count == 0 {
+            return 0.0;
+        }
+        (self.covered_count as f64 / self.assigned_count as f64).min(1.0)
+    }
+
+    fn label(&self) -> String {
+        format!("{:.0}% of Block", self.ratio() * 100.0)
+    }
+}
+
 pub struct CharacterPreviewCanvas {
+    chr: char,
     character_preview: Result<CharacterPreview>,
+    preview_mode: PreviewMode,
+    pan_offset: (usize, usize),
+    panned_glyph_size: Option<RenderSize>,
+    zoom: usize,
+    recenter_pan: bool,
+    show_glyph_bounds: bool,
+    show_reference_glyph: bool,
+    show_dot_grid: bool,
+    show_guides: bool,
+    show_outline: bool,
+    attach_combining_base: bool,
+    presentation_selector: Option<char>,
+
+    // Matching fonts with a glyph for the variation sequence of the presentation selector
+    presentation_fonts: Vec<String>,
+    diff_font_index: usize,
+    diff_font_render: Option<DiffFontRender>,
+    side_by_side_renders: Option<SideBySideRenders>,
+    #[cfg(feature = "shaping")]
+    shaping_failure: Option<ShapingFailure>,
+
+    // Font, face and character of the last render failure logged, not to log it on every draw
+    logged_render_failure: Option<(Option<String>, usize, char)>,
+    block_coverage: Option<BlockCoverage>,
+    selected_variation_axis: usize,
+    strip_characters: Vec<char>,
+    shaped_cluster: Option<String>,
+    shaped_string: Option<String>,
+    glyph_renderer: Rc<dyn GlyphRenderer>,
+    terminal_images: RefCell<Vec<TerminalImage>>,
+    settings: Rc<Settings>,
 }
 
 impl CharacterPreviewCanvas {
-    pub fn new(chr: char, preferred_font_path: Option<&String>) -> Self {
+    // The face index applies to the preferred font path, e.g. a face of a font collection
+    pub fn new(
+        preview_context: &PreviewContext,
+        chr: char,
+        preferred_font_path: Option<&String>,
+        preferred_face_index: isize,
+        glyph_renderer: Rc<dyn GlyphRenderer>,
+        settings: Rc<Settings>,
+    ) -> Self {
+        let (preferred_font_path, face_index) =
+            preferred_face_of(&settings, chr, preferred_font_path, preferred_face_index);
+        let character_preview = CharacterPreview::with_face_index(
+            preview_context,
+            chr,
+            preferred_font_path,
+            face_index,
+            &settings.font_match_options(),
+        );
+        CharacterPreviewCanvas::with_preview(chr, character_preview, glyph_renderer, settings)
+    }
+
+    // Previews a sequence (e.g. a ZWJ emoji sequence) composed into a single glyph, the fonts
+    // being those of its first character
+    pub fn for_sequence(
+        preview_context: &PreviewContext,
+        sequence: &str,
+        preferred_font_path: Option<&String>,
+        preferred_face_index: isize,
+        glyph_renderer: Rc<dyn GlyphRenderer>,
+        settings: Rc<Settings>,
+    ) -> Self {
+        let chr = sequence.chars().next().unwrap_or_default();
+        let (preferred_font_path, face_index) =
+            preferred_face_of(&settings, chr, preferred_font_path, preferred_face_index);
+        let character_preview = CharacterPreview::for_sequence(
+            preview_context,
+            sequence,
+            preferred_font_path,
+            face_index,
+            &settings.font_match_options(),
+        );
+        CharacterPreviewCanvas::with_preview(chr, character_preview, glyph_renderer, settings)
+    }
+
+    fn with_preview(
+        chr: char,
+        mut character_preview: Result<CharacterPreview>,
+        glyph_renderer: Rc<dyn GlyphRenderer>,
+        settings: Rc<Settings>,
+    ) -> Self {
+        if let Ok(character_preview) = &mut character_preview {
+            character_preview.set_sizing(settings.sizing());
+            character_preview.set_font_cycling_wraps(settings.wrap_font_cycling);
+            character_preview.set_render_options(
+                RenderOptions::default().with_bold_strength(settings.synthetic_bold_strength),
+            );
+        }
+
         CharacterPreviewCanvas {
-            character_preview: CharacterPreview::new(chr, preferred_font_path),
+            chr,
+            character_preview,
+            preview_mode: PreviewMode::Glyph,
+            pan_offset: (0, 0),
+            panned_glyph_size: None,
+            zoom: 1,
+            recenter_pan: false,
+            show_glyph_bounds: false,
+            show_reference_glyph: false,
+            show_dot_grid: false,
+            show_guides: false,
+            show_outline: false,
+            attach_combining_base: settings.attach_combining_marks,
+            presentation_selector: None,
+            presentation_fonts: vec![],
+            diff_font_index: 0,
+            diff_font_render: None,
+            side_by_side_renders: None,
+            #[cfg(feature = "shaping")]
+            shaping_failure: None,
+            logged_render_failure: None,
+            block_coverage: None,
+            selected_variation_axis: 0,
+            strip_characters: vec![],
+            shaped_cluster: None,
+            shaped_string: None,
+            glyph_renderer,
+            terminal_images: RefCell::new(vec![]),
+            settings,
         }
     }
 
     pub fn draw(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        self.terminal_images.borrow_mut().clear();
+        if let Ok(character_preview) = &self.character_preview {
+            character_preview.clear_render_timing();
+        }
+
+        // Lines of additional information below the font selection, shown only when relevant
+        let info_lines: Vec<String> = vec![
+            self.font_info_description(),
+            self.sizing_description(),
+            self.ink_coverage_description(),
+            self.advance_description(),
+            self.synthetic_style_description(),
+            self.hinting_description(),
+            self.strike_description(),
+            self.combining_base_description(),
+            self.presentation_description(),
+            self.sequence_description(),
+            self.variation_description(),
+            self.skipped_fonts_description(),
+            self.omitted_fonts_description(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        // The timings are those of the render drawn in this frame, their line is filled in last
+        let mut info_line_count = info_lines.len();
+        if self.shows_render_timings() {
+            info_line_count += 1;
+        }
+
+        let mut constraints = vec![
+            Constraint::Min(RENDER_PADDING_IN_CELLS),
+            Constraint::Length(1),
+        ];
+        constraints.extend((0..info_line_count).map(|_| Constraint::Length(1)));
         let chunks = Layout::default()
             .vertical_margin(1)
             .horizontal_margin(1)
-            .constraints(
-                [
-                    Constraint::Min(RENDER_PADDING_IN_CELLS),
-                    Constraint::Length(1),
-                ]
-                .as_ref(),
-            )
+            .constraints(constraints)
             .direction(Direction::Vertical)
             .split(rect);
 
-        self.draw_character_preview(frame, chunks[0]);
+        match self.preview_mode {
+            PreviewMode::Glyph => self.draw_character_preview(frame, chunks[0]),
+            PreviewMode::ComparisonGrid => self.draw_comparison_grid(frame, chunks[0]),
+            PreviewMode::NativeSize => self.draw_native_size(frame, chunks[0]),
+            PreviewMode::SizeComparison => self.draw_size_comparison(frame, chunks[0]),
+            PreviewMode::CodePointStrip => self.draw_code_point_strip(frame, chunks[0]),
+            PreviewMode::TerminalGlyph => {
+                draw_terminal_glyph(frame, chunks[0], self.chr, self.theme().muted)
+            }
+            PreviewMode::FontDiff => self.draw_font_diff(frame, chunks[0]),
+            PreviewMode::ShapedCluster => self.draw_shaped_cluster(frame, chunks[0]),
+            PreviewMode::ShapedString => self.draw_shaped_string(frame, chunks[0]),
+            PreviewMode::SideBySide => self.draw_side_by_side(frame, chunks[0]),
+        }
         self.draw_font_selection(frame, chunks[1]);
+        let info_lines = info_lines.into_iter().chain(self.timings_description());
+        for (info_line, chunk) in info_lines.zip(&chunks[2..]) {
+            let info_item = [Text::raw(info_line)];
+            let info_text = Paragraph::new(info_item.iter())
+                .style(Style::default().fg(self.theme().muted))
+                .alignment(Alignment::Center);
+            frame.render_widget(info_text, *chunk);
+        }
         self.draw_borders(frame, rect);
     }
 
+    // Fonts providing the requested presentation are those mapping its variation sequence to a
+    // glyph, other fonts render the character as they would alone
+    fn presentation_description(&self) -> Option<String> {
+        let presentation = match self.presentation_selector? {
+            TEXT_PRESENTATION_SELECTOR => "Text presentation (VS15)",
+            _ => "Emoji presentation (VS16)",
+        };
+        if self.presentation_fonts.is_empty() {
+            return Some(format!("{} | Provided by no matching font", presentation));
+        }
+        Some(format!(
+            "{} | Provided by {}",
+            presentation,
+            self.presentation_fonts.join(", ")
+        ))
+    }
+
+    fn sequence_description(&self) -> Option<String> {
+        self.sequence().map(|sequence| {
+            format!(
+                "Sequence of {} code points, shaped as a whole",
+                sequence.chars().count()
+            )
+        })
+    }
+
+    fn combining_base_description(&self) -> Option<String> {
+        self.combining_base().map(|combining_base| {
+            format!(
+                "Shaped on {} ({})",
+                combining_base,
+                code_point_description(combining_base)
+            )
+        })
+    }
+
+    // Glyphs sized in points depend on the DPI of the display they are meant for
+    fn sizing_description(&self) -> Option<String> {
+        let zoom_description = format!("Zoom: {}x", self.zoom);
+        match self.settings.sizing() {
+            Sizing::Points { size, dpi } if self.zoom > 1 => {
+                Some(format!("{} pt at {} DPI | {}", size, dpi, zoom_description))
+            }
+            Sizing::Points { size, dpi } => Some(format!("{} pt at {} DPI", size, dpi)),
+            Sizing::Pixels if self.zoom > 1 => Some(zoom_description),
+            Sizing::Pixels => None,
+        }
+    }
+
+    fn ink_coverage_description(&self) -> Option<String> {
+        let character_preview = self.character_preview.as_ref().ok()?;
+        let ink_coverage = character_preview.ink_coverage_in_background(RenderSize::new(
+            INK_COVERAGE_RENDER_SIZE,
+            INK_COVERAGE_RENDER_SIZE,
+        ))?;
+        Some(format!("Ink Coverage: {:.1}%", ink_coverage * 100.0))
+    }
+
+    fn omitted_fonts_description(&self) -> Option<String> {
+        let character_preview = self.character_preview.as_ref().ok()?;
+        match character_preview.omitted_font_count() {
+            0 => None,
+            omitted_font_count => Some(format!(
+                "{} more matching fonts not listed (max_matching_fonts)",
+                omitted_font_count
+            )),
+        }
+    }
+
+    fn shows_render_timings(&self) -> bool {
+        self.settings.show_render_timings && self.character_preview.is_ok()
+    }
+
+    fn timings_description(&self) -> Option<String> {
+        if !self.shows_render_timings() {
+            return None;
+        }
+        let character_preview = self.character_preview.as_ref().ok()?;
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let render_timing = match character_preview.last_render_timing() {
+            Some(render_timing) if render_timing.cached => {
+                format!("{:.2} ms (cached)", milliseconds(render_timing.duration))
+            }
+            Some(render_timing) => format!("{:.2} ms", milliseconds(render_timing.duration)),
+            None => "-".to_owned(),
+        };
+        Some(format!(
+            "Render: {} | Font Matching: {:.2} ms",
+            render_timing,
+            milliseconds(character_preview.font_matching_duration())
+        ))
+    }
+
+    // Which file on disk is previewed, along with its format and what it contains, e.g.
+    // "/usr/share/fonts/noto/NotoSans-Regular.ttf | TrueType 2.007 | 3741 glyphs"
+    fn font_info_description(&self) -> Option<String> {
+        Some(font_info_label(&self.font_info()?))
+    }
+
+    fn hinting_description(&self) -> Option<String> {
+        let character_preview = self.character_preview.as_ref().ok()?;
+        let hinting = character_preview.render_options().hinting;
+        if !character_preview.is_outline_font() || hinting == Hinting::Default {
+            return None;
+        }
+        Some(hinting.description().to_owned())
+    }
+
+    fn synthetic_style_description(&self) -> Option<String> {
+        let character_preview = self.character_preview.as_ref().ok()?;
+        if !character_preview.is_outline_font() {
+            return None;
+        }
+
+        let render_options = character_preview.render_options();
+        // The strength is shown as a percentage of the em size
+        let bold_strength = render_options.bold_strength as f64 / 10.0;
+        match render_options {
+            RenderOptions {
+                synthetic_bold: true,
+                synthetic_oblique: true,
+                ..
+            } => Some(format!("Synthetic Bold ({:.1}%) Italic", bold_strength)),
+            RenderOptions {
+                synthetic_bold: true,
+                ..
+            } => Some(format!("Synthetic Bold ({:.1}%)", bold_strength)),
+            RenderOptions {
+                synthetic_oblique: true,
+                ..
+            } => Some("Synthetic Italic".to_owned()),
+            _ => None,
+        }
+    }
+
+    fn variation_description(&self) -> Option<String> {
+        let character_preview = self.character_preview.as_ref().ok()?;
+        let variation_axes = character_preview.variation_axes();
+        if variation_axes.is_empty() {
+            return None;
+        }
+
+        let named_instances = character_preview.named_instances();
+        let instance_description = match character_preview.current_named_instance() {
+            Some(instance) => format!(
+                "{} ({}/{})",
+                named_instances[instance],
+                instance + 1,
+                named_instances.len()
+            ),
+            None if variation_axes.iter().any(|axis| axis.value != axis.default) => {
+                "Custom".to_owned()
+            }
+            None => "Default".to_owned(),
+        };
+        // The values of the axes are shown along with the font name, only the adjusted one is here
+        let variation_axis = &variation_axes[self.selected_variation_axis % variation_axes.len()];
+        Some(format!(
+            "Instance: {} | Axis: {} ({} to {})",
+            instance_description,
+            variation_axis.name,
+            variation_axis.minimum,
+            variation_axis.maximum
+        ))
+    }
+
+    // Bitmap fonts are rendered at one of their strikes, the closest one unless one is selected
+    fn strike_description(&self) -> Option<String> {
+        let character_preview = self.character_preview.as_ref().ok()?;
+        let strike_sizes = character_preview.strike_sizes();
+        if strike_sizes.is_empty() {
+            return None;
+        }
+        let strike_list = strike_sizes
+            .iter()
+            .map(|strike_size| strike_size.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        Some(match character_preview.selected_strike() {
+            Some(strike_index) => format!(
+                "Bitmap Strike: {} px of {} px",
+                strike_sizes[strike_index], strike_list
+            ),
+            None => format!("Bitmap Strike: Closest of {} px", strike_list),
+        })
+    }
+
+    // Whitespace has no ink to look at, its advance is what sets it apart
+    fn advance_description(&self) -> Option<String> {
+        if !self.chr.is_whitespace() {
+            return None;
+        }
+        let glyph_metrics = self
+            .character_preview
+            .as_ref()
+            .ok()?
+            .cached_glyph_metrics()?;
+        Some(format!(
+            "Advance Width: {} of {} units per em",
+            glyph_metrics.advance_width, glyph_metrics.units_per_em
+        ))
+    }
+
+    fn skipped_fonts_description(&self) -> Option<String> {
+        let character_preview = self.character_preview.as_ref().ok()?;
+        let skipped_fonts: Vec<String> = character_preview
+            .font_attempts()
+            .iter()
+            .filter_map(|font_attempt| {
+                let reason = match &font_attempt.outcome {
+                    FontAttemptOutcome::Selected => return None,
+                    FontAttemptOutcome::GlyphNotFound => "no glyph".to_owned(),
+                    FontAttemptOutcome::LoadFailed(error) => format!("load failed: {}", error),
+                    FontAttemptOutcome::TimedOut => "timed out".to_owned(),
+                };
+                let font_file_name = Path::new(&font_attempt.font_path)
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| font_attempt.font_path.clone());
+                Some(format!("{} ({})", font_file_name, reason))
+            })
+            .collect();
+
+        if skipped_fonts.is_empty() {
+            None
+        } else {
+            Some(format!("Skipped: {}", skipped_fonts.join(", ")))
+        }
+    }
+
     pub fn get_current_preview_font(&self) -> Option<String> {
         match &self.character_preview {
             Ok(character_preview) => character_preview.get_current_font_path(),
@@ -64,174 +590,2828 @@ impl CharacterPreviewCanvas {
         }
     }
 
-    pub fn previous_preview_font(&mut self) {
+    pub fn get_current_preview_face_index(&self) -> isize {
+        match &self.character_preview {
+            Ok(character_preview) => character_preview.current_face_index() as isize,
+            Err(_) => 0,
+        }
+    }
+
+    pub fn font_info(&self) -> Option<FontInfo> {
+        Some(self.character_preview.as_ref().ok()?.font_info())
+    }
+
+    pub fn font_repertoire(&self) -> Option<Vec<char>> {
+        Some(self.character_preview.as_ref().ok()?.font_repertoire())
+    }
+
+    pub fn glyph_info(&self) -> Option<GlyphInfo> {
+        match &self.character_preview {
+            Ok(character_preview) => Some(character_preview.glyph_info()),
+            Err(_) => None,
+        }
+    }
+
+    pub fn reload_preview_font(&mut self) -> Result<()> {
+        self.side_by_side_renders = None;
+        #[cfg(feature = "shaping")]
+        {
+            self.shaping_failure = None;
+        }
+        self.block_coverage = None;
+        match &mut self.character_preview {
+            Ok(character_preview) => character_preview.reload_current_font(),
+            Err(_) => Ok(()),
+        }
+    }
+
+    // Returns true if cycling wrapped around from the first font to the last one
+    pub fn previous_preview_font(&mut self) -> bool {
         match &mut self.character_preview {
             Ok(character_preview) => {
+                let previous_index = character_preview.current_font_index();
                 let _ = character_preview.select_previous_font();
+                previous_index.is_some() && character_preview.current_font_index() > previous_index
             }
-            Err(_) => {
-                // Do nothing
-            }
+            Err(_) => false,
         }
     }
 
-    pub fn next_preview_font(&mut self) {
+    // Returns true if cycling wrapped around from the last font to the first one
+    pub fn next_preview_font(&mut self) -> bool {
         match &mut self.character_preview {
             Ok(character_preview) => {
+                let previous_index = character_preview.current_font_index();
                 let _ = character_preview.select_next_font();
+                previous_index.is_some() && character_preview.current_font_index() < previous_index
             }
-            Err(_) => {
-                // Do nothing
-            }
+            Err(_) => false,
         }
     }
 
-    fn draw_character_preview(&mut self, frame: &mut TerminalFrame, rect: Rect) {
-        if rect.width < RENDER_PADDING_IN_CELLS || rect.height < RENDER_PADDING_IN_CELLS {
-            return;
+    // Display names of the matching fonts along with the index of the current one
+    pub fn matching_fonts(&self) -> (Vec<String>, Option<usize>) {
+        match &self.character_preview {
+            Ok(character_preview) => (
+                character_preview.matching_font_display_names().to_vec(),
+                character_preview.current_font_index(),
+            ),
+            Err(_) => (vec![], None),
         }
+    }
 
-        let canvas = Canvas::default().paint(|ctx| {
-            let canvas_pixel_width =
-                (rect.width - RENDER_PADDING_IN_CELLS) * BRAILLE_PATTERN_DOTS_PER_CELL_HORIZONTAL;
-            let canvas_pixel_height =
-                (rect.height - RENDER_PADDING_IN_CELLS) * BRAILLE_PATTERN_DOTS_PER_CELL_VERTICAL;
-            let canvas_pixel_size =
-                RenderSize::new(canvas_pixel_width as usize, canvas_pixel_height as usize);
+    pub fn select_preview_font(&mut self, index: usize) -> Result<()> {
+        match &mut self.character_preview {
+            Ok(character_preview) => character_preview.select_font(index),
+            Err(_) => Ok(()),
+        }
+    }
 
-            let render_pixel_size = {
-                let render_pixel_length = min(canvas_pixel_width, canvas_pixel_height);
-                RenderSize::new(render_pixel_length as usize, render_pixel_length as usize)
-            };
+    // Synthetic styles need an outline font, returns false if the current font isn't one
+    pub fn toggle_synthetic_bold(&mut self) -> bool {
+        self.update_render_options(|render_options| {
+            render_options.synthetic_bold = !render_options.synthetic_bold
+        })
+    }
 
-            match &self.character_preview {
-                Ok(character_preview) => match character_preview.render(render_pixel_size) {
-                    Ok(rendered_character) => {
-                        let glyph_size = rendered_character.glyph_size;
-                        let x_padding = (canvas_pixel_size.width - glyph_size.width) / 2;
-                        let y_padding = (canvas_pixel_size.height - glyph_size.height) / 2;
-                        ctx.draw(&CharacterPreviewShape {
-                            rendered_character: &rendered_character,
-                            x_padding,
-                            y_padding,
-                        })
-                    }
-                    Err(_) => {
-                        let x_padding = (canvas_pixel_size.width - render_pixel_size.width) / 2;
-                        let y_padding = (canvas_pixel_size.height - render_pixel_size.height) / 2;
-                        ctx.draw(&ToufuShape {
-                            size: render_pixel_size,
-                            x_padding,
-                            y_padding,
-                        })
-                    }
-                },
-                Err(_) => {
-                    let x_padding = (canvas_pixel_size.width - render_pixel_size.width) / 2;
-                    let y_padding = (canvas_pixel_size.height - render_pixel_size.height) / 2;
-                    ctx.draw(&ToufuShape {
-                        size: render_pixel_size,
-                        x_padding,
-                        y_padding,
-                    })
-                }
-            };
-        });
+    // Adjusting the strength turns the synthetic bold on, so that the change can be seen
+    pub fn adjust_bold_strength(&mut self, direction: i32) -> bool {
+        self.update_render_options(|render_options| {
+            let bold_strength = max(
+                render_options.bold_strength as i32 + direction * BOLD_STRENGTH_STEP,
+                0,
+            );
+            *render_options = render_options.with_bold_strength(bold_strength as u32);
+            render_options.synthetic_bold = true;
+        })
+    }
 
-        frame.render_widget(canvas, rect);
+    pub fn toggle_synthetic_oblique(&mut self) -> bool {
+        self.update_render_options(|render_options| {
+            render_options.synthetic_oblique = !render_options.synthetic_oblique
+        })
     }
 
-    fn draw_font_selection(&mut self, frame: &mut TerminalFrame, rect: Rect) {
-        let chunks = Layout::default()
-            .horizontal_margin(1)
-            .constraints(
-                [
-                    Constraint::Length(15),
-                    Constraint::Min(1),
-                    Constraint::Length(15),
-                ]
-                .as_ref(),
-            )
-            .direction(Direction::Horizontal)
-            .split(rect);
+    // Cycles through the hinting modes of FreeType, to compare how they render small sizes
+    pub fn cycle_hinting(&mut self) -> bool {
+        self.update_render_options(|render_options| {
+            render_options.hinting = render_options.hinting.next()
+        })
+    }
 
-        match &self.character_preview {
-            Ok(character_preview) => {
-                if character_preview.has_previous_font() {
-                    let help_item = [Text::raw("[\u{2190}]: Prev. Font")];
-                    let help_text = Paragraph::new(help_item.iter())
-                        .style(Style::default().fg(Color::LightGreen))
-                        .alignment(Alignment::Left);
-                    frame.render_widget(help_text, chunks[0]);
-                }
-                {
-                    let font_name = character_preview.get_current_font_display_name();
-                    let help_item = [Text::raw(font_name)];
-                    let help_text = Paragraph::new(help_item.iter())
-                        .style(Style::default())
-                        .alignment(Alignment::Center);
-                    frame.render_widget(help_text, chunks[1]);
-                }
-                if character_preview.has_next_font() {
-                    let help_item = [Text::raw("[\u{2192}]: Next Font")];
-                    let help_text = Paragraph::new(help_item.iter())
-                        .style(Style::default().fg(Color::LightGreen))
-                        .alignment(Alignment::Right);
-                    frame.render_widget(help_text, chunks[2]);
-                }
+    fn update_render_options(&mut self, update: impl FnOnce(&mut RenderOptions)) -> bool {
+        let character_preview = match &mut self.character_preview {
+            Ok(character_preview) if character_preview.is_outline_font() => character_preview,
+            _ => return false,
+        };
+
+        let mut render_options = character_preview.render_options();
+        update(&mut render_options);
+        character_preview.set_render_options(render_options);
+        true
+    }
+
+    // Cycles through the default instance and the named instances of a variable font
+    pub fn next_named_instance(&mut self) {
+        if let Ok(character_preview) = &mut self.character_preview {
+            let instance_count = character_preview.named_instances().len();
+            if instance_count == 0 {
+                return;
             }
-            Err(_) => {
-                let help_item = [Text::raw("Preview Not Available")];
-                let help_text = Paragraph::new(help_item.iter())
-                    .style(Style::default().fg(Color::LightGreen))
-                    .alignment(Alignment::Center);
-                frame.render_widget(help_text, chunks[1]);
+
+            let next_instance = match character_preview.current_named_instance() {
+                Some(instance) if instance + 1 < instance_count => Some(instance + 1),
+                Some(_) => None,
+                None => Some(0),
+            };
+            let _ = character_preview.select_named_instance(next_instance);
+        }
+    }
+
+    // Selects the design axis of a variable font the given number of axes away, wrapping around
+    pub fn select_variation_axis(&mut self, steps: isize) {
+        let axis_count = match &self.character_preview {
+            Ok(character_preview) => character_preview.variation_axes().len() as isize,
+            Err(_) => return,
+        };
+        if axis_count == 0 {
+            return;
+        }
+        let axis = self.selected_variation_axis as isize % axis_count + steps;
+        self.selected_variation_axis = axis.rem_euclid(axis_count) as usize;
+    }
+
+    // Changes the value of the selected design axis of a variable font, in steps of a twentieth of
+    // the range of the axis
+    pub fn adjust_variation_axis(&mut self, steps: isize) {
+        if let Ok(character_preview) = &mut self.character_preview {
+            let variation_axes = character_preview.variation_axes();
+            if variation_axes.is_empty() {
+                return;
             }
+
+            let axis = self.selected_variation_axis % variation_axes.len();
+            let variation_axis = &variation_axes[axis];
+            let step = (variation_axis.maximum - variation_axis.minimum) / 20.0;
+            let _ = character_preview
+                .set_variation_axis_value(axis, variation_axis.value + step * steps as f64);
         }
     }
 
-    fn draw_borders(&mut self, frame: &mut TerminalFrame, rect: Rect) {
-        let block = Block::default().title("Preview").borders(Borders::ALL);
-        frame.render_widget(block, rect);
+    // Moves to the next font for the slideshow, returns false once there is no font left
+    pub fn advance_slideshow(&mut self, loop_around: bool) -> bool {
+        let character_preview = match &mut self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return false,
+        };
+
+        if !character_preview.is_at_last_font() {
+            character_preview.select_next_font().is_ok()
+        } else if loop_around {
+            character_preview.select_first_font().is_ok()
+        } else {
+            false
+        }
     }
-}
 
-struct CharacterPreviewShape<'a> {
-    rendered_character: &'a RenderedCharacter,
-    x_padding: usize,
-    y_padding: usize,
-}
+    // Saves the character in every matching font as a contact sheet in the current directory, and
+    // returns the path of the image
+    #[cfg(feature = "image")]
+    pub fn export_contact_sheet(&mut self) -> Result<String> {
+        let character_preview = match &mut self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(error) => return Err(error.to_string().into()),
+        };
 
-impl Shape for CharacterPreviewShape<'_> {
-    fn draw(&self, painter: &mut Painter) {
-        for (y, row) in self.rendered_character.bitmap.iter().enumerate() {
-            for (x, pixel) in row.iter().enumerate() {
-                if *pixel == 0u8 {
-                    continue;
-                }
+        let contact_sheet =
+            render_contact_sheet(character_preview, &self.settings.contact_sheet_options())?;
+        let path = format!(
+            "cicero-contact-sheet-U+{:04X}.png",
+            character_preview.chr as u32
+        );
+        contact_sheet.save(&path)?;
+        Ok(path)
+    }
 
-                painter.paint(
-                    x + self.x_padding as usize,
-                    y + self.y_padding as usize,
-                    Color::Reset,
-                )
-            }
+    #[cfg(not(feature = "image"))]
+    pub fn export_contact_sheet(&mut self) -> Result<String> {
+        Err("Cicero was built without the image feature".into())
+    }
+
+    // Saves which code points of the block of the character the current font covers as an image in
+    // the current directory, and returns the path of the image with the number of covered and
+    // assigned code points
+    #[cfg(feature = "image")]
+    pub fn export_coverage_map(&self) -> Result<(String, usize, usize)> {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(error) => return Err(error.to_string().into()),
+        };
+
+        let chr = character_preview.chr;
+        let (range, name) = match UnicodeBlock::of(chr) {
+            Some(block) => (
+                block.range.low..=block.range.high,
+                block.name.replace(' ', "-"),
+            ),
+            None => return Err(format!("U+{:04X} is not in a Unicode block", chr as u32).into()),
+        };
+
+        let coverage = range_coverage(character_preview, range);
+        let (covered_count, assigned_count) = coverage_counts(&coverage);
+
+        let coverage_map = render_coverage_map(&coverage, &self.settings.coverage_map_options());
+        let path = format!(
+            "cicero-coverage-{}-{}.png",
+            name,
+            export_font_name(character_preview)
+        );
+        coverage_map.save(&path)?;
+        Ok((path, covered_count, assigned_count))
+    }
+
+    #[cfg(not(feature = "image"))]
+    pub fn export_coverage_map(&self) -> Result<(String, usize, usize)> {
+        Err("Cicero was built without the image feature".into())
+    }
+
+    // Saves the glyph of the character in the current font as an image in the current directory,
+    // and returns the path of the image
+    #[cfg(feature = "image")]
+    pub fn export_glyph_png(&self) -> Result<String> {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(error) => return Err(error.to_string().into()),
+        };
+
+        let path = format!(
+            "cicero-glyph-U+{:04X}-{}.png",
+            character_preview.chr as u32,
+            export_font_name(character_preview)
+        );
+        export_png(
+            character_preview,
+            self.settings.export_pixel_size,
+            Path::new(&path),
+        )?;
+        Ok(path)
+    }
+
+    #[cfg(not(feature = "image"))]
+    pub fn export_glyph_png(&self) -> Result<String> {
+        Err("Cicero was built without the image feature".into())
+    }
+
+    // Saves the outline of the glyph in the current font as an SVG document in the current
+    // directory, and returns the path of the document
+    pub fn export_glyph_svg(&self) -> Result<String> {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(error) => return Err(error.to_string().into()),
+        };
+
+        let path = format!(
+            "cicero-glyph-U+{:04X}-{}.svg",
+            character_preview.chr as u32,
+            export_font_name(character_preview)
+        );
+        export_svg(character_preview, Path::new(&path))?;
+        Ok(path)
+    }
+
+    pub fn toggle_comparison_grid(&mut self) {
+        self.toggle_preview_mode(PreviewMode::ComparisonGrid);
+    }
+
+    pub fn toggle_native_size(&mut self) {
+        self.toggle_preview_mode(PreviewMode::NativeSize);
+    }
+
+    pub fn toggle_size_comparison(&mut self) {
+        self.toggle_preview_mode(PreviewMode::SizeComparison);
+    }
+
+    // The strip shows the given characters (e.g. every code point of a range) in the current font
+    pub fn toggle_code_point_strip(&mut self, characters: Vec<char>) {
+        self.strip_characters = characters;
+        self.toggle_preview_mode(PreviewMode::CodePointStrip);
+    }
+
+    // The cluster is shaped as a whole (e.g. the grapheme cluster the character is part of), the
+    // character alone is shaped without one
+    pub fn toggle_shaped_cluster(&mut self, cluster: Option<String>) {
+        self.shaped_cluster = cluster;
+        self.toggle_preview_mode(PreviewMode::ShapedCluster);
+    }
+
+    // The string (e.g. the whole input) is shaped as a single run, with the advances of its glyphs
+    pub fn toggle_shaped_string(&mut self, string: String) {
+        self.shaped_string = Some(string);
+        self.toggle_preview_mode(PreviewMode::ShapedString);
+    }
+
+    // The character as the terminal draws it with its own font, to compare with the rendered glyph
+    pub fn toggle_terminal_glyph(&mut self) {
+        self.toggle_preview_mode(PreviewMode::TerminalGlyph);
+    }
+
+    // The current font is compared with the next matching font at first
+    pub fn toggle_font_diff(&mut self) {
+        if let Ok(character_preview) = &self.character_preview {
+            let font_paths = character_preview.matching_font_paths();
+            let current_index = character_preview
+                .get_current_font_path()
+                .and_then(|font_path| font_paths.iter().position(|path| *path == font_path))
+                .unwrap_or(0);
+            self.diff_font_index = (current_index + 1) % max(font_paths.len(), 1);
         }
+        self.toggle_preview_mode(PreviewMode::FontDiff);
     }
-}
 
-struct ToufuShape {
-    size: RenderSize,
-    x_padding: usize,
-    y_padding: usize,
-}
+    pub fn select_next_diff_font(&mut self) {
+        if let Ok(character_preview) = &self.character_preview {
+            let font_count = max(character_preview.matching_font_paths().len(), 1);
+            self.diff_font_index = (self.diff_font_index + 1) % font_count;
+        }
+    }
 
-impl Shape for ToufuShape {
-    fn draw(&self, painter: &mut Painter) {
-        for x in 0..self.size.width {
-            for y in 0..self.size.height {
-                painter.paint(x + self.x_padding, y + self.y_padding, Color::Reset)
-            }
+    pub fn toggle_glyph_bounds(&mut self) {
+        self.show_glyph_bounds = !self.show_glyph_bounds;
+    }
+
+    // Switches between the filled glyph and its contour
+    pub fn toggle_outline(&mut self) {
+        self.show_outline = !self.show_outline;
+    }
+
+    // Only combining marks are shaped on a base, returns false for other characters or without the
+    // shaping feature
+    pub fn toggle_combining_base(&mut self) -> bool {
+        if !cfg!(feature = "shaping") || !GeneralCategory::of(self.chr).is_mark() {
+            return false;
+        }
+        self.attach_combining_base = !self.attach_combining_base;
+        true
+    }
+
+    // Only bitmap fonts have strikes, returns false for the others
+    pub fn select_next_strike(&mut self) -> bool {
+        match &mut self.character_preview {
+            Ok(character_preview) => character_preview.select_next_strike(),
+            Err(_) => false,
+        }
+    }
+
+    pub fn toggle_guides(&mut self) {
+        self.show_guides = !self.show_guides;
+    }
+
+    pub fn toggle_reference_glyph(&mut self) {
+        self.show_reference_glyph = !self.show_reference_glyph;
+    }
+
+    // Only the braille renderer has dots, returns false for the others
+    pub fn toggle_dot_grid(&mut self) -> bool {
+        if self.draws_terminal_images() {
+            return false;
+        }
+        match self.preview_renderer() {
+            PreviewRenderer::Auto | PreviewRenderer::Braille => {
+                self.show_dot_grid = !self.show_dot_grid;
+                true
+            }
+            PreviewRenderer::HalfBlock | PreviewRenderer::Ascii => false,
+        }
+    }
+
+    pub fn toggle_side_by_side(&mut self) {
+        self.toggle_preview_mode(PreviewMode::SideBySide);
+    }
+
+    // Paths of the fonts compared side by side: the current font, then the matching fonts after it
+    // (continuing from the first one past the last one)
+    fn side_by_side_font_paths(&self) -> Vec<String> {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return vec![],
+        };
+        let font_paths = character_preview.matching_font_paths();
+        let current_index = character_preview
+            .get_current_font_path()
+            .and_then(|font_path| font_paths.iter().position(|path| *path == font_path))
+            .unwrap_or(0);
+        let font_count = self
+            .settings
+            .side_by_side_fonts
+            .max(MIN_SIDE_BY_SIDE_FONTS)
+            .min(MAX_SIDE_BY_SIDE_FONTS)
+            .min(font_paths.len());
+        font_paths
+            .iter()
+            .cycle()
+            .skip(current_index)
+            .take(font_count)
+            .cloned()
+            .collect()
+    }
+
+    // Scans the block of the character again only once another font (or face) is previewed
+    fn update_block_coverage(&mut self) {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+        let font_path = character_preview.get_current_font_path();
+        let face_index = character_preview.current_face_index();
+        if let Some(block_coverage) = &self.block_coverage {
+            if block_coverage.font_path == font_path && block_coverage.face_index == face_index {
+                return;
+            }
+        }
+
+        self.block_coverage = UnicodeBlock::of(self.chr).map(|block| {
+            let coverage = range_coverage(character_preview, block.range.low..=block.range.high);
+            let (covered_count, assigned_count) = coverage_counts(&coverage);
+            BlockCoverage {
+                font_path,
+                face_index,
+                covered_count,
+                assigned_count,
+            }
+        });
+    }
+
+    // Renders the character in each of the fonts compared side by side, unless they were already
+    // rendered at this size. The current font is rendered as is, e.g. at its selected named
+    // instance.
+    fn update_side_by_side_renders(&mut self, size: RenderSize) {
+        let font_paths = self.side_by_side_font_paths();
+        let character_preview = match &mut self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+        let render_options = character_preview.render_options();
+        if let Some(side_by_side_renders) = &self.side_by_side_renders {
+            if side_by_side_renders.font_paths == font_paths
+                && side_by_side_renders.size == size
+                && side_by_side_renders.render_options == render_options
+            {
+                return;
+            }
+        }
+
+        let rendered_characters = font_paths
+            .iter()
+            .enumerate()
+            .map(|(index, font_path)| {
+                if index == 0 {
+                    character_preview.render(size).ok()
+                } else {
+                    character_preview.render_with_font(font_path, size).ok()
+                }
+            })
+            .collect();
+        self.side_by_side_renders = Some(SideBySideRenders {
+            font_paths,
+            size,
+            render_options,
+            rendered_characters,
+        });
+    }
+
+    fn toggle_preview_mode(&mut self, preview_mode: PreviewMode) {
+        self.preview_mode = if self.preview_mode == preview_mode {
+            PreviewMode::Glyph
+        } else {
+            preview_mode
+        };
+        self.pan_offset = (0, 0);
+    }
+
+    // Renders the glyph larger than the canvas to inspect its details, the zoomed glyph is panned
+    // around like any glyph larger than the preview, starting from its center
+    pub fn zoom_in(&mut self) {
+        self.set_zoom(min(self.zoom * 2, MAX_ZOOM));
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.set_zoom(max(self.zoom / 2, 1));
+    }
+
+    fn set_zoom(&mut self, zoom: usize) {
+        if zoom == self.zoom {
+            return;
+        }
+        self.zoom = zoom;
+        self.recenter_pan = true;
+
+        // Glyphs sized in points ignore the render size, their point size is zoomed instead
+        if let Ok(character_preview) = &mut self.character_preview {
+            character_preview.set_sizing(match self.settings.sizing() {
+                Sizing::Points { size, dpi } => Sizing::Points {
+                    size: size * zoom as f64,
+                    dpi,
+                },
+                Sizing::Pixels => Sizing::Pixels,
+            });
+        }
+    }
+
+    // Moves the visible portion of a glyph which is larger than the preview, in steps
+    pub fn pan(&mut self, x_steps: isize, y_steps: isize) {
+        let (x_offset, y_offset) = self.pan_offset;
+        self.pan_offset = (
+            max(x_offset as isize + x_steps * PAN_STEP_IN_PIXELS, 0) as usize,
+            max(y_offset as isize + y_steps * PAN_STEP_IN_PIXELS, 0) as usize,
+        );
+    }
+
+    fn draw_character_preview(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        // The metrics readout takes the bottom lines of the preview, below the canvas
+        let (rect, metrics_rect) = if self.settings.show_glyph_metrics {
+            let chunks = Layout::default()
+                .constraints(vec![
+                    Constraint::Min(RENDER_PADDING_IN_CELLS),
+                    Constraint::Length(GLYPH_METRICS_LINE_COUNT),
+                ])
+                .direction(Direction::Vertical)
+                .split(rect);
+            (chunks[0], Some(chunks[1]))
+        } else {
+            (rect, None)
+        };
+
+        let glyph_preview = self.glyph_preview(rect.width, rect.height);
+        if let Some(metrics_rect) = metrics_rect {
+            draw_glyph_metrics(frame, metrics_rect, &glyph_preview, self.theme().muted);
+        }
+        if let GlyphPreview::Message(message) = &glyph_preview {
+            draw_centered_message(frame, rect, message, self.theme().muted);
+            return;
+        }
+
+        let shapes =
+            self.glyph_preview_shapes(&glyph_preview, self.pixel_size(rect.width, rect.height));
+        self.draw_shapes(frame, rect, &shapes);
+
+        if let GlyphPreview::Whitespace { label, .. } = &glyph_preview {
+            draw_centered_message(frame, rect, label, self.theme().muted);
+        }
+    }
+
+    // Cycles from the default presentation to the text (VS15) and emoji (VS16) ones, the variation
+    // selector is shaped along with the character. Returns false for characters without both
+    // presentations, or without the shaping feature.
+    pub fn cycle_presentation(&mut self) -> bool {
+        if !cfg!(feature = "shaping")
+            || self.sequence().is_some()
+            || !has_presentation_variants(self.chr)
+        {
+            return false;
+        }
+        self.presentation_selector = match self.presentation_selector {
+            None => Some(TEXT_PRESENTATION_SELECTOR),
+            Some(TEXT_PRESENTATION_SELECTOR) => Some(EMOJI_PRESENTATION_SELECTOR),
+            Some(_) => None,
+        };
+        self.presentation_fonts = match (&self.character_preview, self.presentation_selector) {
+            (Ok(character_preview), Some(presentation_selector)) => {
+                character_preview.fonts_with_variation_sequence(presentation_selector)
+            }
+            _ => vec![],
+        };
+        true
+    }
+
+    // The variation sequence previewed instead of the character alone, e.g. "U+2764 U+FE0F"
+    pub fn presentation_sequence(&self) -> Option<String> {
+        self.presentation_selector.map(|presentation_selector| {
+            format!(
+                "{} {}",
+                code_point_description(self.chr),
+                code_point_description(presentation_selector)
+            )
+        })
+    }
+
+    // The sequence previewed instead of the character alone, if any
+    pub fn sequence(&self) -> Option<&str> {
+        self.character_preview.as_ref().ok()?.sequence()
+    }
+
+    // The base the character is shaped on, if it is a combining mark and attached to one. The base
+    // of the settings gives way to the dotted circle or a no-break space if the current font lacks
+    // it, the mark is shaped alone if the font has none of them.
+    fn combining_base(&self) -> Option<char> {
+        if !cfg!(feature = "shaping")
+            || !self.attach_combining_base
+            || self.sequence().is_some()
+            || !GeneralCategory::of(self.chr).is_mark()
+        {
+            return None;
+        }
+        let character_preview = self.character_preview.as_ref().ok()?;
+        Some(self.settings.combining_base_character)
+            .into_iter()
+            .chain(FALLBACK_COMBINING_BASES.iter().copied())
+            .find(|combining_base| character_preview.has_glyph(*combining_base))
+    }
+
+    // Combining marks are shaped on their base so that they are positioned as in text, instead of
+    // floating alone, and characters with a variation selector are shaped along with it. The glyph
+    // alone may be rendered in the background, None is returned until it is.
+    fn render_glyph(
+        &self,
+        character_preview: &CharacterPreview,
+        size: RenderSize,
+    ) -> Option<Result<RenderedCharacter>> {
+        #[cfg(feature = "shaping")]
+        {
+            if self.combining_base().is_some() || self.presentation_selector.is_some() {
+                let text: String = self
+                    .combining_base()
+                    .into_iter()
+                    .chain(Some(self.chr))
+                    .chain(self.presentation_selector)
+                    .collect();
+                return character_preview.render_shaped_in_background(&text, size);
+            }
+        }
+        character_preview.render_uncropped_in_background(size)
+    }
+
+    // Renders and positions the glyph for a preview of the given size, without drawing anything
+    fn glyph_preview(&mut self, width_in_cells: u16, height_in_cells: u16) -> GlyphPreview {
+        if width_in_cells < RENDER_PADDING_IN_CELLS || height_in_cells < RENDER_PADDING_IN_CELLS {
+            return GlyphPreview::Message(TOO_SMALL_TO_RENDER_MESSAGE.to_owned());
+        }
+
+        let canvas_pixel_size = self.pixel_size(
+            width_in_cells - RENDER_PADDING_IN_CELLS,
+            height_in_cells - RENDER_PADDING_IN_CELLS,
+        );
+
+        let fallback_pixel_size = if self.settings.stretch_preview {
+            canvas_pixel_size
+        } else {
+            canvas_pixel_size.largest_square()
+        };
+        // A mark on its base, or a whole sequence, is much larger than the character alone, which
+        // can't be fitted on its own
+        let render_pixel_size = match &self.character_preview {
+            Ok(character_preview)
+                if !self.settings.stretch_preview
+                    && self.combining_base().is_none()
+                    && character_preview.sequence().is_none() =>
+            {
+                character_preview.fitting_render_size(canvas_pixel_size)
+            }
+            _ => fallback_pixel_size,
+        };
+        let render_pixel_size = RenderSize::new(
+            render_pixel_size.width * self.zoom,
+            render_pixel_size.height * self.zoom,
+        );
+
+        let rendered_character = match &self.character_preview {
+            Ok(character_preview) => {
+                match self.render_glyph(character_preview, render_pixel_size) {
+                    Some(Ok(rendered_character)) => Some(rendered_character),
+                    None => return GlyphPreview::Message(RENDERING_MESSAGE.to_owned()),
+                    Some(Err(error)) => {
+                        if let Some(PreviewError::RenderSizeTooSmall) =
+                            error.downcast_ref::<PreviewError>()
+                        {
+                            return GlyphPreview::Message(TOO_SMALL_TO_RENDER_MESSAGE.to_owned());
+                        }
+                        let render_failure = (
+                            character_preview.get_current_font_path(),
+                            character_preview.current_face_index(),
+                            self.chr,
+                        );
+                        if self.logged_render_failure.as_ref() != Some(&render_failure) {
+                            warn!("Failed to render U+{:04X}: {}", self.chr as u32, error);
+                            self.logged_render_failure = Some(render_failure);
+                        }
+                        None
+                    }
+                }
+            }
+            Err(_) => None,
+        };
+
+        // Whitespace is shown as a dotted box as wide as its advance, named along with the advance
+        if let Some(rendered_character) = &rendered_character {
+            if self.chr.is_whitespace() && rendered_character.is_blank() && !self.show_glyph_bounds
+            {
+                let size = RenderSize::new(
+                    rendered_character
+                        .advance_width
+                        .max(1)
+                        .min(canvas_pixel_size.width),
+                    render_pixel_size.height.min(canvas_pixel_size.height),
+                );
+                return GlyphPreview::Whitespace {
+                    label: whitespace_label(self.chr, rendered_character.advance_width),
+                    size,
+                    x_padding: (canvas_pixel_size.width - size.width) / 2,
+                    y_padding: (canvas_pixel_size.height - size.height) / 2,
+                };
+            }
+        }
+
+        let reference_character = match &self.character_preview {
+            Ok(character_preview) if self.show_reference_glyph => character_preview
+                .render_alongside_in_background(
+                    self.settings.reference_character,
+                    render_pixel_size,
+                )
+                .and_then(Result::ok),
+            _ => None,
+        };
+
+        // Non-printing characters usually have no glyph (or a blank one), name them instead
+        let has_ink = rendered_character
+            .as_ref()
+            .map_or(false, |rendered_character| !rendered_character.is_blank());
+        if !has_ink && !self.show_glyph_bounds && self.settings.show_control_pictures {
+            if let Some(symbol) = non_printing_symbol(self.chr) {
+                return GlyphPreview::Message(symbol);
+            }
+        }
+
+        let rendered_character = match rendered_character {
+            Some(rendered_character) => rendered_character,
+            None => {
+                return GlyphPreview::Tofu {
+                    size: fallback_pixel_size,
+                    x_padding: (canvas_pixel_size.width - fallback_pixel_size.width) / 2,
+                    y_padding: (canvas_pixel_size.height - fallback_pixel_size.height) / 2,
+                }
+            }
+        };
+
+        // An empty preview would look like a bug, tell that the glyph has no ink instead
+        if rendered_character.is_blank() && !self.show_glyph_bounds {
+            return GlyphPreview::Message("Blank Glyph".to_owned());
+        }
+
+        // Dense glyphs (e.g. CJK) are easier to read from their contour in small previews
+        let rendered_character = if self.show_outline {
+            rendered_character.edges(max(self.settings.coverage_threshold, 1))
+        } else {
+            rendered_character
+        };
+
+        // Glyphs larger than the canvas (e.g. at a large point size) are shown alone and can be
+        // panned around, as in the native size mode
+        let glyph_size = rendered_character.glyph_size;
+        if glyph_size.width > canvas_pixel_size.width
+            || glyph_size.height > canvas_pixel_size.height
+        {
+            let viewport = self.pixel_size(width_in_cells, height_in_cells);
+            return GlyphPreview::Oversized {
+                rendered_character,
+                viewport,
+                offset: self.clamp_pan_offset(glyph_size, viewport),
+            };
+        }
+
+        let face_metrics = match &self.character_preview {
+            Ok(character_preview) if self.show_guides => {
+                character_preview.face_metrics(render_pixel_size).ok()
+            }
+            _ => None,
+        };
+
+        GlyphPreview::Glyph {
+            x_padding: (canvas_pixel_size.width - glyph_size.width) / 2,
+            y_padding: (canvas_pixel_size.height - glyph_size.height) / 2,
+            rendered_character,
+            reference_character,
+            face_metrics,
+        }
+    }
+
+    // The shapes drawing a glyph preview on a canvas grid of `grid_size` pixels, messages have none
+    fn glyph_preview_shapes<'a>(
+        &'a self,
+        glyph_preview: &'a GlyphPreview,
+        grid_size: RenderSize,
+    ) -> Vec<Box<dyn PixelShape + 'a>> {
+        let mut shapes: Vec<Box<dyn PixelShape + 'a>> = vec![];
+        if self.show_dot_grid && !matches!(glyph_preview, GlyphPreview::Message(_)) {
+            shapes.push(Box::new(DotGridShape {
+                grid_size,
+                color: self.theme().dot_grid,
+            }));
+        }
+        match glyph_preview {
+            GlyphPreview::Message(_) => {}
+            GlyphPreview::Oversized {
+                rendered_character,
+                viewport,
+                offset,
+            } => shapes.push(Box::new(ViewportShape {
+                rendered_character,
+                viewport: *viewport,
+                offset: *offset,
+                glyph_renderer: self.glyph_renderer.as_ref(),
+            })),
+            GlyphPreview::Glyph {
+                rendered_character,
+                x_padding,
+                y_padding,
+                reference_character,
+                face_metrics,
+            } => {
+                let (x_padding, y_padding) = (*x_padding, *y_padding);
+                if let Some(face_metrics) = face_metrics {
+                    shapes.push(Box::new(GuidesShape {
+                        face_metrics: *face_metrics,
+                        origin: (
+                            x_padding as i32 - rendered_character.bitmap_left,
+                            y_padding as i32 + rendered_character.bitmap_top,
+                        ),
+                        grid_size,
+                        color: self.theme().guides,
+                    }));
+                }
+                if let Some(reference_character) = reference_character {
+                    shapes.push(Box::new(ReferenceGlyphShape {
+                        rendered_character: reference_character,
+                        origin: (
+                            x_padding as i32 - rendered_character.bitmap_left,
+                            y_padding as i32 + rendered_character.bitmap_top,
+                        ),
+                        grid_size,
+                        color: self.theme().muted,
+                    }));
+                }
+                shapes.push(Box::new(CharacterPreviewShape {
+                    rendered_character,
+                    x_padding,
+                    y_padding,
+                    glyph_renderer: self.glyph_renderer.as_ref(),
+                }));
+                if self.show_glyph_bounds {
+                    shapes.push(Box::new(GlyphBoundsShape {
+                        rendered_character,
+                        x_padding,
+                        y_padding,
+                        grid_size,
+                        theme: self.theme(),
+                    }));
+                }
+            }
+            GlyphPreview::Whitespace {
+                size,
+                x_padding,
+                y_padding,
+                ..
+            } => shapes.push(Box::new(DottedBoxShape {
+                size: *size,
+                x_padding: *x_padding,
+                y_padding: *y_padding,
+                color: self.theme().muted,
+            })),
+            GlyphPreview::Tofu {
+                size,
+                x_padding,
+                y_padding,
+            } => shapes.push(Box::new(ToufuShape {
+                size: *size,
+                x_padding: *x_padding,
+                y_padding: *y_padding,
+            })),
+        }
+        shapes
+    }
+
+    fn draw_comparison_grid(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        if rect.width < RENDER_PADDING_IN_CELLS || rect.height < RENDER_PADDING_IN_CELLS {
+            return;
+        }
+
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+
+        let canvas_pixel_size = self.pixel_size(
+            rect.width - RENDER_PADDING_IN_CELLS,
+            rect.height - RENDER_PADDING_IN_CELLS,
+        );
+        let positioned_characters = layout_comparison_grid(
+            character_preview,
+            &self.settings.comparison_characters,
+            canvas_pixel_size,
+        );
+
+        let shapes: Vec<Box<dyn PixelShape + '_>> = positioned_characters
+            .iter()
+            .map(|positioned_character| -> Box<dyn PixelShape + '_> {
+                Box::new(CharacterPreviewShape {
+                    rendered_character: &positioned_character.rendered_character,
+                    x_padding: positioned_character.x_padding,
+                    y_padding: positioned_character.y_padding,
+                    glyph_renderer: self.glyph_renderer.as_ref(),
+                })
+            })
+            .collect();
+
+        self.draw_shapes(frame, rect, &shapes);
+    }
+
+    // Compares the glyph of the current font with the one of the diff font, both rendered at the
+    // size fitting the current font on a canvas of `canvas_pixel_size`
+    fn font_diff(&mut self, canvas_pixel_size: RenderSize) -> Option<GlyphDiff> {
+        let character_preview = self.character_preview.as_mut().ok()?;
+        let render_size = character_preview.fitting_render_size(canvas_pixel_size);
+        let render_options = character_preview.render_options();
+        let rendered_character = character_preview.render(render_size).ok()?;
+
+        let is_cached = match &self.diff_font_render {
+            Some(diff_font_render) => {
+                diff_font_render.font_index == self.diff_font_index
+                    && diff_font_render.size == render_size
+                    && diff_font_render.render_options == render_options
+            }
+            None => false,
+        };
+        if !is_cached {
+            let font_path = character_preview
+                .matching_font_paths()
+                .get(self.diff_font_index)?
+                .clone();
+            self.diff_font_render = Some(DiffFontRender {
+                font_index: self.diff_font_index,
+                size: render_size,
+                render_options,
+                rendered_character: character_preview
+                    .render_with_font(&font_path, render_size)
+                    .ok(),
+            });
+        }
+
+        let diff_rendered_character = self
+            .diff_font_render
+            .as_ref()?
+            .rendered_character
+            .as_ref()?;
+        Some(diff_glyphs(
+            &rendered_character,
+            diff_rendered_character,
+            max(self.settings.coverage_threshold, 1),
+        ))
+    }
+
+    // Overlays the glyphs of the two fonts in a color each, ink they share keeps the default color.
+    // A legend below names the diff font and tells how much of the ink differs.
+    fn draw_font_diff(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        if rect.width < RENDER_PADDING_IN_CELLS || rect.height < RENDER_PADDING_IN_CELLS {
+            return;
+        }
+
+        let chunks = Layout::default()
+            .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+            .direction(Direction::Vertical)
+            .split(rect);
+        let canvas_pixel_size = self.pixel_size(
+            rect.width - RENDER_PADDING_IN_CELLS,
+            rect.height - RENDER_PADDING_IN_CELLS,
+        );
+        let glyph_diff = match self.font_diff(canvas_pixel_size) {
+            Some(glyph_diff) => glyph_diff,
+            None => {
+                draw_centered_message(frame, rect, "No Glyph to Compare", self.theme().muted);
+                return;
+            }
+        };
+
+        let theme = self.theme();
+        let diff_font_name = match &self.character_preview {
+            Ok(character_preview) => character_preview
+                .matching_font_display_names()
+                .get(self.diff_font_index)
+                .cloned()
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+        let legend_items = [
+            Text::styled("Current Font", Style::default().fg(theme.first_font_ink)),
+            Text::raw(" vs "),
+            Text::styled(diff_font_name, Style::default().fg(theme.second_font_ink)),
+            Text::raw(format!(
+                " | {:.1}% of the ink differs",
+                glyph_diff.difference_ratio() * 100.0
+            )),
+        ];
+        let legend = Paragraph::new(legend_items.iter()).alignment(Alignment::Center);
+        frame.render_widget(legend, chunks[1]);
+
+        let grid_size = self.pixel_size(chunks[0].width, chunks[0].height);
+        let glyph_diff_shape = GlyphDiffShape {
+            glyph_diff: &glyph_diff,
+            x_padding: grid_size.width.saturating_sub(glyph_diff.size.width) / 2,
+            y_padding: grid_size.height.saturating_sub(glyph_diff.size.height) / 2,
+            grid_size,
+            theme,
+        };
+        let shapes: Vec<Box<dyn PixelShape + '_>> = vec![Box::new(glyph_diff_shape)];
+        self.draw_shapes(frame, chunks[0], &shapes);
+    }
+
+    // Renders the cluster shaped by HarfBuzz with the current font, labeled with its code points.
+    // Clusters larger than the preview can be panned around.
+    fn draw_shaped_cluster(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let cluster = self
+            .shaped_cluster
+            .clone()
+            .unwrap_or_else(|| self.chr.to_string());
+        let label = cluster
+            .chars()
+            .map(code_point_description)
+            .collect::<Vec<String>>()
+            .join(" ");
+        self.draw_shaped_text(frame, rect, &cluster, label);
+    }
+
+    // Renders the whole input shaped as a single run with the current font, e.g. to check its
+    // ligatures, kerning or mark positioning
+    fn draw_shaped_string(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let text = self.shaped_string.clone().unwrap_or_default();
+        if text.is_empty() {
+            draw_centered_message(frame, rect, "Nothing to Shape", self.theme().muted);
+            return;
+        }
+        let label = format!(
+            "{} code points shaped as a single run",
+            text.chars().count()
+        );
+        self.draw_shaped_text(frame, rect, &text, label);
+    }
+
+    #[cfg(feature = "shaping")]
+    fn draw_shaped_text(
+        &mut self,
+        frame: &mut TerminalFrame,
+        rect: Rect,
+        text: &str,
+        label: String,
+    ) {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+        if rect.width < RENDER_PADDING_IN_CELLS || rect.height < RENDER_PADDING_IN_CELLS {
+            draw_centered_message(frame, rect, TOO_SMALL_TO_RENDER_MESSAGE, self.theme().muted);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+            .direction(Direction::Vertical)
+            .split(rect);
+        let label_item = [Text::raw(label)];
+        let label = Paragraph::new(label_item.iter())
+            .style(Style::default().fg(self.theme().muted))
+            .alignment(Alignment::Center);
+        frame.render_widget(label, chunks[1]);
+
+        let render_size = self
+            .pixel_size(
+                rect.width - RENDER_PADDING_IN_CELLS,
+                rect.height - RENDER_PADDING_IN_CELLS,
+            )
+            .largest_square();
+        let font_path = character_preview.get_current_font_path();
+        let face_index = character_preview.current_face_index();
+        let render_options = character_preview.render_options();
+        if let Some(shaping_failure) = &self.shaping_failure {
+            if shaping_failure.text == text
+                && shaping_failure.font_path == font_path
+                && shaping_failure.face_index == face_index
+                && shaping_failure.size == render_size
+                && shaping_failure.render_options == render_options
+            {
+                draw_centered_message(
+                    frame,
+                    chunks[0],
+                    &shaping_failure.message,
+                    self.theme().muted,
+                );
+                return;
+            }
+        }
+        let rendered_character =
+            match character_preview.render_shaped_in_background(text, render_size) {
+                Some(Ok(rendered_character)) => rendered_character,
+                None => {
+                    draw_centered_message(frame, chunks[0], RENDERING_MESSAGE, self.theme().muted);
+                    return;
+                }
+                Some(Err(error)) => {
+                    warn!("Failed to shape {:?}: {}", text, error);
+                    let message = error.to_string();
+                    draw_centered_message(frame, chunks[0], &message, self.theme().muted);
+                    self.shaping_failure = Some(ShapingFailure {
+                        text: text.to_owned(),
+                        font_path,
+                        face_index,
+                        size: render_size,
+                        render_options,
+                        message,
+                    });
+                    return;
+                }
+            };
+
+        let viewport = self.pixel_size(chunks[0].width, chunks[0].height);
+        let offset = self.clamp_pan_offset(rendered_character.glyph_size, viewport);
+        let viewport_shape = ViewportShape {
+            rendered_character: &rendered_character,
+            viewport,
+            offset,
+            glyph_renderer: self.glyph_renderer.as_ref(),
+        };
+        let shapes: Vec<Box<dyn PixelShape + '_>> = vec![Box::new(viewport_shape)];
+        self.draw_shapes(frame, chunks[0], &shapes);
+    }
+
+    #[cfg(not(feature = "shaping"))]
+    fn draw_shaped_text(&mut self, frame: &mut TerminalFrame, rect: Rect, _: &str, _: String) {
+        draw_centered_message(
+            frame,
+            rect,
+            "Cicero was built without the shaping feature",
+            self.theme().muted,
+        );
+    }
+
+    fn draw_native_size(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let rendered_character = match &self.character_preview {
+            Ok(character_preview) => match character_preview.render_native_in_background() {
+                Some(Ok(rendered_character)) => rendered_character,
+                Some(Err(error)) => {
+                    draw_centered_message(frame, rect, &error.to_string(), self.theme().muted);
+                    return;
+                }
+                None => {
+                    draw_centered_message(frame, rect, RENDERING_MESSAGE, self.theme().muted);
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+
+        // Glyphs smaller than the viewport are centered, larger ones can be panned around
+        let viewport = self.pixel_size(rect.width, rect.height);
+        let offset = self.clamp_pan_offset(rendered_character.glyph_size, viewport);
+        let viewport_shape = ViewportShape {
+            rendered_character: &rendered_character,
+            viewport,
+            offset,
+            glyph_renderer: self.glyph_renderer.as_ref(),
+        };
+
+        let shapes: Vec<Box<dyn PixelShape + '_>> = vec![Box::new(viewport_shape)];
+        self.draw_shapes(frame, rect, &shapes);
+    }
+
+    // Renders the character at each of the comparison sizes side by side, each in its own column
+    // labeled with its size. Sizes are always in pixels, so that hinting can be judged.
+    fn draw_size_comparison(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+        let sizes = &self.settings.comparison_sizes;
+        if sizes.is_empty() {
+            return;
+        }
+
+        let columns = Layout::default()
+            .constraints(
+                sizes
+                    .iter()
+                    .map(|_| Constraint::Ratio(1, sizes.len() as u32))
+                    .collect::<Vec<Constraint>>(),
+            )
+            .direction(Direction::Horizontal)
+            .split(rect);
+        for (size, column) in sizes.iter().zip(columns) {
+            let chunks = Layout::default()
+                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                .direction(Direction::Vertical)
+                .split(column);
+
+            let label_item = [Text::raw(format!("{}px", size))];
+            let label = Paragraph::new(label_item.iter())
+                .style(Style::default().fg(self.theme().muted))
+                .alignment(Alignment::Center);
+            frame.render_widget(label, chunks[1]);
+
+            let rendered_character = match character_preview.render_character_in_background(
+                character_preview.chr,
+                RenderSize::new(*size, *size),
+            ) {
+                Some(Ok(rendered_character)) => rendered_character,
+                _ => continue,
+            };
+            let viewport_shape = ViewportShape {
+                rendered_character: &rendered_character,
+                viewport: self.pixel_size(chunks[0].width, chunks[0].height),
+                offset: (0, 0),
+                glyph_renderer: self.glyph_renderer.as_ref(),
+            };
+
+            let shapes: Vec<Box<dyn PixelShape + '_>> = vec![Box::new(viewport_shape)];
+            self.draw_shapes(frame, chunks[0], &shapes);
+        }
+    }
+
+    // Draws the character in several matching fonts next to each other, each labeled with the name
+    // of its font. Glyphs are rendered at the same size so that their proportions can be compared.
+    fn draw_side_by_side(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let font_count = self.side_by_side_font_paths().len();
+        if font_count == 0 || rect.height < RENDER_PADDING_IN_CELLS {
+            return;
+        }
+
+        let columns = Layout::default()
+            .constraints(
+                (0..font_count)
+                    .map(|_| Constraint::Ratio(1, font_count as u32))
+                    .collect::<Vec<Constraint>>(),
+            )
+            .direction(Direction::Horizontal)
+            .horizontal_margin(1)
+            .split(rect);
+        let chunks_of = |column: Rect| {
+            Layout::default()
+                .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+                .direction(Direction::Vertical)
+                .split(column)
+        };
+
+        let glyph_area = chunks_of(columns[0])[0];
+        let render_size = self
+            .pixel_size(glyph_area.width, glyph_area.height)
+            .largest_square();
+        self.update_side_by_side_renders(render_size);
+
+        let side_by_side_renders = match &self.side_by_side_renders {
+            Some(side_by_side_renders) => side_by_side_renders,
+            None => return,
+        };
+        let font_names: Vec<String> = match &self.character_preview {
+            Ok(character_preview) => side_by_side_renders
+                .font_paths
+                .iter()
+                .map(|font_path| {
+                    character_preview
+                        .matching_font_paths()
+                        .iter()
+                        .position(|path| path == font_path)
+                        .and_then(|index| {
+                            character_preview.matching_font_display_names().get(index)
+                        })
+                        .cloned()
+                        .unwrap_or_else(|| font_path.clone())
+                })
+                .collect(),
+            Err(_) => return,
+        };
+
+        let theme = self.theme();
+        for (index, column) in columns.into_iter().enumerate() {
+            let chunks = chunks_of(column);
+            let label_style = if index == 0 {
+                Style::default().fg(theme.accent)
+            } else {
+                Style::default().fg(theme.muted)
+            };
+            let label_item = [Text::styled(font_names[index].clone(), label_style)];
+            let label = Paragraph::new(label_item.iter()).alignment(Alignment::Center);
+            frame.render_widget(label, chunks[1]);
+
+            let rendered_character = match &side_by_side_renders.rendered_characters[index] {
+                Some(rendered_character) => rendered_character,
+                None => {
+                    draw_centered_message(frame, chunks[0], "No Glyph", theme.muted);
+                    continue;
+                }
+            };
+            let grid_size = self.pixel_size(chunks[0].width, chunks[0].height);
+            let character_preview_shape = CharacterPreviewShape {
+                rendered_character,
+                x_padding: grid_size
+                    .width
+                    .saturating_sub(rendered_character.glyph_size.width)
+                    / 2,
+                y_padding: grid_size
+                    .height
+                    .saturating_sub(rendered_character.glyph_size.height)
+                    / 2,
+                glyph_renderer: self.glyph_renderer.as_ref(),
+            };
+            let shapes: Vec<Box<dyn PixelShape + '_>> = vec![Box::new(character_preview_shape)];
+            self.draw_shapes(frame, chunks[0], &shapes);
+        }
+    }
+
+    // Renders each character of the strip with the current font in a cell of its own, labeled with
+    // its code point. The label of the previewed character is highlighted.
+    fn draw_code_point_strip(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let character_preview = match &self.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => return,
+        };
+
+        let glyph_size = self
+            .pixel_size(
+                STRIP_CELL_WIDTH_IN_CELLS - STRIP_SPACING_IN_CELLS,
+                STRIP_GLYPH_HEIGHT_IN_CELLS,
+            )
+            .largest_square();
+        let shown_index = self
+            .strip_characters
+            .iter()
+            .position(|chr| *chr == self.chr)
+            .unwrap_or(0);
+        let cells = layout_code_point_strip(rect, self.strip_characters.len(), shown_index);
+        for (index, cell) in cells {
+            let chr = self.strip_characters[index];
+            let label_item = [Text::raw(code_point_description(chr))];
+            let label_color = if chr == self.chr {
+                Color::Reset
+            } else {
+                self.theme().muted
+            };
+            let label = Paragraph::new(label_item.iter())
+                .style(Style::default().fg(label_color))
+                .alignment(Alignment::Center);
+            frame.render_widget(
+                label,
+                Rect::new(cell.x, cell.y + STRIP_GLYPH_HEIGHT_IN_CELLS, cell.width, 1),
+            );
+
+            let rendered_character =
+                match character_preview.render_character_in_background(chr, glyph_size) {
+                    Some(Ok(rendered_character)) => rendered_character,
+                    _ => continue,
+                };
+            let glyph_rect = Rect::new(
+                cell.x + STRIP_SPACING_IN_CELLS / 2,
+                cell.y,
+                cell.width - STRIP_SPACING_IN_CELLS,
+                STRIP_GLYPH_HEIGHT_IN_CELLS,
+            );
+            let canvas_pixel_size = self.pixel_size(glyph_rect.width, glyph_rect.height);
+            let preview_shape = CharacterPreviewShape {
+                rendered_character: &rendered_character,
+                x_padding: canvas_pixel_size
+                    .width
+                    .saturating_sub(rendered_character.glyph_size.width)
+                    / 2,
+                y_padding: canvas_pixel_size
+                    .height
+                    .saturating_sub(rendered_character.glyph_size.height)
+                    / 2,
+                glyph_renderer: self.glyph_renderer.as_ref(),
+            };
+
+            let shapes: Vec<Box<dyn PixelShape + '_>> = vec![Box::new(preview_shape)];
+            self.draw_shapes(frame, glyph_rect, &shapes);
+        }
+    }
+
+    // Keeps the pan offset within the glyph, panning starts over from the top left corner whenever
+    // the glyph changes size (e.g. in another font), or from the center after zooming
+    fn clamp_pan_offset(&mut self, glyph_size: RenderSize, viewport: RenderSize) -> (usize, usize) {
+        if self.recenter_pan {
+            self.recenter_pan = false;
+            self.panned_glyph_size = Some(glyph_size);
+            self.pan_offset = (
+                glyph_size.width.saturating_sub(viewport.width) / 2,
+                glyph_size.height.saturating_sub(viewport.height) / 2,
+            );
+        } else if self.panned_glyph_size != Some(glyph_size) {
+            self.panned_glyph_size = Some(glyph_size);
+            self.pan_offset = (0, 0);
+        }
+
+        self.pan_offset = (
+            min(
+                self.pan_offset.0,
+                glyph_size.width.saturating_sub(viewport.width),
+            ),
+            min(
+                self.pan_offset.1,
+                glyph_size.height.saturating_sub(viewport.height),
+            ),
+        );
+        self.pan_offset
+    }
+
+    // Color glyphs (e.g. emoji) are drawn with half blocks in truecolor, since every dot of a
+    // braille pattern has the same color
+    fn preview_renderer(&self) -> PreviewRenderer {
+        let has_color_glyphs = match &self.character_preview {
+            Ok(character_preview) => character_preview.has_color_glyphs(),
+            Err(_) => false,
+        };
+        // The font diff tells the ink of each font apart by color, which a braille cell would
+        // collapse into one
+        let needs_colors = has_color_glyphs || self.preview_mode == PreviewMode::FontDiff;
+        match self.settings.preview_renderer {
+            PreviewRenderer::Auto | PreviewRenderer::Braille if needs_colors => {
+                PreviewRenderer::HalfBlock
+            }
+            preview_renderer => preview_renderer,
+        }
+    }
+
+    // The images drawn by the last draw, to be written over the preview once the TUI is drawn
+    pub fn take_terminal_images(&mut self) -> Vec<TerminalImage> {
+        self.terminal_images.replace(vec![])
+    }
+
+    fn draws_terminal_images(&self) -> bool {
+        preview_backend(self.settings.preview_protocol).is_some()
+    }
+
+    fn theme(&self) -> Theme {
+        Theme::of(self.settings.palette)
+    }
+
+    fn pixel_size(&self, width_in_cells: u16, height_in_cells: u16) -> RenderSize {
+        let (pixels_per_cell_horizontal, pixels_per_cell_vertical) = match self.preview_renderer() {
+            _ if self.draws_terminal_images() => self.settings.image_cell_size(),
+            PreviewRenderer::Auto | PreviewRenderer::Braille => (
+                BRAILLE_PATTERN_DOTS_PER_CELL_HORIZONTAL,
+                BRAILLE_PATTERN_DOTS_PER_CELL_VERTICAL,
+            ),
+            PreviewRenderer::HalfBlock | PreviewRenderer::Ascii => (
+                TEXT_PIXELS_PER_CELL_HORIZONTAL,
+                TEXT_PIXELS_PER_CELL_VERTICAL,
+            ),
+        };
+        RenderSize::new(
+            width_in_cells as usize * pixels_per_cell_horizontal as usize,
+            height_in_cells as usize * pixels_per_cell_vertical as usize,
+        )
+    }
+
+    fn draw_shapes(
+        &self,
+        frame: &mut TerminalFrame,
+        rect: Rect,
+        shapes: &[Box<dyn PixelShape + '_>],
+    ) {
+        // The cells of the preview are left blank, the image is written over them
+        if let Some(preview_backend) = preview_backend(self.settings.preview_protocol) {
+            let pixel_size = self.pixel_size(rect.width, rect.height);
+            let mut image_canvas = ImageCanvas::new(pixel_size.width, pixel_size.height);
+            for shape in shapes {
+                shape.paint_pixels(&mut |x, y, color| image_canvas.paint(x, y, color));
+            }
+            self.terminal_images.borrow_mut().push(TerminalImage {
+                rect,
+                data: preview_backend.encode(&image_canvas, rect.width, rect.height),
+            });
+            return;
+        }
+
+        let text_marker = match self.preview_renderer() {
+            PreviewRenderer::Auto | PreviewRenderer::Braille => {
+                let canvas = Canvas::default().paint(|ctx| {
+                    for shape in shapes {
+                        ctx.draw(&BrailleShape(shape.as_ref()));
+                        if shape.is_backdrop() {
+                            ctx.layer();
+                        }
+                    }
+                });
+                frame.render_widget(canvas, rect);
+                return;
+            }
+            PreviewRenderer::HalfBlock => TextMarker::HalfBlock,
+            PreviewRenderer::Ascii => TextMarker::Ascii,
+        };
+
+        let mut text_canvas = TextCanvas::new(text_marker, rect.width, rect.height);
+        for shape in shapes {
+            shape.paint_pixels(&mut |x, y, color| text_canvas.paint(x, y, color));
+        }
+        let text = text_canvas.text();
+        frame.render_widget(Paragraph::new(text.iter()), rect);
+    }
+
+    fn draw_font_selection(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        // Without the hints, the font name gets the whole width
+        let hint_width = if self.settings.show_font_hints {
+            FONT_HINT_WIDTH_IN_CELLS
+        } else {
+            0
+        };
+        let chunks = Layout::default()
+            .horizontal_margin(1)
+            .constraints(
+                [
+                    Constraint::Length(hint_width),
+                    Constraint::Min(1),
+                    Constraint::Length(hint_width),
+                ]
+                .as_ref(),
+            )
+            .direction(Direction::Horizontal)
+            .split(rect);
+
+        self.update_block_coverage();
+        let theme = self.theme();
+        match &self.character_preview {
+            Ok(character_preview) => {
+                if character_preview.has_previous_font() && self.settings.show_font_hints {
+                    let help_item = [Text::raw("[\u{2190}]: Prev. Font")];
+                    let help_text = Paragraph::new(help_item.iter())
+                        .style(Style::default().fg(theme.accent))
+                        .alignment(Alignment::Left);
+                    frame.render_widget(help_text, chunks[0]);
+                }
+                {
+                    let mut font_label = character_preview.get_current_font_display_name();
+                    let face_count = character_preview.face_count();
+                    if face_count > 1 {
+                        font_label = format!(
+                            "{} [Face {}/{}]",
+                            font_label,
+                            character_preview.current_face_index() + 1,
+                            face_count
+                        );
+                    }
+                    let variation_axes = character_preview.variation_axes();
+                    if !variation_axes.is_empty() {
+                        font_label = format!(
+                            "{} ({})",
+                            font_label,
+                            variation_values_label(&variation_axes)
+                        );
+                    }
+                    // The coverage of the block follows the font name, when there is room for both
+                    let font_label_rect = match &self.block_coverage {
+                        Some(block_coverage)
+                            if chunks[1].width > 2 * BLOCK_COVERAGE_WIDTH_IN_CELLS =>
+                        {
+                            let font_label_chunks = Layout::default()
+                                .constraints(
+                                    [
+                                        Constraint::Min(1),
+                                        Constraint::Length(BLOCK_COVERAGE_WIDTH_IN_CELLS),
+                                    ]
+                                    .as_ref(),
+                                )
+                                .direction(Direction::Horizontal)
+                                .split(chunks[1]);
+                            let label = block_coverage.label();
+                            let gauge = Gauge::default()
+                                .style(Style::default().fg(theme.accent))
+                                .ratio(block_coverage.ratio())
+                                .label(&label);
+                            frame.render_widget(gauge, font_label_chunks[1]);
+                            font_label_chunks[0]
+                        }
+                        _ => chunks[1],
+                    };
+                    let help_item = [Text::raw(font_label)];
+                    let help_text = Paragraph::new(help_item.iter())
+                        .style(Style::default())
+                        .alignment(Alignment::Center);
+                    frame.render_widget(help_text, font_label_rect);
+                }
+                if character_preview.has_next_font() && self.settings.show_font_hints {
+                    let help_item = [Text::raw("[\u{2192}]: Next Font")];
+                    let help_text = Paragraph::new(help_item.iter())
+                        .style(Style::default().fg(theme.accent))
+                        .alignment(Alignment::Right);
+                    frame.render_widget(help_text, chunks[2]);
+                }
+            }
+            Err(error) => {
+                let help_item = [Text::raw(format!("Preview Not Available: {}", error))];
+                let help_text = Paragraph::new(help_item.iter())
+                    .style(Style::default().fg(theme.accent))
+                    .alignment(Alignment::Center);
+                frame.render_widget(help_text, chunks[1]);
+            }
+        }
+    }
+
+    fn draw_borders(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let block = Block::default().title("Preview").borders(Borders::ALL);
+        frame.render_widget(block, rect);
+    }
+}
+
+// The font of the private use character in the settings takes precedence over the preferred font,
+// its first face being previewed
+fn preferred_face_of<'a>(
+    settings: &'a Settings,
+    chr: char,
+    preferred_font_path: Option<&'a String>,
+    preferred_face_index: isize,
+) -> (Option<&'a String>, isize) {
+    match settings.private_use_font_of(chr) {
+        Some(font_path) => (Some(font_path), 0),
+        None => (preferred_font_path, preferred_face_index),
+    }
+}
+
+fn font_info_label(font_info: &FontInfo) -> String {
+    let mut format = font_info
+        .format
+        .clone()
+        .unwrap_or_else(|| "Unknown Format".to_owned());
+    if let Some(version) = &font_info.version {
+        format = format!("{} {}", format, version);
+    }
+    let mut details = vec![
+        font_info.path.clone(),
+        format,
+        format!("{} glyphs", font_info.glyph_count),
+    ];
+    if font_info.is_variable {
+        details.push("Variable".to_owned());
+    }
+    if font_info.has_color_glyphs {
+        details.push("Color".to_owned());
+    }
+    details.join(" | ")
+}
+
+// The symbol of a whitespace character along with its advance in pixels at the size of the preview,
+// e.g. "SP | 12 px" for a space
+fn whitespace_label(chr: char, advance_width: usize) -> String {
+    let symbol = non_printing_symbol(chr).unwrap_or_else(|| code_point_description(chr));
+    format!("{} | {} px", symbol, advance_width)
+}
+
+// What the terminal is given to draw a character with its own font. Non-printing characters would
+// be interpreted (or not drawn at all) by the terminal, they are named instead. Combining marks are
+// drawn on a dotted circle, as in the code charts.
+fn terminal_glyph_text(chr: char) -> String {
+    if let Some(symbol) = non_printing_symbol(chr) {
+        return symbol;
+    }
+    if GeneralCategory::of(chr).is_mark() {
+        return format!("{}{}", DOTTED_CIRCLE, chr);
+    }
+    chr.to_string()
+}
+
+// The value of each design axis of a variable font by its tag, e.g. "wght 700 wdth 100"
+fn variation_values_label(variation_axes: &[VariationAxis]) -> String {
+    variation_axes
+        .iter()
+        .map(|axis| format!("{} {}", axis.tag, axis.value))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// The file stem of the current font, along with the face index for font collections, so that files
+// exported from different fonts don't overwrite each other
+fn export_font_name(character_preview: &CharacterPreview) -> String {
+    let font_name = character_preview
+        .get_current_font_path()
+        .as_ref()
+        .and_then(|font_path| Path::new(font_path).file_stem())
+        .map(|file_stem| file_stem.to_string_lossy().replace(' ', "-"))
+        .unwrap_or_else(|| "unknown-font".to_owned());
+    if character_preview.face_count() > 1 {
+        format!("{}-{}", font_name, character_preview.current_face_index())
+    } else {
+        font_name
+    }
+}
+
+// The character in the middle of the area, labeled below so that it isn't mistaken for a render
+fn draw_terminal_glyph(frame: &mut TerminalFrame, rect: Rect, chr: char, color: Color) {
+    let chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Percentage(50),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Percentage(50),
+            ]
+            .as_ref(),
+        )
+        .direction(Direction::Vertical)
+        .split(rect);
+
+    let glyph_item = [Text::raw(terminal_glyph_text(chr))];
+    let glyph_text = Paragraph::new(glyph_item.iter()).alignment(Alignment::Center);
+    frame.render_widget(glyph_text, chunks[1]);
+
+    let label_item = [Text::raw("Terminal Font")];
+    let label_text = Paragraph::new(label_item.iter())
+        .style(Style::default().fg(color))
+        .alignment(Alignment::Center);
+    frame.render_widget(label_text, chunks[3]);
+}
+
+// Advances, bearings and bounding box of the previewed glyph, nothing for previews without a glyph
+fn glyph_metrics_lines(glyph_preview: &GlyphPreview) -> Vec<String> {
+    let rendered_character = match glyph_preview {
+        GlyphPreview::Glyph {
+            rendered_character, ..
+        }
+        | GlyphPreview::Oversized {
+            rendered_character, ..
+        } => rendered_character,
+        _ => return vec![],
+    };
+    let bounding_box = rendered_character.bounding_box();
+    vec![
+        format!(
+            "Advance: {} x {} px | Bounding Box: ({}, {}) to ({}, {})",
+            rendered_character.advance_width,
+            rendered_character.advance_height,
+            bounding_box.x_min,
+            bounding_box.y_min,
+            bounding_box.x_max,
+            bounding_box.y_max
+        ),
+        format!(
+            "Bearings: ({}, {}) horizontal, ({}, {}) vertical",
+            rendered_character.bitmap_left,
+            rendered_character.bitmap_top,
+            rendered_character.vertical_bearing_x,
+            rendered_character.vertical_bearing_y
+        ),
+    ]
+}
+
+fn draw_glyph_metrics(
+    frame: &mut TerminalFrame,
+    rect: Rect,
+    glyph_preview: &GlyphPreview,
+    color: Color,
+) {
+    let metrics_text = [Text::raw(glyph_metrics_lines(glyph_preview).join("\n"))];
+    let metrics_paragraph = Paragraph::new(metrics_text.iter())
+        .style(Style::default().fg(color))
+        .alignment(Alignment::Center);
+    frame.render_widget(metrics_paragraph, rect);
+}
+
+fn draw_centered_message(frame: &mut TerminalFrame, rect: Rect, message: &str, color: Color) {
+    let message_item = [Text::raw(message)];
+    let message_text = Paragraph::new(message_item.iter())
+        .style(Style::default().fg(color))
+        .alignment(Alignment::Center);
+    let chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Percentage(50),
+                Constraint::Length(1),
+                Constraint::Percentage(50),
+            ]
+            .as_ref(),
+        )
+        .direction(Direction::Vertical)
+        .split(rect);
+    frame.render_widget(message_text, chunks[1]);
+}
+
+// Splits the area into the cells of a strip of `count` characters, wrapping to as many rows as
+// needed. Each cell has room for a glyph and the label below it, paired with the index of its
+// character. Rows which don't fit are left out, the strip scrolls down until the row of
+// `shown_index` is visible.
+fn layout_code_point_strip(rect: Rect, count: usize, shown_index: usize) -> Vec<(usize, Rect)> {
+    let row_height = STRIP_GLYPH_HEIGHT_IN_CELLS + 1 + STRIP_SPACING_IN_CELLS / 2;
+    let columns = max(1, rect.width / STRIP_CELL_WIDTH_IN_CELLS) as usize;
+    // The last row needs no spacing below it
+    let visible_rows = ((rect.height + STRIP_SPACING_IN_CELLS / 2) / row_height) as usize;
+    if visible_rows == 0 || rect.width < STRIP_CELL_WIDTH_IN_CELLS {
+        return vec![];
+    }
+
+    let first_row = (shown_index / columns).saturating_sub(visible_rows - 1);
+    (first_row * columns..count)
+        .take(visible_rows * columns)
+        .map(|index| {
+            let column = (index % columns) as u16;
+            let row = (index / columns - first_row) as u16;
+            let cell = Rect::new(
+                rect.x + column * STRIP_CELL_WIDTH_IN_CELLS,
+                rect.y + row * row_height,
+                STRIP_CELL_WIDTH_IN_CELLS,
+                STRIP_GLYPH_HEIGHT_IN_CELLS + 1,
+            );
+            (index, cell)
+        })
+        .collect()
+}
+
+// What the glyph preview shows, worked out apart from drawing it so that it can be tested without
+// a terminal
+#[derive(Debug)]
+enum GlyphPreview {
+    Message(String),
+
+    // A glyph larger than the canvas, shown alone through a viewport which can be panned
+    Oversized {
+        rendered_character: RenderedCharacter,
+        viewport: RenderSize,
+        offset: (usize, usize),
+    },
+
+    // A glyph centered on the canvas, along with the reference glyph and the metrics of the font
+    // for its guides if they are shown
+    Glyph {
+        rendered_character: RenderedCharacter,
+        x_padding: usize,
+        y_padding: usize,
+        reference_character: Option<RenderedCharacter>,
+        face_metrics: Option<FaceMetrics>,
+    },
+
+    // A whitespace character, outlined by a box of its advance width and named by its label
+    Whitespace {
+        label: String,
+        size: RenderSize,
+        x_padding: usize,
+        y_padding: usize,
+    },
+
+    // Drawn in place of a glyph which couldn't be rendered
+    Tofu {
+        size: RenderSize,
+        x_padding: usize,
+        y_padding: usize,
+    },
+}
+
+struct PositionedCharacter {
+    rendered_character: RenderedCharacter,
+    x_padding: usize,
+    y_padding: usize,
+}
+
+// Returns the (ascent, descent) in pixels of a row of rendered characters sharing the same baseline
+fn vertical_extent(rendered_characters: &[RenderedCharacter]) -> (i32, i32) {
+    let ascent = rendered_characters
+        .iter()
+        .map(|rendered_character| rendered_character.bitmap_top)
+        .max()
+        .unwrap_or(0);
+    let descent = rendered_characters
+        .iter()
+        .map(|rendered_character| {
+            rendered_character.glyph_size.height as i32 - rendered_character.bitmap_top
+        })
+        .max()
+        .unwrap_or(0);
+    (ascent, descent)
+}
+
+fn layout_comparison_grid(
+    character_preview: &CharacterPreview,
+    characters: &str,
+    canvas_pixel_size: RenderSize,
+) -> Vec<PositionedCharacter> {
+    let render_characters = |pixel_length: usize| -> Vec<RenderedCharacter> {
+        characters
+            .chars()
+            .filter_map(|chr| {
+                character_preview
+                    .render_character(chr, RenderSize::new(pixel_length, pixel_length))
+                    .ok()
+            })
+            .collect()
+    };
+
+    // Render once at the canvas height, then scale down so that the whole row fits the canvas
+    let mut pixel_length = canvas_pixel_size.height;
+    let mut rendered_characters = render_characters(pixel_length);
+    {
+        let total_advance: usize = rendered_characters
+            .iter()
+            .map(|rendered_character| rendered_character.advance_width)
+            .sum();
+        let (ascent, descent) = vertical_extent(&rendered_characters);
+        if total_advance == 0 || ascent + descent <= 0 {
+            return vec![];
+        }
+
+        let scale = f64::min(
+            canvas_pixel_size.width as f64 / total_advance as f64,
+            canvas_pixel_size.height as f64 / (ascent + descent) as f64,
+        );
+        if scale < 1.0 {
+            pixel_length = (pixel_length as f64 * scale) as usize;
+            if pixel_length == 0 {
+                return vec![];
+            }
+            rendered_characters = render_characters(pixel_length);
+        }
+    }
+
+    let total_advance: usize = rendered_characters
+        .iter()
+        .map(|rendered_character| rendered_character.advance_width)
+        .sum();
+    let (ascent, descent) = vertical_extent(&rendered_characters);
+
+    let mut pen_x = (canvas_pixel_size.width.saturating_sub(total_advance) / 2) as i32;
+    let baseline_y = max(canvas_pixel_size.height as i32 - (ascent + descent), 0) / 2 + ascent;
+
+    rendered_characters
+        .into_iter()
+        .map(|rendered_character| {
+            let x_padding = max(pen_x + rendered_character.bitmap_left, 0) as usize;
+            let y_padding = max(baseline_y - rendered_character.bitmap_top, 0) as usize;
+            pen_x += rendered_character.advance_width as i32;
+            PositionedCharacter {
+                rendered_character,
+                x_padding,
+                y_padding,
+            }
+        })
+        .collect()
+}
+
+// Something drawn pixel by pixel, on either a braille canvas or a text canvas
+trait PixelShape {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color));
+
+    // Same as `paint_pixels`, on a canvas whose cells of `cell_size` pixels have a single color
+    fn paint_cells(&self, _cell_size: (usize, usize), paint: &mut dyn FnMut(usize, usize, Color)) {
+        self.paint_pixels(paint);
+    }
+
+    // Dots of a braille cell share a single color, shapes drawn behind others (e.g. the dot grid)
+    // are drawn on a layer of their own so that the cells drawn over show the dots and the color of
+    // the shapes above alone
+    fn is_backdrop(&self) -> bool {
+        false
+    }
+}
+
+struct BrailleShape<'a>(&'a dyn PixelShape);
+
+impl Shape for BrailleShape<'_> {
+    fn draw(&self, painter: &mut Painter) {
+        let cell_size = (
+            BRAILLE_PATTERN_DOTS_PER_CELL_HORIZONTAL as usize,
+            BRAILLE_PATTERN_DOTS_PER_CELL_VERTICAL as usize,
+        );
+        self.0
+            .paint_cells(cell_size, &mut |x, y, color| painter.paint(x, y, color));
+    }
+}
+
+struct CharacterPreviewShape<'a> {
+    rendered_character: &'a RenderedCharacter,
+    x_padding: usize,
+    y_padding: usize,
+    glyph_renderer: &'a dyn GlyphRenderer,
+}
+
+impl PixelShape for CharacterPreviewShape<'_> {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        self.glyph_renderer
+            .paint_glyph(self.rendered_character, &mut |x, y, color| {
+                paint(x + self.x_padding, y + self.y_padding, color)
+            });
+    }
+
+    fn paint_cells(&self, cell_size: (usize, usize), paint: &mut dyn FnMut(usize, usize, Color)) {
+        let cell_phase = (self.x_padding % cell_size.0, self.y_padding % cell_size.1);
+        self.glyph_renderer.paint_glyph_in_cells(
+            self.rendered_character,
+            cell_size,
+            cell_phase,
+            &mut |x, y, color| paint(x + self.x_padding, y + self.y_padding, color),
+        );
+    }
+}
+
+// Draws another glyph of the same font faintly, sharing the pen origin of the previewed glyph, so
+// that it is drawn behind it at its actual position. Points outside of the canvas grid are clipped.
+struct ReferenceGlyphShape<'a> {
+    rendered_character: &'a RenderedCharacter,
+    origin: (i32, i32),
+    grid_size: RenderSize,
+    color: Color,
+}
+
+impl PixelShape for ReferenceGlyphShape<'_> {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        let left = self.origin.0 + self.rendered_character.bitmap_left;
+        let top = self.origin.1 - self.rendered_character.bitmap_top;
+        for (y, row) in self.rendered_character.bitmap.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                let (x, y) = (left + x as i32, top + y as i32);
+                if *pixel == 0u8
+                    || x < 0
+                    || y < 0
+                    || x as usize >= self.grid_size.width
+                    || y as usize >= self.grid_size.height
+                {
+                    continue;
+                }
+                paint(x as usize, y as usize, self.color);
+            }
+        }
+    }
+}
+
+// Draws the ink bounding box and the pen origin of a rendered character drawn at the same padding,
+// points outside of the canvas grid are clipped
+struct GlyphBoundsShape<'a> {
+    rendered_character: &'a RenderedCharacter,
+    x_padding: usize,
+    y_padding: usize,
+    grid_size: RenderSize,
+    theme: Theme,
+}
+
+impl PixelShape for GlyphBoundsShape<'_> {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        let grid_size = self.grid_size;
+        let mut paint_clipped = |x: i32, y: i32, color: Color| {
+            if x >= 0 && y >= 0 && (x as usize) < grid_size.width && (y as usize) < grid_size.height
+            {
+                paint(x as usize, y as usize, color);
+            }
+        };
+
+        let glyph_size = self.rendered_character.glyph_size;
+        if glyph_size.width > 0 && glyph_size.height > 0 {
+            let (left, top) = (self.x_padding as i32, self.y_padding as i32);
+            let right = left + glyph_size.width as i32 - 1;
+            let bottom = top + glyph_size.height as i32 - 1;
+            for x in left..=right {
+                paint_clipped(x, top, self.theme.glyph_bounds);
+                paint_clipped(x, bottom, self.theme.glyph_bounds);
+            }
+            for y in top..=bottom {
+                paint_clipped(left, y, self.theme.glyph_bounds);
+                paint_clipped(right, y, self.theme.glyph_bounds);
+            }
+        }
+
+        let origin_x = self.x_padding as i32 - self.rendered_character.bitmap_left;
+        let origin_y = self.y_padding as i32 + self.rendered_character.bitmap_top;
+        for delta in -2..=2 {
+            paint_clipped(origin_x + delta, origin_y, self.theme.pen_origin);
+            paint_clipped(origin_x, origin_y + delta, self.theme.pen_origin);
+        }
+    }
+}
+
+// Draws the baseline (solid), the x-height, ascender and descender lines (dotted) across the canvas
+// grid, and the em box (dotted) from the pen origin at `origin`. Drawn before the glyph, the glyph
+// covers the cells it inks.
+struct GuidesShape {
+    face_metrics: FaceMetrics,
+    origin: (i32, i32),
+    grid_size: RenderSize,
+    color: Color,
+}
+
+impl PixelShape for GuidesShape {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        let grid_size = self.grid_size;
+        let mut paint_clipped = |x: i32, y: i32| {
+            if x >= 0 && y >= 0 && (x as usize) < grid_size.width && (y as usize) < grid_size.height
+            {
+                paint(x as usize, y as usize, self.color);
+            }
+        };
+
+        let (origin_x, baseline_y) = self.origin;
+        let face_metrics = self.face_metrics;
+        let mut horizontal_lines = vec![
+            (baseline_y, 1),
+            (baseline_y - face_metrics.ascender, 2),
+            (baseline_y - face_metrics.descender, 2),
+        ];
+        if let Some(x_height) = face_metrics.x_height {
+            horizontal_lines.push((baseline_y - x_height, 2));
+        }
+        for (y, step) in horizontal_lines {
+            for x in (0..grid_size.width as i32).step_by(step) {
+                paint_clipped(x, y);
+            }
+        }
+
+        // The em box is split above and below the baseline like the ascender and descender are
+        let em_size = face_metrics.em_size as i32;
+        let line_height = face_metrics.ascender - face_metrics.descender;
+        let em_top = if line_height > 0 {
+            baseline_y - em_size * face_metrics.ascender / line_height
+        } else {
+            baseline_y - em_size
+        };
+        let (em_right, em_bottom) = (origin_x + em_size, em_top + em_size);
+        for x in (origin_x..=em_right).step_by(2) {
+            paint_clipped(x, em_top);
+            paint_clipped(x, em_bottom);
+        }
+        for y in (em_top..=em_bottom).step_by(2) {
+            paint_clipped(origin_x, y);
+            paint_clipped(em_right, y);
+        }
+    }
+    fn is_backdrop(&self) -> bool {
+        true
+    }
+}
+
+// Marks the first dot of each braille cell over the whole canvas grid, so that the cells (and so
+// the resolution of the preview) can be told apart. Drawn first, the glyph covers the cells it
+// inks.
+struct DotGridShape {
+    grid_size: RenderSize,
+    color: Color,
+}
+
+impl PixelShape for DotGridShape {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        let horizontal_step = BRAILLE_PATTERN_DOTS_PER_CELL_HORIZONTAL as usize;
+        let vertical_step = BRAILLE_PATTERN_DOTS_PER_CELL_VERTICAL as usize;
+        for y in (0..self.grid_size.height).step_by(vertical_step) {
+            for x in (0..self.grid_size.width).step_by(horizontal_step) {
+                paint(x, y, self.color);
+            }
+        }
+    }
+
+    fn is_backdrop(&self) -> bool {
+        true
+    }
+}
+
+// Draws the portion of a rendered character starting at `offset` which fits in `viewport`
+struct ViewportShape<'a> {
+    rendered_character: &'a RenderedCharacter,
+    viewport: RenderSize,
+    offset: (usize, usize),
+    glyph_renderer: &'a dyn GlyphRenderer,
+}
+
+impl PixelShape for ViewportShape<'_> {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        self.paint_viewport(None, paint);
+    }
+
+    fn paint_cells(&self, cell_size: (usize, usize), paint: &mut dyn FnMut(usize, usize, Color)) {
+        self.paint_viewport(Some(cell_size), paint);
+    }
+}
+
+impl ViewportShape<'_> {
+    fn paint_viewport(
+        &self,
+        cell_size: Option<(usize, usize)>,
+        paint: &mut dyn FnMut(usize, usize, Color),
+    ) {
+        let glyph_size = self.rendered_character.glyph_size;
+        let x_padding = self.viewport.width.saturating_sub(glyph_size.width) / 2;
+        let y_padding = self.viewport.height.saturating_sub(glyph_size.height) / 2;
+
+        let (x_offset, y_offset) = self.offset;
+        let x_end = min(glyph_size.width, x_offset + self.viewport.width);
+        let y_end = min(glyph_size.height, y_offset + self.viewport.height);
+
+        let mut paint_visible = |x: usize, y: usize, color: Color| {
+            if x >= x_offset && x < x_end && y >= y_offset && y < y_end {
+                paint(x - x_offset + x_padding, y - y_offset + y_padding, color)
+            }
+        };
+        match cell_size {
+            Some(cell_size) => {
+                // Where the top left corner of the bitmap lies in its cell, once panned
+                let phase = |padding: usize, offset: usize, cell_length: usize| {
+                    (padding as isize - offset as isize).rem_euclid(cell_length as isize) as usize
+                };
+                let cell_phase = (
+                    phase(x_padding, x_offset, cell_size.0),
+                    phase(y_padding, y_offset, cell_size.1),
+                );
+                self.glyph_renderer.paint_glyph_in_cells(
+                    self.rendered_character,
+                    cell_size,
+                    cell_phase,
+                    &mut paint_visible,
+                );
+            }
+            None => self
+                .glyph_renderer
+                .paint_glyph(self.rendered_character, &mut paint_visible),
+        }
+    }
+}
+
+// Draws a glyph diff with a color for the ink of each font, points outside of the canvas grid are
+// clipped
+struct GlyphDiffShape<'a> {
+    glyph_diff: &'a GlyphDiff,
+    x_padding: usize,
+    y_padding: usize,
+    grid_size: RenderSize,
+    theme: Theme,
+}
+
+impl PixelShape for GlyphDiffShape<'_> {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        let size = self.glyph_diff.size;
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let color = match self.glyph_diff.pixel(x, y) {
+                    DiffPixel::Neither => continue,
+                    DiffPixel::OnlyFirst => self.theme.first_font_ink,
+                    DiffPixel::OnlySecond => self.theme.second_font_ink,
+                    DiffPixel::Both => Color::Reset,
+                };
+                let (x, y) = (x + self.x_padding, y + self.y_padding);
+                if x < self.grid_size.width && y < self.grid_size.height {
+                    paint(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+// The outline of a box, every other pixel of it
+struct DottedBoxShape {
+    size: RenderSize,
+    x_padding: usize,
+    y_padding: usize,
+    color: Color,
+}
+
+impl PixelShape for DottedBoxShape {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        if self.size.width == 0 || self.size.height == 0 {
+            return;
+        }
+        let (right, bottom) = (self.size.width - 1, self.size.height - 1);
+        for x in (0..=right).step_by(2) {
+            paint(x + self.x_padding, self.y_padding, self.color);
+            paint(x + self.x_padding, bottom + self.y_padding, self.color);
+        }
+        for y in (0..=bottom).step_by(2) {
+            paint(self.x_padding, y + self.y_padding, self.color);
+            paint(right + self.x_padding, y + self.y_padding, self.color);
+        }
+    }
+}
+
+struct ToufuShape {
+    size: RenderSize,
+    x_padding: usize,
+    y_padding: usize,
+}
+
+impl PixelShape for ToufuShape {
+    fn paint_pixels(&self, paint: &mut dyn FnMut(usize, usize, Color)) {
+        for x in 0..self.size.width {
+            for y in 0..self.size.height {
+                paint(x + self.x_padding, y + self.y_padding, Color::Reset)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::CoverageRenderer;
+
+    fn canvas_of(chr: char) -> CharacterPreviewCanvas {
+        CharacterPreviewCanvas::new(
+            &PreviewContext::new().unwrap(),
+            chr,
+            None,
+            0,
+            Rc::new(CoverageRenderer::new(None, 1)),
+            Rc::new(Settings::default()),
+        )
+    }
+
+    #[test]
+    fn test_glyph_preview() {
+        let mut canvas = canvas_of('A');
+        let glyph_preview = canvas.glyph_preview(40, 20);
+        let (rendered_character, x_padding, y_padding) = match &glyph_preview {
+            GlyphPreview::Glyph {
+                rendered_character,
+                x_padding,
+                y_padding,
+                ..
+            } => (rendered_character, *x_padding, *y_padding),
+            // No font could be found to render the character
+            GlyphPreview::Tofu { .. } => return,
+            glyph_preview => panic!("unexpected glyph preview {:?}", glyph_preview),
+        };
+        assert!(!rendered_character.is_blank());
+
+        // The glyph is centered on the canvas
+        let canvas_size =
+            canvas.pixel_size(40 - RENDER_PADDING_IN_CELLS, 20 - RENDER_PADDING_IN_CELLS);
+        let glyph_size = rendered_character.glyph_size;
+        assert_eq!(x_padding, (canvas_size.width - glyph_size.width) / 2);
+        assert_eq!(y_padding, (canvas_size.height - glyph_size.height) / 2);
+
+        // Every painted pixel falls on the glyph
+        let mut pixels = vec![];
+        for shape in canvas.glyph_preview_shapes(&glyph_preview, canvas.pixel_size(40, 20)) {
+            shape.paint_pixels(&mut |x, y, _| pixels.push((x, y)));
+        }
+        assert!(!pixels.is_empty());
+        assert!(pixels.iter().all(|(x, y)| {
+            (x_padding..x_padding + glyph_size.width).contains(x)
+                && (y_padding..y_padding + glyph_size.height).contains(y)
+        }));
+    }
+
+    #[test]
+    fn test_glyph_metrics_lines() {
+        assert!(glyph_metrics_lines(&GlyphPreview::Message("Blank Glyph".to_owned())).is_empty());
+
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![255; 3]; 4],
+            colors: None,
+            glyph_size: RenderSize::new(3, 4),
+            bitmap_left: 1,
+            bitmap_top: 3,
+            advance_width: 5,
+            advance_height: 6,
+            vertical_bearing_x: -2,
+            vertical_bearing_y: 1,
+        };
+        let glyph_preview = GlyphPreview::Glyph {
+            rendered_character,
+            x_padding: 0,
+            y_padding: 0,
+            reference_character: None,
+            face_metrics: None,
+        };
+        assert_eq!(
+            glyph_metrics_lines(&glyph_preview),
+            vec![
+                "Advance: 5 x 6 px | Bounding Box: (1, -1) to (4, 3)",
+                "Bearings: (1, 3) horizontal, (-2, 1) vertical",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_font_diff() {
+        let mut canvas = canvas_of('A');
+        canvas.toggle_font_diff();
+        let font_count = match &canvas.character_preview {
+            Ok(character_preview) => character_preview.matching_font_paths().len(),
+            Err(_) => return,
+        };
+        let diff_font_index = canvas.diff_font_index;
+        assert!(diff_font_index < font_count);
+        for _ in 0..font_count {
+            canvas.select_next_diff_font();
+        }
+        assert_eq!(canvas.diff_font_index, diff_font_index);
+
+        // A font compared with itself has no difference
+        if font_count == 1 {
+            let glyph_diff = canvas.font_diff(RenderSize::new(64, 64)).unwrap();
+            assert_eq!(glyph_diff.difference_ratio(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_terminal_glyph_text() {
+        assert_eq!(terminal_glyph_text('A'), "A");
+        assert_eq!(terminal_glyph_text('\u{1F600}'), "\u{1F600}");
+        assert_eq!(terminal_glyph_text('\u{301}'), "\u{25CC}\u{301}");
+        assert_eq!(terminal_glyph_text('\u{1B}'), "\u{241B} ESC");
+    }
+
+    #[test]
+    fn test_variation_values_label() {
+        let axis = |tag: &str, value: f64| VariationAxis {
+            name: String::new(),
+            tag: tag.to_owned(),
+            minimum: 0.0,
+            default: 0.0,
+            maximum: 1000.0,
+            value,
+        };
+        assert_eq!(
+            variation_values_label(&[axis("wght", 700.0), axis("wdth", 87.5)]),
+            "wght 700 wdth 87.5"
+        );
+    }
+
+    #[test]
+    fn test_block_coverage() {
+        let mut block_coverage = BlockCoverage {
+            font_path: None,
+            face_index: 0,
+            covered_count: 87,
+            assigned_count: 128,
+        };
+        assert_eq!(block_coverage.label(), "68% of Block");
+        block_coverage.assigned_count = 0;
+        assert_eq!(block_coverage.label(), "0% of Block");
+
+        let mut canvas = canvas_of('A');
+        canvas.update_block_coverage();
+        if let Ok(character_preview) = &canvas.character_preview {
+            let block_coverage = canvas.block_coverage.clone().unwrap();
+            assert_eq!(
+                block_coverage.font_path,
+                character_preview.get_current_font_path()
+            );
+            // Basic Latin has 128 assigned code points, control characters included
+            assert_eq!(block_coverage.assigned_count, 128);
+            assert!(block_coverage.covered_count > 0);
+        }
+    }
+
+    #[test]
+    fn test_font_info_label() {
+        let mut font_info = FontInfo {
+            display_name: "Noto Sans - Regular".to_owned(),
+            path: "/usr/share/fonts/NotoSans-Regular.ttf".to_owned(),
+            format: Some("TrueType".to_owned()),
+            version: Some("2.007".to_owned()),
+            glyph_count: 3741,
+            is_variable: false,
+            has_color_glyphs: false,
+        };
+        assert_eq!(
+            font_info_label(&font_info),
+            "/usr/share/fonts/NotoSans-Regular.ttf | TrueType 2.007 | 3741 glyphs"
+        );
+
+        font_info.format = None;
+        font_info.version = None;
+        font_info.is_variable = true;
+        font_info.has_color_glyphs = true;
+        assert_eq!(
+            font_info_label(&font_info),
+            "/usr/share/fonts/NotoSans-Regular.ttf | Unknown Format | 3741 glyphs | Variable | Color"
+        );
+    }
+
+    #[test]
+    fn test_whitespace_preview() {
+        let mut canvas = canvas_of(' ');
+        let glyph_preview = canvas.glyph_preview(40, 20);
+        let (label, size, x_padding, y_padding) = match &glyph_preview {
+            GlyphPreview::Whitespace {
+                label,
+                size,
+                x_padding,
+                y_padding,
+            } => (label, *size, *x_padding, *y_padding),
+            GlyphPreview::Tofu { .. } => return,
+            glyph_preview => panic!("unexpected glyph preview {:?}", glyph_preview),
+        };
+        assert!(label.starts_with("\u{2420} SP | "), "{}", label);
+
+        // Only the outline of the box is drawn
+        let mut pixels = vec![];
+        for shape in canvas.glyph_preview_shapes(&glyph_preview, canvas.pixel_size(40, 20)) {
+            shape.paint_pixels(&mut |x, y, _| pixels.push((x, y)));
+        }
+        assert!(!pixels.is_empty());
+        assert!(pixels.iter().all(|(x, y)| {
+            *x == x_padding
+                || *x == x_padding + size.width - 1
+                || *y == y_padding
+                || *y == y_padding + size.height - 1
+        }));
+
+        assert!(canvas.advance_description().is_some());
+        assert!(canvas_of('A').advance_description().is_none());
+    }
+
+    #[test]
+    fn test_toggle_outline() {
+        let mut canvas = canvas_of('\u{25A0}');
+        let ink_pixel_count =
+            |canvas: &mut CharacterPreviewCanvas| match canvas.glyph_preview(40, 20) {
+                GlyphPreview::Glyph {
+                    rendered_character, ..
+                } => Some(
+                    rendered_character
+                        .mask(1)
+                        .iter()
+                        .filter(|set| **set)
+                        .count(),
+                ),
+                _ => None,
+            };
+
+        // The contour of a filled square has less ink than the square
+        let filled_pixel_count = match ink_pixel_count(&mut canvas) {
+            Some(filled_pixel_count) => filled_pixel_count,
+            None => return,
+        };
+        canvas.toggle_outline();
+        let outline_pixel_count = ink_pixel_count(&mut canvas).unwrap();
+        assert!(outline_pixel_count > 0 && outline_pixel_count < filled_pixel_count);
+    }
+
+    #[test]
+    fn test_toggle_combining_base() {
+        assert!(!canvas_of('A').toggle_combining_base());
+        assert_eq!(canvas_of('A').combining_base(), None);
+
+        // Marks are attached to the dotted circle by default, when they can be shaped and the font
+        // has it
+        let mut canvas = canvas_of('\u{0301}');
+        if !cfg!(feature = "shaping") {
+            assert!(!canvas.toggle_combining_base());
+            return;
+        }
+        let has_dotted_circle = canvas
+            .character_preview
+            .as_ref()
+            .map_or(false, |character_preview| {
+                character_preview.has_glyph(DOTTED_CIRCLE)
+            });
+        if has_dotted_circle {
+            assert_eq!(canvas.combining_base(), Some(DOTTED_CIRCLE));
+            assert_eq!(
+                canvas.combining_base_description(),
+                Some("Shaped on \u{25CC} (U+25CC)".to_owned())
+            );
+        }
+        assert!(canvas.toggle_combining_base());
+        assert_eq!(canvas.combining_base(), None);
+    }
+
+    #[test]
+    fn test_toggle_shaped_string() {
+        let mut canvas = canvas_of('f');
+        canvas.toggle_shaped_string("fi \u{1E84}\u{0308}".to_owned());
+        assert!(canvas.preview_mode == PreviewMode::ShapedString);
+        assert_eq!(canvas.shaped_string.as_deref(), Some("fi \u{1E84}\u{0308}"));
+        canvas.toggle_shaped_string(String::new());
+        assert!(canvas.preview_mode == PreviewMode::Glyph);
+    }
+
+    #[test]
+    fn test_cycle_presentation() {
+        assert!(!canvas_of('A').cycle_presentation());
+
+        let mut canvas = canvas_of('\u{2764}');
+        assert_eq!(canvas.presentation_sequence(), None);
+        if !cfg!(feature = "shaping") {
+            assert!(!canvas.cycle_presentation());
+            return;
+        }
+
+        assert!(canvas.cycle_presentation());
+        assert_eq!(
+            canvas.presentation_sequence(),
+            Some("U+2764 U+FE0E".to_owned())
+        );
+        assert!(canvas.cycle_presentation());
+        assert_eq!(
+            canvas.presentation_sequence(),
+            Some("U+2764 U+FE0F".to_owned())
+        );
+        assert!(canvas
+            .presentation_description()
+            .unwrap()
+            .starts_with("Emoji presentation (VS16) | Provided by "));
+        assert!(canvas.cycle_presentation());
+        assert_eq!(canvas.presentation_sequence(), None);
+        assert!(canvas.presentation_fonts.is_empty());
+    }
+
+    #[test]
+    fn test_for_sequence() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let mut canvas = CharacterPreviewCanvas::for_sequence(
+            &PreviewContext::new().unwrap(),
+            family,
+            None,
+            0,
+            Rc::new(CoverageRenderer::new(None, 1)),
+            Rc::new(Settings::default()),
+        );
+        assert_eq!(canvas.chr, '\u{1F468}');
+        if canvas.character_preview.is_err() {
+            return;
+        }
+
+        assert_eq!(canvas.sequence(), Some(family));
+        assert_eq!(
+            canvas.sequence_description(),
+            Some("Sequence of 5 code points, shaped as a whole".to_owned())
+        );
+        assert!(!canvas.cycle_presentation());
+        assert_eq!(canvas.combining_base(), None);
+
+        assert_eq!(canvas_of('A').sequence(), None);
+    }
+
+    #[test]
+    fn test_side_by_side_font_paths() {
+        let canvas = canvas_of('A');
+        let character_preview = match &canvas.character_preview {
+            Ok(character_preview) => character_preview,
+            Err(_) => {
+                assert!(canvas.side_by_side_font_paths().is_empty());
+                return;
+            }
+        };
+
+        let font_paths = canvas.side_by_side_font_paths();
+        assert_eq!(
+            font_paths.len(),
+            character_preview
+                .matching_font_paths()
+                .len()
+                .min(Settings::default().side_by_side_fonts)
+        );
+        assert_eq!(
+            font_paths.first(),
+            character_preview.get_current_font_path().as_ref()
+        );
+    }
+
+    #[test]
+    fn test_cycle_hinting() {
+        let mut canvas = canvas_of('A');
+        match &canvas.character_preview {
+            Ok(character_preview) if character_preview.is_outline_font() => {}
+            _ => {
+                assert!(!canvas.cycle_hinting());
+                return;
+            }
+        }
+
+        assert_eq!(canvas.hinting_description(), None);
+        assert!(canvas.cycle_hinting());
+        assert_eq!(canvas.hinting_description(), Some("No hinting".to_owned()));
+        for _ in 0..4 {
+            assert!(canvas.cycle_hinting());
+        }
+        assert_eq!(canvas.hinting_description(), None);
+    }
+
+    #[test]
+    fn test_zoom() {
+        let mut canvas = canvas_of('\u{25A0}');
+        if let GlyphPreview::Message(_) = canvas.glyph_preview(40, 20) {
+            return;
+        }
+
+        // A zoomed glyph no longer fits the canvas and is panned from its center
+        canvas.zoom_in();
+        canvas.zoom_in();
+        match canvas.glyph_preview(40, 20) {
+            GlyphPreview::Oversized {
+                rendered_character,
+                viewport,
+                offset,
+            } => {
+                let glyph_size = rendered_character.glyph_size;
+                assert!(offset.0 > 0 && offset.0 + viewport.width < glyph_size.width);
+                assert!(offset.1 > 0 && offset.1 + viewport.height < glyph_size.height);
+            }
+            _ => panic!("The zoomed glyph should be larger than the canvas"),
+        }
+
+        for _ in 0..10 {
+            canvas.zoom_in();
+        }
+        assert_eq!(canvas.zoom, MAX_ZOOM);
+        for _ in 0..10 {
+            canvas.zoom_out();
+        }
+        assert_eq!(canvas.zoom, 1);
+        match canvas.glyph_preview(40, 20) {
+            GlyphPreview::Glyph { .. } => {}
+            _ => panic!("The glyph should fit the canvas again"),
+        }
+    }
+
+    #[test]
+    fn test_guides_shape() {
+        let guides_shape = GuidesShape {
+            face_metrics: FaceMetrics {
+                em_size: 10,
+                ascender: 8,
+                descender: -2,
+                x_height: Some(5),
+            },
+            origin: (2, 14),
+            grid_size: RenderSize::new(20, 20),
+            color: Color::DarkGray,
+        };
+        let mut pixels = vec![];
+        guides_shape.paint_pixels(&mut |x, y, _| pixels.push((x, y)));
+        assert!(guides_shape.is_backdrop());
+
+        // The baseline is solid, the other lines are dotted
+        assert!((0..20).all(|x| pixels.contains(&(x, 14))));
+        for y in &[6, 9, 16] {
+            assert!(pixels.contains(&(4, *y)));
+            assert!(!pixels.contains(&(5, *y)));
+        }
+
+        // The em box spans from the ascender to the descender line, from the origin
+        assert!(pixels.contains(&(2, 10)));
+        assert!(pixels.contains(&(12, 10)));
+        assert!(!pixels.contains(&(13, 10)));
+        assert!(pixels.iter().all(|(_, y)| (6..=16).contains(y)));
+
+        let mut canvas = canvas_of('A');
+        canvas.toggle_guides();
+        if let GlyphPreview::Glyph { face_metrics, .. } = canvas.glyph_preview(40, 20) {
+            assert!(face_metrics.is_some());
+        }
+    }
+
+    #[test]
+    fn test_dot_grid_shape() {
+        let dot_grid_shape = DotGridShape {
+            grid_size: RenderSize::new(5, 9),
+            color: Color::DarkGray,
+        };
+        let mut pixels = vec![];
+        dot_grid_shape.paint_pixels(&mut |x, y, _| pixels.push((x, y)));
+        assert_eq!(pixels.len(), 3 * 3);
+        assert!(pixels.contains(&(4, 8)));
+        assert!(pixels.iter().all(|(x, y)| x % 2 == 0 && y % 4 == 0));
+        assert!(dot_grid_shape.is_backdrop());
+
+        // Drawn beneath the glyph, but not behind messages
+        let mut canvas = canvas_of('A');
+        assert!(canvas.toggle_dot_grid());
+        let glyph_preview = GlyphPreview::Message("Blank Glyph".to_owned());
+        assert!(canvas
+            .glyph_preview_shapes(&glyph_preview, RenderSize::new(4, 4))
+            .is_empty());
+        let glyph_preview = GlyphPreview::Tofu {
+            size: RenderSize::new(2, 2),
+            x_padding: 0,
+            y_padding: 0,
+        };
+        assert_eq!(
+            canvas
+                .glyph_preview_shapes(&glyph_preview, RenderSize::new(4, 4))
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_layout_code_point_strip() {
+        // Three cells per row, two rows of a glyph and its label with spacing in between
+        let rect = Rect::new(1, 1, 35, 11);
+        let cells = layout_code_point_strip(rect, 17, 0);
+        assert_eq!(cells.len(), 6);
+        assert_eq!(cells[0], (0, Rect::new(1, 1, 10, 5)));
+        assert_eq!(cells[2], (2, Rect::new(21, 1, 10, 5)));
+        assert_eq!(cells[3], (3, Rect::new(1, 7, 10, 5)));
+
+        // Scrolled down to the shown character, up to the last (partial) row
+        let cells = layout_code_point_strip(rect, 17, 16);
+        assert_eq!(cells.first(), Some(&(12, Rect::new(1, 1, 10, 5))));
+        assert_eq!(cells.last(), Some(&(16, Rect::new(11, 7, 10, 5))));
+
+        assert!(layout_code_point_strip(Rect::new(0, 0, 9, 11), 17, 0).is_empty());
+        assert!(layout_code_point_strip(Rect::new(0, 0, 35, 4), 17, 0).is_empty());
+    }
+
+    #[test]
+    fn test_glyph_preview_messages() {
+        match canvas_of('A').glyph_preview(2, 2) {
+            GlyphPreview::Message(message) => assert_eq!(message, TOO_SMALL_TO_RENDER_MESSAGE),
+            glyph_preview => panic!("unexpected glyph preview {:?}", glyph_preview),
+        }
+
+        let mut canvas = canvas_of('\u{0}');
+        let glyph_preview = canvas.glyph_preview(40, 20);
+        match &glyph_preview {
+            GlyphPreview::Message(message) => assert_eq!(message, "\u{2400} NUL"),
+            glyph_preview => panic!("unexpected glyph preview {:?}", glyph_preview),
         }
+        assert!(canvas
+            .glyph_preview_shapes(&glyph_preview, canvas.pixel_size(40, 20))
+            .is_empty());
     }
 }
@@ -16,13 +16,18 @@ use std::borrow::Cow;
 
 use hex_slice::AsHex;
 use tui::layout::{Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Modifier, Style};
+use tui::style::{Modifier, Style};
 use tui::widgets::{Block, Borders, List, ListState, Text};
+use unic::ucd::Name;
 
 use super::main_view::TerminalFrame;
+use super::theme::Theme;
+use crate::preview::{GlyphInfo, OpenTypeFeature};
 use crate::ucd::{code_point_description, CharacterProperties};
 
 const NOT_AVAILABLE_DISPLAY_TEXT: &str = "N/A";
+const UNNAMED_GLYPH_DISPLAY_TEXT: &str = "unnamed";
+const NO_FEATURES_DISPLAY_TEXT: &str = "none";
 
 fn add_padding_to_column_data(string: &str, column_width: u16) -> String {
     if (column_width as usize) < string.len() {
@@ -35,6 +40,16 @@ fn add_padding_to_column_data(string: &str, column_width: u16) -> String {
     )
 }
 
+// Tags of OpenType features separated by spaces, e.g. "kern liga smcp"
+fn feature_tags<'a>(features: impl Iterator<Item = &'a OpenTypeFeature>) -> String {
+    let tags: Vec<&str> = features.map(|feature| feature.tag.as_str()).collect();
+    if tags.is_empty() {
+        NO_FEATURES_DISPLAY_TEXT.to_owned()
+    } else {
+        tags.join(" ")
+    }
+}
+
 #[derive(Debug, Default)]
 struct PropertyRow {
     title: &'static str,
@@ -51,7 +66,7 @@ impl PropertyRow {
         PropertyRow {
             title,
             value,
-            link: None, // TODO: Link not implemented
+            link: None,
         }
     }
 
@@ -153,6 +168,24 @@ impl PropertyRow {
             }
         }
 
+        let normalization_forms = &character_properties.normalization_forms;
+        property_rows.extend(PropertyRow::from_character_components(
+            "NFC",
+            &normalization_forms.nfc,
+        ));
+        property_rows.extend(PropertyRow::from_character_components(
+            "NFD",
+            &normalization_forms.nfd,
+        ));
+        property_rows.extend(PropertyRow::from_character_components(
+            "NFKC",
+            &normalization_forms.nfkc,
+        ));
+        property_rows.extend(PropertyRow::from_character_components(
+            "NFKD",
+            &normalization_forms.nfkd,
+        ));
+
         property_rows.push(PropertyRow::default());
 
         property_rows.push(PropertyRow::new(
@@ -200,6 +233,38 @@ impl PropertyRow {
         property_rows
     }
 
+    // The glyph of the character within the font of the preview, if there is one
+    fn from_glyph_info(glyph_info: &Option<GlyphInfo>) -> Vec<Self> {
+        match glyph_info {
+            Some(glyph_info) => vec![
+                PropertyRow::new("Glyph Index", glyph_info.index.to_string()),
+                PropertyRow::new(
+                    "Glyph Name",
+                    glyph_info
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| UNNAMED_GLYPH_DISPLAY_TEXT.to_owned()),
+                ),
+                PropertyRow::new(
+                    "Glyph Features",
+                    feature_tags(
+                        glyph_info
+                            .features
+                            .iter()
+                            .filter(|feature| feature.involves_glyph),
+                    ),
+                ),
+                PropertyRow::new("Font Features", feature_tags(glyph_info.features.iter())),
+            ],
+            None => vec![
+                PropertyRow::new("Glyph Index", NOT_AVAILABLE_DISPLAY_TEXT.to_owned()),
+                PropertyRow::new("Glyph Name", NOT_AVAILABLE_DISPLAY_TEXT.to_owned()),
+                PropertyRow::new("Glyph Features", NOT_AVAILABLE_DISPLAY_TEXT.to_owned()),
+                PropertyRow::new("Font Features", NOT_AVAILABLE_DISPLAY_TEXT.to_owned()),
+            ],
+        }
+    }
+
     fn from_name_aliases(title: &'static str, name_aliases: NameAliases) -> Vec<Self> {
         let mut property_rows = vec![];
         match name_aliases {
@@ -244,7 +309,15 @@ impl PropertyRow {
     }
 
     fn from_character(title: &'static str, chr: char) -> Self {
-        PropertyRow::new(title, format!("{} {}", code_point_description(chr), chr))
+        let name = match Name::of(chr) {
+            Some(name) => name.to_string(),
+            None => "".to_owned(),
+        };
+        PropertyRow {
+            title,
+            value: format!("{} {}  {}", code_point_description(chr), chr, name),
+            link: Some(chr),
+        }
     }
 
     fn from_optional_character(title: &'static str, optional_chr: Option<char>) -> Self {
@@ -271,14 +344,23 @@ pub struct CharacterPropertyView {
     // some List, they share the same ListState (and have identical number of rows).
     shared_list_state: ListState,
     rows: Vec<PropertyRow>,
+
+    // The code points of the previewed sequence (e.g. a ZWJ emoji sequence), each linking to its
+    // own detail
+    sequence: Vec<char>,
+    glyph_info: Option<GlyphInfo>,
+    theme: Theme,
 }
 
 impl CharacterPropertyView {
-    pub fn new(chr: char) -> Self {
+    pub fn new(chr: char, theme: Theme) -> Self {
         CharacterPropertyView {
             character_properties: CharacterProperties::new(chr),
             shared_list_state: ListState::default(),
             rows: vec![],
+            sequence: vec![],
+            glyph_info: None,
+            theme,
         }
     }
 
@@ -299,15 +381,24 @@ impl CharacterPropertyView {
                 .horizontal_margin(1)
                 .split(rect);
 
-            self.rows = PropertyRow::from_character_properties(&self.character_properties);
+            self.rows = PropertyRow::from_character_components("Sequence", &self.sequence);
+            if !self.rows.is_empty() {
+                self.rows.push(PropertyRow::default());
+            }
+            self.rows.extend(PropertyRow::from_character_properties(
+                &self.character_properties,
+            ));
+            self.rows.push(PropertyRow::default());
+            self.rows
+                .extend(PropertyRow::from_glyph_info(&self.glyph_info));
 
             let title_list = List::new(self.rows.iter().map(|row| {
                 Text::Styled(
                     Cow::from(add_padding_to_column_data(row.title, chunks[0].width)),
-                    Style::new().fg(Color::LightGreen),
+                    Style::new().fg(self.theme.accent),
                 )
             }))
-            .highlight_style(Style::default().fg(Color::LightGreen));
+            .highlight_style(Style::default().fg(self.theme.accent));
             frame.render_stateful_widget(title_list, chunks[0], &mut self.shared_list_state);
 
             let value_list =
@@ -316,17 +407,38 @@ impl CharacterPropertyView {
             frame.render_stateful_widget(value_list, chunks[2], &mut self.shared_list_state);
         }
 
-        // Draw borders
+        // Draw borders, titled with the Unicode block as well so that it stays in sight while the
+        // properties are scrolled
         {
             let code_point_description =
                 code_point_description(self.character_properties.character);
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .title(&code_point_description);
+            let title = match self.character_properties.block_name {
+                Some(block_name) => format!("{} - {}", code_point_description, block_name),
+                None => code_point_description,
+            };
+            let block = Block::default().borders(Borders::ALL).title(&title);
             frame.render_widget(block, rect);
         }
     }
 
+    pub fn character(&self) -> char {
+        self.character_properties.character
+    }
+
+    pub fn set_sequence(&mut self, sequence: Vec<char>) {
+        self.sequence = sequence;
+    }
+
+    pub fn set_glyph_info(&mut self, glyph_info: Option<GlyphInfo>) {
+        self.glyph_info = glyph_info;
+    }
+
+    // The character linked by the selected row, if any
+    pub fn selected_link(&self) -> Option<char> {
+        let selected = self.shared_list_state.selected()?;
+        self.rows.get(selected)?.link
+    }
+
     pub fn scroll_down(&mut self) {
         if self.rows.is_empty() {
             return;
@@ -0,0 +1,84 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use std::io::{stdout, Result, Write};
+
+use crate::ucd::code_point_description;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CopyFormat {
+    Character,
+    RustEscape,
+    CodePoint,
+    HtmlEntity,
+}
+
+impl CopyFormat {
+    pub fn format(self, chr: char) -> String {
+        match self {
+            CopyFormat::Character => chr.to_string(),
+            CopyFormat::RustEscape => format!("\\u{{{:X}}}", chr as u32),
+            CopyFormat::CodePoint => code_point_description(chr),
+            CopyFormat::HtmlEntity => format!("&#x{:X};", chr as u32),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            CopyFormat::Character => "Character",
+            CopyFormat::RustEscape => "Rust Escape",
+            CopyFormat::CodePoint => "Code Point",
+            CopyFormat::HtmlEntity => "HTML Entity",
+        }
+    }
+
+    // Cycles through the escape formats, skipping the literal character
+    pub fn next_escape(self) -> Self {
+        match self {
+            CopyFormat::Character | CopyFormat::HtmlEntity => CopyFormat::RustEscape,
+            CopyFormat::RustEscape => CopyFormat::CodePoint,
+            CopyFormat::CodePoint => CopyFormat::HtmlEntity,
+        }
+    }
+}
+
+// Copies text into the system clipboard with the OSC 52 escape sequence, which is understood by
+// most terminal emulators (also over SSH) without depending on a clipboard library
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut stdout = stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64::encode(text))?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_formats() {
+        assert_eq!(CopyFormat::Character.format('\u{1F600}'), "\u{1F600}");
+        assert_eq!(CopyFormat::RustEscape.format('\u{1F600}'), "\\u{1F600}");
+        assert_eq!(CopyFormat::CodePoint.format('\u{1F600}'), "U+1F600");
+        assert_eq!(CopyFormat::HtmlEntity.format('\u{1F600}'), "&#x1F600;");
+        assert_eq!(CopyFormat::CodePoint.format('a'), "U+0061");
+    }
+
+    #[test]
+    fn test_next_escape() {
+        assert_eq!(CopyFormat::Character.next_escape(), CopyFormat::RustEscape);
+        assert_eq!(CopyFormat::RustEscape.next_escape(), CopyFormat::CodePoint);
+        assert_eq!(CopyFormat::CodePoint.next_escape(), CopyFormat::HtmlEntity);
+        assert_eq!(CopyFormat::HtmlEntity.next_escape(), CopyFormat::RustEscape);
+    }
+}
@@ -0,0 +1,171 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::widgets::{Block, Borders, Clear, List, ListState, Paragraph, Text};
+
+use super::action::{key_binding_for, Action, ACTIONS};
+use super::main_view::TerminalFrame;
+use super::theme::Theme;
+
+pub enum CommandPaletteEvent {
+    None,
+    Close,
+    Perform(Action),
+}
+
+// Lists the actions whose description fuzzily matches what is typed, and performs the selected one
+pub struct CommandPalette {
+    query: String,
+    state: ListState,
+    theme: Theme,
+}
+
+// Returns how spread out the characters of the query are in the text (lower is better), or `None`
+// if the text doesn't contain all of them in order
+fn fuzzy_match(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut first_match = None;
+    let mut position = 0;
+    for query_chr in query.to_lowercase().chars() {
+        let offset = text[position..].iter().position(|chr| *chr == query_chr)?;
+        first_match.get_or_insert(position + offset);
+        position += offset + 1;
+    }
+    Some(position - first_match.unwrap_or(0))
+}
+
+impl CommandPalette {
+    pub fn new(theme: Theme) -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        CommandPalette {
+            query: String::new(),
+            state,
+            theme,
+        }
+    }
+
+    fn matching_actions(&self) -> Vec<Action> {
+        let mut matching_actions: Vec<(usize, Action)> = ACTIONS
+            .iter()
+            .filter(|action| **action != Action::OpenCommandPalette)
+            .filter_map(|action| {
+                fuzzy_match(&self.query, action.description()).map(|score| (score, *action))
+            })
+            .collect();
+        matching_actions.sort_by_key(|(score, _)| *score);
+        matching_actions
+            .into_iter()
+            .map(|(_, action)| action)
+            .collect()
+    }
+
+    pub fn handle_key_event(&mut self, event: KeyEvent) -> CommandPaletteEvent {
+        let action_count = self.matching_actions().len();
+        let selected = self.state.selected().unwrap_or(0);
+        match event.code {
+            KeyCode::Esc => return CommandPaletteEvent::Close,
+            KeyCode::Enter => {
+                return match self.matching_actions().get(selected) {
+                    Some(action) => CommandPaletteEvent::Perform(*action),
+                    None => CommandPaletteEvent::None,
+                };
+            }
+            KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => {
+                if selected + 1 < action_count {
+                    self.state.select(Some(selected + 1));
+                }
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.state.select(Some(0));
+            }
+            KeyCode::Char(chr) => {
+                self.query.push(chr);
+                self.state.select(Some(0));
+            }
+            _ => {}
+        }
+        CommandPaletteEvent::None
+    }
+
+    pub fn draw(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let chunks = Layout::default()
+            .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+            .direction(Direction::Vertical)
+            .split(rect);
+
+        let query_items = [Text::raw(&self.query)];
+        let query_paragraph = Paragraph::new(query_items.iter())
+            .block(Block::default().borders(Borders::ALL).title("Command"))
+            .style(Style::default().fg(self.theme.input));
+
+        let action_items: Vec<Text> = self
+            .matching_actions()
+            .iter()
+            .map(|action| {
+                let key_description = key_binding_for(*action)
+                    .map(|key_binding| format!(" {}", key_binding.key_description()))
+                    .unwrap_or_default();
+                Text::raw(format!("{}{}", action.description(), key_description))
+            })
+            .collect();
+        let action_list = List::new(action_items.into_iter())
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(
+                Style::default()
+                    .fg(self.theme.accent)
+                    .modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">");
+
+        frame.render_widget(Clear, rect);
+        frame.render_widget(query_paragraph, chunks[0]);
+        frame.render_stateful_widget(action_list, chunks[1], &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("", "Copy the character"), Some(0));
+        assert_eq!(fuzzy_match("copy", "Copy the character"), Some(4));
+        assert_eq!(fuzzy_match("cpy", "Copy the character"), Some(4));
+        assert_eq!(fuzzy_match("yoc", "Copy the character"), None);
+    }
+
+    #[test]
+    fn test_matching_actions() {
+        let mut command_palette = CommandPalette::new(Theme::default());
+        for chr in "glyph bounds".chars() {
+            command_palette.handle_key_event(KeyEvent::from(KeyCode::Char(chr)));
+        }
+        assert_eq!(
+            command_palette.matching_actions().first(),
+            Some(&Action::ToggleGlyphBounds)
+        );
+
+        match command_palette.handle_key_event(KeyEvent::from(KeyCode::Enter)) {
+            CommandPaletteEvent::Perform(action) => assert_eq!(action, Action::ToggleGlyphBounds),
+            _ => panic!("Expected an action to perform"),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Opens the previewed font in an external tool, e.g. a font editor, to go from inspecting a glyph
+//! to editing it.
+//!
+
+use std::io::{Error, ErrorKind, Result};
+use std::process::{Command, Stdio};
+use std::thread;
+
+#[cfg(target_os = "macos")]
+const DEFAULT_OPEN_COMMAND: &str = "open";
+#[cfg(target_os = "windows")]
+const DEFAULT_OPEN_COMMAND: &str = "explorer";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const DEFAULT_OPEN_COMMAND: &str = "xdg-open";
+
+// The program and the arguments to run, the command is split on whitespace and the font path is
+// passed as its last argument
+fn command_line(command: Option<&str>, font_path: &str) -> Vec<String> {
+    let command = command.unwrap_or(DEFAULT_OPEN_COMMAND);
+    let mut command_line: Vec<String> = command.split_whitespace().map(str::to_owned).collect();
+    command_line.push(font_path.to_owned());
+    command_line
+}
+
+// Starts the command without waiting for it, the tool is left running after Cicero exits. Its
+// output is discarded, as it would garble the terminal.
+pub fn open_font_in_editor(command: Option<&str>, font_path: &str) -> Result<()> {
+    let command_line = command_line(command, font_path);
+    if command_line.len() < 2 {
+        return Err(Error::new(ErrorKind::InvalidInput, "the command is empty"));
+    }
+
+    let mut child = Command::new(&command_line[0])
+        .args(&command_line[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Waiting on a thread of its own keeps the finished command from lingering as a zombie
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line() {
+        assert_eq!(
+            command_line(Some("fontforge -quiet"), "/fonts/My Font.ttf"),
+            vec!["fontforge", "-quiet", "/fonts/My Font.ttf"]
+        );
+        assert_eq!(
+            command_line(None, "/fonts/a.ttf"),
+            vec![DEFAULT_OPEN_COMMAND, "/fonts/a.ttf"]
+        );
+        assert!(open_font_in_editor(Some(" "), "/fonts/a.ttf").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::layout::Rect;
+use tui::style::{Modifier, Style};
+use tui::widgets::{Block, Borders, Clear, List, ListState, Text};
+
+use super::main_view::TerminalFrame;
+use super::theme::Theme;
+
+// How many fonts PageUp and PageDown move the selection by
+const FONT_LIST_PAGE_SIZE: usize = 10;
+
+pub enum FontListEvent {
+    None,
+    Close,
+    Select(usize),
+}
+
+// Lists the display names of every font matching the previewed character, and previews the
+// selected one without cycling through the fonts in between
+pub struct FontList {
+    font_names: Vec<String>,
+    state: ListState,
+    theme: Theme,
+}
+
+impl FontList {
+    // The current font is selected, the first one if there is none
+    pub fn new(font_names: Vec<String>, current_index: Option<usize>, theme: Theme) -> Self {
+        let mut state = ListState::default();
+        if !font_names.is_empty() {
+            state.select(Some(current_index.unwrap_or(0).min(font_names.len() - 1)));
+        }
+        FontList {
+            font_names,
+            state,
+            theme,
+        }
+    }
+
+    pub fn handle_key_event(&mut self, event: KeyEvent) -> FontListEvent {
+        let selected = match self.state.selected() {
+            Some(selected) => selected,
+            None => return FontListEvent::Close,
+        };
+        let last_index = self.font_names.len() - 1;
+        match event.code {
+            KeyCode::Esc => return FontListEvent::Close,
+            KeyCode::Enter => return FontListEvent::Select(selected),
+            KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => self.state.select(Some((selected + 1).min(last_index))),
+            KeyCode::PageUp => self
+                .state
+                .select(Some(selected.saturating_sub(FONT_LIST_PAGE_SIZE))),
+            KeyCode::PageDown => self
+                .state
+                .select(Some((selected + FONT_LIST_PAGE_SIZE).min(last_index))),
+            KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::End => self.state.select(Some(last_index)),
+            _ => {}
+        }
+        FontListEvent::None
+    }
+
+    pub fn draw(&mut self, frame: &mut TerminalFrame, rect: Rect) {
+        let title = format!("Matching Fonts ({})", self.font_names.len());
+        let font_items = self.font_names.iter().map(Text::raw);
+        let font_list = List::new(font_items)
+            .block(Block::default().borders(Borders::ALL).title(&title))
+            .highlight_style(
+                Style::default()
+                    .fg(self.theme.accent)
+                    .modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">");
+
+        frame.render_widget(Clear, rect);
+        frame.render_stateful_widget(font_list, rect, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font_names(count: usize) -> Vec<String> {
+        (0..count).map(|index| format!("Font {}", index)).collect()
+    }
+
+    #[test]
+    fn test_font_list_selection() {
+        let mut font_list = FontList::new(font_names(15), Some(3), Theme::default());
+        font_list.handle_key_event(KeyEvent::from(KeyCode::Down));
+        match font_list.handle_key_event(KeyEvent::from(KeyCode::Enter)) {
+            FontListEvent::Select(index) => assert_eq!(index, 4),
+            _ => panic!("Expected a font to select"),
+        }
+
+        // The selection stops at both ends of the list
+        font_list.handle_key_event(KeyEvent::from(KeyCode::PageDown));
+        font_list.handle_key_event(KeyEvent::from(KeyCode::PageDown));
+        assert_eq!(font_list.state.selected(), Some(14));
+        font_list.handle_key_event(KeyEvent::from(KeyCode::Home));
+        font_list.handle_key_event(KeyEvent::from(KeyCode::Up));
+        assert_eq!(font_list.state.selected(), Some(0));
+
+        // Without any font, there is nothing to select
+        let mut font_list = FontList::new(vec![], None, Theme::default());
+        match font_list.handle_key_event(KeyEvent::from(KeyCode::Enter)) {
+            FontListEvent::Close => {}
+            _ => panic!("Expected the empty list to close"),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Watches the file of the previewed font, so that the preview follows a font being edited.
+//!
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+const WATCH_DEBOUNCE_DELAY_MS: u64 = 100;
+
+// Fonts are often saved by writing a temporary file and renaming it over the original one, so the
+// font file might be briefly missing (or incomplete) when a change is noticed
+const RELOAD_ATTEMPTS: usize = 8;
+
+// The reloads left to attempt after a change of the font, the first one included
+#[derive(Debug, Default)]
+struct ReloadRetries {
+    attempts_left: usize,
+}
+
+impl ReloadRetries {
+    fn start(&mut self) {
+        self.attempts_left = RELOAD_ATTEMPTS;
+    }
+
+    fn cancel(&mut self) {
+        self.attempts_left = 0;
+    }
+
+    fn is_pending(&self) -> bool {
+        self.attempts_left > 0
+    }
+
+    // Returns true if the reload is given up on
+    fn finish(&mut self, succeeded: bool) -> bool {
+        if succeeded {
+            self.attempts_left = 0;
+            return false;
+        }
+        self.attempts_left = self.attempts_left.saturating_sub(1);
+        self.attempts_left == 0
+    }
+}
+
+pub struct FontWatcher {
+    watcher: RecommendedWatcher,
+    receiver: Receiver<DebouncedEvent>,
+    font_path: Option<PathBuf>,
+    reload_retries: ReloadRetries,
+}
+
+impl FontWatcher {
+    pub fn new() -> notify::Result<Self> {
+        let (sender, receiver) = channel();
+        Ok(FontWatcher {
+            watcher: watcher(sender, Duration::from_millis(WATCH_DEBOUNCE_DELAY_MS))?,
+            receiver,
+            font_path: None,
+            reload_retries: ReloadRetries::default(),
+        })
+    }
+
+    // The directory of the font is watched rather than the font itself, since renaming another
+    // file over the font ends the watch of the font
+    pub fn watch(&mut self, font_path: Option<&str>) {
+        let font_path = font_path.map(PathBuf::from);
+        if font_path == self.font_path {
+            return;
+        }
+
+        if let Some(directory) = self.font_path.as_deref().and_then(Path::parent) {
+            let _ = self.watcher.unwatch(directory);
+        }
+        if let Some(directory) = font_path.as_deref().and_then(Path::parent) {
+            let _ = self.watcher.watch(directory, RecursiveMode::NonRecursive);
+        }
+        self.font_path = font_path;
+        self.reload_retries.cancel();
+    }
+
+    // Returns true if the font has changed since it was last reloaded, or if the last reload
+    // failed and should be retried
+    pub fn should_reload(&mut self) -> bool {
+        let font_path = match &self.font_path {
+            Some(font_path) => font_path,
+            None => return false,
+        };
+
+        // Every pending event is consumed, so that a single save triggers a single reload
+        let change_count = self
+            .receiver
+            .try_iter()
+            .filter(|event| match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Rename(_, path) => path == font_path,
+                _ => false,
+            })
+            .count();
+        if change_count > 0 {
+            self.reload_retries.start();
+        }
+        self.reload_retries.is_pending()
+    }
+
+    // Records the outcome of a reload, returns true if the reload is given up on
+    pub fn finish_reload(&mut self, succeeded: bool) -> bool {
+        self.reload_retries.finish(succeeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_retries() {
+        let mut reload_retries = ReloadRetries::default();
+        assert!(!reload_retries.is_pending());
+
+        // A font missing while it is saved is reloaded again, until it can be loaded
+        reload_retries.start();
+        assert!(!reload_retries.finish(false));
+        assert!(reload_retries.is_pending());
+        assert!(!reload_retries.finish(true));
+        assert!(!reload_retries.is_pending());
+
+        // A font which can't be loaded is given up on after the last attempt
+        reload_retries.start();
+        for _ in 1..RELOAD_ATTEMPTS {
+            assert!(!reload_retries.finish(false));
+        }
+        assert!(reload_retries.finish(false));
+        assert!(!reload_retries.is_pending());
+
+        reload_retries.start();
+        reload_retries.cancel();
+        assert!(!reload_retries.is_pending());
+    }
+}
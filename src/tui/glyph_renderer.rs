@@ -0,0 +1,359 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Glyph renderers turn the coverage of a rendered glyph into the pixels painted in the preview
+//! pane, whichever of the braille, half-block or ASCII canvases they end up on. Another renderer
+//! (e.g. with dithering) can be given to `MainView` to experiment without forking Cicero.
+//!
+
+use std::collections::HashMap;
+
+use tui::style::Color;
+
+use crate::preview::RenderedCharacter;
+use crate::settings::PreviewGradient;
+
+pub trait GlyphRenderer {
+    // Paints pixels of the glyph, (0, 0) being the top left corner of its bitmap. Pixels outside of
+    // the glyph size may be painted, they are clipped by the caller.
+    fn paint_glyph(
+        &self,
+        rendered_character: &RenderedCharacter,
+        paint: &mut dyn FnMut(usize, usize, Color),
+    );
+
+    // Same as `paint_glyph`, on a canvas whose cells (e.g. braille ones) are `cell_size` pixels
+    // painted in a single color, the last one painted in the cell. `cell_phase` is where the top
+    // left corner of the bitmap lies in its cell. Renderers shading pixels may shade whole cells
+    // instead, by default the pixels are painted as by `paint_glyph`.
+    fn paint_glyph_in_cells(
+        &self,
+        rendered_character: &RenderedCharacter,
+        _cell_size: (usize, usize),
+        _cell_phase: (usize, usize),
+        paint: &mut dyn FnMut(usize, usize, Color),
+    ) {
+        self.paint_glyph(rendered_character, paint);
+    }
+}
+
+// Grays of the 256-color palette partially covered pixels are shaded with, from #303030 to #eeeeee.
+// The darkest grays of the palette are left out as they are hardly visible on a dark background.
+const GRAYSCALE_START: u8 = 236;
+const GRAYSCALE_END: u8 = 255;
+
+// The default renderer, which paints every pixel with at least the threshold coverage in the
+// default foreground color, or along the preview gradient if there is one. With antialiasing and
+// no gradient, partially covered pixels are shaded in grays. Inverted shading goes along the
+// gradient or the grays the other way, from the fullest to the faintest pixels (e.g. for light
+// backgrounds). Color glyphs (e.g. emoji) keep their own colors. On canvases with cells of several pixels, every pixel of a cell is painted like the
+// most covered pixel of the cell.
+pub struct CoverageRenderer {
+    gradient: Option<PreviewGradient>,
+    coverage_threshold: u8,
+    antialiasing: bool,
+    inverted_shading: bool,
+}
+
+impl CoverageRenderer {
+    // Pixels without any coverage are never painted, even with a threshold of 0
+    pub fn new(gradient: Option<PreviewGradient>, coverage_threshold: u8) -> Self {
+        CoverageRenderer {
+            gradient,
+            coverage_threshold: coverage_threshold.max(1),
+            antialiasing: false,
+            inverted_shading: false,
+        }
+    }
+
+    pub fn with_antialiasing(mut self, antialiasing: bool) -> Self {
+        self.antialiasing = antialiasing;
+        self
+    }
+
+    pub fn with_inverted_shading(mut self, inverted_shading: bool) -> Self {
+        self.inverted_shading = inverted_shading;
+        self
+    }
+
+    // The color of a pixel of the glyph, either of its own or given by its coverage
+    fn pixel_color(&self, rendered_character: &RenderedCharacter, x: usize, y: usize) -> Color {
+        match &rendered_character.colors {
+            Some(colors) => {
+                let [red, green, blue] = colors[y][x];
+                Color::Rgb(red, green, blue)
+            }
+            None => self.coverage_color(rendered_character.bitmap[y][x]),
+        }
+    }
+
+    // The color of a glyph pixel with the given (non-zero) coverage
+    fn coverage_color(&self, coverage: u8) -> Color {
+        let shade = if self.inverted_shading {
+            255 - coverage
+        } else {
+            coverage
+        };
+        let gradient = match self.gradient {
+            Some(gradient) => gradient,
+            None if self.antialiasing && coverage < 255 => {
+                let shades = (GRAYSCALE_END - GRAYSCALE_START) as u32;
+                return Color::Indexed(GRAYSCALE_START + (shade as u32 * shades / 255) as u8);
+            }
+            None => return Color::Reset,
+        };
+
+        let interpolate = |start: u8, end: u8| -> u8 {
+            let (start, end) = (start as i32, end as i32);
+            (start + (end - start) * shade as i32 / 255) as u8
+        };
+        Color::Rgb(
+            interpolate(gradient.start.red, gradient.end.red),
+            interpolate(gradient.start.green, gradient.end.green),
+            interpolate(gradient.start.blue, gradient.end.blue),
+        )
+    }
+}
+
+impl GlyphRenderer for CoverageRenderer {
+    fn paint_glyph(
+        &self,
+        rendered_character: &RenderedCharacter,
+        paint: &mut dyn FnMut(usize, usize, Color),
+    ) {
+        for (y, row) in rendered_character.bitmap.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                if *pixel >= self.coverage_threshold {
+                    paint(x, y, self.pixel_color(rendered_character, x, y));
+                }
+            }
+        }
+    }
+
+    fn paint_glyph_in_cells(
+        &self,
+        rendered_character: &RenderedCharacter,
+        cell_size: (usize, usize),
+        cell_phase: (usize, usize),
+        paint: &mut dyn FnMut(usize, usize, Color),
+    ) {
+        let (cell_width, cell_height) = (cell_size.0.max(1), cell_size.1.max(1));
+        let cell_of = |x: usize, y: usize| {
+            (
+                (x + cell_phase.0) / cell_width,
+                (y + cell_phase.1) / cell_height,
+            )
+        };
+
+        // The most covered pixel of each cell
+        let mut cell_pixels: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        for (y, row) in rendered_character.bitmap.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                if *pixel < self.coverage_threshold {
+                    continue;
+                }
+                let cell_pixel = cell_pixels.entry(cell_of(x, y)).or_insert((x, y));
+                if rendered_character.bitmap[cell_pixel.1][cell_pixel.0] < *pixel {
+                    *cell_pixel = (x, y);
+                }
+            }
+        }
+
+        for (y, row) in rendered_character.bitmap.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                if *pixel < self.coverage_threshold {
+                    continue;
+                }
+                let (cell_x, cell_y) = cell_pixels[&cell_of(x, y)];
+                paint(x, y, self.pixel_color(rendered_character, cell_x, cell_y));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preview::RenderSize;
+    use crate::settings::RgbColor;
+
+    fn painted_pixels(glyph_renderer: &dyn GlyphRenderer) -> Vec<(usize, usize, Color)> {
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![0, 255], vec![51, 0]],
+            colors: None,
+            glyph_size: RenderSize::new(2, 2),
+            bitmap_left: 0,
+            bitmap_top: 2,
+            advance_width: 2,
+            advance_height: 2,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        let mut pixels = vec![];
+        glyph_renderer.paint_glyph(&rendered_character, &mut |x, y, color| {
+            pixels.push((x, y, color))
+        });
+        pixels
+    }
+
+    #[test]
+    fn test_color_glyph() {
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![0, 255], vec![51, 0]],
+            colors: Some(vec![
+                vec![[0; 3], [255, 200, 0]],
+                vec![[10, 20, 30], [0; 3]],
+            ]),
+            glyph_size: RenderSize::new(2, 2),
+            bitmap_left: 0,
+            bitmap_top: 2,
+            advance_width: 2,
+            advance_height: 2,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        let mut pixels = vec![];
+        CoverageRenderer::new(None, 1)
+            .with_antialiasing(true)
+            .paint_glyph(&rendered_character, &mut |x, y, color| {
+                pixels.push((x, y, color))
+            });
+        assert_eq!(
+            pixels,
+            vec![
+                (1, 0, Color::Rgb(255, 200, 0)),
+                (0, 1, Color::Rgb(10, 20, 30))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coverage_renderer() {
+        assert_eq!(
+            painted_pixels(&CoverageRenderer::new(None, 1)),
+            vec![(1, 0, Color::Reset), (0, 1, Color::Reset)]
+        );
+        assert_eq!(
+            painted_pixels(&CoverageRenderer::new(None, 0)),
+            vec![(1, 0, Color::Reset), (0, 1, Color::Reset)]
+        );
+
+        // Faint pixels are left out with a higher threshold
+        assert_eq!(
+            painted_pixels(&CoverageRenderer::new(None, 128)),
+            vec![(1, 0, Color::Reset)]
+        );
+
+        // Fully covered pixels keep the default foreground color when antialiased
+        assert_eq!(
+            painted_pixels(&CoverageRenderer::new(None, 1).with_antialiasing(true)),
+            vec![(1, 0, Color::Reset), (0, 1, Color::Indexed(239))]
+        );
+
+        let gradient = PreviewGradient {
+            start: RgbColor {
+                red: 0,
+                green: 0,
+                blue: 0,
+            },
+            end: RgbColor {
+                red: 255,
+                green: 100,
+                blue: 0,
+            },
+        };
+        assert_eq!(
+            painted_pixels(&CoverageRenderer::new(Some(gradient), 1)),
+            vec![
+                (1, 0, Color::Rgb(255, 100, 0)),
+                (0, 1, Color::Rgb(51, 20, 0))
+            ]
+        );
+
+        // The gradient takes precedence over the grays
+        assert_eq!(
+            painted_pixels(&CoverageRenderer::new(Some(gradient), 1).with_antialiasing(true)),
+            vec![
+                (1, 0, Color::Rgb(255, 100, 0)),
+                (0, 1, Color::Rgb(51, 20, 0))
+            ]
+        );
+
+        // Inverted shading goes from the end of the gradient, or the lightest gray, for the faintest
+        // pixels to the start of the gradient for the fullest ones
+        assert_eq!(
+            painted_pixels(&CoverageRenderer::new(Some(gradient), 1).with_inverted_shading(true)),
+            vec![(1, 0, Color::Rgb(0, 0, 0)), (0, 1, Color::Rgb(204, 80, 0))]
+        );
+        assert_eq!(
+            painted_pixels(
+                &CoverageRenderer::new(None, 1)
+                    .with_antialiasing(true)
+                    .with_inverted_shading(true)
+            ),
+            vec![(1, 0, Color::Reset), (0, 1, Color::Indexed(251))]
+        );
+    }
+
+    #[test]
+    fn test_paint_glyph_in_cells() {
+        let rendered_character = RenderedCharacter {
+            bitmap: vec![vec![51, 255, 102], vec![0, 0, 0]],
+            colors: None,
+            glyph_size: RenderSize::new(3, 2),
+            bitmap_left: 0,
+            bitmap_top: 2,
+            advance_width: 3,
+            advance_height: 2,
+            vertical_bearing_x: 0,
+            vertical_bearing_y: 0,
+        };
+        let glyph_renderer = CoverageRenderer::new(None, 1).with_antialiasing(true);
+        let mut pixels = vec![];
+        glyph_renderer.paint_glyph_in_cells(
+            &rendered_character,
+            (2, 4),
+            (0, 0),
+            &mut |x, y, color| pixels.push((x, y, color)),
+        );
+
+        // The pixels of the first cell take the color of its fully covered pixel
+        assert_eq!(
+            pixels,
+            vec![
+                (0, 0, Color::Reset),
+                (1, 0, Color::Reset),
+                (2, 0, Color::Indexed(243))
+            ]
+        );
+
+        // Shifted by a pixel, the faint pixel is alone in its cell
+        let mut pixels = vec![];
+        glyph_renderer.paint_glyph_in_cells(
+            &rendered_character,
+            (2, 4),
+            (1, 0),
+            &mut |x, y, color| pixels.push((x, y, color)),
+        );
+        assert_eq!(
+            pixels,
+            vec![
+                (0, 0, Color::Indexed(239)),
+                (1, 0, Color::Reset),
+                (2, 0, Color::Reset)
+            ]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use image::png::PngEncoder;
+use image::ColorType;
+use log::warn;
+
+use super::terminal_image::{ImageCanvas, PreviewBackend};
+
+pub struct Iterm2Backend;
+
+impl PreviewBackend for Iterm2Backend {
+    fn encode(
+        &self,
+        image_canvas: &ImageCanvas,
+        width_in_cells: u16,
+        height_in_cells: u16,
+    ) -> String {
+        encode_iterm2(image_canvas, width_in_cells, height_in_cells)
+    }
+}
+
+// Encodes the canvas as an inline PNG image of iTerm2 (OSC 1337), displayed from the cursor
+// position and stretched to the given cells, leaving the cursor in place
+fn encode_iterm2(image_canvas: &ImageCanvas, width_in_cells: u16, height_in_cells: u16) -> String {
+    let (width, height) = (image_canvas.width(), image_canvas.height());
+    if width == 0 || height == 0 {
+        return String::new();
+    }
+
+    let mut png = vec![];
+    if let Err(error) = PngEncoder::new(&mut png).encode(
+        &image_canvas.rgba(),
+        width as u32,
+        height as u32,
+        ColorType::Rgba8,
+    ) {
+        warn!("Failed to encode the preview as a PNG image: {}", error);
+        return String::new();
+    }
+
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0;\
+         doNotMoveCursor=1:{}\x07",
+        png.len(),
+        width_in_cells,
+        height_in_cells,
+        base64::encode(&png)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_iterm2() {
+        let iterm2 = encode_iterm2(&ImageCanvas::new(4, 2), 2, 1);
+        assert!(iterm2.starts_with("\x1b]1337;File=inline=1;size="));
+        assert!(iterm2.contains(";width=2;height=1;preserveAspectRatio=0;doNotMoveCursor=1:"));
+        assert!(iterm2.ends_with('\x07'));
+
+        // The PNG signature, encoded in base64
+        assert!(iterm2.contains(":iVBORw0KGgo"));
+
+        assert_eq!(encode_iterm2(&ImageCanvas::new(0, 0), 1, 1), "");
+    }
+}
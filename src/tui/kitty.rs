@@ -0,0 +1,93 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use super::terminal_image::{ImageCanvas, PreviewBackend};
+
+// The base64 data of an image is sent in chunks of at most 4096 bytes
+const CHUNK_SIZE: usize = 4096;
+
+// Deletes every image on the screen (and frees their data), text drawn over images doesn't
+const KITTY_DELETE_IMAGES: &str = "\x1b_Ga=d,d=A,q=2\x1b\\";
+
+pub struct KittyBackend;
+
+impl PreviewBackend for KittyBackend {
+    fn encode(
+        &self,
+        image_canvas: &ImageCanvas,
+        width_in_cells: u16,
+        height_in_cells: u16,
+    ) -> String {
+        encode_kitty(image_canvas, width_in_cells, height_in_cells)
+    }
+
+    fn delete_images(&self) -> Option<&'static str> {
+        Some(KITTY_DELETE_IMAGES)
+    }
+}
+
+// Encodes the canvas as RGBA pixels with the kitty graphics protocol, displayed from the cursor
+// position and scaled to the given cells. The cursor is left in place and the terminal doesn't
+// respond (q=2), otherwise its responses would be read as key events.
+fn encode_kitty(image_canvas: &ImageCanvas, width_in_cells: u16, height_in_cells: u16) -> String {
+    let (width, height) = (image_canvas.width(), image_canvas.height());
+    if width == 0 || height == 0 {
+        return String::new();
+    }
+
+    let payload = base64::encode(&image_canvas.rgba());
+
+    let mut kitty = String::new();
+    let chunk_count = (payload.len() + CHUNK_SIZE - 1) / CHUNK_SIZE;
+    for chunk_index in 0..chunk_count {
+        let chunk_start = chunk_index * CHUNK_SIZE;
+        let chunk = &payload[chunk_start..(chunk_start + CHUNK_SIZE).min(payload.len())];
+        let more = (chunk_index + 1 < chunk_count) as u8;
+        if chunk_index == 0 {
+            kitty.push_str(&format!(
+                "\x1b_Ga=T,f=32,s={},v={},c={},r={},C=1,q=2,m={};{}\x1b\\",
+                width, height, width_in_cells, height_in_cells, more, chunk
+            ));
+        } else {
+            kitty.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    kitty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::style::Color;
+
+    #[test]
+    fn test_encode_kitty() {
+        let mut image_canvas = ImageCanvas::new(2, 1);
+        image_canvas.paint(0, 0, Color::Rgb(255, 0, 0));
+        assert_eq!(
+            encode_kitty(&image_canvas, 1, 1),
+            "\x1b_Ga=T,f=32,s=2,v=1,c=1,r=1,C=1,q=2,m=0;/wAA/wAAAAA=\x1b\\"
+        );
+
+        // Large images are sent in chunks, only the last one without more to come
+        let image_canvas = ImageCanvas::new(64, 64);
+        let kitty = encode_kitty(&image_canvas, 8, 4);
+        assert_eq!(kitty.matches("\x1b_G").count(), 6);
+        assert_eq!(kitty.matches("m=1;").count(), 5);
+        assert!(kitty.starts_with("\x1b_Ga=T,f=32,s=64,v=64,c=8,r=4,C=1,q=2,m=1;"));
+        assert!(kitty.contains("\x1b_Gm=0;"));
+
+        assert_eq!(encode_kitty(&ImageCanvas::new(0, 0), 1, 1), "");
+    }
+}
@@ -12,45 +12,234 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
-use std::io::Stdout;
+use std::collections::HashMap;
+use std::io::{Stdout, Write};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::cursor::MoveTo;
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent};
+use crossterm::queue;
+use crossterm::style::Print;
 use crossterm::Result;
 use tui::backend::CrosstermBackend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Modifier, Style};
-use tui::widgets::{Block, Borders, List, Paragraph, Text};
+use tui::style::{Modifier, Style};
+use tui::widgets::{Block, Borders, Clear, List, Paragraph, Text};
 use tui::Frame;
-use unic::ucd::UNICODE_VERSION;
+use unic::ucd::{Block as UnicodeBlock, UNICODE_VERSION};
 
+use super::action::{action_for, Action, KEY_BINDINGS};
 use super::character_detail_view::CharacterDetailView;
+use super::clipboard::{copy_to_clipboard, CopyFormat};
+use super::command_palette::{CommandPalette, CommandPaletteEvent};
+use super::font_editor::open_font_in_editor;
+use super::font_list::{FontList, FontListEvent};
+#[cfg(feature = "font-watch")]
+use super::font_watcher::FontWatcher;
+use super::glyph_renderer::{CoverageRenderer, GlyphRenderer};
 use super::renderer::ApplicationTerminal;
 use super::stateful_graphemes::StatefulGraphemes;
+use super::terminal_image::{preview_backend, TerminalImage};
+use super::theme::Theme;
 use super::ApplicationState;
+use crate::preview::{MissingGlyphReason, PreviewContext};
+use crate::settings::Settings;
+use crate::ucd::{invalid_code_point_in, is_zwj_sequence, next_block, previous_block};
 
 pub type TerminalFrame<'a> = Frame<'a, CrosstermBackend<Stdout>>;
 
+const COMMAND_PALETTE_MIN_WIDTH: u16 = 40;
+
+// How often the file of the previewed font is checked for changes
+#[cfg(feature = "font-watch")]
+const FONT_WATCH_INTERVAL_MS: u64 = 250;
+
+// Renders done in the background are checked for this often while some are pending
+const RENDER_POLL_INTERVAL_MS: u64 = 20;
+
+const SYNTHETIC_STYLE_UNAVAILABLE_MESSAGE: &str = "Synthetic styles need an outline font";
+
+// Returns a rect of the given size centered in `outer_rect`, shrunk to fit in it if needed
+fn centered_rect(outer_rect: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(outer_rect.width);
+    let height = height.min(outer_rect.height);
+    Rect::new(
+        outer_rect.x + (outer_rect.width - width) / 2,
+        outer_rect.y + (outer_rect.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn erase_terminal_images(
+    terminal: &mut ApplicationTerminal,
+    terminal_images: &[TerminalImage],
+) -> Result<()> {
+    let backend = terminal.backend_mut();
+    for terminal_image in terminal_images {
+        let rect = terminal_image.rect;
+        let blank_line = " ".repeat(rect.width as usize);
+        for y in rect.y..rect.y + rect.height {
+            queue!(backend, MoveTo(rect.x, y), Print(&blank_line))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_terminal_images(
+    terminal: &mut ApplicationTerminal,
+    terminal_images: &[TerminalImage],
+) -> Result<()> {
+    let backend = terminal.backend_mut();
+    for terminal_image in terminal_images {
+        queue!(
+            backend,
+            MoveTo(terminal_image.rect.x, terminal_image.rect.y),
+            Print(&terminal_image.data)
+        )?;
+    }
+    backend.flush()?;
+    Ok(())
+}
+
 pub struct MainView {
     user_input: String,
     graphemes: StatefulGraphemes,
     character_detail_view: Option<CharacterDetailView>,
+
+    // The display name of the font whose repertoire is listed instead of the input
+    repertoire_font: Option<String>,
+
+    // Why the font audited with `--audit-font` can't render each of the characters it lists
+    missing_glyph_reasons: HashMap<char, MissingGlyphReason>,
+    confirming_quit: bool,
+    status_message: Option<String>,
+    escape_copy_format: CopyFormat,
+    slideshow_running: bool,
+    slideshow_advanced_at: Instant,
+    showing_help: bool,
+    command_palette: Option<CommandPalette>,
+    font_list: Option<FontList>,
+    #[cfg(feature = "font-watch")]
+    font_watcher: Option<FontWatcher>,
+    preview_context: PreviewContext,
+    glyph_renderer: Rc<dyn GlyphRenderer>,
+    terminal_images: Vec<TerminalImage>,
+    theme: Theme,
+    settings: Rc<Settings>,
 }
 
 impl MainView {
-    pub fn new(user_input: String) -> Self {
+    // The preview context is created once at startup, and shared by the previews of every character
+    pub fn new(
+        user_input: String,
+        preview_context: PreviewContext,
+        settings: Rc<Settings>,
+    ) -> Self {
         let graphemes = StatefulGraphemes::new(&user_input);
+        let status_message = graphemes.omitted_code_points_description();
         MainView {
             user_input,
             graphemes,
             character_detail_view: None,
+            repertoire_font: None,
+            missing_glyph_reasons: HashMap::new(),
+            confirming_quit: false,
+            status_message,
+            escape_copy_format: CopyFormat::Character,
+            slideshow_running: false,
+            slideshow_advanced_at: Instant::now(),
+            showing_help: false,
+            command_palette: None,
+            font_list: None,
+            #[cfg(feature = "font-watch")]
+            font_watcher: FontWatcher::new().ok(),
+            preview_context,
+            glyph_renderer: Rc::new(
+                CoverageRenderer::new(settings.preview_gradient, settings.coverage_threshold)
+                    .with_antialiasing(settings.antialiasing)
+                    .with_inverted_shading(settings.invert_shading),
+            ),
+            terminal_images: vec![],
+            theme: Theme::of(settings.palette),
+            settings,
         }
     }
 
+    // Replaces the default glyph renderer, for the characters shown from then on
+    pub fn set_glyph_renderer(&mut self, glyph_renderer: Rc<dyn GlyphRenderer>) {
+        self.glyph_renderer = glyph_renderer;
+    }
+
+    pub fn set_status_message(&mut self, status_message: String) {
+        self.status_message = Some(status_message);
+    }
+
+    pub fn set_missing_glyph_reasons(
+        &mut self,
+        missing_glyph_reasons: HashMap<char, MissingGlyphReason>,
+    ) {
+        self.missing_glyph_reasons = missing_glyph_reasons;
+    }
+
     pub fn update(
         &mut self,
         terminal: &mut ApplicationTerminal,
         app_state: &mut ApplicationState,
     ) -> Result<()> {
+        self.preview_context.receive_renders();
+        self.draw(terminal)?;
+
+        // Images stay on the screen until they are replaced, those which can't be deleted are only
+        // removed by drawing over them. Blanks are written over the images replaced in place, whose
+        // cells are blank in both frames, the screen is only drawn again from scratch once an image
+        // leaves its place.
+        let terminal_images = self.take_terminal_images();
+        if terminal_images != self.terminal_images {
+            let delete_images = preview_backend(self.settings.preview_protocol)
+                .and_then(|preview_backend| preview_backend.delete_images());
+            let replaced_in_place = self.terminal_images.iter().all(|previous_image| {
+                terminal_images
+                    .iter()
+                    .any(|terminal_image| terminal_image.rect == previous_image.rect)
+            });
+            if let Some(delete_images) = delete_images {
+                queue!(terminal.backend_mut(), Print(delete_images))?;
+            } else if replaced_in_place {
+                erase_terminal_images(terminal, &self.terminal_images)?;
+            } else {
+                terminal.clear()?;
+                self.draw(terminal)?;
+                self.take_terminal_images();
+            }
+            write_terminal_images(terminal, &terminal_images)?;
+            self.terminal_images = terminal_images;
+        }
+
+        // Events are waited for with a timeout while something happens on its own, i.e. the
+        // slideshow advancing, or the previewed font being reloaded. The view is only drawn again
+        // once something did happen.
+        while let Some(timeout) = self.tick_timeout() {
+            if poll(timeout)? {
+                break;
+            }
+            if self.tick(app_state) {
+                return Ok(());
+            }
+        }
+
+        match read()? {
+            Event::Key(event) => self.handle_key_event(event, app_state),
+            // Renders of the previous size are unlikely to be needed again
+            Event::Resize(_, _) => self.preview_context.clear_render_cache(),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, terminal: &mut ApplicationTerminal) -> Result<()> {
         terminal.draw(|mut frame| {
             let chunks = Layout::default()
                 .constraints(
@@ -82,36 +271,73 @@ impl MainView {
             }
 
             self.draw_status_bar(&mut frame, chunks[2]);
+
+            if self.showing_help {
+                self.draw_help(&mut frame);
+            }
+            if let Some(command_palette) = &mut self.command_palette {
+                let frame_size = frame.size();
+                let rect = centered_rect(
+                    frame_size,
+                    (frame_size.width * 3 / 5).max(COMMAND_PALETTE_MIN_WIDTH),
+                    frame_size.height * 3 / 5,
+                );
+                command_palette.draw(&mut frame, rect);
+            }
+            if let Some(font_list) = &mut self.font_list {
+                let frame_size = frame.size();
+                let rect = centered_rect(
+                    frame_size,
+                    (frame_size.width * 3 / 5).max(COMMAND_PALETTE_MIN_WIDTH),
+                    frame_size.height * 3 / 5,
+                );
+                font_list.draw(&mut frame, rect);
+            }
         })?;
+        Ok(())
+    }
 
-        if let Event::Key(event) = read()? {
-            self.handle_key_event(event, app_state);
+    // Overlays are drawn over the preview, which is then left without its images
+    fn take_terminal_images(&mut self) -> Vec<TerminalImage> {
+        let terminal_images = match &mut self.character_detail_view {
+            Some(character_detail_view) => character_detail_view.take_terminal_images(),
+            None => vec![],
+        };
+        if self.showing_help || self.command_palette.is_some() || self.font_list.is_some() {
+            vec![]
+        } else {
+            terminal_images
         }
-
-        Ok(())
     }
 
     fn draw_user_input(&mut self, frame: &mut TerminalFrame, rect: Rect) {
         let user_input_items = [Text::raw(&self.user_input)];
         let user_input_paragraph = Paragraph::new(user_input_items.iter())
             .block(Block::default().borders(Borders::ALL).title("Input"))
-            .style(Style::default().fg(Color::Yellow));
+            .style(Style::default().fg(self.theme.input));
 
         frame.render_widget(user_input_paragraph, rect);
     }
 
     fn draw_graphemes_list(&mut self, frame: &mut TerminalFrame, rect: Rect) {
-        let graphemes_list = List::new(
-            self.graphemes
-                .rows
-                .iter()
-                .map(|row| Text::raw(row.to_string())),
-        )
-        .block(Block::default().borders(Borders::ALL).title("Graphemes"))
+        let title = match &self.repertoire_font {
+            Some(font_name) => format!("Repertoire of {}", font_name),
+            None => "Graphemes".to_owned(),
+        };
+        let graphemes_list = List::new(self.graphemes.rows.iter().map(|row| {
+            let missing_glyph_reason = row
+                .code_point
+                .and_then(|chr| self.missing_glyph_reasons.get(&chr));
+            match missing_glyph_reason {
+                Some(reason) => Text::raw(format!("{}  ({})", row, reason.description())),
+                None => Text::raw(row.to_string()),
+            }
+        }))
+        .block(Block::default().borders(Borders::ALL).title(&title))
         .style(Style::default())
         .highlight_style(
             Style::default()
-                .fg(Color::LightGreen)
+                .fg(self.theme.accent)
                 .modifier(Modifier::BOLD),
         )
         .highlight_symbol(">");
@@ -119,6 +345,52 @@ impl MainView {
         frame.render_stateful_widget(graphemes_list, rect, &mut self.graphemes.state);
     }
 
+    // Lists every key binding in a popup centered over the current view
+    fn draw_help(&mut self, frame: &mut TerminalFrame) {
+        let key_descriptions: Vec<String> = KEY_BINDINGS
+            .iter()
+            .map(|key_binding| key_binding.key_description())
+            .collect();
+        let key_column_width = key_descriptions
+            .iter()
+            .map(|key_description| key_description.chars().count())
+            .max()
+            .unwrap_or(0);
+        let help_lines: Vec<String> = KEY_BINDINGS
+            .iter()
+            .zip(&key_descriptions)
+            .map(|(key_binding, key_description)| {
+                format!(
+                    "{:width$}  {}{}\n",
+                    key_description,
+                    key_binding.action.description(),
+                    if key_binding.action.requires_detail() {
+                        " (detail)"
+                    } else {
+                        ""
+                    },
+                    width = key_column_width
+                )
+            })
+            .collect();
+
+        let width = help_lines
+            .iter()
+            .map(|help_line| help_line.chars().count() as u16 + 3)
+            .max()
+            .unwrap_or(0);
+        let rect = centered_rect(frame.size(), width, help_lines.len() as u16 + 2);
+
+        let help_items: Vec<Text> = help_lines.into_iter().map(Text::raw).collect();
+        let help_paragraph = Paragraph::new(help_items.iter()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Key Bindings (press any key to close)"),
+        );
+        frame.render_widget(Clear, rect);
+        frame.render_widget(help_paragraph, rect);
+    }
+
     fn draw_status_bar(&mut self, frame: &mut TerminalFrame, rect: Rect) {
         let status_bar_chunks = Layout::default()
             .horizontal_margin(1)
@@ -126,96 +398,636 @@ impl MainView {
             .direction(Direction::Horizontal)
             .split(rect);
 
-        let help_item = if self.character_detail_view.is_some() {
-            [Text::raw("[ESC]: Hide Detail | [C-D][C-U]: Scroll Detail")]
+        let help_item = if self.confirming_quit {
+            [Text::raw(
+                "Press [ESC] again to quit, any other key to cancel",
+            )]
+        } else if let Some(status_message) = &self.status_message {
+            [Text::raw(status_message.clone())]
+        } else if self.character_detail_view.is_some() {
+            [Text::raw(
+                "[ESC]: Hide Detail | [C-D][C-U]: Scroll Detail | [C-K]: Comparison Grid \
+                 | [C-L]: Follow Link | [?]: All Keys | [C-P]: Commands",
+            )]
         } else {
-            [Text::raw("[ESC]: Quit")]
+            [Text::raw(
+                "[ESC]: Quit | [C-Y]: Copy | [C-E]: Copy as Escape | [?]: All Keys \
+                 | [C-P]: Commands",
+            )]
         };
         let help_text =
-            Paragraph::new(help_item.iter()).style(Style::default().fg(Color::LightGreen));
+            Paragraph::new(help_item.iter()).style(Style::default().fg(self.theme.accent));
         frame.render_widget(help_text, status_bar_chunks[0]);
 
-        let unicode_version_item = [Text::raw(format!("Unicode Version {}", UNICODE_VERSION))];
+        // The variation sequence previewed instead of the character alone, if any
+        let presentation_sequence = self
+            .character_detail_view
+            .as_ref()
+            .and_then(|character_detail_view| character_detail_view.presentation_sequence());
+        let unicode_version_item = [Text::raw(match presentation_sequence {
+            Some(presentation_sequence) => format!(
+                "{} | Unicode Version {}",
+                presentation_sequence, UNICODE_VERSION
+            ),
+            None => format!("Unicode Version {}", UNICODE_VERSION),
+        })];
         let unicode_version_text = Paragraph::new(unicode_version_item.iter())
-            .style(Style::default().fg(Color::LightGreen))
+            .style(Style::default().fg(self.theme.accent))
             .alignment(Alignment::Right);
         frame.render_widget(unicode_version_text, status_bar_chunks[1]);
     }
 
     fn handle_key_event(&mut self, event: KeyEvent, app_state: &mut ApplicationState) {
-        match event.code {
-            KeyCode::Esc => {
+        self.status_message = None;
+
+        if self.showing_help {
+            self.showing_help = false;
+            return;
+        }
+
+        if let Some(command_palette) = &mut self.command_palette {
+            match command_palette.handle_key_event(event) {
+                CommandPaletteEvent::None => {}
+                CommandPaletteEvent::Close => self.command_palette = None,
+                CommandPaletteEvent::Perform(action) => {
+                    self.command_palette = None;
+                    if self.perform_action(action, app_state).is_none() {
+                        self.status_message =
+                            Some(format!("'{}' needs the detail view", action.description()));
+                    }
+                }
+            }
+            return;
+        }
+
+        if let Some(font_list) = &mut self.font_list {
+            match font_list.handle_key_event(event) {
+                FontListEvent::None => {}
+                FontListEvent::Close => self.font_list = None,
+                FontListEvent::Select(index) => {
+                    self.font_list = None;
+                    if let Some(character_detail_view) = &mut self.character_detail_view {
+                        if let Err(error) = character_detail_view.select_preview_font(index) {
+                            self.status_message = Some(error.to_string());
+                        }
+                    }
+                    self.prefer_preview_font(app_state);
+                }
+            }
+            return;
+        }
+
+        if self.confirming_quit {
+            self.confirming_quit = false;
+            if event.code == KeyCode::Esc {
+                app_state.keep_running = false;
+            }
+            return;
+        }
+
+        if let Some(action) = action_for(event) {
+            if !self.types_into_input(action) && self.perform_action(action, app_state).is_some() {
+                return;
+            }
+        }
+
+        if let KeyCode::Char(chr) = event.code {
+            self.user_input.push(chr);
+            self.update_graphemes();
+        }
+    }
+
+    // [?] is typed into the input like other characters (e.g. to inspect U+003F), it only shows the
+    // help when there is no input yet or the detail view is shown
+    fn types_into_input(&self, action: Action) -> bool {
+        action == Action::ShowHelp
+            && !self.user_input.is_empty()
+            && self.character_detail_view.is_none()
+    }
+
+    // Returns None if the action doesn't apply to the current state, i.e. it needs the detail view
+    // which isn't shown
+    fn perform_action(&mut self, action: Action, app_state: &mut ApplicationState) -> Option<()> {
+        if action.requires_detail() && self.character_detail_view.is_none() {
+            return None;
+        }
+
+        match action {
+            Action::Back => {
                 if self.character_detail_view.is_some() {
                     self.character_detail_view = None;
                 } else {
-                    app_state.keep_running = false;
+                    self.confirming_quit = true;
                 }
             }
-            KeyCode::Up => {
+            Action::ShowDetail => self.update_showing_detail(&app_state),
+            Action::SelectPrevious => {
                 self.graphemes.select_previous();
                 if self.character_detail_view.is_some() {
                     self.update_showing_detail(&app_state);
                 }
             }
-            KeyCode::Down => {
+            Action::SelectNext => {
                 self.graphemes.select_next();
                 if self.character_detail_view.is_some() {
                     self.update_showing_detail(&app_state);
                 }
             }
-            KeyCode::Left => {
-                if let Some(character_detail_view) = &mut self.character_detail_view {
-                    character_detail_view.previous_preview_font();
-                    app_state.preferred_font_path =
-                        character_detail_view.get_current_preview_font_path()
+            Action::DeleteInput => {
+                self.user_input.pop();
+                self.update_graphemes();
+            }
+            Action::PreviousBlock => self.jump_to_block(previous_block, app_state),
+            Action::NextBlock => self.jump_to_block(next_block, app_state),
+            Action::CopyCharacter => self.copy_current_character(CopyFormat::Character),
+            Action::CopyEscape => {
+                self.escape_copy_format = self.escape_copy_format.next_escape();
+                self.copy_current_character(self.escape_copy_format);
+            }
+            Action::ShowHelp => self.showing_help = true,
+            Action::OpenCommandPalette => {
+                self.command_palette = Some(CommandPalette::new(self.theme))
+            }
+            Action::PreviousFont => {
+                if self.detail_view()?.previous_preview_font() {
+                    self.status_message = Some("Wrapped around to the last font".to_owned());
                 }
+                self.prefer_preview_font(app_state);
             }
-            KeyCode::Right => {
-                if let Some(character_detail_view) = &mut self.character_detail_view {
-                    character_detail_view.next_preview_font();
-                    app_state.preferred_font_path =
-                        character_detail_view.get_current_preview_font_path()
+            Action::NextFont => {
+                if self.detail_view()?.next_preview_font() {
+                    self.status_message = Some("Wrapped around to the first font".to_owned());
                 }
+                self.prefer_preview_font(app_state);
             }
-            KeyCode::Enter => self.update_showing_detail(&app_state),
-            KeyCode::Char(c) => self.handle_character_input(c, event.modifiers),
-            KeyCode::Backspace => {
-                self.user_input.pop();
-                self.graphemes = StatefulGraphemes::new(&self.user_input);
+            Action::ListMatchingFonts => {
+                let (font_names, current_index) = self.detail_view()?.matching_fonts();
+                if font_names.is_empty() {
+                    self.status_message = Some("No matching fonts to list".to_owned());
+                    return Some(());
+                }
+                self.font_list = Some(FontList::new(font_names, current_index, self.theme));
             }
-            _ => {}
+            Action::ToggleFontRepertoire => {
+                // The previewed character stays selected in the input if it is listed there
+                if self.repertoire_font.is_some() {
+                    let chr = self.detail_view()?.character();
+                    self.update_graphemes();
+                    if !self.graphemes.select_character(chr) {
+                        self.update_showing_detail(app_state);
+                    }
+                    return Some(());
+                }
+                let (font_info, font_repertoire) = match (
+                    self.detail_view()?.font_info(),
+                    self.detail_view()?.font_repertoire(),
+                ) {
+                    (Some(font_info), Some(font_repertoire)) if !font_repertoire.is_empty() => {
+                        (font_info, font_repertoire)
+                    }
+                    _ => {
+                        self.status_message = Some("No characters to list".to_owned());
+                        return Some(());
+                    }
+                };
+                let chr = self.detail_view()?.character();
+                self.graphemes = StatefulGraphemes::of_characters(&font_repertoire);
+                self.graphemes.select_character(chr);
+                self.status_message = Some(format!("{} characters", font_repertoire.len()));
+                self.repertoire_font = Some(font_info.display_name);
+
+                // The characters listed are previewed with the font they are listed for
+                self.prefer_preview_font(app_state);
+            }
+            Action::ToggleRegularFontsOnly => {
+                let mut settings = (*self.settings).clone();
+                settings.regular_fonts_only = !settings.regular_fonts_only;
+                self.status_message = Some(if settings.regular_fonts_only {
+                    "Matching regular fonts only".to_owned()
+                } else {
+                    "Matching fonts of every style".to_owned()
+                });
+                self.apply_settings(settings, app_state);
+            }
+            Action::ToggleMonospaceFontsOnly => {
+                let mut settings = (*self.settings).clone();
+                settings.monospace_fonts_only = !settings.monospace_fonts_only;
+                self.status_message = Some(if settings.monospace_fonts_only {
+                    "Matching monospaced fonts only".to_owned()
+                } else {
+                    "Matching fonts of every spacing".to_owned()
+                });
+                self.apply_settings(settings, app_state);
+            }
+            Action::ScrollDetailUp => self.detail_view()?.scroll_up(),
+            Action::ScrollDetailDown => self.detail_view()?.scroll_down(),
+            Action::FollowLink => {
+                if let Some(link) = self.detail_view()?.selected_link() {
+                    self.show_detail(link, app_state);
+                }
+            }
+            Action::ToggleComparisonGrid => self.detail_view()?.toggle_comparison_grid(),
+            Action::ToggleNativeSize => self.detail_view()?.toggle_native_size(),
+            Action::ToggleSizeComparison => self.detail_view()?.toggle_size_comparison(),
+            Action::ToggleTerminalGlyph => self.detail_view()?.toggle_terminal_glyph(),
+            Action::ToggleFontDiff => self.detail_view()?.toggle_font_diff(),
+            Action::ToggleSideBySide => self.detail_view()?.toggle_side_by_side(),
+            Action::NextDiffFont => self.detail_view()?.select_next_diff_font(),
+            Action::ToggleCodePointStrip => {
+                let characters = self
+                    .graphemes
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.code_point)
+                    .collect();
+                self.detail_view()?.toggle_code_point_strip(characters);
+            }
+            Action::ToggleShapedString => {
+                let string = self
+                    .graphemes
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.code_point)
+                    .collect();
+                self.detail_view()?.toggle_shaped_string(string);
+            }
+            Action::ToggleShapedCluster => self
+                .detail_view()?
+                .toggle_shaped_cluster(self.graphemes.selected_grapheme()),
+            Action::ToggleGlyphBounds => self.detail_view()?.toggle_glyph_bounds(),
+            Action::ToggleGuides => self.detail_view()?.toggle_guides(),
+            Action::ToggleOutline => self.detail_view()?.toggle_outline(),
+            Action::CyclePresentation => {
+                if !self.detail_view()?.cycle_presentation() {
+                    self.status_message = Some(
+                        "Only emoji shown as text by default have both presentations".to_owned(),
+                    );
+                }
+            }
+            Action::ToggleCombiningBase => {
+                if !self.detail_view()?.toggle_combining_base() {
+                    self.status_message = Some(if cfg!(feature = "shaping") {
+                        "Only combining marks are shaped on a base character".to_owned()
+                    } else {
+                        "Shaping marks on a base needs the shaping feature".to_owned()
+                    });
+                }
+            }
+            Action::ToggleReferenceGlyph => self.detail_view()?.toggle_reference_glyph(),
+            Action::ToggleDotGrid => {
+                if !self.detail_view()?.toggle_dot_grid() {
+                    self.status_message =
+                        Some("The dot grid needs the braille renderer".to_owned());
+                }
+            }
+            Action::NextBitmapStrike => {
+                if !self.detail_view()?.select_next_strike() {
+                    self.status_message =
+                        Some("Only bitmap fonts have strikes to choose from".to_owned());
+                }
+            }
+            Action::ToggleSlideshow => {
+                self.slideshow_running = !self.slideshow_running;
+                self.slideshow_advanced_at = Instant::now();
+                self.status_message = Some(if self.slideshow_running {
+                    "Slideshow started, [C-S] to stop".to_owned()
+                } else {
+                    "Slideshow stopped".to_owned()
+                });
+            }
+            Action::ToggleSyntheticBold => {
+                if !self.detail_view()?.toggle_synthetic_bold() {
+                    self.status_message = Some(SYNTHETIC_STYLE_UNAVAILABLE_MESSAGE.to_owned());
+                }
+            }
+            Action::DecreaseBoldStrength | Action::IncreaseBoldStrength => {
+                let direction = if action == Action::IncreaseBoldStrength {
+                    1
+                } else {
+                    -1
+                };
+                if !self.detail_view()?.adjust_bold_strength(direction) {
+                    self.status_message = Some(SYNTHETIC_STYLE_UNAVAILABLE_MESSAGE.to_owned());
+                }
+            }
+            Action::ToggleSyntheticOblique => {
+                if !self.detail_view()?.toggle_synthetic_oblique() {
+                    self.status_message = Some(SYNTHETIC_STYLE_UNAVAILABLE_MESSAGE.to_owned());
+                }
+            }
+            Action::CycleHinting => {
+                if !self.detail_view()?.cycle_hinting() {
+                    self.status_message = Some("Hinting needs an outline font".to_owned());
+                }
+            }
+            Action::NextNamedInstance => self.detail_view()?.next_named_instance(),
+            Action::PreviousVariationAxis => self.detail_view()?.select_variation_axis(-1),
+            Action::NextVariationAxis => self.detail_view()?.select_variation_axis(1),
+            Action::DecreaseVariationAxis => self.detail_view()?.adjust_variation_axis(-1),
+            Action::IncreaseVariationAxis => self.detail_view()?.adjust_variation_axis(1),
+            Action::PanLeft => self.detail_view()?.pan_preview(-1, 0),
+            Action::PanRight => self.detail_view()?.pan_preview(1, 0),
+            Action::PanUp => self.detail_view()?.pan_preview(0, -1),
+            Action::PanDown => self.detail_view()?.pan_preview(0, 1),
+            Action::ZoomIn => self.detail_view()?.zoom_in_preview(),
+            Action::ZoomOut => self.detail_view()?.zoom_out_preview(),
+            Action::ExportContactSheet => {
+                self.status_message = Some(match self.detail_view()?.export_contact_sheet() {
+                    Ok(path) => format!("Contact sheet saved to {}", path),
+                    Err(error) => format!("Failed to export contact sheet: {}", error),
+                });
+            }
+            Action::ExportCoverageMap => {
+                self.status_message = Some(match self.detail_view()?.export_coverage_map() {
+                    Ok((path, covered_count, assigned_count)) => format!(
+                        "Coverage map saved to {} ({} of {} characters covered)",
+                        path, covered_count, assigned_count
+                    ),
+                    Err(error) => format!("Failed to export coverage map: {}", error),
+                });
+            }
+            Action::ExportGlyphImage => {
+                self.status_message = Some(match self.detail_view()?.export_glyph_png() {
+                    Ok(path) => format!("Glyph saved to {}", path),
+                    Err(error) => format!("Failed to export glyph: {}", error),
+                });
+            }
+            Action::ExportGlyphOutline => {
+                self.status_message = Some(match self.detail_view()?.export_glyph_svg() {
+                    Ok(path) => format!("Outline saved to {}", path),
+                    Err(error) => format!("Failed to export outline: {}", error),
+                });
+            }
+            Action::OpenFontInEditor => {
+                let font_path = match self.detail_view()?.get_current_preview_font_path() {
+                    Some(font_path) => font_path,
+                    None => {
+                        self.status_message = Some("No font to open".to_owned());
+                        return Some(());
+                    }
+                };
+                let command = self.settings.font_editor_command.as_deref();
+                self.status_message = Some(match open_font_in_editor(command, &font_path) {
+                    Ok(()) => format!("Opened {}", font_path),
+                    Err(error) => format!("Failed to open {}: {}", font_path, error),
+                });
+            }
+        }
+        Some(())
+    }
+
+    fn detail_view(&mut self) -> Option<&mut CharacterDetailView> {
+        self.character_detail_view.as_mut()
+    }
+
+    // The character shown in the detail view, or the selected one if the detail is hidden
+    fn current_character(&self) -> Option<char> {
+        if let Some(character_detail_view) = &self.character_detail_view {
+            return Some(character_detail_view.character());
+        }
+        let selected_row_index = self.graphemes.state.selected()?;
+        self.graphemes.rows.get(selected_row_index)?.code_point
+    }
+
+    fn copy_current_character(&mut self, copy_format: CopyFormat) {
+        let chr = match self.current_character() {
+            Some(chr) => chr,
+            None => return,
         };
+
+        let text = copy_format.format(chr);
+        self.status_message = Some(match copy_to_clipboard(&text) {
+            Ok(()) => format!("Copied {} ({})", text, copy_format.description()),
+            Err(error) => format!("Failed to copy {}: {}", text, error),
+        });
+    }
+
+    fn tick_timeout(&self) -> Option<Duration> {
+        let slideshow_timeout = if self.slideshow_running {
+            let interval = Duration::from_millis(self.settings.slideshow_interval_ms);
+            Some(
+                interval
+                    .checked_sub(self.slideshow_advanced_at.elapsed())
+                    .unwrap_or_default(),
+            )
+        } else {
+            None
+        };
+
+        // The preview is drawn again as soon as its render is done in the background
+        let render_timeout = Some(Duration::from_millis(RENDER_POLL_INTERVAL_MS))
+            .filter(|_| self.preview_context.has_pending_renders());
+
+        #[cfg(feature = "font-watch")]
+        let font_watch_timeout = Some(Duration::from_millis(FONT_WATCH_INTERVAL_MS))
+            .filter(|_| self.font_watcher.is_some() && self.character_detail_view.is_some());
+        #[cfg(not(feature = "font-watch"))]
+        let font_watch_timeout: Option<Duration> = None;
+
+        slideshow_timeout
+            .into_iter()
+            .chain(render_timeout)
+            .chain(font_watch_timeout)
+            .min()
     }
 
-    fn handle_character_input(&mut self, chr: char, modifiers: KeyModifiers) {
-        if chr == 'u'
-            && modifiers.contains(KeyModifiers::CONTROL)
-            && self.character_detail_view.is_some()
+    // Returns true if the view has changed and should be drawn again
+    fn tick(&mut self, app_state: &mut ApplicationState) -> bool {
+        let mut changed = self.preview_context.receive_renders();
+        if self.slideshow_running
+            && self.slideshow_advanced_at.elapsed()
+                >= Duration::from_millis(self.settings.slideshow_interval_ms)
         {
-            self.character_detail_view.as_mut().unwrap().scroll_up();
-            return;
+            self.advance_slideshow(app_state);
+            changed = true;
         }
 
-        if chr == 'd'
-            && modifiers.contains(KeyModifiers::CONTROL)
-            && self.character_detail_view.is_some()
+        #[cfg(feature = "font-watch")]
         {
-            self.character_detail_view.as_mut().unwrap().scroll_down();
-            return;
+            changed |= self.reload_changed_font();
+        }
+        changed
+    }
+
+    // Reloads the previewed font if its file has changed, retrying on the next ticks if it can't
+    // be loaded yet. Returns true if the font was reloaded or given up on.
+    #[cfg(feature = "font-watch")]
+    fn reload_changed_font(&mut self) -> bool {
+        let (font_watcher, character_detail_view) =
+            match (&mut self.font_watcher, &mut self.character_detail_view) {
+                (Some(font_watcher), Some(character_detail_view)) => {
+                    (font_watcher, character_detail_view)
+                }
+                _ => return false,
+            };
+
+        font_watcher.watch(
+            character_detail_view
+                .get_current_preview_font_path()
+                .as_deref(),
+        );
+        if !font_watcher.should_reload() {
+            return false;
+        }
+
+        match character_detail_view.reload_preview_font() {
+            Ok(()) => {
+                font_watcher.finish_reload(true);
+                self.status_message = Some("Reloaded the changed font".to_owned());
+                true
+            }
+            Err(error) => {
+                let given_up = font_watcher.finish_reload(false);
+                if given_up {
+                    self.status_message = Some(format!("Failed to reload the font: {}", error));
+                }
+                given_up
+            }
         }
+    }
+
+    fn advance_slideshow(&mut self, app_state: &mut ApplicationState) {
+        let character_detail_view = match &mut self.character_detail_view {
+            Some(character_detail_view) => character_detail_view,
+            None => {
+                self.slideshow_running = false;
+                return;
+            }
+        };
+
+        self.slideshow_advanced_at = Instant::now();
+        if character_detail_view.advance_slideshow(self.settings.loop_slideshow) {
+            self.prefer_preview_font(app_state);
+        } else {
+            self.slideshow_running = false;
+            self.status_message = Some("Slideshow finished".to_owned());
+        }
+    }
+
+    // The font (and face) selected in the preview pane is previewed first for the next characters
+    fn prefer_preview_font(&self, app_state: &mut ApplicationState) {
+        if let Some(character_detail_view) = &self.character_detail_view {
+            app_state.preferred_font_path = character_detail_view.get_current_preview_font_path();
+            app_state.preferred_face_index = character_detail_view.get_current_preview_face_index();
+        }
+    }
 
-        self.user_input.push(chr);
+    // Code points which can't be characters would otherwise silently list nothing, and a range cut
+    // short would silently list less than asked for
+    fn update_graphemes(&mut self) {
         self.graphemes = StatefulGraphemes::new(&self.user_input);
+        self.repertoire_font = None;
+        if let Some(invalid_code_point) = invalid_code_point_in(&self.user_input) {
+            self.status_message = Some(invalid_code_point.to_string());
+        } else if let Some(description) = self.graphemes.omitted_code_points_description() {
+            self.status_message = Some(description);
+        }
     }
 
+    fn jump_to_block(
+        &mut self,
+        find_block: fn(char, bool) -> Option<UnicodeBlock>,
+        app_state: &ApplicationState,
+    ) {
+        let chr = match self.current_character() {
+            Some(chr) => chr,
+            None => return,
+        };
+
+        match find_block(chr, self.settings.wrap_block_navigation) {
+            Some(block) => {
+                self.show_detail(block.range.low, app_state);
+                self.status_message = Some(format!("Block: {}", block.name));
+            }
+            None => self.status_message = Some("No more blocks".to_owned()),
+        }
+    }
+
+    // A ZWJ sequence is previewed as a whole from its first row, its other rows preview their
+    // code point alone
     fn update_showing_detail(&mut self, app_state: &ApplicationState) {
+        if let Some(sequence) = self.graphemes.selected_grapheme().filter(|grapheme| {
+            self.graphemes.is_grapheme_start_selected() && is_zwj_sequence(grapheme)
+        }) {
+            self.character_detail_view = Some(CharacterDetailView::for_sequence(
+                &self.preview_context,
+                &sequence,
+                app_state.preferred_font_path.as_ref(),
+                app_state.preferred_face_index,
+                self.glyph_renderer.clone(),
+                self.settings.clone(),
+            ));
+            return;
+        }
+
         if let Some(selected_row_index) = self.graphemes.state.selected() {
             if let Some(chr) = self.graphemes.rows[selected_row_index].code_point {
-                self.character_detail_view = Some(CharacterDetailView::new(
-                    chr,
+                self.show_detail(chr, app_state);
+            }
+        }
+    }
+
+    // Settings changed from the preview pane apply to the previews shown from then on, the current
+    // character (or sequence) is previewed again with them
+    fn apply_settings(&mut self, settings: Settings, app_state: &ApplicationState) {
+        self.settings = Rc::new(settings);
+        let (chr, sequence) = match &self.character_detail_view {
+            Some(character_detail_view) => (
+                character_detail_view.character(),
+                character_detail_view.sequence().map(str::to_owned),
+            ),
+            None => return,
+        };
+        match sequence {
+            Some(sequence) => {
+                self.character_detail_view = Some(CharacterDetailView::for_sequence(
+                    &self.preview_context,
+                    &sequence,
                     app_state.preferred_font_path.as_ref(),
-                ));
+                    app_state.preferred_face_index,
+                    self.glyph_renderer.clone(),
+                    self.settings.clone(),
+                ))
             }
+            None => self.show_detail(chr, app_state),
         }
     }
+
+    fn show_detail(&mut self, chr: char, app_state: &ApplicationState) {
+        self.character_detail_view = Some(CharacterDetailView::new(
+            &self.preview_context,
+            chr,
+            app_state.preferred_font_path.as_ref(),
+            app_state.preferred_face_index,
+            self.glyph_renderer.clone(),
+            self.settings.clone(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_mark_input() {
+        let preview_context = PreviewContext::new().unwrap();
+        let mut main_view =
+            MainView::new(String::new(), preview_context, Rc::new(Settings::default()));
+        let mut app_state = ApplicationState::default();
+        let question_mark = KeyEvent::from(KeyCode::Char('?'));
+
+        // Without input, [?] shows the help, and any key hides it
+        main_view.handle_key_event(question_mark, &mut app_state);
+        assert!(main_view.showing_help);
+        main_view.handle_key_event(question_mark, &mut app_state);
+        assert!(!main_view.showing_help);
+        assert!(main_view.user_input.is_empty());
+
+        // Otherwise it is typed into the input
+        main_view.handle_key_event(KeyEvent::from(KeyCode::Char('a')), &mut app_state);
+        main_view.handle_key_event(question_mark, &mut app_state);
+        assert!(!main_view.showing_help);
+        assert_eq!(main_view.user_input, "a?");
+    }
 }
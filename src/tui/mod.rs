@@ -16,20 +16,43 @@
 //! This module implements the Terminal User Interface of Cicero
 //!
 
+mod action;
 mod character_detail_view;
 mod character_preview_canvas;
 mod character_property_view;
+mod clipboard;
+mod command_palette;
+mod font_editor;
+mod font_list;
+#[cfg(feature = "font-watch")]
+mod font_watcher;
+mod glyph_renderer;
+#[cfg(feature = "image")]
+mod iterm2;
+mod kitty;
 mod main_view;
 mod renderer;
+mod sixel;
 mod stateful_graphemes;
+mod terminal_capability;
+mod terminal_image;
+mod text_canvas;
+mod theme;
 
+pub use glyph_renderer::{CoverageRenderer, GlyphRenderer};
 pub use main_view::MainView;
 pub use renderer::Renderer;
+pub use terminal_capability::{
+    resolve_image_cell_size, resolve_preview_protocol, resolve_preview_renderer,
+};
 
 #[derive(Debug)]
 pub struct ApplicationState {
     pub keep_running: bool,
     pub preferred_font_path: Option<String>,
+
+    // The face previewed in the preferred font, for font collections
+    pub preferred_face_index: isize,
 }
 
 impl Default for ApplicationState {
@@ -37,6 +60,7 @@ impl Default for ApplicationState {
         ApplicationState {
             keep_running: true,
             preferred_font_path: None,
+            preferred_face_index: 0,
         }
     }
 }
@@ -12,16 +12,29 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
-use std::io::{stdout, Stdout};
+use std::io::{stdout, Stdout, Write};
+use std::panic;
+use std::sync::Arc;
+use std::thread;
 
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use crossterm::cursor::Show;
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
 use crossterm::Result;
 use tui::{backend::CrosstermBackend, Terminal};
 
 pub type ApplicationTerminal = Terminal<CrosstermBackend<Stdout>>;
 
+#[derive(Default)]
 pub struct Renderer;
 
+fn restore_terminal() -> Result<()> {
+    disable_raw_mode()?;
+    execute!(stdout(), LeaveAlternateScreen, Show)
+}
+
 impl Renderer {
     pub fn new() -> Self {
         Renderer {}
@@ -33,7 +46,26 @@ impl Renderer {
     {
         enable_raw_mode()?;
 
-        let stdout = stdout();
+        let mut stdout = stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+
+        // Restore the terminal before the panic message gets printed, otherwise the message is
+        // garbled and the shell is left in raw mode. The previous hook is put back on exit, the
+        // hook can't be changed while panicking though.
+        let previous_panic_hook = Arc::new(panic::take_hook());
+        let chained_panic_hook = Arc::clone(&previous_panic_hook);
+        panic::set_hook(Box::new(move |panic_info| {
+            let _ = restore_terminal();
+            chained_panic_hook(panic_info);
+        }));
+        defer! {
+            if !thread::panicking() {
+                drop(panic::take_hook());
+                if let Ok(previous_panic_hook) = Arc::try_unwrap(previous_panic_hook) {
+                    panic::set_hook(previous_panic_hook);
+                }
+            }
+        }
 
         let backend = CrosstermBackend::new(stdout);
 
@@ -41,12 +73,12 @@ impl Renderer {
         terminal.hide_cursor()?;
         terminal.clear()?;
 
-        let mut keep_running = true;
-        while keep_running {
-            keep_running = f(&mut terminal)?;
+        let mut keep_running = Ok(true);
+        while let Ok(true) = keep_running {
+            keep_running = f(&mut terminal);
         }
 
-        terminal.clear()?;
-        disable_raw_mode()
+        restore_terminal()?;
+        keep_running.map(|_| ())
     }
 }
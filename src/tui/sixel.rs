@@ -0,0 +1,155 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use super::terminal_image::{ImageCanvas, PreviewBackend};
+
+// Sixel images have up to 256 color registers, colors are made coarser until they fit
+const MAX_COLOR_REGISTERS: usize = 256;
+
+// Each sixel character encodes a column of 6 pixels
+const SIXEL_HEIGHT: usize = 6;
+
+pub struct SixelBackend;
+
+impl PreviewBackend for SixelBackend {
+    fn encode(&self, image_canvas: &ImageCanvas, _: u16, _: u16) -> String {
+        encode_sixel(image_canvas)
+    }
+}
+
+// Encodes the canvas as a sixel image, drawn from the cursor position at its own size
+fn encode_sixel(image_canvas: &ImageCanvas) -> String {
+    let (width, height) = (image_canvas.width(), image_canvas.height());
+    let (palette, registers) = color_registers(image_canvas.pixels());
+
+    // Transparent background (P2 = 1) and square pixels, with the size of the canvas
+    let mut sixel = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for (register, [red, green, blue]) in palette.iter().enumerate() {
+        sixel.push_str(&format!(
+            "#{};2;{};{};{}",
+            register,
+            *red as u32 * 100 / 255,
+            *green as u32 * 100 / 255,
+            *blue as u32 * 100 / 255
+        ));
+    }
+
+    for band_top in (0..height).step_by(SIXEL_HEIGHT) {
+        let band_bottom = (band_top + SIXEL_HEIGHT).min(height);
+        // Every color of the band is drawn in its own pass over the band, from its left edge
+        for register in 0..palette.len() {
+            let columns: Vec<u8> = (0..width)
+                .map(|x| {
+                    (band_top..band_bottom)
+                        .filter(|y| registers[y * width + x] == Some(register))
+                        .fold(0, |bits, y| bits | 1 << (y - band_top))
+                })
+                .collect();
+            let used_width = match columns.iter().rposition(|bits| *bits != 0) {
+                Some(last_column) => last_column + 1,
+                None => continue,
+            };
+            sixel.push_str(&format!("#{}", register));
+            push_run_length_encoded(&mut sixel, &columns[..used_width]);
+            sixel.push('$');
+        }
+        sixel.push('-');
+    }
+
+    sixel.push_str("\x1b\\");
+    sixel
+}
+
+// Returns the palette of the image and the register of each pixel
+fn color_registers(pixels: &[Option<[u8; 3]>]) -> (Vec<[u8; 3]>, Vec<Option<usize>>) {
+    let mut shift = 0;
+    loop {
+        let coarse = |[red, green, blue]: [u8; 3]| {
+            [
+                red >> shift << shift,
+                green >> shift << shift,
+                blue >> shift << shift,
+            ]
+        };
+
+        let mut palette = vec![];
+        let mut register_of_color = HashMap::new();
+        let registers: Vec<Option<usize>> = pixels
+            .iter()
+            .map(|pixel| {
+                pixel.map(|color| {
+                    let color = coarse(color);
+                    *register_of_color.entry(color).or_insert_with(|| {
+                        palette.push(color);
+                        palette.len() - 1
+                    })
+                })
+            })
+            .collect();
+        if palette.len() <= MAX_COLOR_REGISTERS {
+            return (palette, registers);
+        }
+        shift += 1;
+    }
+}
+
+fn push_run_length_encoded(sixel: &mut String, columns: &[u8]) {
+    let mut index = 0;
+    while index < columns.len() {
+        let bits = columns[index];
+        let run_length = columns[index..]
+            .iter()
+            .take_while(|other_bits| **other_bits == bits)
+            .count();
+        let symbol = (b'?' + bits) as char;
+        if run_length > 3 {
+            sixel.push_str(&format!("!{}{}", run_length, symbol));
+        } else {
+            sixel.extend(std::iter::repeat(symbol).take(run_length));
+        }
+        index += run_length;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::style::Color;
+
+    #[test]
+    fn test_encode_sixel() {
+        let mut image_canvas = ImageCanvas::new(5, 7);
+        for x in 0..5 {
+            image_canvas.paint(x, 0, Color::Rgb(255, 0, 0));
+        }
+        image_canvas.paint(1, 6, Color::White);
+
+        assert_eq!(
+            encode_sixel(&image_canvas),
+            "\x1bP0;1;0q\"1;1;5;7#0;2;100;0;0#1;2;100;100;100#0!5@$-#1?@$-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_color_registers() {
+        let pixels: Vec<Option<[u8; 3]>> = (0..300)
+            .map(|x| Some([(x / 2) as u8, x as u8, 0]))
+            .collect();
+        let (palette, registers) = color_registers(&pixels);
+        assert!(palette.len() <= MAX_COLOR_REGISTERS);
+        assert!(registers.iter().all(|register| register.is_some()));
+    }
+}
@@ -12,6 +12,7 @@
 // You should have received a copy of the GNU General Public License along with
 // Cicero. If not, see <https://www.gnu.org/licenses/>.
 
+use std::char;
 use std::collections::HashSet;
 use std::fmt;
 
@@ -19,7 +20,10 @@ use tui::widgets::ListState;
 use unic::segment::Graphemes;
 use unic::ucd::name::Name;
 
-use crate::ucd::code_point_description;
+use crate::ucd::{code_point_description, parse_code_point_range_input};
+
+// Listing a whole plane (or more) is of no use and would only slow the list down
+const MAX_CODE_POINT_RANGE_LENGTH: usize = 0x1000;
 
 #[derive(Default)]
 pub struct GraphemeRow {
@@ -57,14 +61,56 @@ pub struct StatefulGraphemes {
 
     grapheme_start_row_indices: HashSet<usize>,
     grapheme_end_row_indices: HashSet<usize>,
+
+    // Code points of a range left out of the list, past its maximum length
+    omitted_code_point_count: usize,
 }
 
 impl StatefulGraphemes {
     pub fn new(s: &str) -> Self {
-        let graphemes: Vec<Vec<char>> = Graphemes::new(s)
-            .map(|grapheme| grapheme.chars().map(|chr| chr).collect())
-            .collect();
+        // A range of code points (e.g. "range:U+0900..U+0910") lists each code point of the range
+        // on its own, so that a block can be scanned code point by code point
+        let range = match parse_code_point_range_input(s) {
+            Some(range) => range,
+            None => {
+                return StatefulGraphemes::of_graphemes(
+                    Graphemes::new(s)
+                        .map(|grapheme| grapheme.chars().map(|chr| chr).collect())
+                        .collect(),
+                );
+            }
+        };
+        let code_points =
+            || (*range.start() as u32..=*range.end() as u32).filter_map(char::from_u32);
+        let mut graphemes = StatefulGraphemes::of_graphemes(
+            code_points()
+                .take(MAX_CODE_POINT_RANGE_LENGTH)
+                .map(|chr| vec![chr])
+                .collect(),
+        );
+        graphemes.omitted_code_point_count = code_points()
+            .count()
+            .saturating_sub(MAX_CODE_POINT_RANGE_LENGTH);
+        graphemes
+    }
+
+    // Tells how much of a range too long to be listed whole is left out, if any
+    pub fn omitted_code_points_description(&self) -> Option<String> {
+        if self.omitted_code_point_count == 0 {
+            return None;
+        }
+        Some(format!(
+            "Only the first {} code points of the range are listed, {} more are left out",
+            MAX_CODE_POINT_RANGE_LENGTH, self.omitted_code_point_count
+        ))
+    }
 
+    // Lists each character on its own, e.g. the whole repertoire of a font
+    pub fn of_characters(characters: &[char]) -> Self {
+        StatefulGraphemes::of_graphemes(characters.iter().map(|chr| vec![*chr]).collect())
+    }
+
+    fn of_graphemes(graphemes: Vec<Vec<char>>) -> Self {
         let mut state = ListState::default();
         if !graphemes.is_empty() {
             state.select(Some(0));
@@ -101,6 +147,7 @@ impl StatefulGraphemes {
             rows,
             grapheme_start_row_indices,
             grapheme_end_row_indices,
+            omitted_code_point_count: 0,
         }
     }
 
@@ -151,6 +198,42 @@ impl StatefulGraphemes {
             }
         }
     }
+
+    // Selects the first row of the character, returns false if it isn't listed
+    pub fn select_character(&mut self, chr: char) -> bool {
+        match self.rows.iter().position(|row| row.code_point == Some(chr)) {
+            Some(index) => {
+                self.state.select(Some(index));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Whether the selected row is the first code point of its grapheme cluster
+    pub fn is_grapheme_start_selected(&self) -> bool {
+        self.state.selected().map_or(false, |selected| {
+            self.grapheme_start_row_indices.contains(&selected)
+        })
+    }
+
+    // The whole grapheme cluster the selected code point is part of
+    pub fn selected_grapheme(&self) -> Option<String> {
+        let selected = self.state.selected()?;
+        self.rows.get(selected)?.code_point?;
+
+        let start = (0..=selected)
+            .rev()
+            .find(|index| self.grapheme_start_row_indices.contains(index))?;
+        let end = (selected..self.rows.len())
+            .find(|index| self.grapheme_end_row_indices.contains(index))?;
+        Some(
+            self.rows[start..=end]
+                .iter()
+                .filter_map(|row| row.code_point)
+                .collect(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -197,6 +280,64 @@ mod tests {
         assert_eq!(graphemes.state.selected().unwrap(), 0);
     }
 
+    #[test]
+    fn test_selected_grapheme() {
+        let mut graphemes = StatefulGraphemes::new(TEST_STR);
+        assert_eq!(graphemes.selected_grapheme().unwrap(), "\u{0CA0}");
+
+        graphemes.state.select(Some(5));
+        assert_eq!(graphemes.selected_grapheme().unwrap(), "\u{0CB0}\u{0CC3}");
+
+        // Separator rows between graphemes have none
+        graphemes.state.select(Some(6));
+        assert!(graphemes.selected_grapheme().is_none());
+    }
+
+    #[test]
+    fn test_is_grapheme_start_selected() {
+        let mut graphemes = StatefulGraphemes::new(TEST_STR);
+        assert!(graphemes.is_grapheme_start_selected());
+
+        graphemes.state.select(Some(4));
+        assert!(graphemes.is_grapheme_start_selected());
+        graphemes.state.select(Some(5));
+        assert!(!graphemes.is_grapheme_start_selected());
+        graphemes.state.select(Some(6));
+        assert!(!graphemes.is_grapheme_start_selected());
+    }
+
+    #[test]
+    fn test_code_point_range() {
+        let graphemes = StatefulGraphemes::new("range:U+0915..U+0917");
+        let code_points: Vec<Option<char>> =
+            graphemes.rows.iter().map(|row| row.code_point).collect();
+        assert_eq!(
+            code_points,
+            vec![
+                Some('\u{915}'),
+                None,
+                Some('\u{916}'),
+                None,
+                Some('\u{917}')
+            ]
+        );
+
+        // Surrogates are skipped, long ranges are cut short and tell so
+        let graphemes = StatefulGraphemes::new("range:U+D7FF..U+E000");
+        assert_eq!(graphemes.rows.len(), 3);
+        assert_eq!(graphemes.omitted_code_points_description(), None);
+        let graphemes = StatefulGraphemes::new("range:U+0000..U+10FFFF");
+        assert_eq!(graphemes.rows.len(), MAX_CODE_POINT_RANGE_LENGTH * 2 - 1);
+        assert_eq!(
+            graphemes.omitted_code_point_count,
+            0x110000 - 0x800 - MAX_CODE_POINT_RANGE_LENGTH
+        );
+
+        // Text which only looks like a range is listed as it is
+        let graphemes = StatefulGraphemes::new("U+0915..U+0917");
+        assert_eq!(graphemes.rows.len(), 14 * 2 - 1);
+    }
+
     #[test]
     fn test_select_next() {
         let mut graphemes = StatefulGraphemes::new(TEST_STR);
@@ -247,4 +388,17 @@ mod tests {
         graphemes.select_previous();
         assert_eq!(graphemes.state.selected().unwrap(), 0);
     }
+
+    #[test]
+    fn test_of_characters() {
+        let mut graphemes = StatefulGraphemes::of_characters(&['A', 'B', '\u{0301}']);
+        assert_eq!(graphemes.rows.len(), 5);
+        assert_eq!(graphemes.state.selected(), Some(0));
+
+        assert!(graphemes.select_character('\u{0301}'));
+        assert_eq!(graphemes.state.selected(), Some(4));
+        assert_eq!(graphemes.selected_grapheme(), Some("\u{0301}".to_owned()));
+        assert!(!graphemes.select_character('C'));
+        assert_eq!(graphemes.state.selected(), Some(4));
+    }
 }
@@ -0,0 +1,208 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use std::env;
+
+use crate::settings::{PreviewProtocol, PreviewRenderer};
+
+// Replaces `PreviewRenderer::Auto` with the renderer the current terminal is likely to display
+pub fn resolve_preview_renderer(preview_renderer: PreviewRenderer) -> PreviewRenderer {
+    if preview_renderer != PreviewRenderer::Auto {
+        return preview_renderer;
+    }
+
+    // The first locale variable set wins, as defined by POSIX
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    let term = env::var("TERM").ok();
+
+    detect_preview_renderer(locale.as_deref(), term.as_deref())
+}
+
+fn detect_preview_renderer(locale: Option<&str>, term: Option<&str>) -> PreviewRenderer {
+    if term == Some("dumb") {
+        return PreviewRenderer::Ascii;
+    }
+
+    // Without any locale the encoding is unknown, most terminals are UTF-8 nowadays
+    if let Some(locale) = locale {
+        let locale = locale.to_ascii_lowercase();
+        if !locale.contains("utf-8") && !locale.contains("utf8") {
+            return PreviewRenderer::Ascii;
+        }
+    }
+
+    // The console fonts of Linux have block elements, but no braille patterns
+    if term == Some("linux") {
+        return PreviewRenderer::HalfBlock;
+    }
+
+    PreviewRenderer::Braille
+}
+
+// Replaces `PreviewProtocol::Auto` with the images the terminal is known to display, preferring
+// the kitty graphics protocol (WezTerm also has sixel graphics and the inline images of iTerm2).
+// Other terminals (e.g. xterm, which needs to be started as a VT340) can be set to sixel
+// explicitly.
+pub fn resolve_preview_protocol(preview_protocol: PreviewProtocol) -> PreviewProtocol {
+    if preview_protocol != PreviewProtocol::Auto {
+        return preview_protocol;
+    }
+
+    let term = env::var("TERM").ok();
+    let term_program = env::var("TERM_PROGRAM").ok();
+    detect_preview_protocol(term.as_deref(), term_program.as_deref())
+}
+
+fn detect_preview_protocol(term: Option<&str>, term_program: Option<&str>) -> PreviewProtocol {
+    let sixel_term = match term {
+        Some(term) => ["foot", "mlterm", "yaft", "contour"]
+            .iter()
+            .any(|prefix| term.starts_with(prefix)),
+        None => false,
+    };
+    if term == Some("xterm-kitty") || term_program == Some("WezTerm") {
+        PreviewProtocol::Kitty
+    } else if term_program == Some("iTerm.app") {
+        PreviewProtocol::Iterm2
+    } else if sixel_term {
+        PreviewProtocol::Sixel
+    } else {
+        PreviewProtocol::Text
+    }
+}
+
+// Fills in the size of a terminal cell in pixels with the size the terminal reports, where it isn't
+// set already. Terminals which don't report their size in pixels leave it unset.
+pub fn resolve_image_cell_size(
+    cell_width: Option<u16>,
+    cell_height: Option<u16>,
+) -> (Option<u16>, Option<u16>) {
+    if cell_width.is_some() && cell_height.is_some() {
+        return (cell_width, cell_height);
+    }
+    let reported_size = reported_cell_size();
+    (
+        cell_width.or_else(|| reported_size.map(|(width, _)| width)),
+        cell_height.or_else(|| reported_size.map(|(_, height)| height)),
+    )
+}
+
+// The size of a cell from the size of the window in pixels (TIOCGWINSZ), which many terminals
+// leave at 0
+fn reported_cell_size() -> Option<(u16, u16)> {
+    let mut window_size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut window_size) } != 0 {
+        return None;
+    }
+    cell_size_of(&window_size)
+}
+
+fn cell_size_of(window_size: &libc::winsize) -> Option<(u16, u16)> {
+    if window_size.ws_col == 0 || window_size.ws_row == 0 {
+        return None;
+    }
+    let cell_width = window_size.ws_xpixel / window_size.ws_col;
+    let cell_height = window_size.ws_ypixel / window_size.ws_row;
+    if cell_width == 0 || cell_height == 0 {
+        return None;
+    }
+    Some((cell_width, cell_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explicit_preview_renderer() {
+        assert_eq!(
+            resolve_preview_renderer(PreviewRenderer::HalfBlock),
+            PreviewRenderer::HalfBlock
+        );
+    }
+
+    #[test]
+    fn test_detect_preview_renderer() {
+        assert_eq!(
+            detect_preview_renderer(Some("en_US.UTF-8"), Some("xterm-256color")),
+            PreviewRenderer::Braille
+        );
+        assert_eq!(
+            detect_preview_renderer(None, Some("xterm")),
+            PreviewRenderer::Braille
+        );
+        assert_eq!(
+            detect_preview_renderer(Some("C.utf8"), Some("linux")),
+            PreviewRenderer::HalfBlock
+        );
+        assert_eq!(
+            detect_preview_renderer(Some("POSIX"), Some("xterm")),
+            PreviewRenderer::Ascii
+        );
+        assert_eq!(
+            detect_preview_renderer(Some("en_US.UTF-8"), Some("dumb")),
+            PreviewRenderer::Ascii
+        );
+    }
+
+    #[test]
+    fn test_detect_preview_protocol() {
+        assert_eq!(
+            resolve_preview_protocol(PreviewProtocol::Text),
+            PreviewProtocol::Text
+        );
+        assert_eq!(
+            detect_preview_protocol(Some("foot-extra"), None),
+            PreviewProtocol::Sixel
+        );
+        assert_eq!(
+            detect_preview_protocol(Some("xterm-256color"), Some("WezTerm")),
+            PreviewProtocol::Kitty
+        );
+        assert_eq!(
+            detect_preview_protocol(Some("xterm-kitty"), None),
+            PreviewProtocol::Kitty
+        );
+        assert_eq!(
+            detect_preview_protocol(Some("xterm-256color"), Some("iTerm.app")),
+            PreviewProtocol::Iterm2
+        );
+        assert_eq!(
+            detect_preview_protocol(Some("xterm-256color"), None),
+            PreviewProtocol::Text
+        );
+        assert_eq!(detect_preview_protocol(None, None), PreviewProtocol::Text);
+    }
+
+    #[test]
+    fn test_resolve_image_cell_size() {
+        assert_eq!(
+            resolve_image_cell_size(Some(8), Some(16)),
+            (Some(8), Some(16))
+        );
+
+        let window_size = |columns, rows, width, height| libc::winsize {
+            ws_row: rows,
+            ws_col: columns,
+            ws_xpixel: width,
+            ws_ypixel: height,
+        };
+        assert_eq!(cell_size_of(&window_size(80, 24, 800, 480)), Some((10, 20)));
+        assert_eq!(cell_size_of(&window_size(80, 24, 0, 0)), None);
+        assert_eq!(cell_size_of(&window_size(0, 0, 800, 480)), None);
+    }
+}
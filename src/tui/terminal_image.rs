@@ -0,0 +1,194 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Images of the preview pane for terminals which display them (with sixel graphics, the kitty
+//! graphics protocol or the inline images of iTerm2). The images are written over the (blank)
+//! cells of the preview once the rest of the TUI is drawn.
+//!
+
+use tui::layout::Rect;
+use tui::style::Color;
+
+#[cfg(feature = "image")]
+use super::iterm2::Iterm2Backend;
+use super::kitty::KittyBackend;
+use super::sixel::SixelBackend;
+use crate::settings::PreviewProtocol;
+
+// Levels of the 6x6x6 color cube of the 256-color palette
+const COLOR_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// Colors of the 16 ANSI colors (as in xterm), the default foreground is shown as light gray
+const ANSI_COLORS: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+// A protocol the preview is sent to the terminal with as images, the canvas only paints pixels
+pub trait PreviewBackend {
+    // Encodes the canvas, displayed from the cursor position over the given cells
+    fn encode(
+        &self,
+        image_canvas: &ImageCanvas,
+        width_in_cells: u16,
+        height_in_cells: u16,
+    ) -> String;
+
+    // The sequence deleting the images on the screen, `None` if they are only removed by drawing
+    // over them (the screen is then drawn again from scratch)
+    fn delete_images(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+// The backend of an image protocol, `None` for text and protocols Cicero was built without (the
+// inline images of iTerm2 are PNG images)
+pub fn preview_backend(preview_protocol: PreviewProtocol) -> Option<&'static dyn PreviewBackend> {
+    match preview_protocol {
+        PreviewProtocol::Sixel => Some(&SixelBackend),
+        PreviewProtocol::Kitty => Some(&KittyBackend),
+        #[cfg(feature = "image")]
+        PreviewProtocol::Iterm2 => Some(&Iterm2Backend),
+        _ => None,
+    }
+}
+
+// An encoded image and the cells it covers
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TerminalImage {
+    pub rect: Rect,
+    pub data: String,
+}
+
+// A canvas of pixels sent to the terminal as an image, unpainted pixels are transparent
+pub struct ImageCanvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Option<[u8; 3]>>,
+}
+
+impl ImageCanvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        ImageCanvas {
+            width,
+            height,
+            pixels: vec![None; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Rows of pixels from the top
+    pub fn pixels(&self) -> &[Option<[u8; 3]>] {
+        &self.pixels
+    }
+
+    // The pixels as RGBA bytes, unpainted pixels being fully transparent
+    pub fn rgba(&self) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in &self.pixels {
+            match pixel {
+                Some([red, green, blue]) => rgba.extend_from_slice(&[*red, *green, *blue, 255]),
+                None => rgba.extend_from_slice(&[0; 4]),
+            }
+        }
+        rgba
+    }
+
+    // Pixels outside of the canvas are ignored
+    pub fn paint(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = Some(color_rgb(color));
+        }
+    }
+}
+
+// The RGB color a terminal would likely show for a color of the TUI
+fn color_rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Reset => ANSI_COLORS[7],
+        Color::Black => ANSI_COLORS[0],
+        Color::Red => ANSI_COLORS[1],
+        Color::Green => ANSI_COLORS[2],
+        Color::Yellow => ANSI_COLORS[3],
+        Color::Blue => ANSI_COLORS[4],
+        Color::Magenta => ANSI_COLORS[5],
+        Color::Cyan => ANSI_COLORS[6],
+        Color::Gray => ANSI_COLORS[7],
+        Color::DarkGray => ANSI_COLORS[8],
+        Color::LightRed => ANSI_COLORS[9],
+        Color::LightGreen => ANSI_COLORS[10],
+        Color::LightYellow => ANSI_COLORS[11],
+        Color::LightBlue => ANSI_COLORS[12],
+        Color::LightMagenta => ANSI_COLORS[13],
+        Color::LightCyan => ANSI_COLORS[14],
+        Color::White => ANSI_COLORS[15],
+        Color::Rgb(red, green, blue) => [red, green, blue],
+        Color::Indexed(index) if index < 16 => ANSI_COLORS[index as usize],
+        Color::Indexed(index) if index < 232 => {
+            let index = (index - 16) as usize;
+            [
+                COLOR_CUBE_LEVELS[index / 36],
+                COLOR_CUBE_LEVELS[index / 6 % 6],
+                COLOR_CUBE_LEVELS[index % 6],
+            ]
+        }
+        Color::Indexed(index) => {
+            let level = 8 + (index - 232) * 10;
+            [level, level, level]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        let mut image_canvas = ImageCanvas::new(2, 2);
+        image_canvas.paint(1, 0, Color::Rgb(1, 2, 3));
+        image_canvas.paint(2, 0, Color::White);
+        assert_eq!(image_canvas.pixels(), &[None, Some([1, 2, 3]), None, None]);
+        assert_eq!(&image_canvas.rgba()[..8], &[0, 0, 0, 0, 1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn test_color_rgb() {
+        assert_eq!(color_rgb(Color::Indexed(196)), [255, 0, 0]);
+        assert_eq!(color_rgb(Color::Indexed(255)), [238, 238, 238]);
+        assert_eq!(color_rgb(Color::Indexed(9)), color_rgb(Color::LightRed));
+    }
+}
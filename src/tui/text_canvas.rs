@@ -0,0 +1,182 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use tui::style::{Color, Style};
+use tui::widgets::Text;
+
+// Both markers draw two vertically stacked pixels per cell
+pub const TEXT_PIXELS_PER_CELL_HORIZONTAL: u16 = 1;
+pub const TEXT_PIXELS_PER_CELL_VERTICAL: u16 = 2;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextMarker {
+    HalfBlock,
+    Ascii,
+}
+
+impl TextMarker {
+    fn symbol(self, top: bool, bottom: bool) -> char {
+        match (self, top, bottom) {
+            (_, false, false) => ' ',
+            (TextMarker::HalfBlock, true, false) => '\u{2580}',
+            (TextMarker::HalfBlock, false, true) => '\u{2584}',
+            (TextMarker::HalfBlock, true, true) => '\u{2588}',
+            (TextMarker::Ascii, true, false) => '"',
+            (TextMarker::Ascii, false, true) => '.',
+            (TextMarker::Ascii, true, true) => '#',
+        }
+    }
+}
+
+// A canvas drawn with plain text, for terminals which can't display braille patterns
+pub struct TextCanvas {
+    marker: TextMarker,
+    width: usize,
+    height: usize,
+    pixels: Vec<Option<Color>>,
+}
+
+impl TextCanvas {
+    pub fn new(marker: TextMarker, width_in_cells: u16, height_in_cells: u16) -> Self {
+        let width = (width_in_cells * TEXT_PIXELS_PER_CELL_HORIZONTAL) as usize;
+        let height = (height_in_cells * TEXT_PIXELS_PER_CELL_VERTICAL) as usize;
+        TextCanvas {
+            marker,
+            width,
+            height,
+            pixels: vec![None; width * height],
+        }
+    }
+
+    // Pixels outside of the canvas are ignored
+    pub fn paint(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = Some(color);
+        }
+    }
+
+    // Returns the rows of the canvas, consecutive cells of the same colors share a single item
+    pub fn text(&self) -> Vec<Text<'static>> {
+        let mut items = vec![];
+        for row in 0..self.height / 2 {
+            let mut run = String::new();
+            let mut run_colors = (None, None);
+            for x in 0..self.width {
+                let top = self.pixels[row * 2 * self.width + x];
+                let bottom = self.pixels[(row * 2 + 1) * self.width + x];
+                let (symbol, colors) = self.cell(top, bottom);
+                if colors != run_colors && !run.is_empty() {
+                    items.push(styled_text(run, run_colors));
+                    run = String::new();
+                }
+                run_colors = colors;
+                run.push(symbol);
+            }
+            run.push('\n');
+            items.push(styled_text(run, run_colors));
+        }
+        items
+    }
+
+    // The symbol of a cell with its foreground and background colors. Half blocks show two colors
+    // at once (e.g. for color glyphs) with the upper half block over a background of the bottom
+    // color, other cells are drawn in a single color.
+    fn cell(
+        &self,
+        top: Option<Color>,
+        bottom: Option<Color>,
+    ) -> (char, (Option<Color>, Option<Color>)) {
+        match (self.marker, top, bottom) {
+            (TextMarker::HalfBlock, Some(top), Some(bottom)) if top != bottom => {
+                (self.marker.symbol(true, false), (Some(top), Some(bottom)))
+            }
+            _ => (
+                self.marker.symbol(top.is_some(), bottom.is_some()),
+                (bottom.or(top), None),
+            ),
+        }
+    }
+}
+
+fn styled_text(
+    text: String,
+    (foreground, background): (Option<Color>, Option<Color>),
+) -> Text<'static> {
+    let style = Style::default().fg(foreground.unwrap_or(Color::Reset));
+    Text::styled(
+        text,
+        match background {
+            Some(background) => style.bg(background),
+            None => style,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain_text(text_canvas: &TextCanvas) -> String {
+        text_canvas
+            .text()
+            .iter()
+            .map(|item| match item {
+                Text::Raw(text) | Text::Styled(text, _) => text.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ascii_canvas() {
+        let mut text_canvas = TextCanvas::new(TextMarker::Ascii, 3, 1);
+        text_canvas.paint(0, 0, Color::Reset);
+        text_canvas.paint(1, 1, Color::Reset);
+        text_canvas.paint(2, 0, Color::Reset);
+        text_canvas.paint(2, 1, Color::Reset);
+        text_canvas.paint(3, 0, Color::Reset); // Clipped
+        assert_eq!(plain_text(&text_canvas), "\".#\n");
+    }
+
+    #[test]
+    fn test_half_block_canvas() {
+        let mut text_canvas = TextCanvas::new(TextMarker::HalfBlock, 2, 2);
+        text_canvas.paint(0, 0, Color::Reset);
+        text_canvas.paint(1, 3, Color::Yellow);
+        assert_eq!(plain_text(&text_canvas), "\u{2580} \n \u{2584}\n");
+        assert_eq!(text_canvas.text().len(), 4);
+    }
+
+    #[test]
+    fn test_two_colored_half_blocks() {
+        let mut text_canvas = TextCanvas::new(TextMarker::HalfBlock, 2, 1);
+        text_canvas.paint(0, 0, Color::Red);
+        text_canvas.paint(0, 1, Color::Blue);
+        text_canvas.paint(1, 0, Color::Red);
+        text_canvas.paint(1, 1, Color::Red);
+        assert_eq!(plain_text(&text_canvas), "\u{2580}\u{2588}\n");
+        assert_eq!(
+            text_canvas.text()[0],
+            Text::styled("\u{2580}", Style::default().fg(Color::Red).bg(Color::Blue))
+        );
+
+        // Ascii cells have a single color
+        let mut text_canvas = TextCanvas::new(TextMarker::Ascii, 1, 1);
+        text_canvas.paint(0, 0, Color::Red);
+        text_canvas.paint(0, 1, Color::Blue);
+        assert_eq!(
+            text_canvas.text()[0],
+            Text::styled("#\n", Style::default().fg(Color::Blue))
+        );
+    }
+}
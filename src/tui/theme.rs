@@ -0,0 +1,104 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use tui::style::Color;
+
+use crate::settings::Palette;
+
+// Colors of the hints, highlights and guides of the TUI
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Theme {
+    // Key hints, property titles and selected list items
+    pub accent: Color,
+
+    // Labels, notes and messages which stay in the background of the view
+    pub muted: Color,
+
+    // Text typed by the user into the input and the command palette
+    pub input: Color,
+
+    // Ink bounds and pen origin drawn over the glyph
+    pub glyph_bounds: Color,
+    pub pen_origin: Color,
+
+    // Braille dot grid and font guide lines drawn beneath the glyph
+    pub dot_grid: Color,
+    pub guides: Color,
+
+    // Ink of only one of the two fonts compared by the font diff, shared ink keeps the default
+    // color
+    pub first_font_ink: Color,
+    pub second_font_ink: Color,
+}
+
+impl Theme {
+    pub fn of(palette: Palette) -> Self {
+        match palette {
+            Palette::Default => Theme {
+                accent: Color::LightGreen,
+                muted: Color::DarkGray,
+                input: Color::Yellow,
+                glyph_bounds: Color::Yellow,
+                pen_origin: Color::Red,
+                dot_grid: Color::DarkGray,
+                guides: Color::DarkGray,
+                first_font_ink: Color::LightRed,
+                second_font_ink: Color::LightGreen,
+            },
+            // Red and green are the hardest to tell apart with the common color vision
+            // deficiencies, blue and yellow/orange hues remain distinct
+            Palette::ColorBlind => Theme {
+                accent: Color::LightCyan,
+                muted: Color::DarkGray,
+                input: Color::Yellow,
+                glyph_bounds: Color::Yellow,
+                pen_origin: Color::LightBlue,
+                dot_grid: Color::DarkGray,
+                guides: Color::DarkGray,
+                first_font_ink: Color::LightBlue,
+                second_font_ink: Color::Yellow,
+            },
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::of(Palette::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palettes_avoid_red_green() {
+        let red_green = [Color::Red, Color::LightRed, Color::Green, Color::LightGreen];
+        let theme = Theme::of(Palette::ColorBlind);
+        for color in &[
+            theme.accent,
+            theme.glyph_bounds,
+            theme.pen_origin,
+            theme.first_font_ink,
+            theme.second_font_ink,
+        ] {
+            assert!(!red_green.contains(color));
+        }
+        assert_ne!(theme.glyph_bounds, theme.pen_origin);
+        assert_ne!(theme.first_font_ink, theme.second_font_ink);
+
+        assert_eq!(Theme::default().accent, Color::LightGreen);
+    }
+}
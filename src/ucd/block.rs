@@ -0,0 +1,77 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use unic::ucd::{Block, BlockIter};
+
+// Returns the first block starting after the given character, or the first block if `wrap` is set
+// and there is no such block
+pub fn next_block(chr: char, wrap: bool) -> Option<Block> {
+    let mut blocks = BlockIter::new();
+    match blocks.find(|block| block.range.low > chr) {
+        Some(block) => Some(block),
+        None if wrap => BlockIter::new().next(),
+        None => None,
+    }
+}
+
+// Returns the last block ending before the block of the given character (or before the character
+// itself if it is not in a block), or the last block if `wrap` is set and there is no such block
+pub fn previous_block(chr: char, wrap: bool) -> Option<Block> {
+    let current_block_start = match Block::of(chr) {
+        Some(block) => block.range.low,
+        None => chr,
+    };
+    match BlockIter::new()
+        .filter(|block| block.range.high < current_block_start)
+        .last()
+    {
+        Some(block) => Some(block),
+        None if wrap => BlockIter::new().last(),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_block() {
+        let block = next_block('A', false).unwrap();
+        assert_eq!(block.name, "Latin-1 Supplement");
+        assert_eq!(block.range.low, '\u{0080}');
+
+        assert!(next_block('\u{10FFFF}', false).is_none());
+        assert_eq!(next_block('\u{10FFFF}', true).unwrap().name, "Basic Latin");
+    }
+
+    #[test]
+    fn test_previous_block() {
+        let block = previous_block('\u{00E9}', false).unwrap();
+        assert_eq!(block.name, "Basic Latin");
+        assert_eq!(block.range.low, '\u{0000}');
+
+        // Characters outside of any block move to the block right before them
+        assert_eq!(
+            previous_block('\u{2FE0}', false).unwrap().name,
+            "Kangxi Radicals"
+        );
+
+        assert!(previous_block('A', false).is_none());
+        assert_eq!(
+            previous_block('A', true).unwrap().name,
+            "Supplementary Private Use Area-B"
+        );
+    }
+}
@@ -15,6 +15,7 @@
 use hex_slice::AsHex;
 use serde::Serialize;
 use unic::char::property::EnumeratedCharProperty;
+use unic::normal::StrNormalForm;
 use unic::segment::Graphemes;
 use unic::ucd::{
     bidi::{is_bidi_control, is_bidi_mirrored, BidiClass},
@@ -89,6 +90,7 @@ pub struct CharacterProperties {
 
     pub ccc: u8,
     pub decomposition: Option<Decomposition>,
+    pub normalization_forms: NormalizationForms,
 
     pub bidi_class: StringValuedProperty,
     pub is_bidi_control: bool,
@@ -149,6 +151,7 @@ impl CharacterProperties {
 
             ccc: CanonicalCombiningClass::of(character).number(),
             decomposition: Decomposition::new(character),
+            normalization_forms: NormalizationForms::new(character),
 
             bidi_class: StringValuedProperty::new(BidiClass::of(character)),
             is_bidi_control: is_bidi_control(character),
@@ -258,3 +261,42 @@ impl Decomposition {
         })
     }
 }
+
+#[derive(Serialize, Debug)]
+pub struct NormalizationForms {
+    pub nfc: Vec<char>,
+    pub nfd: Vec<char>,
+    pub nfkc: Vec<char>,
+    pub nfkd: Vec<char>,
+}
+
+impl NormalizationForms {
+    pub fn new(chr: char) -> Self {
+        let mut utf8_storage = [0; 4];
+        let string: &str = chr.encode_utf8(&mut utf8_storage);
+
+        NormalizationForms {
+            nfc: string.nfc().collect(),
+            nfd: string.nfd().collect(),
+            nfkc: string.nfkc().collect(),
+            nfkd: string.nfkd().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_forms() {
+        let precomposed = NormalizationForms::new('\u{00C5}');
+        assert_eq!(precomposed.nfc, vec!['\u{00C5}']);
+        assert_eq!(precomposed.nfd, vec!['A', '\u{030A}']);
+
+        let compatibility = NormalizationForms::new('\u{FB01}');
+        assert_eq!(compatibility.nfc, vec!['\u{FB01}']);
+        assert_eq!(compatibility.nfkc, vec!['f', 'i']);
+        assert_eq!(compatibility.nfkd, vec!['f', 'i']);
+    }
+}
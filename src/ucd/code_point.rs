@@ -0,0 +1,196 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+//!
+//! Parsing of code points written as "U+XXXX". Surrogates (U+D800 to U+DFFF) and code points past
+//! U+10FFFF are code points, but not Unicode scalar values, so no `char` can hold them. Inputs
+//! naming them are reported as such instead of being ignored.
+//!
+
+use std::char;
+use std::error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+const SURROGATES: RangeInclusive<u32> = 0xD800..=0xDFFF;
+
+// Inputs listing a range of code points start with this, e.g. "range:U+0900..U+0910", so that text
+// which only looks like a range is listed as it is
+const CODE_POINT_RANGE_INPUT_PREFIX: &str = "range:";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InvalidCodePoint {
+    Surrogate(u32),
+    OutOfRange(u32),
+}
+
+impl fmt::Display for InvalidCodePoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidCodePoint::Surrogate(code_point) => write!(
+                f,
+                "U+{:04X} is a surrogate, not a valid Unicode scalar value",
+                code_point
+            ),
+            InvalidCodePoint::OutOfRange(code_point) => write!(
+                f,
+                "U+{:04X} is past U+10FFFF, not a valid Unicode scalar value",
+                code_point
+            ),
+        }
+    }
+}
+
+impl error::Error for InvalidCodePoint {}
+
+pub fn char_from_code_point(code_point: u32) -> Result<char, InvalidCodePoint> {
+    char::from_u32(code_point).ok_or_else(|| {
+        if SURROGATES.contains(&code_point) {
+            InvalidCodePoint::Surrogate(code_point)
+        } else {
+            InvalidCodePoint::OutOfRange(code_point)
+        }
+    })
+}
+
+// The value of a code point written as "U+XXXX", whether or not it is a valid character
+fn parse_code_point_value(code_point: &str) -> Option<u32> {
+    if !code_point.to_lowercase().starts_with("u+") {
+        return None;
+    }
+    u32::from_str_radix(&code_point[2..], 16).ok()
+}
+
+// Parses a code point written as "U+XXXX"
+pub fn parse_code_point(code_point: &str) -> Option<char> {
+    char_from_code_point(parse_code_point_value(code_point)?).ok()
+}
+
+// Parses a range of code points written as "U+0900..U+0910", both ends included
+pub fn parse_code_point_range(range: &str) -> Option<RangeInclusive<char>> {
+    let mut ends = range.trim().splitn(2, "..").map(parse_code_point);
+    match (ends.next(), ends.next()) {
+        (Some(Some(first)), Some(Some(last))) if first <= last => Some(first..=last),
+        _ => None,
+    }
+}
+
+fn strip_code_point_range_input_prefix(input: &str) -> Option<&str> {
+    let prefix_length = CODE_POINT_RANGE_INPUT_PREFIX.len();
+    match input.get(..prefix_length) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(CODE_POINT_RANGE_INPUT_PREFIX) => {
+            Some(&input[prefix_length..])
+        }
+        _ => None,
+    }
+}
+
+// Parses an input listing a range of code points, written as "range:U+0900..U+0910"
+pub fn parse_code_point_range_input(input: &str) -> Option<RangeInclusive<char>> {
+    parse_code_point_range(strip_code_point_range_input_prefix(input.trim())?)
+}
+
+// Returns the first invalid code point named by an input written as a code point ("U+XXXX") or a
+// range of them ("range:U+XXXX..U+XXXX"), None if the input is valid or not written as code points
+pub fn invalid_code_point_in(input: &str) -> Option<InvalidCodePoint> {
+    let input = input.trim();
+    strip_code_point_range_input_prefix(input)
+        .unwrap_or(input)
+        .splitn(2, "..")
+        .filter_map(parse_code_point_value)
+        .find_map(|code_point| char_from_code_point(code_point).err())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_from_code_point() {
+        assert_eq!(char_from_code_point(0x41), Ok('A'));
+        assert_eq!(char_from_code_point(0xD7FF), Ok('\u{D7FF}'));
+        assert_eq!(char_from_code_point(0xE000), Ok('\u{E000}'));
+        for code_point in &[0xD800, 0xDBFF, 0xDC00, 0xDFFF] {
+            assert_eq!(
+                char_from_code_point(*code_point),
+                Err(InvalidCodePoint::Surrogate(*code_point))
+            );
+        }
+        assert_eq!(
+            char_from_code_point(0x110000),
+            Err(InvalidCodePoint::OutOfRange(0x110000))
+        );
+        assert_eq!(
+            InvalidCodePoint::Surrogate(0xD800).to_string(),
+            "U+D800 is a surrogate, not a valid Unicode scalar value"
+        );
+    }
+
+    #[test]
+    fn test_parse_code_point() {
+        assert_eq!(parse_code_point("U+0041"), Some('A'));
+        assert_eq!(parse_code_point("u+1f600"), Some('\u{1F600}'));
+        assert_eq!(parse_code_point("U+D800"), None);
+        assert_eq!(parse_code_point("0041"), None);
+    }
+
+    #[test]
+    fn test_parse_code_point_range() {
+        assert_eq!(
+            parse_code_point_range("U+0900..U+0910"),
+            Some('\u{900}'..='\u{910}')
+        );
+        assert_eq!(parse_code_point_range("U+0041..U+0041"), Some('A'..='A'));
+        assert_eq!(parse_code_point_range("U+0910..U+0900"), None);
+        assert_eq!(parse_code_point_range("U+0900"), None);
+        assert_eq!(parse_code_point_range("U+0900..0910"), None);
+    }
+
+    #[test]
+    fn test_parse_code_point_range_input() {
+        assert_eq!(
+            parse_code_point_range_input(" range:U+0900..U+0910"),
+            Some('\u{900}'..='\u{910}')
+        );
+        assert_eq!(
+            parse_code_point_range_input("Range:U+0041..U+0042"),
+            Some('A'..='B')
+        );
+        assert_eq!(parse_code_point_range_input("U+0900..U+0910"), None);
+        assert_eq!(parse_code_point_range_input("range:"), None);
+        assert_eq!(parse_code_point_range_input("ränge"), None);
+    }
+
+    #[test]
+    fn test_invalid_code_point_in() {
+        assert_eq!(
+            invalid_code_point_in("U+D800"),
+            Some(InvalidCodePoint::Surrogate(0xD800))
+        );
+        assert_eq!(
+            invalid_code_point_in("U+0041..U+DFFF"),
+            Some(InvalidCodePoint::Surrogate(0xDFFF))
+        );
+        assert_eq!(
+            invalid_code_point_in("U+110000"),
+            Some(InvalidCodePoint::OutOfRange(0x110000))
+        );
+        assert_eq!(
+            invalid_code_point_in("range:U+0041..U+D800"),
+            Some(InvalidCodePoint::Surrogate(0xD800))
+        );
+        assert_eq!(invalid_code_point_in("U+0041..U+0042"), None);
+        assert_eq!(invalid_code_point_in("hello"), None);
+    }
+}
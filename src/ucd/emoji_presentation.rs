@@ -0,0 +1,61 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use unic::emoji::char::{is_emoji, is_emoji_presentation};
+
+const LAST_BMP_CODE_POINT: u32 = 0xFFFF;
+
+// Variation selectors requesting the text (VS15) or emoji (VS16) presentation of a character
+pub const TEXT_PRESENTATION_SELECTOR: char = '\u{FE0E}';
+pub const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+// Joins emoji into a single one (e.g. family or profession emoji)
+pub const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+// Whether the character is commonly shown both as text and as emoji, depending on the variation
+// selector following it (e.g. U+2764 HEAVY BLACK HEART). Those are the emoji shown as text by
+// default (Emoji_Presentation=No), along with the emoji of the BMP shown as emoji by default
+// (e.g. U+231A WATCH), which were encoded before emoji and have a text presentation as well.
+pub fn has_presentation_variants(chr: char) -> bool {
+    is_emoji(chr) && (!is_emoji_presentation(chr) || (chr as u32) <= LAST_BMP_CODE_POINT)
+}
+
+// Whether a grapheme cluster is a sequence of emoji joined into a single one by ZWJ
+pub fn is_zwj_sequence(grapheme: &str) -> bool {
+    grapheme.contains(ZERO_WIDTH_JOINER) && grapheme.chars().count() > 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_presentation_variants() {
+        assert!(has_presentation_variants('\u{2764}'));
+        assert!(has_presentation_variants('#'));
+        assert!(has_presentation_variants('\u{1F6F3}'));
+        assert!(has_presentation_variants('\u{231A}'));
+        assert!(!has_presentation_variants('A'));
+        assert!(!has_presentation_variants('\u{1F600}'));
+    }
+
+    #[test]
+    fn test_is_zwj_sequence() {
+        assert!(is_zwj_sequence(
+            "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"
+        ));
+        assert!(!is_zwj_sequence("\u{200D}"));
+        assert!(!is_zwj_sequence("\u{1F468}"));
+    }
+}
@@ -21,11 +21,29 @@
 //! Cicero is a Unicode tool after all, so sometimes it needs to work with all aspects of Unicode.
 //!
 
+mod block;
 mod character_properties;
+mod code_point;
+mod emoji_presentation;
+mod non_printing;
 mod plane;
+mod script;
 
-pub use character_properties::{CharacterProperties, GraphemeProperties};
+pub use block::{next_block, previous_block};
+pub use character_properties::{
+    CharacterProperties, GraphemeProperties, NormalizationForms, StringValuedProperty,
+};
+pub use code_point::{
+    char_from_code_point, invalid_code_point_in, parse_code_point, parse_code_point_range,
+    parse_code_point_range_input, InvalidCodePoint,
+};
+pub use emoji_presentation::{
+    has_presentation_variants, is_zwj_sequence, EMOJI_PRESENTATION_SELECTOR,
+    TEXT_PRESENTATION_SELECTOR, ZERO_WIDTH_JOINER,
+};
+pub use non_printing::{control_picture_of, is_non_printing, non_printing_symbol};
 pub use plane::Plane;
+pub use script::blocks_of_script;
 
 #[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Range {
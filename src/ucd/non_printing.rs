@@ -0,0 +1,95 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+use std::char;
+
+use unic::ucd::{name_aliases_of, GeneralCategory, Name, NameAliasType};
+
+const CONTROL_PICTURES_START: u32 = 0x2400;
+const CONTROL_PICTURE_FOR_DELETE: char = '\u{2421}';
+
+// Characters without a visible glyph of their own: controls, format characters and separators
+pub fn is_non_printing(chr: char) -> bool {
+    match GeneralCategory::of(chr) {
+        GeneralCategory::Control
+        | GeneralCategory::Format
+        | GeneralCategory::SpaceSeparator
+        | GeneralCategory::LineSeparator
+        | GeneralCategory::ParagraphSeparator => true,
+        _ => false,
+    }
+}
+
+// The symbol of the Control Pictures block standing for a character, if there is one
+pub fn control_picture_of(chr: char) -> Option<char> {
+    match chr as u32 {
+        code_point @ 0x00..=0x20 => char::from_u32(CONTROL_PICTURES_START + code_point),
+        0x7F => Some(CONTROL_PICTURE_FOR_DELETE),
+        _ => None,
+    }
+}
+
+// A short description of a non-printing character, e.g. "␀ NUL" or "ZWJ", made of its control
+// picture and its abbreviation (or its name if it has no abbreviation)
+pub fn non_printing_symbol(chr: char) -> Option<String> {
+    if !is_non_printing(chr) {
+        return None;
+    }
+
+    let abbreviation = match name_aliases_of(chr, NameAliasType::NameAbbreviations) {
+        Some(abbreviations) if !abbreviations.is_empty() => abbreviations[0].to_owned(),
+        _ => match Name::of(chr) {
+            Some(name) => name.to_string(),
+            None => super::code_point_description(chr),
+        },
+    };
+    Some(match control_picture_of(chr) {
+        Some(control_picture) => format!("{} {}", control_picture, abbreviation),
+        None => abbreviation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_non_printing() {
+        assert!(is_non_printing('\u{0}'));
+        assert!(is_non_printing(' '));
+        assert!(is_non_printing('\u{200D}'));
+        assert!(is_non_printing('\u{2028}'));
+        assert!(!is_non_printing('A'));
+        assert!(!is_non_printing('\u{2400}'));
+    }
+
+    #[test]
+    fn test_control_picture_of() {
+        assert_eq!(control_picture_of('\u{0}'), Some('\u{2400}'));
+        assert_eq!(control_picture_of('\n'), Some('\u{240A}'));
+        assert_eq!(control_picture_of(' '), Some('\u{2420}'));
+        assert_eq!(control_picture_of('\u{7F}'), Some('\u{2421}'));
+        assert_eq!(control_picture_of('\u{85}'), None);
+    }
+
+    #[test]
+    fn test_non_printing_symbol() {
+        assert_eq!(
+            non_printing_symbol('\u{0}'),
+            Some("\u{2400} NUL".to_owned())
+        );
+        assert_eq!(non_printing_symbol('\u{200D}'), Some("ZWJ".to_owned()));
+        assert_eq!(non_printing_symbol('A'), None);
+    }
+}
@@ -0,0 +1,155 @@
+// This file is part of Cicero.
+//
+// Cicero is free software: you can redistribute it and/or modify it under the
+// terms of the GNU General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later
+// version.
+//
+// Cicero is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR
+// A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// Cicero. If not, see <https://www.gnu.org/licenses/>.
+
+// `unic` has no Script property, so scripts are approximated by the blocks made (mostly) of their
+// characters, e.g. Han spans the CJK ideograph blocks and their extensions
+const SCRIPT_BLOCKS: &[(&str, &[&str])] = &[
+    (
+        "Latin",
+        &[
+            "Basic Latin",
+            "Latin-1 Supplement",
+            "Latin Extended-A",
+            "Latin Extended-B",
+            "IPA Extensions",
+            "Latin Extended Additional",
+            "Latin Extended-C",
+            "Latin Extended-D",
+            "Latin Extended-E",
+        ],
+    ),
+    ("Greek", &["Greek and Coptic", "Greek Extended"]),
+    (
+        "Cyrillic",
+        &[
+            "Cyrillic",
+            "Cyrillic Supplement",
+            "Cyrillic Extended-A",
+            "Cyrillic Extended-B",
+            "Cyrillic Extended-C",
+        ],
+    ),
+    ("Armenian", &["Armenian"]),
+    ("Hebrew", &["Hebrew"]),
+    (
+        "Arabic",
+        &[
+            "Arabic",
+            "Arabic Supplement",
+            "Arabic Extended-A",
+            "Arabic Presentation Forms-A",
+            "Arabic Presentation Forms-B",
+            "Arabic Mathematical Alphabetic Symbols",
+        ],
+    ),
+    ("Syriac", &["Syriac", "Syriac Supplement"]),
+    ("Thaana", &["Thaana"]),
+    ("Devanagari", &["Devanagari", "Devanagari Extended"]),
+    ("Bengali", &["Bengali"]),
+    ("Gurmukhi", &["Gurmukhi"]),
+    ("Gujarati", &["Gujarati"]),
+    ("Oriya", &["Oriya"]),
+    ("Tamil", &["Tamil"]),
+    ("Telugu", &["Telugu"]),
+    ("Kannada", &["Kannada"]),
+    ("Malayalam", &["Malayalam"]),
+    ("Sinhala", &["Sinhala", "Sinhala Archaic Numbers"]),
+    ("Thai", &["Thai"]),
+    ("Lao", &["Lao"]),
+    ("Tibetan", &["Tibetan"]),
+    (
+        "Myanmar",
+        &["Myanmar", "Myanmar Extended-A", "Myanmar Extended-B"],
+    ),
+    ("Georgian", &["Georgian", "Georgian Supplement"]),
+    (
+        "Hangul",
+        &[
+            "Hangul Jamo",
+            "Hangul Compatibility Jamo",
+            "Hangul Jamo Extended-A",
+            "Hangul Syllables",
+            "Hangul Jamo Extended-B",
+        ],
+    ),
+    (
+        "Ethiopic",
+        &[
+            "Ethiopic",
+            "Ethiopic Supplement",
+            "Ethiopic Extended",
+            "Ethiopic Extended-A",
+        ],
+    ),
+    ("Cherokee", &["Cherokee", "Cherokee Supplement"]),
+    ("Khmer", &["Khmer", "Khmer Symbols"]),
+    ("Mongolian", &["Mongolian", "Mongolian Supplement"]),
+    (
+        "Hiragana",
+        &["Hiragana", "Kana Supplement", "Kana Extended-A"],
+    ),
+    ("Katakana", &["Katakana", "Katakana Phonetic Extensions"]),
+    ("Bopomofo", &["Bopomofo", "Bopomofo Extended"]),
+    (
+        "Han",
+        &[
+            "CJK Radicals Supplement",
+            "Kangxi Radicals",
+            "CJK Symbols and Punctuation",
+            "CJK Unified Ideographs Extension A",
+            "CJK Unified Ideographs",
+            "CJK Compatibility Ideographs",
+            "CJK Unified Ideographs Extension B",
+            "CJK Unified Ideographs Extension C",
+            "CJK Unified Ideographs Extension D",
+            "CJK Unified Ideographs Extension E",
+            "CJK Unified Ideographs Extension F",
+            "CJK Compatibility Ideographs Supplement",
+        ],
+    ),
+];
+
+// Returns the name of the script (case insensitive) as listed and the names of its blocks, or
+// None for a script without a listed set of blocks
+pub fn blocks_of_script(name: &str) -> Option<(&'static str, &'static [&'static str])> {
+    SCRIPT_BLOCKS
+        .iter()
+        .find(|(script, _)| script.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use unic::ucd::BlockIter;
+
+    #[test]
+    fn test_blocks_of_script() {
+        let (name, blocks) = blocks_of_script("han").unwrap();
+        assert_eq!(name, "Han");
+        assert!(blocks.contains(&"CJK Unified Ideographs Extension B"));
+        assert!(blocks_of_script("Klingon").is_none());
+
+        for (_, blocks) in SCRIPT_BLOCKS {
+            for block in blocks.iter() {
+                assert!(
+                    BlockIter::new().any(|unicode_block| unicode_block.name == *block),
+                    "{} is not a Unicode block",
+                    block
+                );
+            }
+        }
+    }
+}